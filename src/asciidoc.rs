@@ -0,0 +1,270 @@
+//! AsciiDoc模块
+//!
+//! 按行识别AsciiDoc（Asciidoctor、Antora）的块级结构：代码清单、字面量、透传、注释和表格块、
+//! 属性条目、块属性行、块宏和注释行原样保留；段落中的行内代码、属性引用、锚点和宏以占位符保护，
+//! 交叉引用和链接只翻译可见文本部分。
+
+use crate::error::Result;
+use crate::protect::Placeholders;
+use crate::translator::{split_at_boundaries, CallContext, TranslationService, MAX_CONCURRENT_CHUNKS};
+use futures::stream::{self, StreamExt};
+use regex::{Captures, Regex};
+use std::sync::LazyLock;
+
+/// AsciiDoc占位符的类型标记
+const ASCIIDOC_PLACEHOLDER: char = 'A';
+
+/// 内容原样保留的分隔块：代码清单、字面量、透传、注释和表格
+const VERBATIM_DELIMITERS: [char; 4] = ['-', '.', '+', '/'];
+
+/// 内容照常翻译的分隔块：示例、侧栏和引用
+const CONTAINER_DELIMITERS: [char; 3] = ['=', '*', '_'];
+
+/// 行内标记，`text` 分组是需要翻译的可见文本，其余部分原样保留
+static INLINE_MARKUP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        // 带可见文本的交叉引用
+        r"(?P<open><<[^,>\s]+,\s*)(?P<text>[^>]+?)(?P<close>>>)",
+        r"|<<[^>]+>>",
+        // 可见文本需要翻译的宏和带文本的URL
+        r"|(?P<macro_open>\b(?:xref|link|footnote|mailto):[^\s\[]*\[|https?://[^\s\[\]]+\[)(?P<macro_text>[^\]]+)(?P<macro_close>\])",
+        // 其他行内宏（image、kbd、btn、menu、pass等）
+        r"|\b[a-z]+:[^\s\[]*\[[^\]]*\]",
+        r"|``[^`]+``|`[^`]+`|\+\+\+.+?\+\+\+",
+        r"|\{[\w-]+\}",
+        r"|\[\[[^\]]+\]\]|\[#[^\]]+\]",
+        r"|https?://[^\s\[\]<>]+",
+        r"|(?m)^(?:NOTE|TIP|IMPORTANT|WARNING|CAUTION): ",
+    ))
+    .unwrap()
+});
+
+/// 属性条目，例如 `:toc:`、`:source-highlighter: rouge`、`:!sectnums:`
+static ATTRIBUTE_ENTRY: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^:!?[\w-]+!?:(\s.*)?$").unwrap());
+
+/// 块宏，例如 `image::diagram.png[Architecture]`、`include::partial.adoc[]`
+static BLOCK_MACRO: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[\w-]+::\S*\[.*\]$").unwrap());
+
+/// AsciiDoc中的块
+#[derive(Debug, PartialEq)]
+enum AsciidocBlock<'a> {
+    /// 原样保留的内容
+    Verbatim(&'a str),
+    /// 需要翻译的段落，包括列表项和提示段落
+    Paragraph(&'a str),
+    /// 只翻译中间部分的行：节标题（`== Title`）和块标题（`.Title`）
+    Labeled {
+        prefix: &'a str,
+        text: &'a str,
+        newline: &'a str,
+    },
+}
+
+impl TranslationService {
+    /// 翻译一段AsciiDoc文本，保留分隔块、属性、宏和行内标记
+    pub(crate) async fn translate_asciidoc(&self, text: &str, context: &CallContext) -> Result<(String, Vec<String>)> {
+        let blocks: Vec<_> = segment_asciidoc(text)
+            .into_iter()
+            .map(|block| self.translate_asciidoc_block(block, context))
+            .collect();
+        let translated: Vec<Result<String>> = stream::iter(blocks)
+            .buffered(MAX_CONCURRENT_CHUNKS)
+            .collect()
+            .await;
+        Ok((translated.into_iter().collect::<Result<String>>()?, Vec::new()))
+    }
+
+    async fn translate_asciidoc_block(&self, block: AsciidocBlock<'_>, context: &CallContext) -> Result<String> {
+        match block {
+            AsciidocBlock::Verbatim(raw) => Ok(raw.to_string()),
+            AsciidocBlock::Paragraph(raw) => {
+                let body = raw.trim_end_matches(['\n', '\r']);
+                let translated = self.translate_asciidoc_inline(body, context).await?;
+                Ok(format!("{}{}", translated.trim_end(), &raw[body.len()..]))
+            }
+            AsciidocBlock::Labeled { prefix, text, newline } => {
+                let translated = self.translate_asciidoc_inline(text, context).await?;
+                Ok(format!("{}{}{}", prefix, translated.trim().replace('\n', " "), newline))
+            }
+        }
+    }
+
+    /// 以占位符保护行内标记后翻译，交叉引用和链接的可见文本留在句子中一起翻译
+    async fn translate_asciidoc_inline(&self, text: &str, context: &CallContext) -> Result<String> {
+        if !text.chars().any(char::is_alphanumeric) {
+            return Ok(text.to_string());
+        }
+        let mut placeholders = Placeholders::default();
+        let protected = INLINE_MARKUP.replace_all(text, |found: &Captures| {
+            let visible = found
+                .name("text")
+                .map(|visible| (visible, &found["open"], &found["close"]))
+                .or_else(|| {
+                    found
+                        .name("macro_text")
+                        .map(|visible| (visible, &found["macro_open"], &found["macro_close"]))
+                });
+            match visible {
+                Some((visible, open, close)) => format!(
+                    "{}{}{}",
+                    placeholders.insert(ASCIIDOC_PLACEHOLDER, open.to_string()),
+                    visible.as_str(),
+                    placeholders.insert(ASCIIDOC_PLACEHOLDER, close.to_string()),
+                ),
+                None => placeholders.insert(ASCIIDOC_PLACEHOLDER, found[0].to_string()),
+            }
+        });
+        self.translate_protected(&protected, &placeholders, "AsciiDoc", context).await
+    }
+}
+
+/// 将AsciiDoc文本拆分为块，所有块按顺序拼接后与输入完全一致
+fn segment_asciidoc(text: &str) -> Vec<AsciidocBlock<'_>> {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let offsets: Vec<usize> = lines
+        .iter()
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some(start)
+        })
+        .chain(std::iter::once(text.len()))
+        .collect();
+    let slice = |start: usize, end: usize| &text[offsets[start]..offsets[end]];
+    let mut blocks = Vec::new();
+    let mut index = 0;
+
+    while index < lines.len() {
+        let line = lines[index];
+        let content = line.trim_end_matches(['\n', '\r']);
+        let newline = &line[content.len()..];
+
+        if let Some(delimiter) = delimiter(content) {
+            if is_verbatim_delimiter(delimiter) {
+                let end = (index + 1..lines.len())
+                    .find(|&i| lines[i].trim_end() == delimiter)
+                    .map_or(lines.len(), |i| i + 1);
+                blocks.push(AsciidocBlock::Verbatim(slice(index, end)));
+                index = end;
+                continue;
+            }
+            // 示例、侧栏、引用和开放块的分隔行原样保留，内容照常处理
+            blocks.push(AsciidocBlock::Verbatim(line));
+            index += 1;
+            continue;
+        }
+
+        if content.trim().is_empty()
+            || content == "+"
+            || (content.starts_with("//") && !content.starts_with("////"))
+            || ATTRIBUTE_ENTRY.is_match(content)
+            || BLOCK_MACRO.is_match(content)
+            || (content.starts_with('[') && content.ends_with(']'))
+        {
+            blocks.push(AsciidocBlock::Verbatim(line));
+            index += 1;
+            continue;
+        }
+
+        if let Some(prefix) = heading_prefix(content) {
+            blocks.push(AsciidocBlock::Labeled {
+                prefix: &content[..prefix],
+                text: &content[prefix..],
+                newline,
+            });
+            index += 1;
+            continue;
+        }
+
+        if content.starts_with('.') && content[1..].starts_with(|c: char| !c.is_whitespace() && c != '.') {
+            blocks.push(AsciidocBlock::Labeled {
+                prefix: ".",
+                text: &content[1..],
+                newline,
+            });
+            index += 1;
+            continue;
+        }
+
+        // 段落到空行、分隔行或其他块级结构之前结束；缩进开头的段落是字面量段落
+        let end = (index + 1..lines.len())
+            .find(|&i| ends_paragraph(lines[i]))
+            .unwrap_or(lines.len());
+        let paragraph = slice(index, end);
+        if content.starts_with(char::is_whitespace) {
+            blocks.push(AsciidocBlock::Verbatim(paragraph));
+        } else {
+            blocks.push(AsciidocBlock::Paragraph(paragraph));
+        }
+        index = end;
+    }
+    blocks
+}
+
+/// 按块边界将AsciiDoc文本拆分为不超过 `max_length` 的块，各块按顺序拼接后与原文完全一致
+///
+/// 只在分隔块之外的空行之后断开，代码清单等分隔块不会被拆到两个块中。
+pub(crate) fn split_asciidoc_text(text: &str, max_length: usize) -> Vec<&str> {
+    let mut boundaries = Vec::new();
+    let mut open: Vec<&str> = Vec::new();
+    let mut offset = 0;
+    let mut previous_blank = false;
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end();
+        let blank = content.is_empty();
+        if previous_blank && !blank && open.is_empty() {
+            boundaries.push(offset);
+        }
+        if let Some(delimiter) = delimiter(content) {
+            match open.last() {
+                Some(&last) if last == delimiter => {
+                    open.pop();
+                }
+                // 原样保留的分隔块内部不会嵌套其他块
+                Some(&last) if is_verbatim_delimiter(last) => {}
+                _ => open.push(delimiter),
+            }
+        }
+        previous_blank = blank;
+        offset += line.len();
+    }
+    split_at_boundaries(text, &boundaries, max_length)
+}
+
+/// 行是否为分隔块的边界，返回分隔符
+fn delimiter(content: &str) -> Option<&str> {
+    let content = content.trim_end();
+    if content == "--" || matches!(content, "|===" | ",===" | ":===" | "!===") {
+        return Some(content);
+    }
+    let first = content.chars().next()?;
+    let repeated = content.len() >= 4 && content.chars().all(|c| c == first);
+    let known = VERBATIM_DELIMITERS.contains(&first) || CONTAINER_DELIMITERS.contains(&first);
+    (repeated && known).then_some(content)
+}
+
+/// 分隔块的内容是否原样保留
+fn is_verbatim_delimiter(delimiter: &str) -> bool {
+    matches!(delimiter, "|===" | ",===" | ":===" | "!===")
+        || (delimiter.starts_with(VERBATIM_DELIMITERS) && delimiter != "--")
+}
+
+/// 节标题标记（`=` 到 `======` 加空格）的长度，不是节标题时返回 `None`
+fn heading_prefix(content: &str) -> Option<usize> {
+    let level = content.chars().take_while(|&c| c == '=').count();
+    let rest = &content[level..];
+    let spaces = rest.len() - rest.trim_start().len();
+    ((1..=6).contains(&level) && spaces > 0 && !rest.trim().is_empty()).then_some(level + spaces)
+}
+
+/// 行是否结束当前段落
+fn ends_paragraph(line: &str) -> bool {
+    let content = line.trim_end_matches(['\n', '\r']);
+    content.trim().is_empty()
+        || content == "+"
+        || delimiter(content).is_some()
+        || heading_prefix(content).is_some()
+        || ATTRIBUTE_ENTRY.is_match(content)
+        || BLOCK_MACRO.is_match(content)
+        || (content.starts_with('[') && content.ends_with(']'))
+}
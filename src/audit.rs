@@ -0,0 +1,161 @@
+//! 审计日志模块
+//!
+//! 设置 `audit_log_path` 后，每次发往翻译后端的请求（包括重试）在文件末尾追加一行JSON，
+//! 记录时间、文档ID、内容哈希、语言、端点和结果；只有显式开启 `audit_include_text` 时才记录原文和译文。
+//! 记录经通道交给后台写入线程，翻译流程不等待磁盘；`shutdown` 时等待已提交的记录写入磁盘。
+
+use crate::cache::stable_hash;
+use crate::types::{TranslationConfig, Verbosity};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::oneshot;
+
+/// 发给写入线程的命令
+enum Command {
+    /// 追加一行记录（已包含换行符）
+    Record(String),
+    /// 写入缓冲区中的记录并同步到磁盘，完成后通知
+    Flush(oneshot::Sender<()>),
+}
+
+/// 单次请求的审计信息
+pub(crate) struct AuditEntry<'a> {
+    /// 文档ID
+    pub(crate) document_id: &'a str,
+    /// 块序号和尝试次数，不属于文档分块的请求为 `None`
+    pub(crate) chunk: Option<(usize, usize)>,
+    /// 源语言
+    pub(crate) source_lang: &'a str,
+    /// 目标语言
+    pub(crate) target_lang: &'a str,
+    /// 后端的主机和端口
+    pub(crate) backend: &'a str,
+    /// 完整的端点地址
+    pub(crate) endpoint: &'a str,
+    /// 请求结果：`ok`、HTTP或API状态码、`timeout` 或 `error`
+    pub(crate) status: &'a str,
+    /// 请求耗时（毫秒）
+    pub(crate) duration_ms: u128,
+    /// 发送给后端的文本
+    pub(crate) source_text: &'a str,
+    /// 后端返回的译文，请求失败时为 `None`
+    pub(crate) translated_text: Option<&'a str>,
+}
+
+/// 追加写入的审计日志
+#[derive(Debug)]
+pub(crate) struct AuditLog {
+    /// 写入线程的命令通道
+    sender: mpsc::Sender<Command>,
+    /// 是否记录原文和译文
+    include_text: bool,
+}
+
+impl AuditLog {
+    /// 按配置打开审计日志并启动写入线程，未设置 `audit_log_path` 时返回 `Ok(None)`
+    ///
+    /// 文件以追加模式打开，不存在时创建；无法打开时返回错误而不启用。
+    pub(crate) fn from_config(config: &TranslationConfig) -> Result<Option<Self>, String> {
+        let Some(path) = &config.audit_log_path else {
+            return Ok(None);
+        };
+        let path = PathBuf::from(path);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("无法打开审计日志 {}: {}", path.display(), e))?;
+        let (sender, receiver) = mpsc::channel();
+        let verbosity = config.verbosity;
+        std::thread::Builder::new()
+            .name("translation-audit".to_string())
+            .spawn(move || write_records(file, &path, receiver, verbosity))
+            .map_err(|e| format!("无法启动审计日志写入线程: {}", e))?;
+        Ok(Some(Self {
+            sender,
+            include_text: config.audit_include_text,
+        }))
+    }
+
+    /// 提交一条记录，不等待写入
+    pub(crate) fn record(&self, entry: AuditEntry<'_>) {
+        let mut record = serde_json::json!({
+            "timestamp": timestamp(SystemTime::now()),
+            "document_id": entry.document_id,
+            "chunk_index": entry.chunk.map(|(index, _)| index),
+            "attempt": entry.chunk.map(|(_, attempt)| attempt),
+            "chunk_hash": format!("{:016x}", stable_hash(entry.source_text.as_bytes())),
+            "source_lang": entry.source_lang,
+            "target_lang": entry.target_lang,
+            "backend": entry.backend,
+            "endpoint": entry.endpoint,
+            "status": entry.status,
+            "duration_ms": entry.duration_ms,
+        });
+        if self.include_text {
+            record["source_text"] = entry.source_text.into();
+            record["translated_text"] = entry.translated_text.into();
+        }
+        let _ = self.sender.send(Command::Record(format!("{}\n", record)));
+    }
+
+    /// 等待此前提交的记录全部写入并同步到磁盘
+    pub(crate) async fn flush(&self) {
+        let (done, finished) = oneshot::channel();
+        if self.sender.send(Command::Flush(done)).is_ok() {
+            let _ = finished.await;
+        }
+    }
+}
+
+/// 写入线程：逐条追加记录，通道暂时清空时写出缓冲区，所有发送端关闭后退出
+fn write_records(file: File, path: &Path, receiver: mpsc::Receiver<Command>, verbosity: Verbosity) {
+    let mut writer = BufWriter::new(file);
+    let report = |e: std::io::Error| {
+        verbosity.log(Verbosity::Warn, format_args!("无法写入审计日志 {}: {}", path.display(), e));
+    };
+    while let Ok(command) = receiver.recv() {
+        for command in std::iter::once(command).chain(receiver.try_iter()) {
+            match command {
+                Command::Record(line) => writer.write_all(line.as_bytes()).unwrap_or_else(report),
+                Command::Flush(done) => {
+                    writer.flush().and_then(|_| writer.get_ref().sync_data()).unwrap_or_else(report);
+                    let _ = done.send(());
+                }
+            }
+        }
+        writer.flush().unwrap_or_else(report);
+    }
+}
+
+/// RFC 3339格式的UTC时间，精确到毫秒，例如 `2024-05-01T08:30:00.123Z`
+fn timestamp(time: SystemTime) -> String {
+    let elapsed = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = elapsed.as_secs();
+    let (days, rest) = (seconds / 86_400, seconds % 86_400);
+
+    // 由1970-01-01起的天数换算公历日期
+    let shifted = days as i64 + 719_468;
+    let era = shifted.div_euclid(146_097);
+    let day_of_era = shifted.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        rest / 3_600,
+        rest % 3_600 / 60,
+        rest % 60,
+        elapsed.subsec_millis()
+    )
+}
@@ -0,0 +1,198 @@
+//! 批量翻译模块
+//!
+//! 为大量相互独立的短文本（界面文案、提交信息等）提供按条目返回结果的批量翻译。
+
+use crate::error::{Result, TranslationError};
+use crate::translator::{CallContext, TranslationService, MAX_CONCURRENT_CHUNKS};
+use crate::types::{TranslateOptions, TranslationConfig};
+use futures::stream::{self, StreamExt};
+
+/// 打包多个条目时使用的分隔行，后端不会翻译该标记
+const BATCH_SEPARATOR: &str = "[#####]";
+
+impl TranslationService {
+    /// 批量翻译多条相互独立的文本
+    ///
+    /// 所有条目共享速率限制器和并发上限，结果按输入顺序对齐，
+    /// 单个条目失败只影响该条目的结果，不会使整批失败。
+    ///
+    /// 启用 `pack_batch_items` 时，相邻的短条目会以分隔行拼接后在一个请求中翻译，
+    /// 返回后按分隔行拆分并校验条目数；条目数不一致时回退为逐条翻译。
+    /// 超过 `max_text_length` 的条目按完整文档流程分块翻译。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{TranslationService, TranslationConfig};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let service = TranslationService::new(TranslationConfig::default());
+    ///     let items = vec!["Save".to_string(), "Cancel".to_string()];
+    ///
+    ///     let results = service.translate_batch(&items).await;
+    ///     assert_eq!(results.len(), items.len());
+    ///     for (item, result) in items.iter().zip(results) {
+    ///         match result {
+    ///             Ok(translated) => println!("{} -> {}", item, translated),
+    ///             Err(e) => eprintln!("{} failed: {}", item, e),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn translate_batch(&self, items: &[String]) -> Vec<Result<String>> {
+        if !self.config().enabled {
+            return items.iter().cloned().map(Ok).collect();
+        }
+
+        let context = match self.new_context(&TranslateOptions::default()) {
+            Ok(context) => context,
+            Err(_) => return items.iter().map(|_| Err(TranslationError::ShuttingDown)).collect(),
+        };
+
+        self.translate_items(items, context.components.config.pack_batch_items, &context).await
+    }
+
+    /// 在给定的调用上下文中逐条翻译，结果按输入顺序对齐
+    ///
+    /// `pack` 为 `true` 时相邻的短条目以分隔行拼接后在一个请求中翻译。
+    pub(crate) async fn translate_items(&self, items: &[String], pack: bool, context: &CallContext) -> Vec<Result<String>> {
+        let mut results: Vec<Option<Result<String>>> = items.iter().map(|_| None).collect();
+        let mut groups = stream::iter(self.plan_batch(items, pack, &context.components.config))
+            .map(|group| async move {
+                let group_results = self.translate_batch_group(items, &group, context).await;
+                (group, group_results)
+            })
+            .buffer_unordered(MAX_CONCURRENT_CHUNKS);
+
+        while let Some((group, group_results)) = groups.next().await {
+            for (index, result) in group.into_iter().zip(group_results) {
+                results[index] = Some(result);
+            }
+        }
+
+        results
+            .into_iter()
+            .map(|result| result.expect("every batch item belongs to exactly one group"))
+            .collect()
+    }
+
+    /// 将条目划分为请求组，每组的条目序号连续
+    fn plan_batch(&self, items: &[String], pack: bool, config: &TranslationConfig) -> Vec<Vec<usize>> {
+        if !pack {
+            return (0..items.len()).map(|index| vec![index]).collect();
+        }
+
+        let max_length = config.max_text_length;
+        let max_items = config.max_paragraphs_per_request.max(1);
+        let mut groups = Vec::new();
+        let mut current: Vec<usize> = Vec::new();
+        let mut current_length = 0;
+
+        for (index, item) in items.iter().enumerate() {
+            let packable = item.len() <= max_length
+                && !item.trim().is_empty()
+                && !item.lines().any(|line| line.trim() == BATCH_SEPARATOR);
+            if !packable {
+                if !current.is_empty() {
+                    groups.push(std::mem::take(&mut current));
+                    current_length = 0;
+                }
+                groups.push(vec![index]);
+                continue;
+            }
+
+            let separator_length = BATCH_SEPARATOR.len() + 2;
+            let potential_length = if current.is_empty() {
+                item.len()
+            } else {
+                current_length + separator_length + item.len()
+            };
+            if current.len() >= max_items || potential_length > max_length {
+                groups.push(std::mem::take(&mut current));
+                current_length = item.len();
+            } else {
+                current_length = potential_length;
+            }
+            current.push(index);
+        }
+
+        if !current.is_empty() {
+            groups.push(current);
+        }
+        groups
+    }
+
+    /// 翻译一个请求组，返回与组内条目一一对应的结果
+    async fn translate_batch_group(
+        &self,
+        items: &[String],
+        group: &[usize],
+        context: &CallContext,
+    ) -> Vec<Result<String>> {
+        if let [index] = group {
+            return vec![self.translate_batch_item(&items[*index], context).await];
+        }
+
+        let packed = group
+            .iter()
+            .map(|&index| items[index].as_str())
+            .collect::<Vec<_>>()
+            .join(&format!("\n{}\n", BATCH_SEPARATOR));
+
+        match self.translate_chunk(&packed, context).await {
+            Ok(translated) => {
+                let parts = split_packed(&translated);
+                if parts.len() == group.len() {
+                    return parts.into_iter().map(Ok).collect();
+                }
+                context.warn(format!(
+                    "批量请求返回 {} 条结果，预期 {} 条，改为逐条翻译",
+                    parts.len(),
+                    group.len()
+                ));
+            }
+            Err(e) => {
+                context.warn(format!("批量请求失败，改为逐条翻译: {}", e));
+            }
+        }
+
+        let mut results = Vec::with_capacity(group.len());
+        for &index in group {
+            results.push(self.translate_batch_item(&items[index], context).await);
+        }
+        results
+    }
+
+    /// 翻译单个条目，过长的条目按完整文档流程分块翻译
+    async fn translate_batch_item(&self, item: &str, context: &CallContext) -> Result<String> {
+        if item.trim().is_empty() {
+            return Ok(item.to_string());
+        }
+        if item.len() > context.components.config.max_text_length {
+            return self
+                .run_translation(item, &TranslateOptions::default())
+                .await
+                .map(|(translated, _)| translated);
+        }
+
+        let result = self.translate_chunk(item, context).await;
+        self.apply_failure_policy(result, item, context.on_error, context)
+    }
+}
+
+/// 按分隔行拆分打包翻译的结果
+fn split_packed(translated: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    for line in translated.lines() {
+        if line.trim() == BATCH_SEPARATOR {
+            parts.push(current.join("\n").trim().to_string());
+            current.clear();
+        } else {
+            current.push(line);
+        }
+    }
+    parts.push(current.join("\n").trim().to_string());
+    parts
+}
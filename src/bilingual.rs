@@ -0,0 +1,90 @@
+//! 双语对照输出模块
+//!
+//! 将已翻译的段按段落与原文交错排列。
+
+use crate::types::{BilingualOrder, OutputMode, TranslatedSegment};
+
+/// 按输出模式渲染单个已翻译的段
+pub(crate) fn render_segment(segment: &TranslatedSegment, mode: &OutputMode) -> String {
+    let OutputMode::Bilingual { order, source_format } = mode else {
+        return segment.translated.clone();
+    };
+    if segment.is_code_block || segment.translated == segment.source {
+        return segment.translated.clone();
+    }
+
+    let sources = split_blocks(&segment.source);
+    let translations = split_blocks(&segment.translated);
+    let pairs: Vec<(&str, &str)> = if sources.len() == translations.len() {
+        sources.into_iter().zip(translations).collect()
+    } else {
+        // 段落数不一致时无法逐段对齐，退化为整块对照
+        vec![(segment.source.as_str(), segment.translated.as_str())]
+    };
+
+    pairs
+        .into_iter()
+        .map(|(source, translated)| {
+            if is_fenced_block(source) || source.trim() == translated.trim() {
+                return translated.to_string();
+            }
+            let source = source_format.replace("{source}", &demote_headings(source));
+            match order {
+                BilingualOrder::TranslationFirst => format!("{}\n\n{}", translated, source),
+                BilingualOrder::SourceFirst => format!("{}\n\n{}", source, translated),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// 按空行拆分段落，围栏代码块内部的空行不拆分
+fn split_blocks(text: &str) -> Vec<&str> {
+    let mut blocks = Vec::new();
+    let mut start = 0;
+    let mut in_fence = false;
+    let mut offset = 0;
+
+    for line in text.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if !in_fence && line.trim().is_empty() {
+            let block = text[start..offset].trim_matches('\n');
+            if !block.trim().is_empty() {
+                blocks.push(block);
+            }
+            start = offset + line.len();
+        }
+        offset += line.len();
+    }
+
+    let block = text[start..].trim_matches('\n');
+    if !block.trim().is_empty() {
+        blocks.push(block);
+    }
+    blocks
+}
+
+/// 段落是否为围栏代码块
+fn is_fenced_block(block: &str) -> bool {
+    block.trim_start().starts_with("```")
+}
+
+/// 将ATX标题行渲染为加粗文本，避免重复标题产生冲突的锚点
+fn demote_headings(block: &str) -> String {
+    block
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            let level = trimmed.chars().take_while(|&c| c == '#').count();
+            match trimmed[level..].strip_prefix(' ') {
+                Some(title) if (1..=6).contains(&level) => {
+                    format!("**{}**", title.trim().trim_end_matches('#').trim_end())
+                }
+                _ => line.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,138 @@
+//! 同步翻译接口
+//!
+//! 启用 `blocking` 特性后可用。[`TranslationService`] 内部持有一个单线程tokio运行时，
+//! 在调用线程上阻塞执行异步服务的对应方法，适合非异步的命令行工具和构建脚本。
+
+use crate::error::{Result, TranslationError};
+use crate::types::{DirOptions, DirReport, FileReport, TranslateOptions, TranslationConfig};
+use std::path::Path;
+use tokio::runtime::{Builder, Runtime};
+
+/// 同步翻译服务
+///
+/// 包装异步的 [`crate::TranslationService`]，每个方法都会阻塞当前线程直到翻译完成。
+/// 不能在tokio运行时内部创建或调用（例如 `#[tokio::main]` 的异步函数中），
+/// 此时返回 [`TranslationError::InsideAsyncRuntime`] 而不是panic；异步代码请直接使用异步服务。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::blocking::TranslationService;
+/// use markdown_translator::TranslationConfig;
+///
+/// let service = TranslationService::new(TranslationConfig::default()).unwrap();
+/// let translated = service.translate("Hello, world!").unwrap();
+/// assert_eq!(translated, "Hello, world!");
+/// ```
+///
+/// 在异步上下文中调用会返回错误：
+///
+/// ```rust
+/// use markdown_translator::blocking::TranslationService;
+/// use markdown_translator::{TranslationConfig, TranslationError};
+///
+/// let service = TranslationService::new(TranslationConfig::default()).unwrap();
+/// tokio_test::block_on(async {
+///     let result = service.translate("Hello");
+///     assert!(matches!(result, Err(TranslationError::InsideAsyncRuntime)));
+/// });
+/// ```
+pub struct TranslationService {
+    /// 实际执行翻译的异步服务
+    inner: crate::TranslationService,
+    /// 驱动异步服务的单线程运行时，析构时在后台关闭
+    runtime: Option<Runtime>,
+}
+
+impl TranslationService {
+    /// 按配置创建同步翻译服务
+    ///
+    /// # 返回
+    ///
+    /// * `Ok(TranslationService)` - 创建成功
+    /// * `Err(TranslationError::InsideAsyncRuntime)` - 在tokio运行时内部调用
+    /// * `Err(TranslationError::Custom)` - 运行时创建失败
+    pub fn new(config: TranslationConfig) -> Result<Self> {
+        Self::from_async(crate::TranslationService::new(config))
+    }
+
+    /// 包装已创建的异步服务，例如由 [`TranslationServiceBuilder`](crate::TranslationServiceBuilder) 构建的服务
+    ///
+    /// 同步服务与原服务共享配置、指标和速率限制器。
+    pub fn from_async(inner: crate::TranslationService) -> Result<Self> {
+        ensure_outside_runtime()?;
+        Ok(Self {
+            inner,
+            runtime: Some(build_runtime()?),
+        })
+    }
+
+    /// 获取底层的异步服务，用于访问指标、配置等不需要运行时的方法
+    pub fn inner(&self) -> &crate::TranslationService {
+        &self.inner
+    }
+
+    /// 翻译文本，见 [`crate::TranslationService::translate`]
+    pub fn translate(&self, text: &str) -> Result<String> {
+        self.block_on(self.inner.translate(text))
+    }
+
+    /// 使用单次调用选项翻译文本，见 [`crate::TranslationService::translate_with`]
+    pub fn translate_with(&self, text: &str, options: &TranslateOptions) -> Result<String> {
+        self.block_on(self.inner.translate_with(text, options))
+    }
+
+    /// 翻译为指定目标语言，见 [`crate::TranslationService::translate_to`]
+    pub fn translate_to(&self, text: &str, target_lang: &str) -> Result<String> {
+        self.block_on(self.inner.translate_to(text, target_lang))
+    }
+
+    /// 翻译文件，见 [`crate::TranslationService::translate_file`]
+    pub fn translate_file(&self, input: &Path, output: &Path) -> Result<FileReport> {
+        self.block_on(self.inner.translate_file(input, output))
+    }
+
+    /// 原地翻译文件，见 [`crate::TranslationService::translate_file_in_place`]
+    pub fn translate_file_in_place(&self, path: &Path, backup: bool) -> Result<FileReport> {
+        self.block_on(self.inner.translate_file_in_place(path, backup))
+    }
+
+    /// 递归翻译目录，见 [`crate::TranslationService::translate_dir`]
+    pub fn translate_dir(&self, src: &Path, dst: &Path, options: &DirOptions) -> Result<DirReport> {
+        self.block_on(self.inner.translate_dir(src, dst, options))
+    }
+
+    /// 在内部运行时上阻塞执行异步操作
+    fn block_on<T>(&self, future: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+        ensure_outside_runtime()?;
+        self.runtime
+            .as_ref()
+            .expect("runtime is only taken on drop")
+            .block_on(future)
+    }
+}
+
+impl Drop for TranslationService {
+    fn drop(&mut self) {
+        // 直接析构运行时会在异步上下文中panic，后台关闭在任何线程上都是安全的
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// 在tokio运行时内部阻塞会导致panic或死锁，提前返回错误
+fn ensure_outside_runtime() -> Result<()> {
+    match tokio::runtime::Handle::try_current() {
+        Ok(_) => Err(TranslationError::InsideAsyncRuntime),
+        Err(_) => Ok(()),
+    }
+}
+
+/// 创建驱动异步服务的单线程运行时
+fn build_runtime() -> Result<Runtime> {
+    Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| TranslationError::Custom(format!("无法创建tokio运行时: {}", e)))
+}
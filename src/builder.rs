@@ -0,0 +1,373 @@
+//! 服务构建器模块
+//!
+//! 提供 `TranslationServiceBuilder`，用链式调用代替手动填写 `TranslationConfig` 的全部字段。
+
+use crate::cache::TranslationCache;
+use crate::config::TranslationLibConfig;
+use crate::error::{Result, TranslationError};
+use crate::glossary::Glossary;
+use crate::language::check_language;
+use crate::metrics::MetricsRecorder;
+use crate::protect::KeepTerms;
+use crate::traits::Translator;
+use crate::translator::{Hooks, LoadedParts, TranslationService};
+use crate::types::{ApiKey, HttpConfig, QualityCheck, QualityVerdict, RetryConfig, TextHook, TranslationConfig, Verbosity};
+use reqwest::Client;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 翻译服务构建器
+///
+/// 以一份基础配置为起点（默认为 `TranslationConfig::default()`），
+/// 链式设置需要修改的字段，最后由 [`build`](Self::build) 校验配置并创建服务。
+/// 与 `TranslationService::new` 不同，HTTP客户端构建失败时 `build` 会返回错误，
+/// 而不是静默回退到默认客户端。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{TranslationError, TranslationServiceBuilder};
+///
+/// let service = TranslationServiceBuilder::new()
+///     .enabled(true)
+///     .source_lang("en")
+///     .target_lang("ja")
+///     .max_rps(1.0)
+///     .build()
+///     .unwrap();
+/// let config = service.config();
+/// assert!(config.enabled);
+/// assert_eq!((config.source_lang.as_str(), config.target_lang.as_str()), ("en", "ja"));
+/// assert_eq!(config.max_requests_per_second, 1.0);
+///
+/// // 配置无效时 build 返回错误，而不是创建服务
+/// let result = TranslationServiceBuilder::new().max_rps(0.0).target_lang("").build();
+/// assert!(matches!(&result, Err(TranslationError::ConfigError(message))
+///     if message.contains("max_requests_per_second") && message.contains("target_lang")));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TranslationServiceBuilder {
+    config: TranslationConfig,
+    retry: RetryConfig,
+    client: Option<Client>,
+    hooks: Hooks,
+}
+
+impl TranslationServiceBuilder {
+    /// 以 `TranslationConfig::default()` 为基础创建构建器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 以给定配置为基础创建构建器
+    pub fn from_config(config: TranslationConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// 以自动发现的配置文件为基础创建构建器
+    ///
+    /// 使用 `TranslationLibConfig::load_from_default_locations` 查找配置文件，
+    /// 未找到时使用默认配置。
+    pub fn from_default_locations() -> Self {
+        Self::from_config(TranslationLibConfig::load_from_default_locations().translation)
+    }
+
+    /// 是否启用翻译功能
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.config.enabled = enabled;
+        self
+    }
+
+    /// 源语言代码，"auto"表示自动检测
+    pub fn source_lang(mut self, lang: impl Into<String>) -> Self {
+        self.config.source_lang = lang.into();
+        self
+    }
+
+    /// 目标语言代码
+    pub fn target_lang(mut self, lang: impl Into<String>) -> Self {
+        self.config.target_lang = lang.into();
+        self
+    }
+
+    /// 翻译API地址
+    pub fn api_url(mut self, url: impl Into<String>) -> Self {
+        self.config.deeplx_api_url = url.into();
+        self
+    }
+
+    /// 备用API地址
+    pub fn fallback_api_urls(mut self, urls: Vec<String>) -> Self {
+        self.config.fallback_api_urls = urls;
+        self
+    }
+
+    /// 每秒最大请求数
+    pub fn max_rps(mut self, requests_per_second: f64) -> Self {
+        self.config.max_requests_per_second = requests_per_second;
+        self
+    }
+
+    /// 允许的最大突发请求数
+    pub fn burst_size(mut self, burst_size: usize) -> Self {
+        self.config.burst_size = Some(burst_size);
+        self
+    }
+
+    /// 单次翻译的最大文本长度
+    pub fn max_text_length(mut self, max_text_length: usize) -> Self {
+        self.config.max_text_length = max_text_length;
+        self
+    }
+
+    /// 术语表，源术语会被替换为指定的目标术语
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{FnTranslator, TranslationServiceBuilder};
+    /// use std::collections::HashMap;
+    ///
+    /// let service = TranslationServiceBuilder::new()
+    ///     .enabled(true)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_string())))
+    ///     .glossary(HashMap::from([("worker node".to_string(), "工作节点".to_string())]))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// # tokio_test::block_on(async {
+    /// assert_eq!(service.translate("Add a worker node.").await.unwrap(), "Add a 工作节点.");
+    /// # });
+    /// ```
+    pub fn glossary(mut self, glossary: HashMap<String, String>) -> Self {
+        self.config.glossary = glossary;
+        self
+    }
+
+    /// 单个HTTP请求的超时时间（秒）
+    pub fn request_timeout_secs(mut self, secs: u64) -> Self {
+        self.config.request_timeout_secs = secs;
+        self
+    }
+
+    /// HTTP连接池设置，见 [`HttpConfig`]；通过 [`client`](Self::client) 提供客户端时不使用
+    pub fn http(mut self, http: HttpConfig) -> Self {
+        self.config.http = http;
+        self
+    }
+
+    /// 翻译API的密钥，以 `Authorization: Bearer` 请求头发送
+    pub fn api_key(mut self, key: impl Into<String>) -> Self {
+        self.config.api_key = Some(ApiKey::new(key));
+        self
+    }
+
+    /// 写入标准错误输出的日志详细程度，默认为 [`Verbosity::Quiet`]
+    pub fn verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.config.verbosity = verbosity;
+        self
+    }
+
+    /// 所有请求的 `User-Agent`，覆盖内置的默认值
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.config.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// 添加随每个请求发送的请求头，例如网关用于路由或限流的自定义请求头
+    ///
+    /// 配置的请求头优先于内置的 `Content-Type`、`Accept` 和 `User-Agent`。
+    /// 无效的请求头名称或值由 [`build`](Self::build) 报告。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::TranslationServiceBuilder;
+    ///
+    /// let builder = TranslationServiceBuilder::new()
+    ///     .user_agent("docs-pipeline/2.1")
+    ///     .default_header("X-Team", "docs");
+    /// assert!(builder.build().is_ok());
+    ///
+    /// assert!(TranslationServiceBuilder::new().default_header("X Team", "docs").build().is_err());
+    /// ```
+    pub fn default_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.default_headers.insert(name.into(), value.into());
+        self
+    }
+
+    /// 单个块的重试配置
+    pub fn retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// 使用 [`Translator`] 实现代替HTTP请求翻译每个块
+    ///
+    /// 服务照常完成分块、代码块保护、术语表占位符、翻译缓存、速率限制和重试，
+    /// 只在原本发送HTTP请求的位置调用 `backend.translate_with`，选项中带有本次调用的源语言和目标语言。
+    /// 返回的错误按 [`TranslationError::is_retryable`] 决定是否重试；请求超时由实现自行负责，
+    /// 块超时和整体时限仍由服务控制。指标和审计日志中的后端标签为 `custom`。
+    /// 常用于以 [`FnTranslator`](crate::FnTranslator) 在测试中模拟后端，或接入非DeepLX的翻译接口。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{FnTranslator, TranslationService};
+    ///
+    /// let service = TranslationService::builder()
+    ///     .enabled(true)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// # tokio_test::block_on(async {
+    /// let (translated, report) = service.translate_with_report("Hello").await.unwrap();
+    /// assert_eq!(translated, "HELLO");
+    /// assert_eq!(report.backend.as_deref(), Some("custom"));
+    /// # });
+    /// ```
+    pub fn backend(mut self, backend: impl Translator + 'static) -> Self {
+        self.hooks.backend = Some(Arc::new(backend));
+        self
+    }
+
+    /// 使用调用方提供的HTTP客户端，跳过内部客户端构建
+    ///
+    /// 超时、User-Agent等由调用方负责，见 [`TranslationService::with_client`]。
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// 设置服务级翻译前钩子，对每个需要翻译的块在发送请求前调用
+    ///
+    /// 钩子在保留术语和术语表替换为占位符之后运行，不会作用于代码块；
+    /// 完整的调用顺序见 [`TextHook`]。
+    ///
+    /// # 示例
+    ///
+    /// 在文本离开本机前抹去内部主机名：
+    ///
+    /// ```rust
+    /// use markdown_translator::{FnTranslator, TranslationServiceBuilder};
+    ///
+    /// let service = TranslationServiceBuilder::new()
+    ///     .enabled(true)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_string())))
+    ///     .pre_process(|text| text.replace("db01.corp.internal", "[host]"))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// # tokio_test::block_on(async {
+    /// let translated = service.translate("Connect to db01.corp.internal first.").await.unwrap();
+    /// assert_eq!(translated, "Connect to [host] first.");
+    /// # });
+    /// ```
+    pub fn pre_process(mut self, hook: impl Fn(&str) -> String + Send + Sync + 'static) -> Self {
+        self.hooks.pre_process = Some(TextHook::new(hook));
+        self
+    }
+
+    /// 设置服务级翻译后钩子，对每个块的译文在还原占位符之前调用
+    pub fn post_process(mut self, hook: impl Fn(&str) -> String + Send + Sync + 'static) -> Self {
+        self.hooks.post_process = Some(TextHook::new(hook));
+        self
+    }
+
+    /// 设置服务级质量检查钩子，见 [`QualityCheck`](crate::QualityCheck)
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{QualityVerdict, TranslationServiceBuilder};
+    ///
+    /// let service = TranslationServiceBuilder::new()
+    ///     .enabled(true)
+    ///     .quality_check(|_source, translated| {
+    ///         if translated.contains('\u{fffd}') {
+    ///             QualityVerdict::Reject("译文包含替换字符".to_string())
+    ///         } else {
+    ///             QualityVerdict::Accept
+    ///         }
+    ///     })
+    ///     .build();
+    /// assert!(service.is_ok());
+    /// ```
+    pub fn quality_check(mut self, check: impl Fn(&str, &str) -> QualityVerdict + Send + Sync + 'static) -> Self {
+        self.hooks.quality_check = Some(QualityCheck::new(check));
+        self
+    }
+
+    /// 设置外部指标记录器，见 [`MetricsRecorder`](crate::metrics::MetricsRecorder)
+    ///
+    /// 服务的所有克隆共享同一个记录器；启用 `prometheus` 特性时可以使用
+    /// `PrometheusRecorder`。
+    pub fn metrics_recorder(mut self, recorder: Arc<dyn MetricsRecorder>) -> Self {
+        self.hooks.metrics_recorder = Some(recorder);
+        self
+    }
+
+    /// 直接修改底层配置，用于设置没有专门方法的字段
+    pub fn configure(mut self, configure: impl FnOnce(&mut TranslationConfig)) -> Self {
+        configure(&mut self.config);
+        self
+    }
+
+    /// 校验配置并创建服务
+    ///
+    /// # 返回
+    ///
+    /// * `Ok(TranslationService)` - 创建成功
+    /// * `Err(TranslationError::InvalidLanguage)` - 源语言或目标语言不是已知的语言代码
+    /// * `Err(TranslationError::ConfigError)` - 配置无效（含术语表无法解析）
+    /// * `Err(TranslationError::Io)` - 术语表文件无法读取，或 `disk` 缓存目录无法创建、条目文件无法读写
+    /// * `Err(TranslationError::Http)` - HTTP客户端构建失败
+    pub fn build(self) -> Result<TranslationService> {
+        self.validate()?;
+
+        let custom_client = self.client.is_some();
+        let client = match self.client {
+            Some(client) => client,
+            None => TranslationService::build_client(&self.config)?,
+        };
+
+        let parts = LoadedParts {
+            glossary: Glossary::from_config(&self.config)?,
+            keep_terms: KeepTerms::new(&self.config.keep_terms)?,
+            cache: TranslationCache::from_config(&self.config)?,
+        };
+
+        Ok(TranslationService::from_parts(self.config, client, custom_client, self.retry, parts, self.hooks))
+    }
+
+    fn validate(&self) -> Result<()> {
+        check_config_languages(&self.config)?;
+        let mut errors = self.config.validate().err().unwrap_or_default();
+        errors.extend(self.retry.validate().err().unwrap_or_default());
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.into())
+        }
+    }
+}
+
+/// 校验配置字段的取值，构建服务和运行时更新配置时使用
+pub(crate) fn validate_config(config: &TranslationConfig) -> Result<()> {
+    check_config_languages(config)?;
+    config.validate().map_err(TranslationError::from)
+}
+
+/// 配置的语言代码不是已知代码时返回 `InvalidLanguage`，空值留给 [`TranslationConfig::validate`] 报告
+fn check_config_languages(config: &TranslationConfig) -> Result<()> {
+    for (lang, allow_auto) in [(&config.source_lang, true), (&config.target_lang, false)] {
+        if !lang.trim().is_empty() {
+            check_language(lang, allow_auto)?;
+        }
+    }
+    Ok(())
+}
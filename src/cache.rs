@@ -0,0 +1,781 @@
+//! 翻译缓存模块
+//!
+//! 按 [`CacheKey`]（后端、语言、术语表、请求选项和块文本）缓存后端的响应，命中时不经过速率限制器、不发送请求。
+//! 缓存属于服务的共享组件，服务的所有克隆共用同一份缓存。写入后条目数超过 `max_entries`
+//! 或文本总量超过 `max_size_mb` 时淘汰最久未使用的条目；超过 `ttl_hours` 的条目在查找时视为未命中并删除，
+//! [`TranslationService::compact_cache`] 一次性清理所有过期条目。
+//!
+//! `disk` 后端在内存中维护同样的LRU，另外把每次写入追加到 `path` 目录下的条目文件，
+//! 创建服务时读取该文件恢复条目，因此重启进程或重建服务后仍能命中（包括离线模式）。
+
+use crate::error::{Result, TranslationError};
+use crate::glossary::Glossary;
+use crate::translator::{Reply, TranslationService};
+use crate::types::{CacheBackend, TranslationConfig, Verbosity, WarmOptions, WarmReport};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// 翻译缓存键
+///
+/// 由以下部分的哈希组成，任何一部分变化都会得到不同的键：
+///
+/// * 键格式的版本 [`CacheKey::VERSION`]，格式变化时递增，旧条目随之失效
+/// * 后端标识：主端点地址及其请求格式（标准DeepLX或dptrans）；通过构建器的 `backend`
+///   使用自定义后端时为固定的 `custom`，与任何端点的条目互不命中
+/// * 规范化的源语言和目标语言（忽略大小写、首尾空白，`_` 视为 `-`）
+/// * 术语表：本地术语表的条目和匹配方式，以及后端原生术语表的 `glossary_id`
+/// * 随请求发送的选项：文档上下文（含分块重叠带上的前文）、备选译文数量和标签处理方式
+/// * 替换保留术语和术语表占位符之后、实际发送的块文本
+///
+/// 速率限制、突发容量、超时等不影响译文的配置不参与计算。目前的后端没有模型、提示词模板
+/// 或正式程度的设置，引入这类后端时需要把它们加入键并递增版本。
+/// 哈希算法为FNV-1a，结果不随Rust版本和平台变化。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{CacheKey, TranslationConfig};
+///
+/// let config = TranslationConfig::default();
+/// let key = CacheKey::new(&config, "Hello").unwrap();
+/// assert_ne!(CacheKey::new(&config, "Hello!").unwrap(), key);
+///
+/// let mut japanese = config.clone();
+/// japanese.target_lang = "ja".to_string();
+/// assert_ne!(CacheKey::new(&japanese, "Hello").unwrap(), key);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    /// 除块文本以外各部分的哈希，模糊匹配只在该值相同的条目之间进行
+    scope: u64,
+    /// 各部分的哈希
+    digest: u64,
+}
+
+impl CacheKey {
+    /// 键格式的版本
+    pub const VERSION: u32 = 2;
+
+    /// 按配置中的后端、语言和术语表计算文本的缓存键
+    ///
+    /// 与翻译时使用的键一致（未使用自定义后端、调用级选项未覆盖语言且未设置上下文等请求选项时）。
+    /// 配置了 `glossary_file` 时会读取该文件，读取或编译失败时返回错误。
+    /// `text` 应为替换保留术语和术语表占位符之后的文本。
+    pub fn new(config: &TranslationConfig, text: &str) -> Result<Self> {
+        let glossary = Glossary::from_config(config)?;
+        let request = RequestScope {
+            source_lang: &config.source_lang,
+            target_lang: &config.target_lang,
+            ..Default::default()
+        };
+        Ok(Self::for_request(config, &glossary, &request, text))
+    }
+
+    /// 计算一次请求的缓存键，语言和请求选项取调用级设置
+    pub(crate) fn for_request(
+        config: &TranslationConfig,
+        glossary: &Glossary,
+        request: &RequestScope<'_>,
+        text: &str,
+    ) -> Self {
+        let mut hasher = StableHasher::default();
+        hasher.field(&Self::VERSION.to_le_bytes());
+        if request.custom_backend {
+            hasher.field(b"custom");
+        } else {
+            let url = config.deeplx_api_url.trim();
+            let format = if url.contains("dptrans") { "dptrans" } else { "deeplx" };
+            hasher.field(format.as_bytes());
+            hasher.field(url.as_bytes());
+        }
+        hasher.field(normalize_lang(request.source_lang).as_bytes());
+        hasher.field(normalize_lang(request.target_lang).as_bytes());
+        hasher.field(&glossary.fingerprint().to_le_bytes());
+        hasher.field(config.glossary_id.as_deref().unwrap_or_default().as_bytes());
+        hasher.optional(request.context.map(str::as_bytes));
+        let alternatives = request.num_alternatives.map(|count| (count as u64).to_le_bytes());
+        hasher.optional(alternatives.as_ref().map(|bytes| bytes.as_slice()));
+        hasher.optional(request.tag_handling.map(str::as_bytes));
+        let scope = hasher.finish();
+        hasher.field(text.as_bytes());
+        Self {
+            scope,
+            digest: hasher.finish(),
+        }
+    }
+}
+
+/// 一次请求中参与缓存键计算的调用级设置
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct RequestScope<'a> {
+    /// 是否通过构建器的 `backend` 使用自定义后端，此时端点地址不参与计算
+    pub(crate) custom_backend: bool,
+    /// 本次调用的源语言
+    pub(crate) source_lang: &'a str,
+    /// 本次调用的目标语言
+    pub(crate) target_lang: &'a str,
+    /// 随请求发送的文档上下文
+    pub(crate) context: Option<&'a str>,
+    /// 请求的备选译文数量
+    pub(crate) num_alternatives: Option<usize>,
+    /// 请求中的标签处理方式
+    pub(crate) tag_handling: Option<&'a str>,
+}
+
+impl fmt::Display for CacheKey {
+    /// 形如 `v2-0123456789abcdef`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}-{:016x}", Self::VERSION, self.digest)
+    }
+}
+
+/// 规范化语言代码：去除首尾空白、转为小写，`_` 替换为 `-`
+fn normalize_lang(lang: &str) -> String {
+    lang.trim().to_lowercase().replace('_', "-")
+}
+
+/// FNV-1a 64位哈希，结果不随Rust版本和平台变化
+#[derive(Debug)]
+pub(crate) struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl StableHasher {
+    /// 写入原始字节
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    /// 写入带长度前缀的字段，避免相邻字段的边界产生歧义
+    pub(crate) fn field(&mut self, bytes: &[u8]) {
+        self.write(&(bytes.len() as u64).to_le_bytes());
+        self.write(bytes);
+    }
+
+    /// 写入可选字段，`None` 与任何值（包括空值）都不同
+    pub(crate) fn optional(&mut self, bytes: Option<&[u8]>) {
+        self.write(&[u8::from(bytes.is_some())]);
+        self.field(bytes.unwrap_or_default());
+    }
+
+    /// 当前的哈希值
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// 字节序列的FNV-1a哈希
+pub(crate) fn stable_hash(bytes: &[u8]) -> u64 {
+    let mut hasher = StableHasher::default();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+/// 翻译缓存的统计
+///
+/// 由 [`TranslationService::cache_stats`] 和 [`TranslationService::compact_cache`] 返回，
+/// 淘汰和过期次数从缓存创建（或因配置变化重建）时开始累计。
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CacheStats {
+    /// 当前的条目数
+    pub entries: usize,
+    /// 当前条目的原文和译文总字节数
+    pub bytes: u64,
+    /// 因超过 `max_entries` 或 `max_size_mb` 而淘汰的条目数
+    pub evictions: u64,
+    /// 因超过 `ttl_hours` 而删除的条目数
+    pub expirations: u64,
+}
+
+/// LRU翻译缓存，`disk` 后端另外把条目写入磁盘
+#[derive(Debug)]
+pub(crate) struct TranslationCache {
+    /// 最多缓存的条目数，`None` 表示不限制
+    max_entries: Option<usize>,
+    /// 条目的原文和译文最多占用的字节数，`None` 表示不限制
+    max_bytes: Option<u64>,
+    /// 条目的有效期，`None` 表示永不过期
+    ttl: Option<Duration>,
+    /// 模糊匹配的相似度阈值，`None` 表示只做精确匹配
+    fuzzy_threshold: Option<f64>,
+    /// 缓存条目及其使用顺序
+    state: Mutex<Lru>,
+    /// 磁盘上的条目文件，`memory` 后端为 `None`
+    store: Option<DiskStore>,
+}
+
+/// LRU状态：条目记录最近一次使用的序号，`recency` 按序号索引条目，序号最小的最久未使用
+#[derive(Debug, Default)]
+struct Lru {
+    /// 缓存键到条目
+    entries: HashMap<CacheKey, Entry>,
+    /// 使用序号到缓存键
+    recency: BTreeMap<u64, CacheKey>,
+    /// 下一个使用序号
+    tick: u64,
+    /// 所有条目的字节数之和
+    bytes: u64,
+    /// 累计淘汰的条目数
+    evictions: u64,
+    /// 累计过期删除的条目数
+    expirations: u64,
+}
+
+/// 缓存条目
+#[derive(Debug)]
+struct Entry {
+    /// 发送的块文本，用于模糊匹配
+    source: String,
+    /// 后端的响应
+    reply: Reply,
+    /// 最近一次使用的序号
+    used: u64,
+    /// 写入的时间，磁盘缓存按该时间判断重启前写入的条目是否过期
+    stored: SystemTime,
+    /// 原文和响应的字节数
+    size: u64,
+}
+
+impl Entry {
+    /// 创建条目并计算原文和响应的字节数
+    fn new(source: String, reply: Reply, stored: SystemTime) -> Self {
+        let size = (source.len()
+            + reply.text.len()
+            + reply.detected_source_lang.as_ref().map_or(0, String::len)
+            + reply.alternatives.iter().map(String::len).sum::<usize>()) as u64;
+        Self {
+            source,
+            reply,
+            used: 0,
+            stored,
+            size,
+        }
+    }
+
+    /// 写入后是否已超过有效期
+    fn expired(&self, ttl: Option<Duration>) -> bool {
+        ttl.is_some_and(|ttl| self.stored.elapsed().is_ok_and(|age| age >= ttl))
+    }
+
+    /// 条目在磁盘文件中的一行
+    fn to_stored(&self, key: CacheKey) -> StoredEntry {
+        StoredEntry {
+            scope: key.scope,
+            digest: key.digest,
+            stored_secs: self.stored.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs()),
+            source: self.source.clone(),
+            text: self.reply.text.clone(),
+            detected_source_lang: self.reply.detected_source_lang.clone(),
+            alternatives: self.reply.alternatives.clone(),
+        }
+    }
+}
+
+/// 磁盘缓存条目文件中的一行（JSON）
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredEntry {
+    /// 缓存键中除块文本以外各部分的哈希
+    scope: u64,
+    /// 缓存键的哈希
+    digest: u64,
+    /// 写入时间（Unix时间戳，秒）
+    stored_secs: u64,
+    /// 发送的块文本
+    source: String,
+    /// 译文
+    text: String,
+    /// 后端检测到的源语言
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detected_source_lang: Option<String>,
+    /// 备选译文
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    alternatives: Vec<String>,
+}
+
+/// 磁盘缓存的条目文件
+///
+/// 每次写入追加一行，同一个键以最后一行为准；淘汰和过期的条目留在文件中，
+/// 直到加载时发现多余的行或调用 `compact` 时按内存中的条目重写文件。
+#[derive(Debug)]
+struct DiskStore {
+    /// 条目文件的路径
+    path: PathBuf,
+    /// 以追加模式打开的条目文件
+    file: Mutex<File>,
+    /// 写入失败时的日志级别
+    verbosity: Verbosity,
+}
+
+impl DiskStore {
+    /// 条目文件的路径，文件名带键格式的版本，版本变化后旧文件不再读取
+    fn entries_path(dir: &Path) -> PathBuf {
+        dir.join(format!("entries-v{}.jsonl", CacheKey::VERSION))
+    }
+
+    /// 以追加模式打开条目文件，不存在时创建
+    fn open(path: PathBuf, verbosity: Verbosity) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|source| TranslationError::Io {
+                path: path.clone(),
+                source,
+            })?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+            verbosity,
+        })
+    }
+
+    /// 追加一个条目，失败时记录警告，条目仍保留在内存中
+    fn append(&self, line: &StoredEntry) {
+        let mut line = serde_json::to_string(line).unwrap_or_default();
+        line.push('\n');
+        if let Err(e) = self.file.lock().unwrap().write_all(line.as_bytes()) {
+            self.verbosity
+                .log(Verbosity::Warn, format_args!("无法写入磁盘缓存 {}: {}", self.path.display(), e));
+        }
+    }
+
+    /// 按给定的条目重写文件：先写入临时文件再重命名，之后的写入追加到新文件
+    fn rewrite(&self, lines: &[StoredEntry]) -> Result<()> {
+        let io_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source| TranslationError::Io { path, source }
+        };
+        let temp = self.path.with_extension(format!("jsonl.{}.tmp", std::process::id()));
+        let mut contents = String::new();
+        for line in lines {
+            contents.push_str(&serde_json::to_string(line).unwrap_or_default());
+            contents.push('\n');
+        }
+        fs::write(&temp, contents).map_err(io_error(&temp))?;
+        let mut file = self.file.lock().unwrap();
+        fs::rename(&temp, &self.path).map_err(io_error(&self.path))?;
+        *file = OpenOptions::new()
+            .append(true)
+            .open(&self.path)
+            .map_err(io_error(&self.path))?;
+        Ok(())
+    }
+}
+
+impl Lru {
+    /// 分配新的使用序号并记录条目的使用顺序
+    fn touch(&mut self, key: CacheKey) -> u64 {
+        self.tick += 1;
+        self.recency.insert(self.tick, key);
+        self.tick
+    }
+
+    /// 删除条目并更新字节数
+    fn remove(&mut self, key: CacheKey) -> Option<Entry> {
+        let entry = self.entries.remove(&key)?;
+        self.recency.remove(&entry.used);
+        self.bytes -= entry.size;
+        Some(entry)
+    }
+
+    /// 写入条目（替换同键的旧条目）并标记为最近使用
+    fn insert(&mut self, key: CacheKey, mut entry: Entry) {
+        self.remove(key);
+        entry.used = self.touch(key);
+        self.bytes += entry.size;
+        self.entries.insert(key, entry);
+    }
+
+    /// 按最近使用顺序淘汰条目，直到条目数和字节数都不超过上限
+    fn evict(&mut self, max_entries: Option<usize>, max_bytes: Option<u64>) {
+        while max_entries.is_some_and(|max| self.entries.len() > max) || max_bytes.is_some_and(|max| self.bytes > max) {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            self.remove(oldest);
+            self.evictions += 1;
+        }
+    }
+
+    /// 所有条目在磁盘文件中的行，最久未使用的在前，重新加载后保持同样的使用顺序
+    fn stored_entries(&self) -> Vec<StoredEntry> {
+        self.recency
+            .values()
+            .filter_map(|key| Some(self.entries.get(key)?.to_stored(*key)))
+            .collect()
+    }
+
+    /// 当前的统计
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.entries.len(),
+            bytes: self.bytes,
+            evictions: self.evictions,
+            expirations: self.expirations,
+        }
+    }
+}
+
+impl TranslationCache {
+    /// 按配置创建缓存，没有 `[translation.cache]` 表或未启用时返回 `Ok(None)`
+    ///
+    /// `disk` 后端创建 `path` 目录（不存在时），读取其中的条目文件恢复未过期的条目并按上限淘汰。
+    /// 文件中无法解析的行被跳过；跳过、过期或被淘汰的行存在时重写文件。
+    /// 目录无法创建或文件无法读写时返回 `Io` 错误。
+    pub(crate) fn from_config(config: &TranslationConfig) -> Result<Option<Self>> {
+        let Some(cache) = config.cache.as_ref().filter(|cache| cache.enabled) else {
+            return Ok(None);
+        };
+        let mut translation_cache = Self {
+            max_entries: cache.max_entries,
+            max_bytes: cache.max_size_mb.map(|mb| mb.saturating_mul(1024 * 1024)),
+            ttl: cache.ttl_hours.map(|hours| Duration::from_secs(hours.saturating_mul(3600))),
+            fuzzy_threshold: cache.fuzzy_threshold,
+            state: Mutex::default(),
+            store: None,
+        };
+        if cache.backend == CacheBackend::Disk {
+            let dir = cache.path.as_deref().ok_or_else(|| {
+                TranslationError::ConfigError("cache.path: disk 后端必须指定缓存目录".to_string())
+            })?;
+            fs::create_dir_all(dir).map_err(|source| TranslationError::Io {
+                path: dir.to_path_buf(),
+                source,
+            })?;
+            let path = DiskStore::entries_path(dir);
+            let lines = translation_cache.load(&path, config.verbosity)?;
+            let store = DiskStore::open(path, config.verbosity)?;
+            let state = translation_cache.state.get_mut().unwrap();
+            if lines > state.entries.len() {
+                store.rewrite(&state.stored_entries())?;
+            }
+            translation_cache.store = Some(store);
+        }
+        Ok(Some(translation_cache))
+    }
+
+    /// 从条目文件恢复条目，返回文件中的行数；文件不存在时没有条目
+    fn load(&mut self, path: &Path, verbosity: Verbosity) -> Result<usize> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(source) => {
+                return Err(TranslationError::Io {
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        };
+        let ttl = self.ttl;
+        let state = self.state.get_mut().unwrap();
+        let mut lines = 0;
+        let mut invalid = 0;
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|source| TranslationError::Io {
+                path: path.to_path_buf(),
+                source,
+            })?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            lines += 1;
+            let Ok(stored) = serde_json::from_str::<StoredEntry>(&line) else {
+                invalid += 1;
+                continue;
+            };
+            let key = CacheKey {
+                scope: stored.scope,
+                digest: stored.digest,
+            };
+            let reply = Reply {
+                text: stored.text,
+                detected_source_lang: stored.detected_source_lang,
+                alternatives: stored.alternatives,
+            };
+            let entry = Entry::new(stored.source, reply, UNIX_EPOCH + Duration::from_secs(stored.stored_secs));
+            if entry.expired(ttl) {
+                state.remove(key);
+                continue;
+            }
+            state.insert(key, entry);
+        }
+        if invalid > 0 {
+            verbosity.log(
+                Verbosity::Warn,
+                format_args!("磁盘缓存 {} 中有 {} 行无法解析，已跳过", path.display(), invalid),
+            );
+        }
+        state.evict(self.max_entries, self.max_bytes);
+        // 恢复时的淘汰不计入统计
+        state.evictions = 0;
+        Ok(lines)
+    }
+
+    /// 查找缓存的响应，命中时将条目标记为最近使用，过期的条目视为未命中并删除
+    pub(crate) fn get(&self, key: CacheKey) -> Option<Reply> {
+        let mut state = self.state.lock().unwrap();
+        let entry = state.entries.get(&key)?;
+        if entry.expired(self.ttl) {
+            state.remove(key);
+            state.expirations += 1;
+            return None;
+        }
+        let used = entry.used;
+        state.recency.remove(&used);
+        let tick = state.touch(key);
+        let entry = state.entries.get_mut(&key)?;
+        entry.used = tick;
+        Some(entry.reply.clone())
+    }
+
+    /// 在后端、语言、术语表和请求选项相同的条目中查找与 `text` 最相似的一条
+    ///
+    /// 相似度为 1 减去字符级编辑距离与较长文本长度之比，不低于 `fuzzy_threshold` 时返回其响应和相似度，
+    /// 并将条目标记为最近使用。过期的条目不参与比较，未设置阈值时返回 `None`。
+    pub(crate) fn get_fuzzy(&self, key: CacheKey, text: &str) -> Option<(Reply, f64)> {
+        let threshold = self.fuzzy_threshold?;
+        let mut state = self.state.lock().unwrap();
+        let text: Vec<char> = text.chars().collect();
+        let (best, score) = state
+            .entries
+            .iter()
+            .filter(|(candidate, entry)| candidate.scope == key.scope && !entry.expired(self.ttl))
+            .filter_map(|(candidate, entry)| {
+                let score = similarity(&text, &entry.source, threshold)?;
+                Some((*candidate, score))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))?;
+        let used = state.entries.get(&best)?.used;
+        state.recency.remove(&used);
+        let tick = state.touch(best);
+        let entry = state.entries.get_mut(&best)?;
+        entry.used = tick;
+        Some((entry.reply.clone(), score))
+    }
+
+    /// 写入响应，超过条目数或字节数上限时淘汰最久未使用的条目；磁盘缓存同时追加到条目文件
+    pub(crate) fn insert(&self, key: CacheKey, source: &str, reply: &Reply) {
+        let mut state = self.state.lock().unwrap();
+        let entry = Entry::new(source.to_string(), reply.clone(), SystemTime::now());
+        if let Some(store) = &self.store {
+            store.append(&entry.to_stored(key));
+        }
+        state.insert(key, entry);
+        state.evict(self.max_entries, self.max_bytes);
+    }
+
+    /// 删除所有过期条目并按上限淘汰，返回清理后的统计
+    ///
+    /// 磁盘缓存同时按剩余的条目重写条目文件，重写失败时记录警告。
+    pub(crate) fn compact(&self) -> CacheStats {
+        let mut state = self.state.lock().unwrap();
+        let expired: Vec<CacheKey> = state
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.expired(self.ttl))
+            .map(|(key, _)| *key)
+            .collect();
+        for key in expired {
+            state.remove(key);
+            state.expirations += 1;
+        }
+        state.evict(self.max_entries, self.max_bytes);
+        if let Some(store) = &self.store {
+            if let Err(e) = store.rewrite(&state.stored_entries()) {
+                store.verbosity.log(Verbosity::Warn, format_args!("无法重写磁盘缓存: {}", e));
+            }
+        }
+        state.stats()
+    }
+
+    /// 当前的统计
+    pub(crate) fn stats(&self) -> CacheStats {
+        self.state.lock().unwrap().stats()
+    }
+}
+
+impl TranslationService {
+    /// 翻译缓存的统计，未启用缓存时返回 `None`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{CacheConfig, FnTranslator, TranslationService};
+    ///
+    /// let service = TranslationService::builder()
+    ///     .enabled(true)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+    ///     .configure(|config| config.cache = Some(CacheConfig::default()))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// # tokio_test::block_on(async {
+    /// service.translate("Hello").await.unwrap();
+    /// assert_eq!(service.cache_stats().unwrap().entries, 1);
+    /// # });
+    ///
+    /// let uncached = TranslationService::builder().build().unwrap();
+    /// assert!(uncached.cache_stats().is_none());
+    /// ```
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.components().cache.as_ref().map(|cache| cache.stats())
+    }
+
+    /// 删除翻译缓存中所有过期的条目并按上限淘汰，返回清理后的统计；未启用缓存时返回 `None`
+    ///
+    /// 查找时只删除命中的过期条目，长期运行的服务可以定期调用本方法释放其余过期条目占用的内存。
+    /// `translate_dir` 结束时会自动调用。
+    pub fn compact_cache(&self) -> Option<CacheStats> {
+        self.components().cache.as_ref().map(|cache| cache.compact())
+    }
+
+    /// 预先翻译一组文本并写入翻译缓存，丢弃拼接后的译文
+    ///
+    /// 与 [`warm_cache_with`](Self::warm_cache_with) 相同，使用默认选项。
+    pub async fn warm_cache(&self, texts: &[&str]) -> Result<WarmReport> {
+        self.warm_cache_with(texts, &WarmOptions::default()).await
+    }
+
+    /// 按选项预热翻译缓存
+    ///
+    /// 各文本按正常流程分块和翻译（遵守速率限制），只是不返回译文，之后翻译相同内容时直接命中缓存。
+    /// 所有文本共用一次调用：内容相同的块无论出现在哪篇文本中都只请求一次。
+    /// `dry_run` 时只统计需要请求的块，不发送翻译请求；开启 `skip_if_already_target` 时仍会请求语言检测。
+    ///
+    /// # 错误
+    ///
+    /// 未启用翻译缓存（`[translation.cache]`）时返回 `ConfigError`；失败处理策略为 `Fail` 时，
+    /// 任一块翻译失败即返回该错误，此前翻译的块仍保留在缓存中。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{CacheConfig, FnTranslator, TranslationService, WarmOptions};
+    ///
+    /// let service = TranslationService::builder()
+    ///     .enabled(true)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+    ///     .configure(|config| config.cache = Some(CacheConfig::default()))
+    ///     .build()
+    ///     .unwrap();
+    /// let texts = ["Install the package.", "Run the tests."];
+    ///
+    /// # tokio_test::block_on(async {
+    /// let plan = service.warm_cache_with(&texts, &WarmOptions::new().dry_run(true)).await.unwrap();
+    /// assert_eq!(plan.translated, 2);
+    ///
+    /// service.warm_cache(&texts).await.unwrap();
+    /// let (_, report) = service.translate_with_report("Run the tests.").await.unwrap();
+    /// assert_eq!(report.cache_hits, 1);
+    /// # });
+    /// ```
+    pub async fn warm_cache_with(&self, texts: &[&str], options: &WarmOptions) -> Result<WarmReport> {
+        if self.components().cache.is_none() {
+            return Err(TranslationError::ConfigError(
+                "预热缓存需要启用翻译缓存（[translation.cache]）".to_string(),
+            ));
+        }
+        let started = Instant::now();
+        let warm = Arc::new(WarmState::new(options.dry_run));
+        let mut context = self.new_context(&options.translate_options)?;
+        context.warm = Some(warm.clone());
+        for text in texts {
+            self.translate_document(text, &context).await?;
+        }
+        Ok(WarmReport {
+            texts: texts.len(),
+            translated: warm.translated.load(Ordering::Relaxed),
+            cached: warm.cached.load(Ordering::Relaxed),
+            duplicates: warm.duplicates.load(Ordering::Relaxed),
+            failed: context.report().failed_chunks,
+            dry_run: options.dry_run,
+            duration: started.elapsed(),
+        })
+    }
+}
+
+/// 缓存预热的统计，由一次 `warm_cache` 调用的所有块共享
+#[derive(Debug)]
+pub(crate) struct WarmState {
+    /// 是否只统计、不请求
+    pub(crate) dry_run: bool,
+    /// 已经计数的缓存键
+    seen: Mutex<HashSet<CacheKey>>,
+    /// 需要请求的块数
+    translated: AtomicUsize,
+    /// 已在缓存中的块数
+    cached: AtomicUsize,
+    /// 重复出现的块数
+    duplicates: AtomicUsize,
+}
+
+impl WarmState {
+    /// 创建空的统计
+    fn new(dry_run: bool) -> Self {
+        Self {
+            dry_run,
+            seen: Mutex::default(),
+            translated: AtomicUsize::new(0),
+            cached: AtomicUsize::new(0),
+            duplicates: AtomicUsize::new(0),
+        }
+    }
+
+    /// 记录一个块：首次出现时按 `cache` 中是否已有可用的条目计数，否则计为重复
+    ///
+    /// 本次调用不读取缓存时 `cache` 为 `None`，所有块都计为需要请求。
+    pub(crate) fn record(&self, key: CacheKey, text: &str, cache: Option<&TranslationCache>) {
+        let counter = if !self.seen.lock().unwrap().insert(key) {
+            &self.duplicates
+        } else if cache.is_some_and(|cache| cache.get(key).is_some() || cache.get_fuzzy(key, text).is_some()) {
+            &self.cached
+        } else {
+            &self.translated
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// 两段文本的相似度：1 减去字符级编辑距离与较长文本长度之比，低于 `threshold` 时返回 `None`
+///
+/// 长度之差已经决定了编辑距离的下限，相差过大的文本不计算距离；逐行计算时一旦整行都超过允许的距离即停止。
+fn similarity(text: &[char], candidate: &str, threshold: f64) -> Option<f64> {
+    let candidate: Vec<char> = candidate.chars().collect();
+    let longest = text.len().max(candidate.len());
+    if longest == 0 {
+        return Some(1.0);
+    }
+    let allowed = ((1.0 - threshold) * longest as f64).floor() as usize;
+    if text.len().abs_diff(candidate.len()) > allowed {
+        return None;
+    }
+
+    let mut previous: Vec<usize> = (0..=candidate.len()).collect();
+    let mut current = vec![0; candidate.len() + 1];
+    for (i, &a) in text.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, &b) in candidate.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        if current.iter().all(|&distance| distance > allowed) {
+            return None;
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    let distance = previous[candidate.len()];
+    (distance <= allowed).then(|| 1.0 - distance as f64 / longest as f64)
+}
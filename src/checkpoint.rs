@@ -0,0 +1,127 @@
+//! 断点续译模块
+//!
+//! 每个块翻译完成后，将块序号、原文哈希和译文作为一行JSON追加到检查点文件中。
+//! 以同一输入再次调用时读取检查点，序号和哈希都一致的块直接使用记录的译文，不再请求API。
+
+use crate::translator::CallContext;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+
+/// 检查点文件中的一条记录
+#[derive(Debug, Serialize, Deserialize)]
+struct CheckpointEntry {
+    /// 块序号
+    index: usize,
+    /// 目标语言和块原文的哈希
+    hash: String,
+    /// 块的译文
+    translated: String,
+}
+
+/// 一次调用的检查点
+#[derive(Debug)]
+pub(crate) struct Checkpoint {
+    /// 检查点文件路径
+    path: PathBuf,
+    /// 从检查点恢复的译文，按块序号索引
+    completed: std::sync::Mutex<HashMap<usize, String>>,
+    /// 串行化追加写入，保证每条记录占完整的一行
+    writer: tokio::sync::Mutex<()>,
+}
+
+impl Checkpoint {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            completed: std::sync::Mutex::default(),
+            writer: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// 读取检查点文件，记录与当前各块原文一致的译文
+    ///
+    /// 文件不存在时视为空检查点；无法解析的行以及序号或哈希与当前原文不一致的记录被忽略，
+    /// 并各汇总为一条警告。
+    pub(crate) async fn load(&self, sources: &[String], context: &CallContext) {
+        let content = match tokio::fs::read_to_string(&self.path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+            Err(e) => {
+                context.warn(format!("无法读取检查点文件 {}，从头翻译: {}", self.path.display(), e));
+                return;
+            }
+        };
+
+        let mut completed = self.completed.lock().unwrap();
+        completed.clear();
+        let mut corrupt = 0;
+        let mut mismatched = 0;
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            match serde_json::from_str::<CheckpointEntry>(line) {
+                Ok(entry) => {
+                    let matches = sources
+                        .get(entry.index)
+                        .is_some_and(|source| chunk_hash(&context.target_lang, source) == entry.hash);
+                    if matches {
+                        completed.insert(entry.index, entry.translated);
+                    } else {
+                        mismatched += 1;
+                    }
+                }
+                Err(_) => corrupt += 1,
+            }
+        }
+        if corrupt > 0 {
+            context.warn(format!("检查点文件 {} 中有 {} 行无法解析，已忽略", self.path.display(), corrupt));
+        }
+        if mismatched > 0 {
+            context.warn(format!(
+                "检查点文件 {} 中有 {} 条记录与当前原文不一致，已忽略",
+                self.path.display(),
+                mismatched
+            ));
+        }
+    }
+
+    /// 取出检查点中记录的块译文
+    pub(crate) fn take(&self, index: usize) -> Option<String> {
+        self.completed.lock().unwrap().remove(&index)
+    }
+
+    /// 将一个已完成的块追加到检查点文件，写入失败只记录警告
+    pub(crate) async fn record(&self, index: usize, source: &str, translated: &str, context: &CallContext) {
+        let entry = CheckpointEntry {
+            index,
+            hash: chunk_hash(&context.target_lang, source),
+            translated: translated.to_string(),
+        };
+        let mut line = serde_json::to_string(&entry).expect("checkpoint entries serialize to JSON");
+        line.push('\n');
+
+        let _guard = self.writer.lock().await;
+        let written = async {
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .await?;
+            file.write_all(line.as_bytes()).await?;
+            file.flush().await
+        };
+        if let Err(e) = written.await {
+            context.warn(format!("无法写入检查点文件 {}: {}", self.path.display(), e));
+        }
+    }
+}
+
+/// 目标语言和块原文的FNV-1a哈希，不依赖标准库哈希算法的实现，跨版本和跨进程稳定
+fn chunk_hash(target_lang: &str, source: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in target_lang.bytes().chain([0]).chain(source.bytes()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
@@ -0,0 +1,266 @@
+//! CSV模块
+//!
+//! 由 `csv` crate解析记录并定位每条记录在原文中的字节范围，只重新生成需要翻译的字段，
+//! 其他字段、引号风格、行结束符和空行逐字节保留。
+
+use crate::error::{Result, TranslationError};
+use crate::translator::TranslationService;
+use crate::types::{CsvColumn, CsvOptions, CsvTranslation};
+use csv::{QuoteStyle, ReaderBuilder, StringRecord, Terminator, WriterBuilder};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// CSV中的一条记录
+struct CsvRecord {
+    /// 记录在原文中的字节范围，不含行结束符
+    span: Range<usize>,
+    /// 记录之后的行结束符和空行
+    trailing: Range<usize>,
+    /// 记录起始的行号
+    line: u64,
+    /// 解析后的字段
+    fields: StringRecord,
+}
+
+impl TranslationService {
+    /// 翻译CSV中指定列的单元格
+    ///
+    /// `columns` 按表头名称或列序号指定需要翻译的列，表头行和其他列逐字节保留，
+    /// 重新生成的字段沿用原字段的引号风格（原字段带引号时总是加引号，否则只在需要时加引号）。
+    /// 设置 `append` 时原列不变，每个被翻译的列在行末追加一列译文，表头为 `{原表头}_{目标语言}`。
+    /// 所有单元格以分隔行打包，在长度预算内合并为尽可能少的请求，结果按原顺序写回。
+    /// 只含数字和符号的单元格不翻译。
+    ///
+    /// 字段数与第一行不一致的行以及单元格翻译失败的行原样保留，并以行号记录在
+    /// [`CsvTranslation::failed_rows`] 中，不会使整个文件失败。需要启用 `csv` 特性。
+    ///
+    /// # 返回
+    ///
+    /// * `Ok(CsvTranslation)` - 翻译后的CSV和逐行的失败记录
+    /// * `Err(TranslationError::ConfigError)` - 指定的列不存在，或未带表头时按名称指定列
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{CsvOptions, FnTranslator, TranslationServiceBuilder};
+    ///
+    /// let service = TranslationServiceBuilder::new()
+    ///     .enabled(true)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// # tokio_test::block_on(async {
+    /// let csv = "id,title\n1,Blue shirt\n";
+    /// let result = service.translate_csv(csv, &CsvOptions::new().column("title")).await.unwrap();
+    /// assert_eq!(result.content, "id,title\n1,BLUE SHIRT\n");
+    /// # });
+    /// ```
+    pub async fn translate_csv(&self, content: &str, options: &CsvOptions) -> Result<CsvTranslation> {
+        let records = read_records(content, options.delimiter)?;
+        let context = self.new_context(&options.translate_options)?;
+        let unchanged = CsvTranslation {
+            content: content.to_string(),
+            ..Default::default()
+        };
+        let Some(first) = records.first() else {
+            return Ok(unchanged);
+        };
+        let columns = resolve_columns(&options.columns, first, options.has_headers)?;
+        if !context.components.config.enabled || columns.is_empty() {
+            return Ok(unchanged);
+        }
+
+        let expected_fields = first.fields.len();
+        let data_start = usize::from(options.has_headers);
+        let mut failed_rows: HashMap<usize, String> = HashMap::new();
+        let mut cells = Vec::new();
+        let mut items = Vec::new();
+        for (index, record) in records.iter().enumerate().skip(data_start) {
+            if record.fields.len() != expected_fields {
+                failed_rows.insert(
+                    index,
+                    format!("字段数为 {}，与第一行的 {} 不一致", record.fields.len(), expected_fields),
+                );
+                continue;
+            }
+            if field_spans(&content[record.span.clone()], options.delimiter).len() != record.fields.len() {
+                failed_rows.insert(index, "无法在原文中定位字段".to_string());
+                continue;
+            }
+            for &column in &columns {
+                let cell = &record.fields[column];
+                if cell.chars().any(char::is_alphabetic) {
+                    cells.push((index, column));
+                    items.push(cell.to_string());
+                }
+            }
+        }
+
+        let mut translated: HashMap<(usize, usize), String> = HashMap::new();
+        for (&(index, column), result) in cells.iter().zip(self.translate_items(&items, true, &context).await) {
+            match result {
+                Ok(text) => {
+                    translated.insert((index, column), text);
+                }
+                Err(e) => {
+                    failed_rows
+                        .entry(index)
+                        .or_insert_with(|| format!("第 {} 列翻译失败: {}", column + 1, e));
+                }
+            }
+        }
+
+        let delimiter = char::from(options.delimiter);
+        let mut output = String::with_capacity(content.len());
+        // 第一条记录之前的空行原样保留
+        output.push_str(&content[..records[0].span.start]);
+        let mut cells_translated = 0;
+        for (index, record) in records.iter().enumerate() {
+            let raw = &content[record.span.clone()];
+            let editable = !failed_rows.contains_key(&index);
+
+            if index < data_start {
+                output.push_str(raw);
+                if options.append {
+                    for &column in &columns {
+                        let header = format!("{}_{}", &record.fields[column], context.target_lang);
+                        output.push(delimiter);
+                        output.push_str(&quote_field(&header, "", options.delimiter)?);
+                    }
+                }
+            } else if !editable {
+                output.push_str(raw);
+            } else if options.append {
+                output.push_str(raw);
+                for &column in &columns {
+                    let value = translated.get(&(index, column)).map_or(&record.fields[column], String::as_str);
+                    output.push(delimiter);
+                    output.push_str(&quote_field(value, "", options.delimiter)?);
+                }
+            } else {
+                for (column, span) in field_spans(raw, options.delimiter).into_iter().enumerate() {
+                    if column > 0 {
+                        output.push(delimiter);
+                    }
+                    let original = &raw[span];
+                    match translated.get(&(index, column)) {
+                        Some(value) => output.push_str(&quote_field(value, original, options.delimiter)?),
+                        None => output.push_str(original),
+                    }
+                }
+            }
+            if index >= data_start && editable {
+                cells_translated += columns.iter().filter(|&&column| translated.contains_key(&(index, column))).count();
+            }
+            output.push_str(&content[record.trailing.clone()]);
+        }
+
+        let mut failed_rows: Vec<(u64, String)> = failed_rows
+            .into_iter()
+            .map(|(index, reason)| (records[index].line, reason))
+            .collect();
+        failed_rows.sort();
+        Ok(CsvTranslation {
+            content: output,
+            cells_translated,
+            failed_rows,
+        })
+    }
+}
+
+/// 解析所有记录并确定每条记录在原文中的字节范围
+fn read_records(content: &str, delimiter: u8) -> Result<Vec<CsvRecord>> {
+    let mut reader = ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(content.as_bytes());
+    let mut records: Vec<CsvRecord> = Vec::new();
+    let mut fields = StringRecord::new();
+    loop {
+        let more = reader
+            .read_record(&mut fields)
+            .map_err(|e| TranslationError::ParseError(format!("无法解析CSV: {}", e)))?;
+        let position = fields.position().cloned();
+        if let Some(previous) = records.last_mut() {
+            let next = position.as_ref().filter(|_| more).map_or(content.len(), |p| p.byte() as usize);
+            let raw = &content[previous.span.start..next];
+            let body = raw.trim_end_matches(['\r', '\n']);
+            previous.span = previous.span.start..previous.span.start + body.len();
+            previous.trailing = previous.span.end..next;
+        }
+        if !more {
+            break;
+        }
+        let position = position.expect("records read from a reader carry a position");
+        let start = position.byte() as usize;
+        records.push(CsvRecord {
+            span: start..start,
+            trailing: start..start,
+            line: position.line(),
+            fields: fields.clone(),
+        });
+    }
+    Ok(records)
+}
+
+/// 将列的指定解析为列序号
+fn resolve_columns(columns: &[CsvColumn], first: &CsvRecord, has_headers: bool) -> Result<Vec<usize>> {
+    columns
+        .iter()
+        .map(|column| match column {
+            CsvColumn::Index(index) if *index < first.fields.len() => Ok(*index),
+            CsvColumn::Index(index) => Err(TranslationError::ConfigError(format!(
+                "CSV只有 {} 列，不存在序号为 {} 的列",
+                first.fields.len(),
+                index
+            ))),
+            CsvColumn::Name(name) if !has_headers => Err(TranslationError::ConfigError(format!(
+                "CSV没有表头，无法按名称 {:?} 指定列",
+                name
+            ))),
+            CsvColumn::Name(name) => first
+                .fields
+                .iter()
+                .position(|header| header == name)
+                .ok_or_else(|| TranslationError::ConfigError(format!("CSV表头中没有名为 {:?} 的列", name))),
+        })
+        .collect()
+}
+
+/// 按引号状态拆分原始记录，返回每个字段（含引号）的字节范围
+fn field_spans(raw: &str, delimiter: u8) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    for (index, &byte) in raw.as_bytes().iter().enumerate() {
+        if byte == b'"' {
+            in_quotes = !in_quotes;
+        } else if byte == delimiter && !in_quotes {
+            spans.push(start..index);
+            start = index + 1;
+        }
+    }
+    spans.push(start..raw.len());
+    spans
+}
+
+/// 由 `csv` crate生成字段，原字段带引号时总是加引号，否则只在需要时加引号
+fn quote_field(value: &str, original: &str, delimiter: u8) -> Result<String> {
+    let style = if original.starts_with('"') {
+        QuoteStyle::Always
+    } else {
+        QuoteStyle::Necessary
+    };
+    let mut writer = WriterBuilder::new()
+        .delimiter(delimiter)
+        .quote_style(style)
+        .terminator(Terminator::Any(b'\n'))
+        .from_writer(Vec::new());
+    let error = |e: String| TranslationError::ParseError(format!("无法生成CSV字段: {}", e));
+    writer.write_record([value]).map_err(|e| error(e.to_string()))?;
+    let mut field = writer.into_inner().map_err(|e| error(e.to_string()))?;
+    field.pop();
+    Ok(String::from_utf8(field).expect("CSV fields are written from valid UTF-8"))
+}
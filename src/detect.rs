@@ -0,0 +1,145 @@
+//! 语言检测模块
+//!
+//! 优先由翻译后端检测：以 `"auto"` 为源语言翻译一小段样本，读取响应中的源语言。
+//! 后端不返回源语言时，若启用了 `whatlang` 特性则使用内置的离线检测器。
+
+use crate::error::{Result, TranslationError};
+use crate::translator::{CallContext, Reply, TranslationService};
+use crate::types::{DetectedLanguage, TranslateOptions, TranslationConfig};
+
+/// 检测语言时发送的样本最大字符数
+const SAMPLE_CHARS: usize = 300;
+
+impl TranslationService {
+    /// 检测文本的语言
+    ///
+    /// 从文本的第一个非代码段中取最多300个字符作为样本。翻译已启用时，
+    /// 先以 `"auto"` 为源语言将样本发送给翻译后端并读取其检测到的源语言；
+    /// 后端未返回源语言（或翻译未启用）时，在启用 `whatlang` 特性的情况下改用离线检测。
+    ///
+    /// # 返回
+    ///
+    /// * `Ok(DetectedLanguage)` - 检测到的语言
+    /// * `Err(TranslationError)` - 没有可检测的文本、请求失败或无法识别语言
+    ///
+    /// # 示例
+    ///
+    /// ```rust,no_run
+    /// use markdown_translator::{TranslationService, TranslationConfig};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let config = TranslationConfig { enabled: true, target_lang: "zh".to_string(), ..Default::default() };
+    ///     let service = TranslationService::new(config);
+    ///
+    ///     let detected = service.detect_language("这篇文档已经是中文了。").await?;
+    ///     if detected.matches("zh") {
+    ///         println!("already Chinese (confidence {:.2})", detected.confidence);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn detect_language(&self, text: &str) -> Result<DetectedLanguage> {
+        let context = self.new_context(&TranslateOptions::default())?;
+        self.detect_with_context(text, &context).await
+    }
+
+    /// 在给定调用上下文中检测语言，请求计入该调用的指标和重试预算
+    pub(crate) async fn detect_with_context(&self, text: &str, context: &CallContext) -> Result<DetectedLanguage> {
+        let sample = self.detection_sample(text, &context.components.config);
+        if sample.trim().is_empty() {
+            return Err(TranslationError::Custom("没有可用于检测语言的文本".to_string()));
+        }
+
+        if context.components.config.enabled {
+            let context = CallContext {
+                source_lang: "auto".to_string(),
+                ..context.clone()
+            };
+            match self.translate_chunk_detecting(&sample, &context).await {
+                Ok(Reply { detected_source_lang: Some(code), .. }) => {
+                    return Ok(DetectedLanguage {
+                        code: code.to_lowercase(),
+                        confidence: 1.0,
+                    });
+                }
+                Ok(_) => {}
+                Err(e) if cfg!(feature = "whatlang") => {
+                    context.warn(format!("后端语言检测失败，改用离线检测: {}", e));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        detect_offline(&sample)
+    }
+
+    /// 取第一个非代码段的前 `SAMPLE_CHARS` 个字符作为检测样本
+    fn detection_sample(&self, text: &str, config: &TranslationConfig) -> String {
+        let segments = self.plan_segments(text, config.format, config);
+        let content = segments
+            .iter()
+            .find(|segment| !segment.is_code_block && !segment.content.trim().is_empty())
+            .map_or("", |segment| segment.content.as_str());
+        content.chars().take(SAMPLE_CHARS).collect()
+    }
+}
+
+/// 使用 whatlang 离线检测
+#[cfg(feature = "whatlang")]
+fn detect_offline(sample: &str) -> Result<DetectedLanguage> {
+    let info = whatlang::detect(sample)
+        .ok_or_else(|| TranslationError::Custom("无法识别文本语言".to_string()))?;
+    Ok(DetectedLanguage {
+        code: iso_639_1(info.lang()).to_string(),
+        confidence: info.confidence(),
+    })
+}
+
+/// 未启用离线检测时无法在后端之外检测语言
+#[cfg(not(feature = "whatlang"))]
+fn detect_offline(_sample: &str) -> Result<DetectedLanguage> {
+    Err(TranslationError::Custom(
+        "翻译后端未返回源语言，且未启用 whatlang 特性".to_string(),
+    ))
+}
+
+/// 将 whatlang 的 ISO 639-3 代码转换为翻译API使用的两字母代码，没有对应代码时保留原值
+#[cfg(feature = "whatlang")]
+fn iso_639_1(lang: whatlang::Lang) -> &'static str {
+    use whatlang::Lang;
+
+    match lang {
+        Lang::Eng => "en",
+        Lang::Cmn => "zh",
+        Lang::Jpn => "ja",
+        Lang::Kor => "ko",
+        Lang::Deu => "de",
+        Lang::Fra => "fr",
+        Lang::Spa => "es",
+        Lang::Ita => "it",
+        Lang::Por => "pt",
+        Lang::Rus => "ru",
+        Lang::Ukr => "uk",
+        Lang::Nld => "nl",
+        Lang::Pol => "pl",
+        Lang::Swe => "sv",
+        Lang::Dan => "da",
+        Lang::Nob => "nb",
+        Lang::Fin => "fi",
+        Lang::Ces => "cs",
+        Lang::Slk => "sk",
+        Lang::Slv => "sl",
+        Lang::Ell => "el",
+        Lang::Hun => "hu",
+        Lang::Ron => "ro",
+        Lang::Bul => "bg",
+        Lang::Est => "et",
+        Lang::Lav => "lv",
+        Lang::Lit => "lt",
+        Lang::Tur => "tr",
+        Lang::Ind => "id",
+        Lang::Ara => "ar",
+        other => other.code(),
+    }
+}
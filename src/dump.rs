@@ -0,0 +1,153 @@
+//! 请求转储模块
+//!
+//! 设置 `debug_dump_dir` 后，每次API请求写出一对文件：请求（地址、请求头和JSON请求体）
+//! 和响应（状态码、响应头和原始响应体），用于向上游报告后端的异常行为。
+//! 文件名由文档ID、块序号和尝试次数组成，总大小超过 `debug_dump_max_bytes` 后停止写入。
+
+use crate::config::SENSITIVE_HEADER_PARTS;
+use crate::types::{TranslationConfig, Verbosity};
+use reqwest::header::HeaderMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+/// 请求转储目录
+#[derive(Debug)]
+pub(crate) struct DebugDump {
+    /// 转储目录
+    dir: PathBuf,
+    /// 允许写入的总字节数
+    max_bytes: u64,
+    /// 已写入的字节数
+    written: AtomicU64,
+    /// 没有块序号的请求使用的序号
+    sequence: AtomicUsize,
+    /// 是否已经报告过达到大小上限
+    exhausted: AtomicBool,
+    /// 日志详细程度
+    verbosity: Verbosity,
+}
+
+impl DebugDump {
+    /// 按配置启用转储，未设置 `debug_dump_dir` 时返回 `Ok(None)`
+    ///
+    /// 目录必须已存在且可写，否则返回错误而不启用。
+    pub(crate) fn from_config(config: &TranslationConfig) -> Result<Option<Self>, String> {
+        let Some(dir) = &config.debug_dump_dir else {
+            return Ok(None);
+        };
+        let dir = PathBuf::from(dir);
+        check_writable(&dir)?;
+        Ok(Some(Self {
+            dir,
+            max_bytes: config.debug_dump_max_bytes,
+            written: AtomicU64::new(0),
+            sequence: AtomicUsize::new(0),
+            exhausted: AtomicBool::new(false),
+            verbosity: config.verbosity,
+        }))
+    }
+
+    /// 本次请求的文件名前缀：`<文档ID>-<块序号>-<尝试次数>`，没有块序号时为 `<文档ID>-call-<序号>`
+    pub(crate) fn stem(&self, document_id: &str, chunk: Option<(usize, usize)>) -> String {
+        match chunk {
+            Some((index, attempt)) => format!("{}-{}-{}", sanitize(document_id), index, attempt),
+            None => format!(
+                "{}-call-{}",
+                sanitize(document_id),
+                self.sequence.fetch_add(1, Ordering::Relaxed) + 1
+            ),
+        }
+    }
+
+    /// 写出请求，凭据类请求头的值被替换为 `***`
+    pub(crate) fn request(&self, stem: &str, url: &reqwest::Url, headers: &HeaderMap, body: &serde_json::Value) {
+        let dump = serde_json::json!({
+            "method": "POST",
+            "url": url.as_str(),
+            "headers": header_object(headers),
+            "body": body,
+        });
+        let content = serde_json::to_string_pretty(&dump).unwrap_or_default();
+        self.write(&format!("{}.request.json", stem), content.as_bytes());
+    }
+
+    /// 写出响应：状态行、响应头、空行和原始响应体
+    pub(crate) fn response(&self, stem: &str, status: reqwest::StatusCode, headers: &HeaderMap, body: &str) {
+        let mut content = format!("HTTP {}\n", status);
+        for (name, value) in headers {
+            content.push_str(&format!("{}: {}\n", name, masked(name.as_str(), value.to_str().unwrap_or("<非ASCII值>"))));
+        }
+        content.push('\n');
+        content.push_str(body);
+        self.write(&format!("{}.response.txt", stem), content.as_bytes());
+    }
+
+    /// 写出未收到响应的请求的错误
+    pub(crate) fn failure(&self, stem: &str, error: &str) {
+        self.write(&format!("{}.response.txt", stem), format!("未收到响应: {}\n", error).as_bytes());
+    }
+
+    /// 在大小上限内写出文件，写入失败只记录日志
+    fn write(&self, name: &str, content: &[u8]) {
+        let size = content.len() as u64;
+        let reserved = self
+            .written
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |written| {
+                (written + size <= self.max_bytes).then_some(written + size)
+            })
+            .is_ok();
+        if !reserved {
+            if !self.exhausted.swap(true, Ordering::Relaxed) {
+                self.verbosity.log(
+                    Verbosity::Warn,
+                    format_args!("请求转储已达到 {} 字节上限，不再写入 {}", self.max_bytes, self.dir.display()),
+                );
+            }
+            return;
+        }
+        let path = self.dir.join(name);
+        if let Err(e) = std::fs::write(&path, content) {
+            self.verbosity.log(Verbosity::Warn, format_args!("无法写入请求转储 {}: {}", path.display(), e));
+        }
+    }
+}
+
+/// 检查目录存在且可写：在其中创建并删除一个探测文件
+pub(crate) fn check_writable(dir: &Path) -> Result<(), String> {
+    if !dir.is_dir() {
+        return Err(format!("目录不存在: {}", dir.display()));
+    }
+    let probe = dir.join(format!(".markdown-translator-probe-{}", std::process::id()));
+    std::fs::write(&probe, b"").map_err(|e| format!("目录不可写: {}: {}", dir.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// 凭据类请求头的值替换为 `***`
+fn masked<'a>(name: &str, value: &'a str) -> &'a str {
+    let lower = name.to_ascii_lowercase();
+    if SENSITIVE_HEADER_PARTS.iter().any(|part| lower.contains(part)) {
+        "***"
+    } else {
+        value
+    }
+}
+
+/// 请求头转换为JSON对象
+fn header_object(headers: &HeaderMap) -> serde_json::Value {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = masked(name.as_str(), value.to_str().unwrap_or("<非ASCII值>"));
+            (name.to_string(), serde_json::Value::String(value.to_string()))
+        })
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+/// 调用方提供的文档ID中不能用于文件名的字符替换为 `_`
+fn sanitize(id: &str) -> String {
+    id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect()
+}
@@ -0,0 +1,199 @@
+//! 端点池模块
+//!
+//! 管理多个翻译API端点，每个端点拥有独立的速率限制器和熔断器，
+//! 主端点不可用时按顺序故障转移到备用端点。
+
+use crate::translator::RateLimiter;
+use crate::types::{parse_api_url, TranslationConfig};
+use reqwest::Url;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 熔断器
+///
+/// 连续失败次数达到阈值后打开熔断，在冷却期内不再向该端点派发请求；
+/// 冷却期结束后允许一次探测请求，成功则恢复，失败则重新打开。
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    /// 连续失败次数
+    consecutive_failures: AtomicU32,
+    /// 熔断打开的截止时间
+    open_until: Mutex<Option<Instant>>,
+    /// 触发熔断的连续失败次数，0表示禁用熔断
+    threshold: u32,
+    /// 熔断冷却时间
+    cooldown: Duration,
+}
+
+impl CircuitBreaker {
+    /// 创建新的熔断器
+    ///
+    /// # 参数
+    ///
+    /// * `threshold` - 触发熔断的连续失败次数，0表示禁用
+    /// * `cooldown` - 熔断打开后的冷却时间
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::CircuitBreaker;
+    /// use std::time::Duration;
+    ///
+    /// let breaker = CircuitBreaker::new(2, Duration::from_secs(30));
+    /// breaker.record_failure();
+    /// assert!(breaker.is_available());
+    /// breaker.record_failure();
+    /// assert!(!breaker.is_available());
+    /// breaker.record_success();
+    /// assert!(breaker.is_available());
+    ///
+    /// // 阈值为0时不熔断
+    /// let disabled = CircuitBreaker::new(0, Duration::from_secs(30));
+    /// (0..10).for_each(|_| disabled.record_failure());
+    /// assert!(disabled.is_available());
+    /// ```
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            consecutive_failures: AtomicU32::new(0),
+            open_until: Mutex::new(None),
+            threshold,
+            cooldown,
+        }
+    }
+
+    /// 当前是否允许派发请求
+    pub fn is_available(&self) -> bool {
+        match *self.open_until.lock().unwrap() {
+            Some(until) => Instant::now() >= until,
+            None => true,
+        }
+    }
+
+    /// 记录一次成功请求，重置失败计数并关闭熔断
+    pub fn record_success(&self) {
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+        *self.open_until.lock().unwrap() = None;
+    }
+
+    /// 记录一次失败请求，达到阈值时打开熔断
+    pub fn record_failure(&self) {
+        if self.threshold == 0 {
+            return;
+        }
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures >= self.threshold {
+            *self.open_until.lock().unwrap() = Some(Instant::now() + self.cooldown);
+        }
+    }
+}
+
+/// 翻译API端点
+///
+/// 每个端点拥有独立的速率限制器和熔断器，一个缓慢的端点不会拖慢其他端点。
+#[derive(Debug)]
+pub struct Endpoint {
+    /// 端点地址，已去除首尾空白
+    pub url: String,
+    /// 构建端点池时解析的地址，无法解析或协议不受支持时为错误原因
+    parsed: std::result::Result<Url, String>,
+    /// 端点专属的速率限制器（如配置了全局上限，取得本端点的许可后再经过全局限制器）
+    pub rate_limiter: RateLimiter,
+    /// 端点专属的熔断器
+    pub circuit_breaker: CircuitBreaker,
+}
+
+impl Endpoint {
+    /// 解析后的端点地址，地址无效时返回原因
+    pub fn parsed_url(&self) -> std::result::Result<&Url, &str> {
+        self.parsed.as_ref().map_err(String::as_str)
+    }
+}
+
+/// 端点池
+///
+/// 由主端点 `deeplx_api_url` 和 `fallback_api_urls` 中的备用端点组成。
+/// 第 N 次尝试会从第 N 个端点开始寻找熔断未打开的端点，
+/// 因此重试会自然地故障转移到下一个端点。
+#[derive(Debug, Clone)]
+pub struct EndpointPool {
+    endpoints: Vec<Arc<Endpoint>>,
+}
+
+impl EndpointPool {
+    /// 根据翻译配置构建端点池
+    ///
+    /// 配置了 `global_max_requests_per_second` 时，所有端点的限制器共享同一个全局上限。
+    pub fn from_config(config: &TranslationConfig) -> Self {
+        // `burst_size` 是单个端点的设置，全局上限按自身的速率取默认突发容量
+        let global = config
+            .global_max_requests_per_second
+            .map(|rate| RateLimiter::with_burst(rate, RateLimiter::default_burst(rate)));
+        let burst_size = config
+            .burst_size
+            .unwrap_or_else(|| RateLimiter::default_burst(config.max_requests_per_second));
+        let cooldown = Duration::from_secs(config.circuit_breaker_cooldown_secs);
+
+        let endpoints = std::iter::once(&config.deeplx_api_url)
+            .chain(config.fallback_api_urls.iter())
+            .map(|url| {
+                let limiter = RateLimiter::with_burst(config.max_requests_per_second, burst_size);
+                let rate_limiter = match &global {
+                    Some(global) => limiter.with_parent(global.clone()),
+                    None => limiter,
+                };
+                Arc::new(Endpoint {
+                    url: url.trim().to_string(),
+                    parsed: parse_api_url(url),
+                    rate_limiter,
+                    circuit_breaker: CircuitBreaker::new(config.circuit_breaker_threshold, cooldown),
+                })
+            })
+            .collect();
+
+        Self { endpoints }
+    }
+
+    /// 端点列表，第一个为主端点
+    pub fn endpoints(&self) -> &[Arc<Endpoint>] {
+        &self.endpoints
+    }
+
+    /// 为第 `attempt` 次尝试选择端点
+    ///
+    /// 从 `attempt % len` 处开始依次寻找熔断未打开的端点；
+    /// 全部熔断时仍返回起始端点，作为探测请求。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{EndpointPool, TranslationConfig};
+    ///
+    /// let pool = EndpointPool::from_config(&TranslationConfig {
+    ///     deeplx_api_url: "http://primary:1188/translate".to_string(),
+    ///     fallback_api_urls: vec!["http://backup:1188/translate".to_string()],
+    ///     circuit_breaker_threshold: 1,
+    ///     ..Default::default()
+    /// });
+    /// // 重试依次轮换端点
+    /// assert_eq!(pool.select(0).url, "http://primary:1188/translate");
+    /// assert_eq!(pool.select(1).url, "http://backup:1188/translate");
+    ///
+    /// // 主端点熔断后，首次尝试也转到备用端点
+    /// pool.endpoints()[0].circuit_breaker.record_failure();
+    /// assert_eq!(pool.select(0).url, "http://backup:1188/translate");
+    ///
+    /// // 全部熔断时返回起始端点作为探测请求
+    /// pool.endpoints()[1].circuit_breaker.record_failure();
+    /// assert_eq!(pool.select(0).url, "http://primary:1188/translate");
+    /// ```
+    pub fn select(&self, attempt: usize) -> Arc<Endpoint> {
+        let len = self.endpoints.len();
+        let start = attempt % len;
+        (0..len)
+            .map(|offset| &self.endpoints[(start + offset) % len])
+            .find(|endpoint| endpoint.circuit_breaker.is_available())
+            .unwrap_or(&self.endpoints[start])
+            .clone()
+    }
+}
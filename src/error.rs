@@ -3,54 +3,646 @@
 //! 定义翻译库中使用的错误类型和错误处理机制。
 
 use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 
 /// 翻译错误类型
 /// 
-/// 包含翻译过程中可能出现的各种错误情况。
+/// 包含翻译过程中可能出现的各种错误情况。底层错误（HTTP、JSON、TOML、IO以及 `ChunkFailed` 包装的错误）
+/// 可以通过 [`std::error::Error::source`] 逐层获取，`anyhow` 等库据此输出完整的错误链。
 /// 
 /// # 变体说明
 /// 
 /// * `Http` - HTTP请求错误
 /// * `Custom` - 自定义错误消息
 /// * `RateLimitError` - 速率限制错误
-/// * `ApiError` - API响应错误，包含错误代码和消息
-/// * `ParseError` - 解析错误
-#[derive(Debug)]
+/// * `ApiError` - API响应错误，包含状态码、`Retry-After`、速率限制响应头和响应体摘录
+/// * `AuthError` - API拒绝了请求的凭据（HTTP 401或403）
+/// * `ParseError` - 输入文档等内容的解析错误
+/// * `ResponseParseError` - 翻译API的响应无法解析，包含后端、期望的格式、`Content-Type` 和响应体摘录
+/// * `Json` - JSON序列化或反序列化错误
+/// * `Toml` - TOML反序列化错误
+/// * `DeadlineExceeded` - 超过整体时限，包含已完成的块数
+/// * `Timeout` - 单个HTTP请求超时
+/// * `ConnectionFailed` - 无法连接到翻译API
+/// * `Cancelled` - 调用被取消，包含已完成的块数
+/// * `ShuttingDown` - 服务正在关闭，不再接受新调用
+/// * `ChunkTimeout` - 单个块（含重试）超时
+/// * `InvalidLanguage` - 语言代码不在已知代码中，附带最接近的已知代码
+/// * `ConfigError` - 配置无效
+/// * `Io` - 文件读写错误，包含出错的路径
+/// * `FileTooLarge` - 文件超过 `max_file_size_bytes` 限制
+/// * `OutputExists` - 输出文件已存在且策略为 `OverwritePolicy::Error`
+/// * `InsideAsyncRuntime` - 在tokio运行时内部调用了同步接口
+/// * `QualityCheckFailed` - 重新翻译后译文仍未通过质量检查
+/// * `ChunkFailed` - 某个块翻译失败，包装底层错误并附带块序号、字节范围、内容预览和请求次数
+/// * `OfflineCacheMiss` - 离线模式下翻译缓存中没有该块的译文
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::TranslationError;
+/// use std::error::Error;
+///
+/// fn read_settings() -> Result<serde_json::Value, TranslationError> {
+///     let value = serde_json::from_str("{ not json")?;
+///     Ok(value)
+/// }
+///
+/// let error = read_settings().unwrap_err();
+/// assert!(matches!(error, TranslationError::Json(_)));
+/// assert!(error.source().unwrap().is::<serde_json::Error>());
+///
+/// // 没有路径信息的IO错误同样可以用 `?` 转换
+/// let error = TranslationError::from(std::io::Error::new(std::io::ErrorKind::NotFound, "missing"));
+/// assert_eq!(error.to_string(), "IO error: missing");
+///
+/// // ChunkFailed 的底层错误位于错误链的下一层，按类型匹配时使用 `root_cause`
+/// let error = TranslationError::ChunkFailed {
+///     index: 0,
+///     byte_range: None,
+///     preview: "<5 字节>".to_string(),
+///     attempts: 1,
+///     source: Box::new(TranslationError::AuthError { status: 401, message: "invalid key".to_string() }),
+/// };
+/// assert_eq!(error.source().unwrap().to_string(), "Authentication failed (401): invalid key");
+/// assert!(matches!(error.root_cause(), TranslationError::AuthError { status: 401, .. }));
+/// ```
+#[derive(Debug, thiserror::Error)]
 pub enum TranslationError {
     /// HTTP请求错误
-    Http(reqwest::Error),
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
     /// 自定义错误消息
+    #[error("{0}")]
     Custom(String),
     /// 速率限制错误
+    #[error("Rate limit error: {0}")]
     RateLimitError(String),
-    /// API响应错误
-    ApiError { 
-        /// 错误代码
-        code: i32, 
+    /// API响应错误，保留原始状态码、速率限制相关的响应头和截断后的响应体
+    #[error("API error {status}{}: {body_excerpt}", retry_hint(.retry_after))]
+    ApiError {
+        /// HTTP状态码；HTTP 200但响应体中带错误代码时为该代码
+        status: u16,
+        /// `Retry-After` 响应头给出的等待时间（只支持秒数形式）
+        retry_after: Option<std::time::Duration>,
+        /// `X-RateLimit-*` 响应头
+        rate_limit: RateLimitHeaders,
+        /// 响应体摘录：启用 `log_content` 时截断到 `error_body_max_chars` 个字符，否则只有长度和哈希值
+        body_excerpt: String,
+    },
+    /// API拒绝了请求的凭据，重试不会改变结果
+    #[error("Authentication failed ({status}): {message}")]
+    AuthError {
+        /// HTTP状态码或响应中的错误代码（401或403）
+        status: u16,
         /// 错误消息
-        message: String 
+        message: String,
     },
-    /// 解析错误
+    /// 输入文档（JSON、YAML、PO、notebook等）的解析错误
+    #[error("Parse error: {0}")]
     ParseError(String),
+    /// 无法从翻译API的成功响应中解析出译文，未写入文档
+    ///
+    /// 以 `<` 开头或 `Content-Type` 为HTML的响应（代理或网关的错误页）不会被当作纯文本译文。
+    #[error(
+        "Could not parse {backend} response: expected {expected}, content-type {}: {body_excerpt}",
+        .content_type.as_deref().unwrap_or("<none>")
+    )]
+    ResponseParseError {
+        /// 请求格式对应的后端（`DeepLX` 或 `dptrans`）
+        backend: String,
+        /// 期望的响应格式
+        expected: String,
+        /// 响应的 `Content-Type`
+        content_type: Option<String>,
+        /// 响应体摘录：启用 `log_content` 时截断到 `error_body_max_chars` 个字符，否则只有长度和哈希值
+        body_excerpt: String,
+    },
+    /// JSON序列化或反序列化错误
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    /// TOML反序列化错误
+    #[error("TOML error: {0}")]
+    Toml(#[from] toml::de::Error),
+    /// 超过整体时限
+    #[error("Deadline exceeded: {completed}/{total} chunks completed")]
+    DeadlineExceeded {
+        /// 截止时已完成的块数
+        completed: usize,
+        /// 总块数
+        total: usize,
+    },
+    /// 单个HTTP请求超过了配置的 `request_timeout_secs`，后端较慢时调大该值
+    #[error("Request timed out (request_timeout_secs = {})", .elapsed.as_secs())]
+    Timeout {
+        /// 配置的请求超时时间，不是实际测量的耗时
+        elapsed: std::time::Duration,
+    },
+    /// 无法连接到翻译API：连接被拒绝、DNS解析失败、TLS握手失败或超过 `connect_timeout_secs`
+    #[error("Connection failed: {message}")]
+    ConnectionFailed {
+        /// 包含底层原因（例如 `Connection refused`）的错误描述
+        message: String,
+        /// 原始的 `reqwest` 错误
+        source: reqwest::Error,
+    },
+    /// 调用被取消令牌取消
+    #[error("Translation cancelled: {completed}/{total} chunks completed")]
+    Cancelled {
+        /// 取消时已完成的块数
+        completed: usize,
+        /// 总块数
+        total: usize,
+    },
+    /// 服务正在关闭，不再接受新调用
+    #[error("Translation service is shutting down")]
+    ShuttingDown,
+    /// 单个块（含重试）超时
+    #[error("Chunk {index} timed out after {}s", .elapsed.as_secs())]
+    ChunkTimeout {
+        /// 块序号
+        index: usize,
+        /// 配置的块超时时间
+        elapsed: std::time::Duration,
+    },
+    /// 语言代码不在已知代码中，未发送请求
+    #[error("Invalid language code {given:?}{}", did_you_mean(.suggestions))]
+    InvalidLanguage {
+        /// 传入的语言代码
+        given: String,
+        /// 最接近的已知代码，由近到远，可能为空
+        suggestions: Vec<String>,
+    },
+    /// 配置无效
+    #[error("Config error: {0}")]
+    ConfigError(String),
+    /// 文件读写错误
+    ///
+    /// 由 `std::io::Error` 直接转换时 `path` 为空。
+    #[error("IO error{}: {source}", on_path(.path))]
+    Io {
+        /// 出错的文件路径
+        path: PathBuf,
+        /// 底层IO错误
+        source: std::io::Error,
+    },
+    /// 文件超过大小限制
+    #[error("File {} is {size} bytes, exceeding the {limit} byte limit", .path.display())]
+    FileTooLarge {
+        /// 文件路径
+        path: PathBuf,
+        /// 文件大小（字节）
+        size: u64,
+        /// 配置的大小上限（字节）
+        limit: u64,
+    },
+    /// 输出文件已存在
+    #[error("Output file {} already exists", .path.display())]
+    OutputExists {
+        /// 已存在的输出路径
+        path: PathBuf,
+    },
+    /// 在tokio运行时内部调用了同步接口，应改用异步服务
+    #[error("Blocking API called from within a tokio runtime; use the async TranslationService instead")]
+    InsideAsyncRuntime,
+    /// 重新翻译后译文仍未通过质量检查
+    #[error("Chunk {index} failed quality check after re-translation: {reason}")]
+    QualityCheckFailed {
+        /// 块序号
+        index: usize,
+        /// 最后一次被拒绝的原因
+        reason: String,
+    },
+    /// 某个块翻译失败，附带块在文档中的位置，便于用最小输入复现
+    #[error("Chunk {index}{} failed after {attempts} attempt(s): {source}\n  content: {preview}", in_range(.byte_range))]
+    ChunkFailed {
+        /// 块序号
+        index: usize,
+        /// 块在源文本中的字节范围，无法定位时为 `None`
+        byte_range: Option<Range<usize>>,
+        /// 块开头的内容：启用 `log_content` 时为前80个字符，否则只有长度和哈希值
+        preview: String,
+        /// 该块发出的请求次数（含重试）
+        attempts: usize,
+        /// 底层错误
+        source: Box<TranslationError>,
+    },
+    /// 离线模式下翻译缓存中没有该块的译文，未发送请求
+    #[error("Offline mode: no cached translation for this chunk")]
+    OfflineCacheMiss,
 }
 
-impl fmt::Display for TranslationError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+/// HTTP状态码是否值得重试：非4xx，以及408和429
+fn is_retryable_status(status: u16) -> bool {
+    !(400..500).contains(&status) || status == 408 || status == 429
+}
+
+/// 响应中与速率限制相关的 `X-RateLimit-*` 请求头，缺少或无法解析的为 `None`
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{RateLimitHeaders, TranslationError};
+/// use std::time::Duration;
+///
+/// let error = TranslationError::ApiError {
+///     status: 429,
+///     retry_after: Some(Duration::from_secs(30)),
+///     rate_limit: RateLimitHeaders { limit: Some(60), remaining: Some(0), reset: Some(30) },
+///     body_excerpt: "<17 字节，哈希 5f0c0d2a9b1e7c44>".to_string(),
+/// };
+/// assert_eq!(error.to_string(), "API error 429 (retry after 30s): <17 字节，哈希 5f0c0d2a9b1e7c44>");
+/// assert_eq!(error.status_code(), Some(429));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateLimitHeaders {
+    /// `X-RateLimit-Limit`：窗口内允许的请求数
+    pub limit: Option<u64>,
+    /// `X-RateLimit-Remaining`：窗口内剩余的请求数
+    pub remaining: Option<u64>,
+    /// `X-RateLimit-Reset`：原样保留，不同后端为距重置的秒数或Unix时间戳
+    pub reset: Option<u64>,
+}
+
+/// `ApiError` 错误消息中的重试提示，没有 `Retry-After` 时省略
+fn retry_hint(retry_after: &Option<std::time::Duration>) -> String {
+    match retry_after {
+        Some(delay) => format!(" (retry after {}s)", delay.as_secs()),
+        None => String::new(),
+    }
+}
+
+/// `InvalidLanguage` 错误消息中的建议部分，没有建议时省略
+fn did_you_mean(suggestions: &[String]) -> String {
+    match suggestions.is_empty() {
+        true => String::new(),
+        false => format!(" (did you mean: {}?)", suggestions.join(", ")),
+    }
+}
+
+/// `Io` 错误消息中的路径部分，路径为空时省略
+fn on_path(path: &Path) -> String {
+    match path.as_os_str().is_empty() {
+        true => String::new(),
+        false => format!(" on {}", path.display()),
+    }
+}
+
+/// `ChunkFailed` 错误消息中的字节范围部分，无法定位时省略
+fn in_range(range: &Option<Range<usize>>) -> String {
+    match range {
+        Some(range) => format!(" (bytes {}..{})", range.start, range.end),
+        None => String::new(),
+    }
+}
+
+impl TranslationError {
+    /// 该错误是否值得重试
+    ///
+    /// 网络错误、连接失败、超时、5xx、429和408可以重试；凭据错误、其余4xx客户端错误、
+    /// 解析错误、文件错误以及取消、超过整体时限不会因重试而改变结果。
+    /// [`retry_with_backoff`](crate::retry_with_backoff) 和服务内部的重试使用同一判断。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{RateLimitHeaders, TranslationError};
+    ///
+    /// let api_error = |status| TranslationError::ApiError {
+    ///     status,
+    ///     retry_after: None,
+    ///     rate_limit: RateLimitHeaders::default(),
+    ///     body_excerpt: String::new(),
+    /// };
+    /// assert!(api_error(503).is_retryable());
+    /// assert!(api_error(429).is_retryable());
+    /// assert!(!api_error(400).is_retryable());
+    /// assert!(!TranslationError::AuthError { status: 401, message: "invalid key".to_string() }.is_retryable());
+    /// ```
+    pub fn is_retryable(&self) -> bool {
         match self {
-            TranslationError::Http(e) => write!(f, "HTTP error: {}", e),
-            TranslationError::Custom(msg) => write!(f, "{}", msg),
-            TranslationError::RateLimitError(msg) => write!(f, "Rate limit error: {}", msg),
-            TranslationError::ApiError { code, message } => write!(f, "API error {}: {}", code, message),
-            TranslationError::ParseError(msg) => write!(f, "Parse error: {}", msg),
+            TranslationError::Http(error) => error.status().is_none_or(|status| is_retryable_status(status.as_u16())),
+            TranslationError::Custom(_)
+            | TranslationError::RateLimitError(_)
+            | TranslationError::Timeout { .. }
+            | TranslationError::ConnectionFailed { .. } => true,
+            TranslationError::AuthError { .. } => false,
+            TranslationError::ApiError { status, .. } => is_retryable_status(*status),
+            TranslationError::ParseError(_)
+            | TranslationError::ResponseParseError { .. }
+            | TranslationError::Json(_)
+            | TranslationError::Toml(_)
+            | TranslationError::DeadlineExceeded { .. }
+            | TranslationError::Cancelled { .. }
+            | TranslationError::ShuttingDown
+            | TranslationError::ChunkTimeout { .. }
+            | TranslationError::InvalidLanguage { .. }
+            | TranslationError::ConfigError(_)
+            | TranslationError::Io { .. }
+            | TranslationError::FileTooLarge { .. }
+            | TranslationError::OutputExists { .. }
+            | TranslationError::InsideAsyncRuntime
+            | TranslationError::QualityCheckFailed { .. }
+            | TranslationError::OfflineCacheMiss => false,
+            TranslationError::ChunkFailed { source, .. } => source.is_retryable(),
+        }
+    }
+
+    /// 该错误是否由速率限制引起（HTTP 429或本地速率限制）
+    ///
+    /// 调用方可以据此让整条流水线退避，而不只是重试单个请求。
+    pub fn is_rate_limited(&self) -> bool {
+        match self.root_cause() {
+            TranslationError::RateLimitError(_) => true,
+            error => error.status_code() == Some(429),
+        }
+    }
+
+    /// 该错误是否是API拒绝了凭据（HTTP 401或403）
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::TranslationError;
+    ///
+    /// let error = TranslationError::ChunkFailed {
+    ///     index: 0,
+    ///     byte_range: None,
+    ///     preview: "<5 字节>".to_string(),
+    ///     attempts: 1,
+    ///     source: Box::new(TranslationError::AuthError { status: 403, message: "forbidden".to_string() }),
+    /// };
+    /// assert!(error.is_auth_error());
+    /// assert!(!error.is_rate_limited());
+    /// assert_eq!(error.status_code(), Some(403));
+    /// ```
+    pub fn is_auth_error(&self) -> bool {
+        match self.root_cause() {
+            TranslationError::AuthError { .. } => true,
+            error => matches!(error.status_code(), Some(401 | 403)),
+        }
+    }
+
+    /// 错误对应的HTTP状态码，没有状态码（网络错误、超时、本地错误）时返回 `None`
+    ///
+    /// `ChunkFailed` 返回被包装错误的状态码。
+    pub fn status_code(&self) -> Option<u16> {
+        match self.root_cause() {
+            TranslationError::Http(error) => error.status().map(|status| status.as_u16()),
+            TranslationError::ApiError { status, .. } => Some(*status),
+            TranslationError::AuthError { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// 去掉 `ChunkFailed` 包装后的底层错误，其他错误返回自身
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{RateLimitHeaders, TranslationError};
+    ///
+    /// let error = TranslationError::ChunkFailed {
+    ///     index: 3,
+    ///     byte_range: Some(1024..2048),
+    ///     preview: "<1024 字节，哈希 5f0c0d2a9b1e7c44>".to_string(),
+    ///     attempts: 4,
+    ///     source: Box::new(TranslationError::ApiError {
+    ///         status: 500,
+    ///         retry_after: None,
+    ///         rate_limit: RateLimitHeaders::default(),
+    ///         body_excerpt: "internal error".to_string(),
+    ///     }),
+    /// };
+    /// assert!(matches!(error.root_cause(), TranslationError::ApiError { status: 500, .. }));
+    /// assert!(error.to_string().starts_with("Chunk 3 (bytes 1024..2048) failed after 4 attempt(s): API error 500"));
+    /// ```
+    pub fn root_cause(&self) -> &TranslationError {
+        match self {
+            TranslationError::ChunkFailed { source, .. } => source.root_cause(),
+            error => error,
+        }
+    }
+
+    /// 稳定的错误代码，供API使用者按类型处理错误
+    ///
+    /// 代码集合随 [`ERROR_CODES_VERSION`] 一起版本化：已有代码的含义不会改变，
+    /// 新增代码或改变映射时递增版本号。当前（版本1）的代码：
+    ///
+    /// | 代码 | 变体 |
+    /// |------|------|
+    /// | `http` | `Http` |
+    /// | `other` | `Custom` |
+    /// | `rate_limited` | `RateLimitError`，以及状态码为429的 `ApiError` |
+    /// | `api` | 其余 `ApiError` |
+    /// | `auth` | `AuthError` |
+    /// | `parse` | `ParseError` |
+    /// | `response_parse` | `ResponseParseError` |
+    /// | `json` | `Json` |
+    /// | `toml` | `Toml` |
+    /// | `deadline_exceeded` | `DeadlineExceeded` |
+    /// | `timeout` | `Timeout` |
+    /// | `connection_failed` | `ConnectionFailed` |
+    /// | `cancelled` | `Cancelled` |
+    /// | `shutting_down` | `ShuttingDown` |
+    /// | `chunk_timeout` | `ChunkTimeout` |
+    /// | `invalid_language` | `InvalidLanguage` |
+    /// | `config` | `ConfigError` |
+    /// | `io` | `Io` |
+    /// | `file_too_large` | `FileTooLarge` |
+    /// | `output_exists` | `OutputExists` |
+    /// | `inside_async_runtime` | `InsideAsyncRuntime` |
+    /// | `quality_check_failed` | `QualityCheckFailed` |
+    /// | `chunk_failed` | `ChunkFailed`（被包装错误的代码见 `root_cause().error_code()`） |
+    /// | `offline_cache_miss` | `OfflineCacheMiss` |
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::TranslationError;
+    ///
+    /// assert_eq!(TranslationError::RateLimitError("slow down".to_string()).error_code(), "rate_limited");
+    /// assert_eq!(TranslationError::AuthError { status: 401, message: "invalid key".to_string() }.error_code(), "auth");
+    /// ```
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            TranslationError::Http(_) => "http",
+            TranslationError::Custom(_) => "other",
+            TranslationError::RateLimitError(_) | TranslationError::ApiError { status: 429, .. } => "rate_limited",
+            TranslationError::ApiError { .. } => "api",
+            TranslationError::AuthError { .. } => "auth",
+            TranslationError::ParseError(_) => "parse",
+            TranslationError::ResponseParseError { .. } => "response_parse",
+            TranslationError::Json(_) => "json",
+            TranslationError::Toml(_) => "toml",
+            TranslationError::DeadlineExceeded { .. } => "deadline_exceeded",
+            TranslationError::Timeout { .. } => "timeout",
+            TranslationError::ConnectionFailed { .. } => "connection_failed",
+            TranslationError::Cancelled { .. } => "cancelled",
+            TranslationError::ShuttingDown => "shutting_down",
+            TranslationError::ChunkTimeout { .. } => "chunk_timeout",
+            TranslationError::InvalidLanguage { .. } => "invalid_language",
+            TranslationError::ConfigError(_) => "config",
+            TranslationError::Io { .. } => "io",
+            TranslationError::FileTooLarge { .. } => "file_too_large",
+            TranslationError::OutputExists { .. } => "output_exists",
+            TranslationError::InsideAsyncRuntime => "inside_async_runtime",
+            TranslationError::QualityCheckFailed { .. } => "quality_check_failed",
+            TranslationError::ChunkFailed { .. } => "chunk_failed",
+            TranslationError::OfflineCacheMiss => "offline_cache_miss",
         }
     }
 }
 
-impl std::error::Error for TranslationError {}
+/// [`TranslationError::error_code`] 代码集合的版本，序列化的错误中以 `version` 字段给出
+pub const ERROR_CODES_VERSION: u32 = 1;
+
+#[cfg(feature = "serde")]
+mod serialize {
+    use super::{TranslationError, ERROR_CODES_VERSION};
+    use serde::ser::{Serialize, SerializeStruct, Serializer};
+    use serde_json::{json, Value};
+
+    /// 序列化为稳定的JSON结构，每个变体都包含全部字段：
+    ///
+    /// * `version` - [`ERROR_CODES_VERSION`]
+    /// * `code` - [`TranslationError::error_code`]
+    /// * `message` - 错误消息，与 `Display` 相同
+    /// * `retryable` - [`TranslationError::is_retryable`]
+    /// * `status` - [`TranslationError::status_code`]，没有时为 `null`
+    /// * `details` - 变体携带的结构化信息（块序号、字节范围、`Retry-After` 等），没有时为空对象
+    /// * `source` - `ChunkFailed` 包装的错误，按同样的结构嵌套，其他变体为 `null`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{RateLimitHeaders, TranslationError};
+    /// use serde_json::json;
+    /// use std::time::Duration;
+    ///
+    /// let error = TranslationError::ChunkFailed {
+    ///     index: 3,
+    ///     byte_range: Some(1024..2048),
+    ///     preview: "<1024 字节，哈希 5f0c0d2a9b1e7c44>".to_string(),
+    ///     attempts: 4,
+    ///     source: Box::new(TranslationError::ApiError {
+    ///         status: 429,
+    ///         retry_after: Some(Duration::from_secs(30)),
+    ///         rate_limit: RateLimitHeaders { limit: Some(60), remaining: Some(0), reset: None },
+    ///         body_excerpt: "<17 字节，哈希 0123456789abcdef>".to_string(),
+    ///     }),
+    /// };
+    /// assert_eq!(
+    ///     serde_json::to_value(&error).unwrap(),
+    ///     json!({
+    ///         "version": 1,
+    ///         "code": "chunk_failed",
+    ///         "message": error.to_string(),
+    ///         "retryable": true,
+    ///         "status": 429,
+    ///         "details": {
+    ///             "index": 3,
+    ///             "byte_range": { "start": 1024, "end": 2048 },
+    ///             "attempts": 4,
+    ///             "preview": "<1024 字节，哈希 5f0c0d2a9b1e7c44>"
+    ///         },
+    ///         "source": {
+    ///             "version": 1,
+    ///             "code": "rate_limited",
+    ///             "message": "API error 429 (retry after 30s): <17 字节，哈希 0123456789abcdef>",
+    ///             "retryable": true,
+    ///             "status": 429,
+    ///             "details": {
+    ///                 "retry_after_secs": 30,
+    ///                 "rate_limit": { "limit": 60, "remaining": 0, "reset": null },
+    ///                 "body_excerpt": "<17 字节，哈希 0123456789abcdef>"
+    ///             },
+    ///             "source": null
+    ///         }
+    ///     })
+    /// );
+    /// ```
+    impl Serialize for TranslationError {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let source = match self {
+                TranslationError::ChunkFailed { source, .. } => Some(source.as_ref()),
+                _ => None,
+            };
+            let mut state = serializer.serialize_struct("TranslationError", 7)?;
+            state.serialize_field("version", &ERROR_CODES_VERSION)?;
+            state.serialize_field("code", self.error_code())?;
+            state.serialize_field("message", &self.to_string())?;
+            state.serialize_field("retryable", &self.is_retryable())?;
+            state.serialize_field("status", &self.status_code())?;
+            state.serialize_field("details", &details(self))?;
+            state.serialize_field("source", &source)?;
+            state.end()
+        }
+    }
+
+    /// 变体携带的结构化信息，时长以毫秒（`Retry-After` 以秒）表示
+    fn details(error: &TranslationError) -> Value {
+        match error {
+            TranslationError::ApiError { retry_after, rate_limit, body_excerpt, .. } => json!({
+                "retry_after_secs": retry_after.map(|delay| delay.as_secs()),
+                "rate_limit": {
+                    "limit": rate_limit.limit,
+                    "remaining": rate_limit.remaining,
+                    "reset": rate_limit.reset,
+                },
+                "body_excerpt": body_excerpt,
+            }),
+            TranslationError::ResponseParseError { backend, expected, content_type, body_excerpt } => json!({
+                "backend": backend,
+                "expected": expected,
+                "content_type": content_type,
+                "body_excerpt": body_excerpt,
+            }),
+            TranslationError::DeadlineExceeded { completed, total }
+            | TranslationError::Cancelled { completed, total } => json!({ "completed": completed, "total": total }),
+            TranslationError::Timeout { elapsed } => json!({ "elapsed_ms": elapsed.as_millis() as u64 }),
+            TranslationError::ChunkTimeout { index, elapsed } => {
+                json!({ "index": index, "elapsed_ms": elapsed.as_millis() as u64 })
+            }
+            TranslationError::InvalidLanguage { given, suggestions } => {
+                json!({ "given": given, "suggestions": suggestions })
+            }
+            TranslationError::Io { path, .. } | TranslationError::OutputExists { path } => {
+                json!({ "path": path.to_string_lossy() })
+            }
+            TranslationError::FileTooLarge { path, size, limit } => {
+                json!({ "path": path.to_string_lossy(), "size": size, "limit": limit })
+            }
+            TranslationError::QualityCheckFailed { index, reason } => json!({ "index": index, "reason": reason }),
+            TranslationError::ChunkFailed { index, byte_range, preview, attempts, .. } => json!({
+                "index": index,
+                "byte_range": byte_range.as_ref().map(|range| json!({ "start": range.start, "end": range.end })),
+                "attempts": attempts,
+                "preview": preview,
+            }),
+            TranslationError::Http(_)
+            | TranslationError::Custom(_)
+            | TranslationError::RateLimitError(_)
+            | TranslationError::AuthError { .. }
+            | TranslationError::ParseError(_)
+            | TranslationError::Json(_)
+            | TranslationError::Toml(_)
+            | TranslationError::ConnectionFailed { .. }
+            | TranslationError::ShuttingDown
+            | TranslationError::ConfigError(_)
+            | TranslationError::InsideAsyncRuntime
+            | TranslationError::OfflineCacheMiss => json!({}),
+        }
+    }
+}
 
-impl From<reqwest::Error> for TranslationError {
-    fn from(error: reqwest::Error) -> Self {
-        TranslationError::Http(error)
+impl From<std::io::Error> for TranslationError {
+    fn from(source: std::io::Error) -> Self {
+        TranslationError::Io {
+            path: PathBuf::new(),
+            source,
+        }
     }
 }
 
@@ -66,6 +658,42 @@ impl From<&str> for TranslationError {
     }
 }
 
+/// 配置中的一个问题
+///
+/// 由 `TranslationConfig::validate` 和 `RetryConfig::validate` 返回，`field` 是出问题的字段名，
+/// 列表中的元素带序号，例如 `fallback_api_urls[1]`。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// 字段名
+    pub field: String,
+    /// 问题描述
+    pub message: String,
+}
+
+impl ConfigError {
+    pub(crate) fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<Vec<ConfigError>> for TranslationError {
+    fn from(errors: Vec<ConfigError>) -> Self {
+        let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        TranslationError::ConfigError(messages.join("; "))
+    }
+}
+
 /// 翻译结果类型别名
 /// 
 /// 简化返回类型，使用 `TranslationError` 作为错误类型。
@@ -0,0 +1,117 @@
+//! 多语言翻译模块
+//!
+//! 将同一份文档一次分块，再把每个块同时发往多个目标语言，避免重复分块和代码块识别。
+
+use crate::bilingual::render_segment;
+use crate::error::{Result, TranslationError};
+use crate::translator::{locate_segments, overlap_contexts, TranslationService, MAX_CONCURRENT_CHUNKS};
+use crate::types::{FailurePolicy, TranslateOptions};
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+use tokio::time::timeout;
+
+impl TranslationService {
+    /// 将同一份文本翻译为多个目标语言
+    ///
+    /// 文本只分块一次，每个需要翻译的块按目标语言展开为多个请求，
+    /// 所有请求共享速率限制器和并发上限。每种语言使用独立的调用上下文，
+    /// 失败处理策略按语言分别应用：`KeepOriginal` 时某种语言的失败块只在该语言的译文中保留原文；
+    /// `Fail` 时任一语言的失败都会使整个调用返回错误。重复的目标语言只翻译一次。
+    ///
+    /// # 返回
+    ///
+    /// * `Ok(HashMap)` - 目标语言到译文的映射
+    /// * `Err(TranslationError)` - 按 `Fail` 策略失败、超过整体时限或服务已关闭
+    ///
+    /// # 示例
+    ///
+    /// ```rust,no_run
+    /// use markdown_translator::TranslationService;
+    ///
+    /// let service = TranslationService::builder().enabled(true).build().unwrap();
+    ///
+    /// # tokio_test::block_on(async {
+    /// let translations = service.translate_to_many("Hello", &["ja", "de", "fr"]).await.unwrap();
+    /// println!("{}", translations["ja"]);
+    /// # });
+    /// ```
+    pub async fn translate_to_many(&self, text: &str, targets: &[&str]) -> Result<HashMap<String, String>> {
+        let mut languages: Vec<&str> = Vec::with_capacity(targets.len());
+        for target in targets {
+            if !languages.contains(target) {
+                languages.push(target);
+            }
+        }
+
+        let contexts = languages
+            .iter()
+            .map(|target| self.new_context(&TranslateOptions::new().target_lang(*target)))
+            .collect::<Result<Vec<_>>>()?;
+        let Some(first) = contexts.first() else {
+            return Ok(HashMap::new());
+        };
+        if !first.components.config.enabled {
+            return Ok(languages.iter().map(|target| (target.to_string(), text.to_string())).collect());
+        }
+
+        let segments = self.plan_segments(text, first.format, &first.components.config);
+        let ranges = locate_segments(text, &segments);
+        let overlaps = overlap_contexts(&segments, &first.components.config.chunking);
+        let separator = first.format.separator();
+        let total = segments.len() * contexts.len();
+        let deadline = first.overall_timeout;
+        let on_error = first.on_error;
+
+        // 每个块依次展开到所有语言，使同一块的各语言请求相邻派发
+        let jobs = segments.iter().zip(&ranges).zip(&overlaps).enumerate().flat_map(|(index, ((segment, range), overlap))| {
+            contexts.iter().enumerate().map(move |(language, context)| {
+                (language, index, segment.clone(), range.clone(), overlap.clone(), context)
+            })
+        });
+
+        let mut outputs: Vec<Vec<Option<String>>> = vec![vec![None; segments.len()]; contexts.len()];
+        let mut completed = 0;
+        let outcome = {
+            let mut translated = stream::iter(jobs)
+                .map(|(language, index, segment, range, overlap, context)| async move {
+                    (language, self.translate_segment(index, segment, range, overlap, context).await)
+                })
+                .buffer_unordered(MAX_CONCURRENT_CHUNKS);
+            let collect = async {
+                while let Some((language, result)) = translated.next().await {
+                    let segment = result?;
+                    outputs[language][segment.index] = Some(render_segment(&segment, &contexts[language].output_mode));
+                    completed += 1;
+                }
+                Ok::<_, TranslationError>(())
+            };
+            match deadline {
+                Some(limit) => timeout(limit, collect).await,
+                None => Ok(collect.await),
+            }
+        };
+
+        match outcome {
+            Ok(result) => result?,
+            Err(_) if on_error == FailurePolicy::Fail => {
+                return Err(TranslationError::DeadlineExceeded { completed, total });
+            }
+            Err(_) => {}
+        }
+
+        // 超过整体时限且策略为保留原文时，未完成的块使用原文
+        Ok(languages
+            .iter()
+            .zip(outputs)
+            .map(|(target, chunks)| {
+                let document = chunks
+                    .into_iter()
+                    .zip(&segments)
+                    .map(|(chunk, segment)| chunk.unwrap_or_else(|| segment.content.clone()))
+                    .collect::<Vec<_>>()
+                    .join(separator);
+                (target.to_string(), document)
+            })
+            .collect())
+    }
+}
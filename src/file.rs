@@ -0,0 +1,800 @@
+//! 文件翻译模块
+//!
+//! 封装读取文件、翻译、写回文件的流程：保留UTF-8 BOM和换行风格，
+//! 并通过临时文件加重命名的方式原子地写出结果；以及按glob过滤的递归目录翻译。
+
+use crate::error::{Result, TranslationError};
+use crate::manifest::{self, Manifest, ManifestEntry, MANIFEST_FILE};
+use crate::translator::TranslationService;
+use crate::segment::frontmatter_end;
+use crate::types::{DirOptions, DirReport, FileReport, LanguageOutput, OutputNaming, OverwritePolicy, TranslateOptions, TranslationConfig};
+use futures::stream::{self, StreamExt};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+/// UTF-8 字节顺序标记
+const UTF8_BOM: &str = "\u{feff}";
+
+/// 将IO错误附上出错的路径
+pub(crate) fn io_error(path: &Path) -> impl FnOnce(std::io::Error) -> TranslationError + '_ {
+    move |source| TranslationError::Io {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+/// 在原文件名后追加后缀，例如 `README.md` -> `README.md.bak`
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// 是否为Jupyter notebook，按 `.ipynb` 扩展名判断
+fn is_notebook(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("ipynb"))
+}
+
+/// 前置元数据中 `key` 声明的目标语言
+///
+/// 支持YAML（`---`）的 `key: [ja, ko]`、`key: ja` 和块列表，以及TOML（`+++`）的 `key = ["ja", "ko"]`，
+/// 只识别顶层的键。没有前置元数据、没有该键或值为空时返回 `None`。
+fn declared_languages(text: &str, key: &str) -> Option<Vec<String>> {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let end = frontmatter_end(&lines)? - 1;
+    let separator = if lines[0].trim_end() == "+++" { '=' } else { ':' };
+    let index = (1..end).find(|&i| {
+        !lines[i].starts_with(char::is_whitespace)
+            && lines[i]
+                .split_once(separator)
+                .is_some_and(|(name, _)| name.trim().trim_matches(['"', '\'']) == key)
+    })?;
+    let value = lines[index].split_once(separator)?.1.trim();
+
+    let values: Vec<&str> = if value.is_empty() {
+        lines[index + 1..end]
+            .iter()
+            .map_while(|line| line.trim().strip_prefix('-'))
+            .collect()
+    } else {
+        value.trim_start_matches('[').trim_end_matches(']').split(',').collect()
+    };
+    let languages: Vec<String> = values
+        .into_iter()
+        .map(|value| value.trim().trim_matches(['"', '\'']).to_string())
+        .filter(|value| !value.is_empty())
+        .collect();
+    (!languages.is_empty()).then_some(languages)
+}
+
+/// 按文件名模式生成某种语言的输出路径，例如 `guide.md` -> `guide.ja.md`
+fn language_output(output: &Path, pattern: &str, lang: &str) -> PathBuf {
+    let stem = output.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+    let extension = output.extension().map(|ext| ext.to_string_lossy()).unwrap_or_default();
+    let name = pattern
+        .replace("{stem}", &stem)
+        .replace("{lang}", lang)
+        .replace("{ext}", &extension);
+    output.with_file_name(name.trim_end_matches('.'))
+}
+
+/// 输出路径的来源
+#[derive(Clone, Copy)]
+enum OutputTarget<'a> {
+    /// 调用方给出的输出路径，声明多种语言时按 `target_lang_file_pattern` 生成各语言的路径
+    Path(&'a Path),
+    /// 按命名规则由输入路径生成；目录翻译时 `rebase` 为源目录和目标目录，生成的路径从源目录换到目标目录下
+    Named {
+        naming: &'a OutputNaming,
+        rebase: Option<(&'a Path, &'a Path)>,
+    },
+}
+
+impl OutputTarget<'_> {
+    /// `input` 翻译为 `lang`（`None` 表示配置的目标语言）时的输出路径
+    fn path(&self, input: &Path, lang: Option<&str>, config: &TranslationConfig) -> Result<PathBuf> {
+        let (naming, rebase) = match *self {
+            OutputTarget::Path(output) => {
+                return Ok(match lang {
+                    Some(lang) => language_output(output, &config.target_lang_file_pattern, lang),
+                    None => output.to_path_buf(),
+                });
+            }
+            OutputTarget::Named { naming, rebase } => (naming, rebase),
+        };
+        let path = match (naming, lang) {
+            (OutputNaming::Explicit(path), Some(lang)) => language_output(path, &config.target_lang_file_pattern, lang),
+            (naming, lang) => naming.resolve(input, lang.unwrap_or(&config.target_lang))?,
+        };
+        let path = match rebase {
+            Some((src, dst)) => dst.join(path.strip_prefix(src).map_err(|_| {
+                TranslationError::ConfigError(format!("命名规则生成的路径 {} 不在源目录 {} 下", path.display(), src.display()))
+            })?),
+            None => path,
+        };
+        if path == input {
+            return Err(TranslationError::ConfigError(format!(
+                "命名规则生成的输出路径与输入 {} 相同",
+                input.display()
+            )));
+        }
+        Ok(path)
+    }
+}
+
+/// 按策略检查输出是否可以写入，返回 `false` 表示应跳过
+async fn check_overwrite(path: &Path, overwrite: OverwritePolicy) -> Result<bool> {
+    if overwrite == OverwritePolicy::Overwrite || !fs::try_exists(path).await.map_err(io_error(path))? {
+        return Ok(true);
+    }
+    match overwrite {
+        OverwritePolicy::Error => Err(TranslationError::OutputExists { path: path.to_path_buf() }),
+        _ => Ok(false),
+    }
+}
+
+/// 目录翻译中单个文件的处理结果
+enum DirEntryOutcome {
+    /// 已翻译，附带源文本字符数、因与源语言相同而跳过的语言，以及所有块都翻译成功时写入清单的记录
+    Translated {
+        characters: usize,
+        skipped_languages: Vec<String>,
+        record: Option<ManifestEntry>,
+    },
+    /// 与翻译清单一致，已跳过；附带沿用的记录
+    UpToDate(ManifestEntry),
+    /// 已原样复制
+    Copied,
+    /// 输出已存在，或声明的语言都与源语言相同，已跳过；附带因与源语言相同而跳过的语言
+    Skipped(Vec<String>),
+}
+
+/// 目录翻译中的一个文件及比较翻译清单所需的信息
+#[derive(Clone, Copy)]
+struct DirEntry<'a> {
+    /// 源文件
+    input: &'a Path,
+    /// 未设置命名规则时的输出路径
+    output: &'a Path,
+    /// 源目录中的相对路径，作为清单中的键
+    relative: &'a str,
+    /// 目标目录
+    dst: &'a Path,
+    /// 上次运行的翻译清单
+    manifest: &'a Manifest,
+    /// 本次运行的配置哈希
+    config_hash: &'a str,
+}
+
+/// 由glob列表构建匹配器，路径分隔符不会被 `*` 匹配
+pub(crate) fn build_glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| TranslationError::ConfigError(format!("无效的glob {:?}: {}", pattern, e)))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| TranslationError::ConfigError(format!("无效的glob: {}", e)))
+}
+
+/// 递归列出 `root` 下的所有文件（相对 `root` 的路径，按路径排序），跳过目标目录 `skip`
+async fn collect_files(root: &Path, skip: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let mut entries = fs::read_dir(&dir).await.map_err(io_error(&dir))?;
+        while let Some(entry) = entries.next_entry().await.map_err(io_error(&dir))? {
+            let path = entry.path();
+            let metadata = fs::metadata(&path).await.map_err(io_error(&path))?;
+            if metadata.is_dir() {
+                if path != skip {
+                    pending.push(path);
+                }
+            } else if metadata.is_file() {
+                if let Ok(relative) = path.strip_prefix(root) {
+                    files.push(relative.to_path_buf());
+                }
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+impl TranslationService {
+    /// 翻译文件并写入输出路径
+    ///
+    /// 读取 `input`（必须为UTF-8编码），翻译后写入 `output`。输入带有UTF-8 BOM
+    /// 或使用CRLF换行时，输出保持相同的格式。写入时先写同目录下的临时文件再重命名，
+    /// 翻译或写入失败不会留下不完整的输出文件。
+    /// 扩展名为 `.ipynb` 的文件按 [`translate_notebook`](Self::translate_notebook) 只翻译Markdown单元格。
+    ///
+    /// 配置了 `target_langs_key` 且文件的前置元数据声明了该键（例如 `translate_to: [ja, ko]`）时，
+    /// 文件被翻译为声明的每种语言，按 `target_lang_file_pattern` 在 `output` 旁各写出一个文件
+    /// （默认 `guide.md` -> `guide.ja.md`），结果记录在报告的 `languages` 中；与 `source_lang` 相同的语言
+    /// 不翻译，记录在 `skipped_languages` 中。未声明该键的文件照常翻译为 `target_lang`。
+    ///
+    /// # 返回
+    ///
+    /// * `Ok(FileReport)` - 翻译成功
+    /// * `Err(TranslationError::FileTooLarge)` - 文件超过 `max_file_size_bytes`
+    /// * `Err(TranslationError::Io)` - 读写失败或文件不是UTF-8编码
+    /// * `Err(TranslationError::ParseError)` - `.ipynb` 文件不是合法的notebook
+    /// * `Err(TranslationError)` - 翻译过程中的其他错误
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{TranslationService, TranslationConfig};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let dir = std::env::temp_dir().join("markdown-translator-doc-translate-file");
+    ///     std::fs::create_dir_all(&dir)?;
+    ///     let input = dir.join("input.md");
+    ///     let output = dir.join("output.md");
+    ///     std::fs::write(&input, "\u{feff}# Title\r\n\r\nHello\r\n")?;
+    ///
+    ///     let service = TranslationService::new(TranslationConfig::default());
+    ///     let report = service.translate_file(&input, &output).await?;
+    ///
+    ///     assert!(report.has_bom && report.crlf);
+    ///     assert_eq!(std::fs::read(&output)?, std::fs::read(&input)?);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// 按前置元数据声明的语言输出：
+    ///
+    /// ```rust
+    /// use markdown_translator::TranslationService;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let dir = std::env::temp_dir().join("markdown-translator-doc-translate-file-languages");
+    ///     std::fs::create_dir_all(&dir)?;
+    ///     let input = dir.join("guide.md");
+    ///     std::fs::write(&input, "---\ntranslate_to: [ja, ko, en]\n---\n# Guide\n")?;
+    ///
+    ///     let service = TranslationService::builder()
+    ///         .configure(|config| {
+    ///             config.source_lang = "en".to_string();
+    ///             config.target_langs_key = Some("translate_to".to_string());
+    ///         })
+    ///         .build()?;
+    ///     let report = service.translate_file(&input, &dir.join("guide.md")).await?;
+    ///
+    ///     let outputs: Vec<_> = report.languages.iter().map(|output| output.lang.as_str()).collect();
+    ///     assert_eq!(outputs, ["ja", "ko"]);
+    ///     assert_eq!(report.skipped_languages, ["en"]);
+    ///     assert!(dir.join("guide.ja.md").exists() && dir.join("guide.ko.md").exists());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn translate_file(&self, input: &Path, output: &Path) -> Result<FileReport> {
+        self.translate_file_impl(input, OutputTarget::Path(output), None, true, OverwritePolicy::Overwrite)
+            .await
+            .map(|report| report.expect("overwriting never skips outputs"))
+    }
+
+    /// 按命名规则翻译文件
+    ///
+    /// 与 [`translate_file`](Self::translate_file) 相同，但输出路径由 `naming` 根据输入路径和目标语言生成，
+    /// 例如 `LangSuffix` 将 `README.md` 翻译为 `README.zh.md`，按需创建输出目录。
+    /// 文件在前置元数据中声明了目标语言时，每种语言按同一规则各生成一个输出路径
+    /// （`Explicit` 按 `target_lang_file_pattern` 生成）。
+    ///
+    /// 输出已存在时按 `overwrite` 处理：`Overwrite` 覆盖，`Skip` 跳过（所有输出都已存在时返回 `Ok(None)`），
+    /// `Error` 返回 `TranslationError::OutputExists`，不会写出任何文件。
+    ///
+    /// # 返回
+    ///
+    /// * `Ok(Some(FileReport))` - 翻译成功
+    /// * `Ok(None)` - 策略为 `Skip` 且所有输出都已存在
+    /// * `Err(TranslationError::OutputExists)` - 策略为 `Error` 且输出已存在
+    /// * `Err(TranslationError::ConfigError)` - 无法按命名规则生成输出路径，或生成的路径与输入相同
+    /// * 其余错误同 [`translate_file`](Self::translate_file)
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{OutputNaming, OverwritePolicy, TranslationError, TranslationService, TranslationConfig};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let dir = std::env::temp_dir().join("markdown-translator-doc-translate-file-named");
+    ///     let _ = std::fs::remove_dir_all(&dir);
+    ///     std::fs::create_dir_all(&dir)?;
+    ///     let input = dir.join("v1.2.notes.md");
+    ///     std::fs::write(&input, "# Notes")?;
+    ///
+    ///     let service = TranslationService::new(TranslationConfig::default());
+    ///     let report = service
+    ///         .translate_file_named(&input, &OutputNaming::LangSuffix, OverwritePolicy::Error)
+    ///         .await?
+    ///         .unwrap();
+    ///     assert_eq!(report.output, dir.join("v1.2.notes.zh.md"));
+    ///
+    ///     // 输出已存在
+    ///     let again = service.translate_file_named(&input, &OutputNaming::LangSuffix, OverwritePolicy::Error).await;
+    ///     assert!(matches!(again, Err(TranslationError::OutputExists { .. })));
+    ///     let skipped = service.translate_file_named(&input, &OutputNaming::LangSuffix, OverwritePolicy::Skip).await?;
+    ///     assert!(skipped.is_none());
+    ///
+    ///     // 镜像到语言目录，按需创建目录
+    ///     let naming = OutputNaming::LangSubdir { base: dir.clone() };
+    ///     let report = service.translate_file_named(&input, &naming, OverwritePolicy::Error).await?.unwrap();
+    ///     assert_eq!(report.output, dir.join("zh").join("v1.2.notes.md"));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn translate_file_named(
+        &self,
+        input: &Path,
+        naming: &OutputNaming,
+        overwrite: OverwritePolicy,
+    ) -> Result<Option<FileReport>> {
+        let target = OutputTarget::Named { naming, rebase: None };
+        self.translate_file_impl(input, target, None, true, overwrite).await
+    }
+
+    /// 原地翻译文件
+    ///
+    /// 与 [`translate_file`](Self::translate_file) 相同，但将结果写回原文件。
+    /// `backup` 为 `true` 时先将原文件复制为同目录下的 `<文件名>.bak`。
+    ///
+    /// # 示例
+    ///
+    /// ```rust,no_run
+    /// use markdown_translator::{TranslationService, TranslationConfig};
+    /// use std::path::Path;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let service = TranslationService::new(TranslationConfig::default());
+    ///
+    ///     let report = service.translate_file_in_place(Path::new("README.md"), true).await?;
+    ///     println!("backup written to {:?}", report.backup);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn translate_file_in_place(&self, path: &Path, backup: bool) -> Result<FileReport> {
+        let backup = backup.then(|| with_suffix(path, ".bak"));
+        self.translate_file_impl(path, OutputTarget::Path(path), backup, false, OverwritePolicy::Overwrite)
+            .await
+            .map(|report| report.expect("overwriting never skips outputs"))
+    }
+
+    /// 递归翻译目录
+    ///
+    /// 将 `src` 下匹配 `include`、且不匹配 `exclude` 的文件逐个通过
+    /// [`translate_file`](Self::translate_file) 翻译到 `dst` 下的相同相对路径，
+    /// 按需创建子目录。最多同时翻译 `file_concurrency` 个文件，
+    /// 每个文件内部的块并发不受影响，所有文件共享同一个速率限制器。
+    ///
+    /// 文件在前置元数据中声明了目标语言时按 [`translate_file`](Self::translate_file) 的规则为每种语言各写出一个文件，
+    /// 因与源语言相同而跳过的语言记录在报告的 `skipped_languages` 中，所有声明的语言都被跳过的文件计入 `files_skipped`。
+    ///
+    /// 单个文件失败只记录在报告的 `failed` 中，不会影响其余文件；
+    /// 设置 `fail_fast` 时第一个失败的文件会使整个调用返回该错误。
+    ///
+    /// # 返回
+    ///
+    /// * `Ok(DirReport)` - 各类文件数、字符数与耗时
+    /// * `Err(TranslationError::ConfigError)` - glob无效
+    /// * `Err(TranslationError::Io)` - 无法遍历源目录
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{DirOptions, TranslationService, TranslationConfig};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let root = std::env::temp_dir().join("markdown-translator-doc-translate-dir");
+    ///     let _ = std::fs::remove_dir_all(&root);
+    ///     let (src, dst) = (root.join("src"), root.join("dst"));
+    ///     std::fs::create_dir_all(src.join("guide"))?;
+    ///     std::fs::write(src.join("index.md"), "# Index")?;
+    ///     std::fs::write(src.join("guide/intro.md"), "# Intro")?;
+    ///     std::fs::write(src.join("logo.svg"), "<svg/>")?;
+    ///
+    ///     let service = TranslationService::new(TranslationConfig::default());
+    ///     let options = DirOptions::new().copy_unmatched(true);
+    ///     let report = service.translate_dir(&src, &dst, &options).await?;
+    ///
+    ///     assert_eq!(report.files_translated, 2);
+    ///     assert_eq!(report.files_copied, 1);
+    ///     assert!(dst.join("guide/intro.md").exists());
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// 按命名规则镜像到目标目录下的语言目录，源目录根下的文件同样镜像：
+    ///
+    /// ```rust
+    /// use markdown_translator::{DirOptions, OutputNaming, TranslationService, TranslationConfig};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let root = std::env::temp_dir().join("markdown-translator-doc-translate-dir-naming");
+    ///     let _ = std::fs::remove_dir_all(&root);
+    ///     let (src, dst) = (root.join("docs"), root.join("site"));
+    ///     std::fs::create_dir_all(src.join("guide"))?;
+    ///     std::fs::write(src.join("index.md"), "# Index")?;
+    ///     std::fs::write(src.join("guide/v2.0.md"), "# Release")?;
+    ///
+    ///     let service = TranslationService::new(TranslationConfig::default());
+    ///     let options = DirOptions::new().naming(OutputNaming::LangSubdir { base: src.clone() });
+    ///     service.translate_dir(&src, &dst, &options).await?;
+    ///     assert!(dst.join("zh/index.md").exists());
+    ///     assert!(dst.join("zh/guide/v2.0.md").exists());
+    ///
+    ///     let options = DirOptions::new().naming(OutputNaming::LangSuffix);
+    ///     service.translate_dir(&src, &dst, &options).await?;
+    ///     assert!(dst.join("index.zh.md").exists());
+    ///     assert!(dst.join("guide/v2.0.zh.md").exists());
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// 目标目录中的 `.translation-manifest.json` 记录每个文件的源内容哈希、配置哈希和输出路径，
+    /// 再次运行时未变化且输出仍存在的文件计入 `files_up_to_date`，不再翻译。有块保留原文的文件不写入清单；
+    /// 清单损坏时重新生成，`force` 忽略清单：
+    ///
+    /// ```rust
+    /// use markdown_translator::{DirOptions, TranslationService, TranslationConfig};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let root = std::env::temp_dir().join("markdown-translator-doc-translate-dir-manifest");
+    ///     let _ = std::fs::remove_dir_all(&root);
+    ///     let (src, dst) = (root.join("src"), root.join("dst"));
+    ///     std::fs::create_dir_all(&src)?;
+    ///     std::fs::write(src.join("a.md"), "# A")?;
+    ///     std::fs::write(src.join("b.md"), "# B")?;
+    ///
+    ///     let service = TranslationService::new(TranslationConfig::default());
+    ///     let options = DirOptions::new();
+    ///     assert_eq!(service.translate_dir(&src, &dst, &options).await?.files_translated, 2);
+    ///     assert!(dst.join(".translation-manifest.json").exists());
+    ///
+    ///     // 只有修改过的文件和缺少输出的文件重新翻译
+    ///     std::fs::write(src.join("a.md"), "# A, revised")?;
+    ///     let report = service.translate_dir(&src, &dst, &options).await?;
+    ///     assert_eq!((report.files_translated, report.files_up_to_date), (1, 1));
+    ///     std::fs::remove_file(dst.join("b.md"))?;
+    ///     let report = service.translate_dir(&src, &dst, &options).await?;
+    ///     assert_eq!((report.files_translated, report.files_up_to_date), (1, 1));
+    ///
+    ///     // 影响译文的配置变化后全部重新翻译
+    ///     let japanese = TranslationService::new(TranslationConfig { target_lang: "ja".to_string(), ..Default::default() });
+    ///     assert_eq!(japanese.translate_dir(&src, &dst, &options).await?.files_translated, 2);
+    ///
+    ///     // 损坏的清单被重新生成
+    ///     std::fs::write(dst.join(".translation-manifest.json"), "{ not json")?;
+    ///     assert_eq!(japanese.translate_dir(&src, &dst, &options).await?.files_translated, 2);
+    ///     assert_eq!(japanese.translate_dir(&src, &dst, &options).await?.files_up_to_date, 2);
+    ///
+    ///     let report = japanese.translate_dir(&src, &dst, &options.clone().force(true)).await?;
+    ///     assert_eq!((report.files_translated, report.files_up_to_date), (2, 0));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn translate_dir(&self, src: &Path, dst: &Path, options: &DirOptions) -> Result<DirReport> {
+        let started = Instant::now();
+        if let Some(OutputNaming::Explicit(path)) = &options.naming {
+            return Err(TranslationError::ConfigError(format!(
+                "目录翻译不能使用固定的输出路径 {}",
+                path.display()
+            )));
+        }
+        let include = build_glob_set(&options.include)?;
+        let exclude = build_glob_set(&options.exclude)?;
+        let components = self.components();
+        let manifest = match options.force {
+            true => Manifest::default(),
+            false => Manifest::load(dst, components.config.verbosity).await,
+        };
+        let config_hash = manifest::config_hash(&components, &format!("{:?}", options.naming));
+        let (manifest, config_hash) = (&manifest, config_hash.as_str());
+
+        let jobs = collect_files(src, dst).await?.into_iter().filter_map(|relative| {
+            let matched = include.is_match(&relative) && !exclude.is_match(&relative);
+            (matched || options.copy_unmatched).then_some((relative, matched))
+        });
+
+        let mut outcomes = stream::iter(jobs)
+            .map(|(relative, matched)| async move {
+                let input = src.join(&relative);
+                let output = dst.join(&relative);
+                let target = match &options.naming {
+                    Some(naming) => OutputTarget::Named {
+                        naming,
+                        rebase: Some((src, dst)),
+                    },
+                    None => OutputTarget::Path(&output),
+                };
+                let entry = DirEntry {
+                    input: &input,
+                    output: &output,
+                    relative: &relative.to_string_lossy(),
+                    dst,
+                    manifest,
+                    config_hash,
+                };
+                let outcome = self.translate_dir_entry(entry, target, matched, options).await;
+                (relative, input, outcome)
+            })
+            .buffer_unordered(options.file_concurrency.max(1));
+
+        let mut report = DirReport::default();
+        let mut records = BTreeMap::new();
+        let mut error = None;
+        while let Some((relative, input, outcome)) = outcomes.next().await {
+            match outcome {
+                Ok(DirEntryOutcome::Translated {
+                    characters,
+                    skipped_languages,
+                    record,
+                }) => {
+                    report.files_translated += 1;
+                    report.characters += characters;
+                    report.skipped_languages.extend(skipped_languages.into_iter().map(|lang| (input.clone(), lang)));
+                    if let Some(record) = record {
+                        records.insert(relative.to_string_lossy().into_owned(), record);
+                    }
+                }
+                Ok(DirEntryOutcome::UpToDate(record)) => {
+                    report.files_up_to_date += 1;
+                    records.insert(relative.to_string_lossy().into_owned(), record);
+                }
+                Ok(DirEntryOutcome::Copied) => report.files_copied += 1,
+                Ok(DirEntryOutcome::Skipped(languages)) => {
+                    report.files_skipped += 1;
+                    report.skipped_languages.extend(languages.into_iter().map(|lang| (input.clone(), lang)));
+                }
+                Err(e) if options.fail_fast => {
+                    error = Some(e);
+                    break;
+                }
+                Err(e) => report.failed.push((input, e.to_string())),
+            }
+        }
+        drop(outcomes);
+
+        if !records.is_empty() {
+            fs::create_dir_all(dst).await.map_err(io_error(dst))?;
+            write_atomically(&dst.join(MANIFEST_FILE), &Manifest::new(records).to_json()).await?;
+        }
+        if let Some(e) = error {
+            return Err(e);
+        }
+        self.compact_cache();
+        report.duration = started.elapsed();
+        Ok(report)
+    }
+
+    /// 翻译或复制目录中的单个文件
+    ///
+    /// 匹配的文件先与翻译清单比较，源内容和配置都未变化且输出仍存在时跳过。
+    async fn translate_dir_entry(
+        &self,
+        entry: DirEntry<'_>,
+        target: OutputTarget<'_>,
+        matched: bool,
+        options: &DirOptions,
+    ) -> Result<DirEntryOutcome> {
+        let DirEntry { input, output, .. } = entry;
+        if !matched {
+            if !check_overwrite(output, options.overwrite).await? {
+                return Ok(DirEntryOutcome::Skipped(Vec::new()));
+            }
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent).await.map_err(io_error(parent))?;
+            }
+            fs::copy(input, output).await.map_err(io_error(output))?;
+            return Ok(DirEntryOutcome::Copied);
+        }
+        let source = fs::read(input).await.map_err(io_error(input))?;
+        let source_hash = manifest::source_hash(&source);
+        if let Some(record) = entry.manifest.up_to_date(entry.relative, entry.dst, &source_hash, entry.config_hash).await {
+            return Ok(DirEntryOutcome::UpToDate(record));
+        }
+        match self.translate_file_impl(input, target, None, true, options.overwrite).await? {
+            None => Ok(DirEntryOutcome::Skipped(Vec::new())),
+            Some(report) if report.languages.is_empty() && !report.skipped_languages.is_empty() => {
+                Ok(DirEntryOutcome::Skipped(report.skipped_languages))
+            }
+            Some(report) => {
+                // 有块保留原文时不写入清单，下次运行重新翻译
+                let complete = match report.languages.is_empty() {
+                    true => report.translation.failed_chunks == 0,
+                    false => report.languages.iter().all(|output| output.translation.failed_chunks == 0),
+                };
+                let outputs = match report.languages.is_empty() {
+                    true => vec![report.output],
+                    false => report.languages.into_iter().map(|output| output.output).collect(),
+                };
+                Ok(DirEntryOutcome::Translated {
+                    characters: report.characters,
+                    skipped_languages: report.skipped_languages,
+                    record: complete.then(|| ManifestEntry::new(source_hash, entry.config_hash.to_string(), outputs, entry.dst)),
+                })
+            }
+        }
+    }
+
+    /// 翻译单个文件
+    ///
+    /// `per_language` 时按前置元数据声明的目标语言各写出一个文件；`overwrite` 为 `Skip` 时跳过已存在的输出，
+    /// 所有输出都已存在时返回 `None`，为 `Error` 时在翻译前检查所有输出，任一已存在即返回错误。
+    async fn translate_file_impl(
+        &self,
+        input: &Path,
+        target: OutputTarget<'_>,
+        backup: Option<PathBuf>,
+        per_language: bool,
+        overwrite: OverwritePolicy,
+    ) -> Result<Option<FileReport>> {
+        let config = self.config();
+        let size = fs::metadata(input).await.map_err(io_error(input))?.len();
+        let limit = config.max_file_size_bytes;
+        if size > limit {
+            return Err(TranslationError::FileTooLarge {
+                path: input.to_path_buf(),
+                size,
+                limit,
+            });
+        }
+
+        let bytes = fs::read(input).await.map_err(io_error(input))?;
+        let content = String::from_utf8(bytes).map_err(|e| TranslationError::Io {
+            path: input.to_path_buf(),
+            source: std::io::Error::new(std::io::ErrorKind::InvalidData, e),
+        })?;
+
+        let (has_bom, body) = match content.strip_prefix(UTF8_BOM) {
+            Some(body) => (true, body),
+            None => (false, content.as_str()),
+        };
+        let crlf = body.contains("\r\n");
+        let normalized = if crlf { body.replace("\r\n", "\n") } else { body.to_string() };
+
+        // 每个目标：前置元数据声明的语言（未声明时为 `None`，使用 `target_lang`）及其输出路径
+        let mut skipped_languages = Vec::new();
+        let declared = config
+            .target_langs_key
+            .as_deref()
+            .filter(|_| per_language && !is_notebook(input))
+            .and_then(|key| declared_languages(&normalized, key));
+        let output = target.path(input, None, &config)?;
+        let targets: Vec<(Option<String>, PathBuf)> = match declared {
+            None => vec![(None, output.clone())],
+            Some(languages) => languages
+                .into_iter()
+                .filter(|lang| {
+                    let is_source = lang.eq_ignore_ascii_case(&config.source_lang);
+                    if is_source {
+                        skipped_languages.push(lang.clone());
+                    }
+                    !is_source
+                })
+                .map(|lang| {
+                    let path = target.path(input, Some(&lang), &config)?;
+                    Ok((Some(lang), path))
+                })
+                .collect::<Result<_>>()?,
+        };
+        let mut pending = Vec::with_capacity(targets.len());
+        for (lang, path) in targets.iter().cloned() {
+            if check_overwrite(&path, overwrite).await? {
+                pending.push((lang, path));
+            }
+        }
+        if !targets.is_empty() && pending.is_empty() {
+            return Ok(None);
+        }
+
+        let mut written = Vec::with_capacity(pending.len());
+        for (lang, path) in pending {
+            let options = TranslateOptions {
+                target_lang: lang.clone(),
+                ..Default::default()
+            };
+            let (translated, translation) = if is_notebook(input) {
+                self.run_notebook_translation(&normalized, &options).await?
+            } else {
+                self.run_translation(&normalized, &options).await?
+            };
+
+            let mut result = String::with_capacity(translated.len() + UTF8_BOM.len());
+            if has_bom {
+                result.push_str(UTF8_BOM);
+            }
+            if crlf {
+                result.push_str(&translated.replace('\n', "\r\n"));
+            } else {
+                result.push_str(&translated);
+            }
+            written.push((lang, path, result, translation));
+        }
+
+        if let Some(backup) = &backup {
+            fs::copy(input, backup).await.map_err(io_error(backup))?;
+        }
+        for (_, path, result, _) in &written {
+            if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                fs::create_dir_all(parent).await.map_err(io_error(parent))?;
+            }
+            write_atomically(path, result.as_bytes()).await?;
+        }
+
+        let (output, bytes_written, translation) = written
+            .first()
+            .map(|(_, path, result, translation)| (path.clone(), result.len() as u64, translation.clone()))
+            .unwrap_or_else(|| (output, 0, Default::default()));
+        let languages = written
+            .into_iter()
+            .filter_map(|(lang, output, result, translation)| {
+                lang.map(|lang| LanguageOutput {
+                    lang,
+                    output,
+                    bytes_written: result.len() as u64,
+                    translation,
+                })
+            })
+            .collect();
+
+        Ok(Some(FileReport {
+            input: input.to_path_buf(),
+            output,
+            backup,
+            bytes_read: size,
+            bytes_written,
+            characters: normalized.chars().count(),
+            has_bom,
+            crlf,
+            translation,
+            languages,
+            skipped_languages,
+        }))
+    }
+}
+
+/// 先写入同目录下的临时文件，同步到磁盘后重命名为目标文件
+async fn write_atomically(path: &Path, contents: &[u8]) -> Result<()> {
+    let temp = with_suffix(path, &format!(".{}.tmp", std::process::id()));
+
+    let written = async {
+        let mut file = fs::File::create(&temp).await?;
+        file.write_all(contents).await?;
+        file.sync_all().await
+    }
+    .await;
+    if let Err(source) = written {
+        let _ = fs::remove_file(&temp).await;
+        return Err(TranslationError::Io {
+            path: temp,
+            source,
+        });
+    }
+
+    if let Err(source) = fs::rename(&temp, path).await {
+        let _ = fs::remove_file(&temp).await;
+        return Err(TranslationError::Io {
+            path: path.to_path_buf(),
+            source,
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,158 @@
+//! 术语表模块
+//!
+//! 在请求前将术语表中的源术语替换为占位符，译文返回后替换为指定的目标术语，
+//! 保证产品名和技术术语的译法一致。
+
+use crate::cache::StableHasher;
+use crate::error::{Result, TranslationError};
+use crate::protect::{whole_word_pattern, Placeholders};
+use crate::types::TranslationConfig;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 术语占位符的类型标记
+pub(crate) const GLOSSARY_PLACEHOLDER: char = 'G';
+
+/// 已编译的术语表
+#[derive(Debug, Default)]
+pub(crate) struct Glossary {
+    /// 匹配所有源术语的正则，术语表为空或使用后端原生术语表时为 `None`
+    pattern: Option<Regex>,
+    /// 源术语到目标术语的映射，不区分大小写时键为小写
+    terms: HashMap<String, String>,
+    /// 是否区分大小写
+    case_sensitive: bool,
+    /// 匹配方式和条目的哈希，用于缓存键；空术语表为0
+    fingerprint: u64,
+}
+
+impl Glossary {
+    /// 根据配置构建术语表
+    ///
+    /// 合并 `glossary_file` 与 `glossary` 中的条目（后者优先）。
+    /// 配置了 `glossary_id` 时由后端应用术语表，不做本地替换。
+    pub(crate) fn from_config(config: &TranslationConfig) -> Result<Self> {
+        let mut entries = match &config.glossary_file {
+            Some(path) => load_glossary_file(Path::new(path))?,
+            None => HashMap::new(),
+        };
+        entries.extend(config.glossary.iter().map(|(source, target)| (source.clone(), target.clone())));
+        entries.retain(|source, _| !source.trim().is_empty());
+
+        if entries.is_empty() || config.glossary_id.is_some() {
+            return Ok(Self::default());
+        }
+
+        // 长术语优先，避免 "worker" 抢先匹配 "worker node"
+        let mut sources: Vec<&String> = entries.keys().collect();
+        sources.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        let alternatives: Vec<String> = sources
+            .iter()
+            .map(|source| {
+                if config.glossary_whole_word {
+                    whole_word_pattern(source)
+                } else {
+                    regex::escape(source)
+                }
+            })
+            .collect();
+        let flags = if config.glossary_case_sensitive { "" } else { "(?i)" };
+        let pattern = Regex::new(&format!("{}(?:{})", flags, alternatives.join("|")))
+            .map_err(|e| TranslationError::ConfigError(format!("术语表无法编译: {}", e)))?;
+
+        let terms: HashMap<String, String> = entries
+            .into_iter()
+            .map(|(source, target)| {
+                let key = if config.glossary_case_sensitive { source } else { source.to_lowercase() };
+                (key, target)
+            })
+            .collect();
+
+        let mut hasher = StableHasher::default();
+        hasher.field(pattern.as_str().as_bytes());
+        let mut sorted: Vec<_> = terms.iter().collect();
+        sorted.sort();
+        for (source, target) in sorted {
+            hasher.field(source.as_bytes());
+            hasher.field(target.as_bytes());
+        }
+
+        Ok(Self {
+            pattern: Some(pattern),
+            terms,
+            case_sensitive: config.glossary_case_sensitive,
+            fingerprint: hasher.finish(),
+        })
+    }
+
+    /// 术语表的哈希，空术语表或使用后端原生术语表时为0
+    pub(crate) fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    /// 将文本中的源术语替换为占位符，还原时替换为目标术语
+    pub(crate) fn protect(&self, text: &str, placeholders: &mut Placeholders) -> String {
+        let Some(pattern) = &self.pattern else {
+            return text.to_string();
+        };
+
+        pattern
+            .replace_all(text, |captures: &regex::Captures| {
+                let matched = &captures[0];
+                let key = if self.case_sensitive { matched.to_string() } else { matched.to_lowercase() };
+                match self.terms.get(&key) {
+                    Some(target) => placeholders.insert(GLOSSARY_PLACEHOLDER, target.clone()),
+                    None => matched.to_string(),
+                }
+            })
+            .into_owned()
+    }
+}
+
+/// 从文件加载术语表
+///
+/// `.csv` 文件每行为 `源术语,目标术语`，空行和以 `#` 开头的行会被忽略；
+/// 其他文件按TOML解析，顶层为 `"源术语" = "目标术语"` 形式的键值对。
+fn load_glossary_file(path: &Path) -> Result<HashMap<String, String>> {
+    let content = std::fs::read_to_string(path).map_err(|source| TranslationError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    let is_csv = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+    if !is_csv {
+        return toml::from_str(&content).map_err(|e| {
+            TranslationError::ConfigError(format!("术语表文件 {} 解析失败: {}", path.display(), e))
+        });
+    }
+
+    let mut entries = HashMap::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (source, target) = line.split_once(',').ok_or_else(|| {
+            TranslationError::ConfigError(format!(
+                "术语表文件 {} 第 {} 行缺少逗号分隔的目标术语",
+                path.display(),
+                number + 1
+            ))
+        })?;
+        entries.insert(unquote(source), unquote(target));
+    }
+    Ok(entries)
+}
+
+/// 去除CSV字段两端的空白和引号
+fn unquote(field: &str) -> String {
+    let field = field.trim();
+    field
+        .strip_prefix('"')
+        .and_then(|field| field.strip_suffix('"'))
+        .unwrap_or(field)
+        .to_string()
+}
@@ -0,0 +1,457 @@
+//! HTML翻译模块
+//!
+//! 用容错的词法扫描遍历HTML（mdBook输出、文档站点等），只翻译文本节点和
+//! `alt`、`title`、`placeholder` 属性，标签结构、注释和实体编码原样保留。
+//! 后端原生支持HTML标签处理时，直接将文档交给后端解析。
+
+use crate::error::Result;
+use crate::protect::Placeholders;
+use crate::translator::{CallContext, TranslationService, MAX_CONCURRENT_CHUNKS};
+use crate::types::TranslateOptions;
+use futures::stream::{self, StreamExt};
+
+/// HTML占位符的类型标记，用于行内标签、行内代码和字符实体
+const HTML_PLACEHOLDER: char = 'H';
+
+/// 内容不翻译的元素
+const SKIPPED_ELEMENTS: [&str; 4] = ["code", "pre", "script", "style"];
+
+/// 内容为原始文本的元素，其中的 `<` 不会开始标签
+const RAW_TEXT_ELEMENTS: [&str; 2] = ["script", "style"];
+
+/// 行内元素，其标签替换为占位符，前后的文本作为一句话整体翻译
+const INLINE_ELEMENTS: [&str; 26] = [
+    "a", "abbr", "b", "bdi", "bdo", "br", "cite", "code", "data", "del", "dfn", "em", "i", "ins", "kbd", "mark",
+    "q", "s", "samp", "small", "span", "strong", "sub", "sup", "u", "var",
+];
+
+/// 需要翻译的属性
+const TRANSLATED_ATTRIBUTES: [&str; 3] = ["alt", "title", "placeholder"];
+
+impl TranslationService {
+    /// 翻译HTML文档
+    ///
+    /// 只翻译文本节点和 `alt`、`title`、`placeholder` 属性的值；`<code>`、`<pre>`、`<script>`、`<style>`
+    /// 的内容、其他属性、注释和文档类型声明原样保留。行内标签（`<a>`、`<em>`、行内 `<code>` 等）
+    /// 以占位符随所在句子一起发送，字符实体同样以占位符保护，保证原有编码不变；
+    /// 占位符在译文中丢失时会追加在该段末尾，使标签保持配对，并在报告中记录警告。
+    ///
+    /// 配置了 `html_tag_handling` 且所有端点都使用标准DeepLX格式时，
+    /// 不超过 `max_text_length` 的文档会带 `tag_handling=html` 整体交给后端处理。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{FnTranslator, TranslationServiceBuilder};
+    ///
+    /// let service = TranslationServiceBuilder::new()
+    ///     .enabled(true)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// # tokio_test::block_on(async {
+    /// let translated = service.translate_html("<h1 class=\"title\">Install</h1>\n<pre>keep me</pre>").await.unwrap();
+    /// assert_eq!(translated, "<h1 class=\"title\">INSTALL</h1>\n<pre>keep me</pre>");
+    /// # });
+    /// ```
+    pub async fn translate_html(&self, html: &str) -> Result<String> {
+        self.translate_html_with(html, &TranslateOptions::default()).await
+    }
+
+    /// 使用单次调用选项翻译HTML文档，见 [`translate_html`](Self::translate_html)
+    pub async fn translate_html_with(&self, html: &str, options: &TranslateOptions) -> Result<String> {
+        let context = self.new_context(options)?;
+        let components = context.components.clone();
+        if !components.config.enabled {
+            return Ok(html.to_string());
+        }
+
+        if components.supports_html_tag_handling() && html.len() <= components.config.max_text_length {
+            let context = CallContext {
+                tag_handling: Some("html"),
+                ..context
+            };
+            let result = self.translate_chunk(html, &context).await;
+            return self.apply_failure_policy(result, html, context.on_error, &context);
+        }
+
+        let pieces = parse_html(html);
+        let translated: Vec<Result<String>> = stream::iter(&pieces)
+            .map(|piece| {
+                let context = &context;
+                async move {
+                    match piece {
+                        Piece::Raw(raw) => Ok(raw.clone()),
+                        Piece::Text(unit) => self.translate_unit(unit, context).await,
+                    }
+                }
+            })
+            .buffered(MAX_CONCURRENT_CHUNKS)
+            .collect()
+            .await;
+        translated.into_iter().collect()
+    }
+
+    /// 翻译一个文本单元，转义译文并还原占位符
+    async fn translate_unit(&self, unit: &Unit, context: &CallContext) -> Result<String> {
+        let translated = match self.translate_chunk(&unit.text, context).await {
+            Ok(translated) => translated,
+            Err(e) => return self.apply_failure_policy(Err(e), &unit.source, context.on_error, context),
+        };
+
+        context.record_protection(&unit.placeholders);
+        let restored = unit.placeholders.restore(&escape(&translated, unit.quote));
+        let mut text = restored.text;
+        if !restored.missing.is_empty() {
+            context.warn(format!("HTML占位符在译文中丢失，{} 处标签或实体追加在该段末尾", restored.missing.len()));
+            for (_, replacement) in restored.missing {
+                text.push_str(&replacement);
+            }
+        }
+        Ok(text)
+    }
+}
+
+/// 解析结果中的片段
+#[derive(Debug)]
+enum Piece {
+    /// 原样输出的内容
+    Raw(String),
+    /// 需要翻译的文本
+    Text(Unit),
+}
+
+/// 需要翻译的文本单元：一段文本节点（含其中的行内标签）或一个属性值
+#[derive(Debug)]
+struct Unit {
+    /// 原始HTML，翻译失败并保留原文时使用
+    source: String,
+    /// 发送给后端的文本，标签和实体已替换为占位符
+    text: String,
+    placeholders: Placeholders,
+    /// 属性值的引号，译文中的同种引号需要转义
+    quote: Option<char>,
+}
+
+/// 正在累积的文本段
+#[derive(Debug, Default)]
+struct Run {
+    source: String,
+    text: String,
+    placeholders: Placeholders,
+    /// 占位符之外是否有文字，只有标签和空白的段不翻译
+    has_words: bool,
+}
+
+impl Run {
+    fn push_text(&mut self, raw: &str) {
+        self.source.push_str(raw);
+        self.has_words |= protect_entities(raw, &mut self.text, &mut self.placeholders);
+    }
+
+    fn push_protected(&mut self, raw: &str) {
+        self.source.push_str(raw);
+        self.text.push_str(&self.placeholders.insert(HTML_PLACEHOLDER, raw.to_string()));
+    }
+
+    /// 结束当前段，前后空白原样输出，其余部分作为一个文本单元
+    fn flush(&mut self, pieces: &mut Vec<Piece>) {
+        let run = std::mem::take(self);
+        if !run.has_words {
+            if !run.source.is_empty() {
+                pieces.push(Piece::Raw(run.source));
+            }
+            return;
+        }
+
+        // 空白不会被替换为占位符，因此 text 与 source 的首尾空白相同
+        let leading = run.text.len() - run.text.trim_start().len();
+        let trailing = run.text.len() - run.text.trim_end().len();
+        let source_end = run.source.len() - trailing;
+        pieces.push(Piece::Raw(run.source[..leading].to_string()));
+        pieces.push(Piece::Text(Unit {
+            source: run.source[leading..source_end].to_string(),
+            text: run.text.trim().to_string(),
+            placeholders: run.placeholders,
+            quote: None,
+        }));
+        pieces.push(Piece::Raw(run.source[source_end..].to_string()));
+    }
+}
+
+/// 将HTML拆分为原样输出的片段和需要翻译的文本单元
+fn parse_html(html: &str) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    let mut run = Run::default();
+    let mut position = 0;
+
+    while position < html.len() {
+        let Some(offset) = html[position..].find('<') else {
+            run.push_text(&html[position..]);
+            break;
+        };
+        let start = position + offset;
+        run.push_text(&html[position..start]);
+        let rest = &html[start..];
+
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").map_or(html.len(), |end| start + end + 3);
+            run.flush(&mut pieces);
+            pieces.push(Piece::Raw(html[start..end].to_string()));
+            position = end;
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            let end = rest.find('>').map_or(html.len(), |end| start + end + 1);
+            run.flush(&mut pieces);
+            pieces.push(Piece::Raw(html[start..end].to_string()));
+            position = end;
+            continue;
+        }
+
+        let closing = rest.starts_with("</");
+        let name = tag_name(&rest[if closing { 2 } else { 1 }..]);
+        if name.is_empty() {
+            // 不是标签的 `<`，按普通文本处理
+            run.push_text("<");
+            position = start + 1;
+            continue;
+        }
+        let Some(end) = tag_end(html, start) else {
+            run.flush(&mut pieces);
+            pieces.push(Piece::Raw(html[start..].to_string()));
+            break;
+        };
+        let tag = &html[start..end];
+        let inline = INLINE_ELEMENTS.contains(&name.as_str());
+
+        if !closing && SKIPPED_ELEMENTS.contains(&name.as_str()) && !tag.ends_with("/>") {
+            let element_end = element_end(html, &name, end);
+            if inline {
+                run.push_protected(&html[start..element_end]);
+            } else {
+                run.flush(&mut pieces);
+                pieces.push(Piece::Raw(html[start..element_end].to_string()));
+            }
+            position = element_end;
+            continue;
+        }
+
+        let attributes = if closing { Vec::new() } else { translated_attributes(tag) };
+        if inline && attributes.is_empty() {
+            run.push_protected(tag);
+        } else {
+            run.flush(&mut pieces);
+            push_tag(tag, &attributes, &mut pieces);
+        }
+        position = end;
+    }
+
+    run.flush(&mut pieces);
+    pieces
+}
+
+/// 输出一个开始或结束标签，需要翻译的属性值作为独立的文本单元
+fn push_tag(tag: &str, attributes: &[Attribute], pieces: &mut Vec<Piece>) {
+    let mut last = 0;
+    for attribute in attributes {
+        let value = &tag[attribute.value.clone()];
+        let mut text = String::new();
+        let mut placeholders = Placeholders::default();
+        protect_entities(value, &mut text, &mut placeholders);
+
+        pieces.push(Piece::Raw(tag[last..attribute.value.start].to_string()));
+        // 未加引号的属性值译文可能包含空白，输出时补上双引号
+        let quoted = attribute.quote.is_none();
+        if quoted {
+            pieces.push(Piece::Raw("\"".to_string()));
+        }
+        pieces.push(Piece::Text(Unit {
+            source: value.to_string(),
+            text,
+            placeholders,
+            quote: Some(attribute.quote.unwrap_or('"')),
+        }));
+        if quoted {
+            pieces.push(Piece::Raw("\"".to_string()));
+        }
+        last = attribute.value.end;
+    }
+    pieces.push(Piece::Raw(tag[last..].to_string()));
+}
+
+/// 需要翻译的属性值
+#[derive(Debug)]
+struct Attribute {
+    /// 属性值在标签中的位置，不含引号
+    value: std::ops::Range<usize>,
+    /// 属性值的引号，未加引号时为 `None`
+    quote: Option<char>,
+}
+
+/// 标签中需要翻译的属性，按出现顺序返回
+fn translated_attributes(tag: &str) -> Vec<Attribute> {
+    let bytes = tag.as_bytes();
+    let mut attributes = Vec::new();
+    // 跳过 `<` 和标签名
+    let mut i = 1 + tag_name(&tag[1..]).len();
+
+    while i < bytes.len() {
+        while i < bytes.len() && (bytes[i].is_ascii_whitespace() || bytes[i] == b'/') {
+            i += 1;
+        }
+        let name_start = i;
+        while i < bytes.len() && !bytes[i].is_ascii_whitespace() && !matches!(bytes[i], b'=' | b'>' | b'/') {
+            i += 1;
+        }
+        if i == name_start {
+            break;
+        }
+        let name = tag[name_start..i].to_ascii_lowercase();
+
+        let mut j = i;
+        while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+            j += 1;
+        }
+        if j >= bytes.len() || bytes[j] != b'=' {
+            continue;
+        }
+        j += 1;
+        while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+            j += 1;
+        }
+
+        let (value, quote, next) = match bytes.get(j) {
+            Some(&quote @ (b'"' | b'\'')) => {
+                let end = tag[j + 1..].find(quote as char).map_or(bytes.len(), |end| j + 1 + end);
+                (j + 1..end, Some(quote as char), end + 1)
+            }
+            _ => {
+                let end = tag[j..]
+                    .find(|c: char| c.is_ascii_whitespace() || c == '>')
+                    .map_or(bytes.len(), |end| j + end);
+                (j..end, None, end)
+            }
+        };
+        if TRANSLATED_ATTRIBUTES.contains(&name.as_str()) && tag[value.clone()].chars().any(char::is_alphanumeric) {
+            attributes.push(Attribute { value, quote });
+        }
+        i = next;
+    }
+    attributes
+}
+
+/// 标签名（小写），`text` 从 `<` 或 `</` 之后开始；不以字母开头时返回空字符串
+fn tag_name(text: &str) -> String {
+    if !text.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return String::new();
+    }
+    text.chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// 从 `start` 处的 `<` 开始查找标签结束位置（`>` 之后），跳过引号中的 `>`
+fn tag_end(html: &str, start: usize) -> Option<usize> {
+    let mut quote = None;
+    for (offset, c) in html[start..].char_indices() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(start + offset + 1),
+            (None, _) => {}
+        }
+    }
+    None
+}
+
+/// 查找从 `from` 开始的 `name` 元素内容之后、结束标签之后的位置
+///
+/// 原始文本元素取第一个结束标签，其他元素计入同名元素的嵌套；缺少结束标签时返回文档末尾。
+fn element_end(html: &str, name: &str, from: usize) -> usize {
+    let lower = html.to_ascii_lowercase();
+    let open = format!("<{}", name);
+    let close = format!("</{}", name);
+    let is_boundary = |position: usize| {
+        !lower[position..].starts_with(|c: char| c.is_ascii_alphanumeric() || c == '-')
+    };
+    let raw_text = RAW_TEXT_ELEMENTS.contains(&name);
+    let mut depth = 1;
+    let mut position = from;
+
+    while let Some(offset) = lower[position..].find('<') {
+        let start = position + offset;
+        if lower[start..].starts_with(&close) && is_boundary(start + close.len()) {
+            let end = tag_end(html, start).unwrap_or(html.len());
+            depth -= 1;
+            if depth == 0 || raw_text {
+                return end;
+            }
+            position = end;
+        } else if !raw_text && lower[start..].starts_with(&open) && is_boundary(start + open.len()) {
+            depth += 1;
+            position = start + open.len();
+        } else {
+            position = start + 1;
+        }
+    }
+    html.len()
+}
+
+/// 将字符实体替换为占位符后追加到 `text`，返回实体之外是否有文字
+fn protect_entities(raw: &str, text: &mut String, placeholders: &mut Placeholders) -> bool {
+    let mut has_words = false;
+    let mut rest = raw;
+    while let Some(offset) = rest.find('&') {
+        let (before, candidate) = rest.split_at(offset);
+        has_words |= before.chars().any(char::is_alphanumeric);
+        text.push_str(before);
+        match entity_len(candidate) {
+            Some(len) => {
+                text.push_str(&placeholders.insert(HTML_PLACEHOLDER, candidate[..len].to_string()));
+                rest = &candidate[len..];
+            }
+            None => {
+                text.push('&');
+                rest = &candidate[1..];
+            }
+        }
+    }
+    has_words |= rest.chars().any(char::is_alphanumeric);
+    text.push_str(rest);
+    has_words
+}
+
+/// `text` 开头的字符实体（`&name;`、`&#123;`、`&#x1F;`）长度
+fn entity_len(text: &str) -> Option<usize> {
+    let body = text.strip_prefix('&')?;
+    let (prefix, allowed): (usize, fn(char) -> bool) = if body.starts_with("#x") || body.starts_with("#X") {
+        (2, |c| c.is_ascii_hexdigit())
+    } else if body.starts_with('#') {
+        (1, |c| c.is_ascii_digit())
+    } else {
+        (0, |c| c.is_ascii_alphanumeric())
+    };
+    let name = &body[prefix..];
+    let len = name.find(|c: char| !allowed(c)).unwrap_or(name.len());
+    (len > 0 && name[len..].starts_with(';')).then_some(1 + prefix + len + 1)
+}
+
+/// 转义译文中的HTML特殊字符，属性值中还需转义包围它的引号
+pub(crate) fn escape(text: &str, quote: Option<char>) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' if quote == Some('"') => escaped.push_str("&quot;"),
+            '\'' if quote == Some('\'') => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
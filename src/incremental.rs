@@ -0,0 +1,135 @@
+//! 增量翻译模块
+//!
+//! 按Markdown分块比较新旧原文，未改动的块直接沿用旧译文，只翻译新增或修改的块。
+//! 块按内容而不是位置匹配，移动过的段落同样可以沿用旧译文。
+
+use crate::error::Result;
+use crate::segment::segment_markdown;
+use crate::translator::{CallContext, TranslationService, MAX_CONCURRENT_CHUNKS};
+use crate::types::{TextFormat, TranslateOptions, TranslationReport};
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+
+/// 新原文中的一段连续内容
+enum Piece<'a> {
+    /// 沿用旧译文的块：新原文的首尾空白和旧译文去掉首尾空白后的内容
+    Reused { block: &'a str, translation: &'a str },
+    /// 需要翻译的连续块
+    Changed(String),
+}
+
+impl TranslationService {
+    /// 基于旧的原文和译文增量翻译修改后的文档
+    ///
+    /// 将 `new_source`、`old_source` 和 `old_translation` 按 [`segment_markdown`](crate::segment_markdown) 分块，
+    /// 旧原文与旧译文按块序号配对。新原文中内容（忽略首尾空白）与某个旧原文块相同的块直接沿用对应的旧译文，
+    /// 与位置无关，因此移动过的段落也会被沿用；其余相邻的块合并后按完整流程翻译，最后按新原文的顺序拼接。
+    /// 报告的 `reused_segments` 记录沿用的块数。
+    ///
+    /// 旧译文与旧原文的块数或块类型不一致（译文改变了文档结构），或 `format` 不是Markdown时，
+    /// 无法可靠地配对，改为翻译整个新原文并在报告中记录一条警告。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{FnTranslator, TranslationServiceBuilder};
+    ///
+    /// let service = TranslationServiceBuilder::new()
+    ///     .enabled(true)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let old_source = "# Guide\n\nFirst paragraph.\n";
+    /// let old_translation = "# 指南\n\n第一段。\n";
+    /// let new_source = "# Guide\n\nFirst paragraph.\n\nA new paragraph.\n";
+    ///
+    /// # tokio_test::block_on(async {
+    /// let (translated, report) = service
+    ///     .translate_incremental(new_source, old_source, old_translation)
+    ///     .await
+    ///     .unwrap();
+    /// assert_eq!(translated, "# 指南\n\n第一段。\n\nA NEW PARAGRAPH.\n");
+    /// assert_eq!(report.reused_segments, 2);
+    /// # });
+    /// ```
+    pub async fn translate_incremental(
+        &self,
+        new_source: &str,
+        old_source: &str,
+        old_translation: &str,
+    ) -> Result<(String, TranslationReport)> {
+        let context = self.new_context(&TranslateOptions::default())?;
+        if !context.components.config.enabled {
+            return Ok((new_source.to_string(), context.report()));
+        }
+
+        let old_blocks = segment_markdown(old_source);
+        let translated_blocks = segment_markdown(old_translation);
+        let aligned = old_blocks.len() == translated_blocks.len()
+            && old_blocks.iter().zip(&translated_blocks).all(|(old, translated)| old.kind == translated.kind);
+        if context.format != TextFormat::Markdown || !aligned {
+            if context.format == TextFormat::Markdown {
+                context.warn(format!(
+                    "旧译文与旧原文的块结构不一致（{} 块 / {} 块），重新翻译全文",
+                    translated_blocks.len(),
+                    old_blocks.len()
+                ));
+            } else {
+                context.warn("增量翻译只支持Markdown，重新翻译全文".to_string());
+            }
+            let translated = self.translate_document(new_source, &context).await?;
+            return Ok((translated, context.report()));
+        }
+
+        // 同一内容出现多次时沿用第一次出现的译文
+        let mut previous: HashMap<&str, &str> = HashMap::new();
+        for (old, translated) in old_blocks.iter().zip(&translated_blocks) {
+            if !old.content.trim().is_empty() {
+                previous.entry(old.content.trim()).or_insert(translated.content.trim());
+            }
+        }
+
+        let new_blocks = segment_markdown(new_source);
+        let mut pieces: Vec<Piece> = Vec::new();
+        let mut reused = 0;
+        for block in &new_blocks {
+            match previous.get(block.content.trim()) {
+                Some(translation) => {
+                    reused += 1;
+                    pieces.push(Piece::Reused {
+                        block: &block.content,
+                        translation,
+                    });
+                }
+                None => match pieces.last_mut() {
+                    Some(Piece::Changed(run)) => run.push_str(&block.content),
+                    _ => pieces.push(Piece::Changed(block.content.clone())),
+                },
+            }
+        }
+
+        let translations: Vec<_> = pieces.iter().map(|piece| self.translate_piece(piece, &context)).collect();
+        let translated: Vec<Result<String>> = stream::iter(translations)
+            .buffered(MAX_CONCURRENT_CHUNKS)
+            .collect()
+            .await;
+        let translated = translated.into_iter().collect::<Result<String>>()?;
+
+        let mut report = context.report();
+        report.reused_segments = reused;
+        Ok((translated, report))
+    }
+
+    /// 翻译或沿用一段内容，保留新原文的首尾空白
+    async fn translate_piece(&self, piece: &Piece<'_>, context: &CallContext) -> Result<String> {
+        let (source, translated) = match piece {
+            Piece::Reused { block, translation } => (*block, translation.to_string()),
+            Piece::Changed(run) if run.trim().is_empty() => return Ok(run.clone()),
+            Piece::Changed(run) => (run.as_str(), self.translate_document(run.trim(), context).await?),
+        };
+        let leading = &source[..source.len() - source.trim_start().len()];
+        let trailing = &source[source.trim_end().len()..];
+        Ok(format!("{}{}{}", leading, translated.trim(), trailing))
+    }
+}
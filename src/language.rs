@@ -0,0 +1,110 @@
+//! 语言代码模块
+//!
+//! 维护已知的语言代码（DeepL/DeepLX支持的代码），在配置校验和按调用覆盖语言时提前检查，
+//! 不合法的代码返回 `TranslationError::InvalidLanguage` 并附带最接近的已知代码，而不是等到后端逐块拒绝。
+
+use crate::error::{Result, TranslationError};
+
+/// 已知的语言代码，比较时不区分大小写
+const LANGUAGES: &[&str] = &[
+    "ar", "bg", "cs", "da", "de", "el", "en", "en-gb", "en-us", "es", "es-419", "et", "fi", "fr", "he", "hu", "id",
+    "it", "ja", "ko", "lt", "lv", "nb", "nl", "pl", "pt", "pt-br", "pt-pt", "ro", "ru", "sk", "sl", "sv", "th", "tr",
+    "uk", "vi", "zh", "zh-hans", "zh-hant",
+];
+
+/// 常见的错误写法（国家代码、旧代码）及对应的语言代码
+const ALIASES: &[(&str, &str)] = &[
+    ("cn", "zh"),
+    ("zh-cn", "zh-hans"),
+    ("zh-tw", "zh-hant"),
+    ("zh-hk", "zh-hant"),
+    ("jp", "ja"),
+    ("kr", "ko"),
+    ("kor", "ko"),
+    ("gr", "el"),
+    ("se", "sv"),
+    ("dk", "da"),
+    ("cz", "cs"),
+    ("ua", "uk"),
+    ("br", "pt-br"),
+    ("no", "nb"),
+    ("iw", "he"),
+    ("in", "id"),
+    ("us", "en-us"),
+    ("gb", "en-gb"),
+];
+
+/// 最多返回的建议数
+const MAX_SUGGESTIONS: usize = 3;
+
+/// 已知的语言代码（小写），源语言另外接受 `"auto"`
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::supported_languages;
+///
+/// assert!(supported_languages().contains(&"zh"));
+/// assert!(!supported_languages().contains(&"cn"));
+/// ```
+pub fn supported_languages() -> &'static [&'static str] {
+    LANGUAGES
+}
+
+/// 检查语言代码是否已知，`allow_auto` 为 `true` 时接受 `"auto"`
+///
+/// 不区分大小写并忽略首尾空白；已知语言的地区变体（如 `es-MX`）同样接受。
+pub(crate) fn check_language(code: &str, allow_auto: bool) -> Result<()> {
+    let normalized = code.trim().to_ascii_lowercase().replace('_', "-");
+    let primary = normalized.split('-').next().unwrap_or_default();
+    if (allow_auto && normalized == "auto") || LANGUAGES.contains(&primary) {
+        return Ok(());
+    }
+    Err(TranslationError::InvalidLanguage {
+        given: code.to_string(),
+        suggestions: suggestions(&normalized),
+    })
+}
+
+/// 最接近 `code` 的已知代码：先取别名，再按编辑距离由近到远
+///
+/// 三个字符以内的代码只接受距离1，否则几乎所有两字母代码都会成为建议。
+fn suggestions(code: &str) -> Vec<String> {
+    let max_distance = if code.len() <= 3 { 1 } else { 2 };
+    let mut suggestions: Vec<String> = ALIASES
+        .iter()
+        .filter(|(alias, _)| *alias == code)
+        .map(|(_, target)| target.to_string())
+        .collect();
+
+    let mut nearest: Vec<(usize, &str)> = LANGUAGES
+        .iter()
+        .map(|known| (edit_distance(code, known), *known))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    nearest.sort();
+    for (_, known) in nearest {
+        if suggestions.len() >= MAX_SUGGESTIONS {
+            break;
+        }
+        if !suggestions.iter().any(|suggestion| suggestion == known) {
+            suggestions.push(known.to_string());
+        }
+    }
+    suggestions
+}
+
+/// 两个ASCII字符串之间的编辑距离（Levenshtein）
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.as_bytes();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, &left) in a.as_bytes().iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, &right) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(left != right);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
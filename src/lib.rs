@@ -13,7 +13,7 @@
 //! 
 //! ## 快速开始
 //! 
-//! ```rust
+//! ```rust,no_run
 //! use markdown_translator::{TranslationService, TranslationConfig};
 //! 
 //! #[tokio::main]
@@ -26,6 +26,7 @@
 //!         max_requests_per_second: 1.0,
 //!         max_text_length: 3000,
 //!         max_paragraphs_per_request: 10,
+//!         ..Default::default()
 //!     };
 //!     
 //!     let translator = TranslationService::new(config);
@@ -49,15 +50,64 @@
 //! max_paragraphs_per_request = 10
 //! ```
 
+mod asciidoc;
+mod audit;
+mod batch;
+mod bilingual;
+mod cache;
+mod checkpoint;
+#[cfg(feature = "csv")]
+mod csv_table;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod builder;
 pub mod config;
+mod detect;
+mod dump;
+pub mod endpoint;
 pub mod error;
+mod fanout;
+mod file;
+mod glossary;
+mod html;
+mod incremental;
+mod language;
+mod manifest;
+pub mod metrics;
+mod notebook;
+mod po;
+mod protect;
+mod quality;
+#[cfg(feature = "hot-reload")]
+mod reload;
+mod review;
+mod rst;
+mod scope;
+pub mod segment;
+mod subtitle;
+pub mod traits;
+pub mod tmx;
 pub mod types;
 pub mod translator;
+mod values;
 
-pub use config::TranslationLibConfig;
-pub use error::{TranslationError, Result};
+pub use builder::TranslationServiceBuilder;
+pub use cache::{CacheKey, CacheStats};
+pub use config::{ConfigFormat, ConfigReport, ConfigReportEntry, ConfigSource, LayeredConfig, ResolvedConfig, TranslationLibConfig};
+pub use endpoint::{CircuitBreaker, Endpoint, EndpointPool};
+pub use error::{ConfigError, RateLimitHeaders, TranslationError, Result, ERROR_CODES_VERSION};
+pub use language::supported_languages;
+pub use metrics::{Metrics, MetricsRecorder, MetricsSnapshot};
+#[cfg(feature = "prometheus")]
+pub use metrics::PrometheusRecorder;
 pub use types::{
-    TranslationConfig, RetryConfig, DeepLXRequest, DeepLXResponse, 
-    DpTransRequest, TextSegment
+    TranslationConfig, ApiKey, CacheBackend, CacheConfig, ChunkingConfig, ChunkStrategy, HttpConfig, LengthUnit, UnclosedFence, Verbosity, RetryConfig, FailurePolicy, TranslateOptions, CacheMode, OutputMode, BilingualOrder, ReviewFormat, TranslationScope, TextFormat, SubtitleFormat, SegmentKind, MarkdownBlock, Progress, ProgressCallback, TextHook, QualityCheck, QualityVerdict, Retranslation, ProgressEvent, TranslationEvent, TranslationReport, TranslationResult, ChunkTiming, Percentiles, TimingSummary, ProtectionStats, Estimate, DetectedLanguage, FileReport, LanguageOutput, DirOptions, DirReport, WarmOptions, WarmReport, OverwritePolicy, OutputNaming, PoOptions, ValueOptions, CsvColumn, CsvOptions, CsvTranslation, DeepLXRequest, DeepLXResponse, 
+    DpTransRequest, TextSegment, TranslatedSegment
 };
-pub use translator::{TranslationService, RateLimiter, retry_with_backoff};
\ No newline at end of file
+#[cfg(feature = "hot-reload")]
+pub use reload::{ConfigWatcher, ReloadEvent};
+pub use review::render_review;
+pub use segment::segment_markdown;
+pub use traits::{FnTranslator, NoopTranslator, Translator};
+pub use translator::{TranslationService, RateLimiter, retry_with_backoff};
+pub use tokio_util::sync::CancellationToken;
\ No newline at end of file
@@ -0,0 +1,178 @@
+//! 目录翻译清单模块
+//!
+//! `translate_dir` 在目标目录中维护 `.translation-manifest.json`，记录每个已翻译文件的源内容哈希、
+//! 影响译文的配置哈希和输出路径。再次运行时三者都未变化且输出仍存在的文件直接跳过，不读取缓存也不请求API。
+
+use crate::cache::{stable_hash, StableHasher};
+use crate::translator::Components;
+use crate::types::Verbosity;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// 清单文件名，位于目标目录的根
+pub(crate) const MANIFEST_FILE: &str = ".translation-manifest.json";
+
+/// 清单格式的版本，格式变化时递增，旧清单随之作废
+const MANIFEST_VERSION: u32 = 1;
+
+/// 不影响译文、不参与配置哈希的配置项
+const IRRELEVANT_FIELDS: &[&str] = &[
+    "max_requests_per_second",
+    "burst_size",
+    "fallback_api_urls",
+    "global_max_requests_per_second",
+    "circuit_breaker_threshold",
+    "circuit_breaker_cooldown_secs",
+    "overall_timeout_secs",
+    "request_timeout_secs",
+    "connect_timeout_secs",
+    "max_total_retries",
+    "chunk_timeout_secs",
+    "pack_batch_items",
+    "max_file_size_bytes",
+    "cache",
+    "offline",
+    "http",
+    "user_agent",
+    "default_headers",
+    "request_id_header",
+    "verbosity",
+    "log_content",
+    "error_body_max_chars",
+    "debug_dump_dir",
+    "debug_dump_max_bytes",
+    "audit_log_path",
+    "audit_include_text",
+    "api_key",
+];
+
+/// 清单中一个源文件的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ManifestEntry {
+    /// 源文件内容的哈希
+    source_hash: String,
+    /// 翻译时配置的哈希
+    config_hash: String,
+    /// 输出文件，能表示为目标目录下的相对路径时为相对路径
+    outputs: Vec<PathBuf>,
+}
+
+/// 清单文件的内容
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Manifest {
+    /// 清单格式的版本
+    version: u32,
+    /// 源目录中的相对路径到记录
+    files: BTreeMap<String, ManifestEntry>,
+}
+
+impl Manifest {
+    /// 读取目标目录中的清单
+    ///
+    /// 文件不存在时返回空清单；无法读取、解析失败或版本不符时记录警告并返回空清单，本次运行结束后重新生成。
+    pub(crate) async fn load(dst: &Path, verbosity: Verbosity) -> Self {
+        let path = dst.join(MANIFEST_FILE);
+        let content = match tokio::fs::read(&path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                verbosity.log(Verbosity::Warn, format_args!("无法读取翻译清单 {}，重新生成: {}", path.display(), e));
+                return Self::default();
+            }
+        };
+        match serde_json::from_slice::<Self>(&content) {
+            Ok(manifest) if manifest.version == MANIFEST_VERSION => manifest,
+            Ok(_) => Self::default(),
+            Err(e) => {
+                verbosity.log(Verbosity::Warn, format_args!("翻译清单 {} 已损坏，重新生成: {}", path.display(), e));
+                Self::default()
+            }
+        }
+    }
+
+    /// 由本次运行的记录生成清单
+    pub(crate) fn new(files: BTreeMap<String, ManifestEntry>) -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            files,
+        }
+    }
+
+    /// 序列化为写入清单文件的内容
+    pub(crate) fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec_pretty(self).unwrap_or_default()
+    }
+
+    /// 文件是否无需重新翻译：源内容和配置哈希与记录一致，且记录的输出都存在
+    ///
+    /// 返回仍然有效的记录，以便写入新的清单。
+    pub(crate) async fn up_to_date(&self, relative: &str, dst: &Path, source_hash: &str, config_hash: &str) -> Option<ManifestEntry> {
+        let entry = self.files.get(relative)?;
+        if entry.source_hash != source_hash || entry.config_hash != config_hash || entry.outputs.is_empty() {
+            return None;
+        }
+        for output in &entry.outputs {
+            if !tokio::fs::try_exists(dst.join(output)).await.unwrap_or(false) {
+                return None;
+            }
+        }
+        Some(entry.clone())
+    }
+}
+
+impl ManifestEntry {
+    /// 由源内容哈希、配置哈希和输出路径创建记录
+    pub(crate) fn new(source_hash: String, config_hash: String, outputs: Vec<PathBuf>, dst: &Path) -> Self {
+        let outputs = outputs
+            .into_iter()
+            .map(|output| output.strip_prefix(dst).map(Path::to_path_buf).unwrap_or(output))
+            .collect();
+        Self {
+            source_hash,
+            config_hash,
+            outputs,
+        }
+    }
+}
+
+/// 源文件内容的哈希
+pub(crate) fn source_hash(content: &[u8]) -> String {
+    format!("{:016x}", stable_hash(content))
+}
+
+/// 影响译文的配置的哈希
+///
+/// 由序列化后去掉速率限制、超时、日志等无关项的配置、本地术语表文件的内容和命名规则组成；
+/// 新增的配置项默认参与计算，宁可多翻译一次也不跳过可能变化的文件。
+pub(crate) fn config_hash(components: &Components, naming: &str) -> String {
+    let mut config = serde_json::to_value(&*components.config).unwrap_or_default();
+    if let Some(fields) = config.as_object_mut() {
+        for field in IRRELEVANT_FIELDS {
+            fields.remove(*field);
+        }
+    }
+    sort_keys(&mut config);
+    let mut hasher = StableHasher::default();
+    hasher.field(&MANIFEST_VERSION.to_le_bytes());
+    hasher.field(config.to_string().as_bytes());
+    hasher.field(&components.glossary.fingerprint().to_le_bytes());
+    hasher.field(naming.as_bytes());
+    format!("{:016x}", hasher.finish())
+}
+
+/// 递归地按键名排序对象，使由 `HashMap` 序列化的部分（例如术语表）得到稳定的顺序
+fn sort_keys(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = std::mem::take(map).into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            for (key, mut value) in entries {
+                sort_keys(&mut value);
+                map.insert(key, value);
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(sort_keys),
+        _ => {}
+    }
+}
@@ -0,0 +1,335 @@
+//! 运行指标模块
+//!
+//! 记录请求次数、重试次数、收发字符数以及速率限制等待、退避等待和HTTP耗时，
+//! 用于判断一次缓慢的翻译任务时间花在了哪里。
+//! 另外可以通过 [`MetricsRecorder`] 把按后端和状态区分的计数器与直方图转发给监控系统，
+//! 启用 `prometheus` 特性时提供以Prometheus文本格式导出的 `PrometheusRecorder`。
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// 发出的API请求数（含重试），标签 `backend`（端点的主机和端口）和 `status`（`ok`、HTTP状态码或错误类型）
+pub const REQUESTS_TOTAL: &str = "translation_requests_total";
+/// 单个API请求的耗时（秒），标签 `backend`
+pub const REQUEST_DURATION_SECONDS: &str = "translation_request_duration_seconds";
+/// 重试次数
+pub const RETRIES_TOTAL: &str = "translation_retries_total";
+/// 每次请求前在速率限制器上的等待时间（秒）
+pub const RATE_LIMITER_WAIT_SECONDS: &str = "translation_rate_limiter_wait_seconds";
+/// 单个块（含重试）的翻译耗时（秒），标签 `outcome`（`ok` 或 `error`）
+pub const CHUNK_DURATION_SECONDS: &str = "translation_chunk_duration_seconds";
+/// 收发的字符数，标签 `direction`（`sent` 或 `received`）
+pub const CHARACTERS_TOTAL: &str = "translation_characters_total";
+/// 翻译缓存命中数，命中的块不发送请求
+pub const CACHE_HITS_TOTAL: &str = "translation_cache_hits_total";
+
+/// 指标记录器
+///
+/// 通过 [`TranslationServiceBuilder::metrics_recorder`](crate::TranslationServiceBuilder::metrics_recorder)
+/// 设置后，服务在每个请求、重试、速率限制等待和块完成时调用它，指标名见本模块的常量。
+/// 实现需要自行聚合；调用发生在翻译任务中，应当快速返回。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::metrics::{MetricsRecorder, REQUESTS_TOTAL};
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// #[derive(Default)]
+/// struct RequestCounter(AtomicU64);
+///
+/// impl MetricsRecorder for RequestCounter {
+///     fn increment_counter(&self, name: &'static str, _labels: &[(&'static str, &str)], value: u64) {
+///         if name == REQUESTS_TOTAL {
+///             self.0.fetch_add(value, Ordering::Relaxed);
+///         }
+///     }
+///
+///     fn record_histogram(&self, _name: &'static str, _labels: &[(&'static str, &str)], _value: f64) {}
+/// }
+/// ```
+pub trait MetricsRecorder: Send + Sync {
+    /// 计数器增加 `value`
+    fn increment_counter(&self, name: &'static str, labels: &[(&'static str, &str)], value: u64);
+
+    /// 直方图记录一次观测值，时间以秒为单位
+    fn record_histogram(&self, name: &'static str, labels: &[(&'static str, &str)], value: f64);
+}
+
+impl std::fmt::Debug for dyn MetricsRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("MetricsRecorder")
+    }
+}
+
+/// 累计耗时统计（总计与最大值）
+#[derive(Debug, Default)]
+struct DurationStat {
+    total_micros: AtomicU64,
+    max_micros: AtomicU64,
+}
+
+impl DurationStat {
+    fn record(&self, duration: Duration) {
+        let micros = duration.as_micros() as u64;
+        self.total_micros.fetch_add(micros, Ordering::Relaxed);
+        self.max_micros.fetch_max(micros, Ordering::Relaxed);
+    }
+
+    fn total(&self) -> Duration {
+        Duration::from_micros(self.total_micros.load(Ordering::Relaxed))
+    }
+
+    fn max(&self) -> Duration {
+        Duration::from_micros(self.max_micros.load(Ordering::Relaxed))
+    }
+}
+
+/// 运行指标
+///
+/// 基于原子计数器，可在并发的块任务之间无锁共享。
+/// `TranslationService` 持有一份累计所有调用的指标，每次调用另有一份写入报告。
+#[derive(Debug, Default)]
+pub struct Metrics {
+    requests: AtomicU64,
+    retries: AtomicU64,
+    rate_limited: AtomicU64,
+    characters_sent: AtomicU64,
+    characters_received: AtomicU64,
+    limiter_wait: DurationStat,
+    backoff_wait: DurationStat,
+    http_time: DurationStat,
+}
+
+impl Metrics {
+    /// 记录一次实际发出的请求及其HTTP耗时
+    pub(crate) fn record_request(&self, elapsed: Duration) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.http_time.record(elapsed);
+    }
+
+    /// 记录一次被限流（HTTP 429）的响应
+    pub(crate) fn record_rate_limited(&self) {
+        self.rate_limited.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录一次重试及其退避等待时间
+    pub(crate) fn record_retry(&self, backoff: Duration) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+        self.backoff_wait.record(backoff);
+    }
+
+    /// 记录一次请求发送的字符数
+    pub(crate) fn record_characters_sent(&self, characters: usize) {
+        self.characters_sent.fetch_add(characters as u64, Ordering::Relaxed);
+    }
+
+    /// 记录一次成功响应返回的字符数
+    pub(crate) fn record_characters_received(&self, characters: usize) {
+        self.characters_received.fetch_add(characters as u64, Ordering::Relaxed);
+    }
+
+    /// 记录一次速率限制器等待时间
+    pub(crate) fn record_limiter_wait(&self, waited: Duration) {
+        self.limiter_wait.record(waited);
+    }
+
+    /// 获取当前指标快照
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            requests: self.requests.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            rate_limited_responses: self.rate_limited.load(Ordering::Relaxed),
+            characters_sent: self.characters_sent.load(Ordering::Relaxed),
+            characters_received: self.characters_received.load(Ordering::Relaxed),
+            limiter_wait_total: self.limiter_wait.total(),
+            limiter_wait_max: self.limiter_wait.max(),
+            backoff_wait_total: self.backoff_wait.total(),
+            backoff_wait_max: self.backoff_wait.max(),
+            http_time_total: self.http_time.total(),
+            http_time_max: self.http_time.max(),
+        }
+    }
+}
+
+/// 运行指标快照
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MetricsSnapshot {
+    /// 实际发出的API请求数（含重试）
+    pub requests: u64,
+    /// 重试次数
+    pub retries: u64,
+    /// 收到的HTTP 429响应数
+    pub rate_limited_responses: u64,
+    /// 请求中发送的字符数（含重试）
+    pub characters_sent: u64,
+    /// 成功响应中返回的字符数
+    pub characters_received: u64,
+    /// 速率限制器累计等待时间
+    pub limiter_wait_total: Duration,
+    /// 速率限制器单次最长等待时间
+    pub limiter_wait_max: Duration,
+    /// 重试退避累计等待时间
+    pub backoff_wait_total: Duration,
+    /// 重试退避单次最长等待时间
+    pub backoff_wait_max: Duration,
+    /// HTTP请求累计耗时
+    pub http_time_total: Duration,
+    /// HTTP请求单次最长耗时
+    pub http_time_max: Duration,
+}
+
+#[cfg(feature = "prometheus")]
+pub use prometheus::PrometheusRecorder;
+
+#[cfg(feature = "prometheus")]
+mod prometheus {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::fmt::Write;
+    use std::sync::Mutex;
+
+    /// 直方图的桶上限（秒）
+    const BUCKETS: [f64; 13] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+    /// 已知指标的说明
+    const HELP: [(&str, &str); 7] = [
+        (REQUESTS_TOTAL, "Translation API requests, including retries"),
+        (REQUEST_DURATION_SECONDS, "Duration of a single translation API request"),
+        (RETRIES_TOTAL, "Retried translation API requests"),
+        (RATE_LIMITER_WAIT_SECONDS, "Time spent waiting for the rate limiter before a request"),
+        (CHUNK_DURATION_SECONDS, "Duration of translating one chunk, including retries"),
+        (CHARACTERS_TOTAL, "Characters sent to and received from the translation API"),
+        (CACHE_HITS_TOTAL, "Translation cache hits"),
+    ];
+
+    /// 指标名和按名称排序的标签
+    type Key = (&'static str, Vec<(&'static str, String)>);
+
+    /// 直方图的累计值
+    #[derive(Debug, Default)]
+    struct Histogram {
+        /// 每个桶（不含 `+Inf`）的观测数，非累计
+        buckets: [u64; BUCKETS.len()],
+        count: u64,
+        sum: f64,
+    }
+
+    /// 以Prometheus文本格式导出的指标记录器
+    ///
+    /// 在内存中聚合计数器和直方图，[`gather`](Self::gather) 返回文本格式（`text/plain; version=0.0.4`），
+    /// 可以直接作为已有 `/metrics` 端点的响应体。需要启用 `prometheus` 特性。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::metrics::PrometheusRecorder;
+    /// use markdown_translator::{FnTranslator, TranslationServiceBuilder};
+    /// use std::sync::Arc;
+    ///
+    /// let recorder = Arc::new(PrometheusRecorder::new());
+    /// let service = TranslationServiceBuilder::new()
+    ///     .enabled(true)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+    ///     .metrics_recorder(recorder.clone())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// # tokio_test::block_on(async {
+    /// service.translate("Hello").await.unwrap();
+    /// assert!(recorder.gather().contains("# TYPE translation_requests_total counter"));
+    /// # });
+    /// ```
+    #[derive(Debug, Default)]
+    pub struct PrometheusRecorder {
+        counters: Mutex<BTreeMap<Key, u64>>,
+        histograms: Mutex<BTreeMap<Key, Histogram>>,
+    }
+
+    impl PrometheusRecorder {
+        /// 创建空的记录器
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// 以Prometheus文本格式输出所有指标，按指标名和标签排序
+        pub fn gather(&self) -> String {
+            let mut output = String::new();
+            let mut last = None;
+            for ((name, labels), value) in self.counters.lock().unwrap().iter() {
+                if last != Some(*name) {
+                    write_header(&mut output, name, "counter");
+                    last = Some(*name);
+                }
+                let _ = writeln!(output, "{}{} {}", name, format_labels(labels, None), value);
+            }
+            for ((name, labels), histogram) in self.histograms.lock().unwrap().iter() {
+                if last != Some(*name) {
+                    write_header(&mut output, name, "histogram");
+                    last = Some(*name);
+                }
+                let mut cumulative = 0;
+                for (bound, count) in BUCKETS.iter().zip(histogram.buckets) {
+                    cumulative += count;
+                    let le = bound.to_string();
+                    let _ = writeln!(output, "{}_bucket{} {}", name, format_labels(labels, Some(&le)), cumulative);
+                }
+                let _ = writeln!(output, "{}_bucket{} {}", name, format_labels(labels, Some("+Inf")), histogram.count);
+                let _ = writeln!(output, "{}_sum{} {}", name, format_labels(labels, None), histogram.sum);
+                let _ = writeln!(output, "{}_count{} {}", name, format_labels(labels, None), histogram.count);
+            }
+            output
+        }
+    }
+
+    impl MetricsRecorder for PrometheusRecorder {
+        fn increment_counter(&self, name: &'static str, labels: &[(&'static str, &str)], value: u64) {
+            *self.counters.lock().unwrap().entry(key(name, labels)).or_default() += value;
+        }
+
+        fn record_histogram(&self, name: &'static str, labels: &[(&'static str, &str)], value: f64) {
+            let mut histograms = self.histograms.lock().unwrap();
+            let histogram = histograms.entry(key(name, labels)).or_default();
+            if let Some(bucket) = BUCKETS.iter().position(|&bound| value <= bound) {
+                histogram.buckets[bucket] += 1;
+            }
+            histogram.count += 1;
+            histogram.sum += value;
+        }
+    }
+
+    /// 由指标名和标签构造键，标签按名称排序
+    fn key(name: &'static str, labels: &[(&'static str, &str)]) -> Key {
+        let mut labels: Vec<(&'static str, String)> =
+            labels.iter().map(|&(label, value)| (label, value.to_string())).collect();
+        labels.sort();
+        (name, labels)
+    }
+
+    /// 写出指标的 `HELP` 和 `TYPE` 行
+    fn write_header(output: &mut String, name: &str, kind: &str) {
+        if let Some((_, help)) = HELP.iter().find(|(known, _)| *known == name) {
+            let _ = writeln!(output, "# HELP {} {}", name, help);
+        }
+        let _ = writeln!(output, "# TYPE {} {}", name, kind);
+    }
+
+    /// 格式化标签，`le` 为直方图桶的上限
+    fn format_labels(labels: &[(&'static str, String)], le: Option<&str>) -> String {
+        let pairs: Vec<String> = labels
+            .iter()
+            .map(|(label, value)| (*label, value.as_str()))
+            .chain(le.map(|le| ("le", le)))
+            .map(|(label, value)| {
+                let escaped = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+                format!("{}=\"{}\"", label, escaped)
+            })
+            .collect();
+        if pairs.is_empty() {
+            String::new()
+        } else {
+            format!("{{{}}}", pairs.join(","))
+        }
+    }
+}
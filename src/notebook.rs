@@ -0,0 +1,142 @@
+//! Jupyter notebook翻译模块
+//!
+//! 解析nbformat 4的JSON，只翻译Markdown单元格的源文本，代码单元格、输出和元数据原样保留，
+//! 序列化时保持键的顺序和缩进，使结果仍能被Jupyter工具读取。
+
+use crate::error::{Result, TranslationError};
+use crate::translator::{TranslationService, MAX_CONCURRENT_CHUNKS};
+use crate::types::{TranslateOptions, TranslationReport};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use serde_json::ser::PrettyFormatter;
+use serde_json::Value;
+
+impl TranslationService {
+    /// 翻译Jupyter notebook中的Markdown单元格
+    ///
+    /// `json` 是 `.ipynb` 文件的内容（nbformat 4）。每个 `cell_type` 为 `markdown` 的单元格按完整的
+    /// Markdown流程翻译，代码单元格、输出、附件和元数据不会发送给翻译API。
+    /// 源文本为行列表时，译文按同样的约定拆分：除最后一行外每行以换行结尾。
+    /// 输出保持原有的键顺序和缩进（nbformat默认为1个空格）。
+    ///
+    /// # 返回
+    ///
+    /// * `Ok(String)` - 翻译后的notebook JSON
+    /// * `Err(TranslationError::ParseError)` - 不是合法的JSON或不是nbformat 4的notebook
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{FnTranslator, TranslationServiceBuilder};
+    ///
+    /// let service = TranslationServiceBuilder::new()
+    ///     .enabled(true)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let notebook = r#"{"cells": [{"cell_type": "markdown", "metadata": {}, "source": ["Load the data."]}], "metadata": {}, "nbformat": 4, "nbformat_minor": 5}"#;
+    /// # tokio_test::block_on(async {
+    /// let translated = service.translate_notebook(notebook).await.unwrap();
+    /// assert!(translated.contains("LOAD THE DATA."));
+    /// # });
+    /// ```
+    pub async fn translate_notebook(&self, json: &str) -> Result<String> {
+        self.run_notebook_translation(json, &TranslateOptions::default())
+            .await
+            .map(|(translated, _)| translated)
+    }
+
+    /// notebook翻译的公共实现，所有单元格共享同一个调用上下文和报告
+    pub(crate) async fn run_notebook_translation(
+        &self,
+        json: &str,
+        options: &TranslateOptions,
+    ) -> Result<(String, TranslationReport)> {
+        let mut notebook: Value = serde_json::from_str(json)
+            .map_err(|e| TranslationError::ParseError(format!("无法解析notebook: {}", e)))?;
+        let context = self.new_context(options)?;
+        if !context.components.config.enabled {
+            return Ok((json.to_string(), context.report()));
+        }
+
+        if notebook.get("nbformat").and_then(Value::as_u64) != Some(4) {
+            return Err(TranslationError::ParseError("只支持nbformat 4的notebook".to_string()));
+        }
+        let cells = notebook
+            .get_mut("cells")
+            .and_then(Value::as_array_mut)
+            .ok_or_else(|| TranslationError::ParseError("notebook缺少cells数组".to_string()))?;
+
+        let sources: Vec<(usize, String)> = cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.get("cell_type").and_then(Value::as_str) == Some("markdown"))
+            .filter_map(|(index, cell)| cell.get("source").and_then(join_source).map(|source| (index, source)))
+            .collect();
+        let translations: Vec<_> = sources
+            .iter()
+            .map(|(_, source)| self.translate_document(source, &context))
+            .collect();
+        let translations: Vec<Result<String>> = stream::iter(translations)
+            .buffered(MAX_CONCURRENT_CHUNKS)
+            .collect()
+            .await;
+
+        for ((index, source), translated) in sources.iter().zip(translations) {
+            let translated = match_trailing_newline(source, translated?);
+            let cell_source = &mut cells[*index]["source"];
+            *cell_source = match cell_source {
+                Value::Array(_) => Value::Array(split_source(&translated)),
+                _ => Value::String(translated),
+            };
+        }
+
+        Ok((serialize_like(&notebook, json)?, context.report()))
+    }
+}
+
+/// 单元格源文本：行列表拼接为完整文本，字符串原样返回
+fn join_source(source: &Value) -> Option<String> {
+    match source {
+        Value::String(text) => Some(text.clone()),
+        Value::Array(lines) => lines.iter().map(Value::as_str).collect::<Option<String>>(),
+        _ => None,
+    }
+}
+
+/// 按nbformat的约定拆分为行列表：除最后一行外每行以换行结尾，空文本对应空列表
+fn split_source(text: &str) -> Vec<Value> {
+    text.split_inclusive('\n').map(|line| Value::String(line.to_string())).collect()
+}
+
+/// 使译文的结尾换行与原文一致
+fn match_trailing_newline(source: &str, translated: String) -> String {
+    let body = translated.trim_end_matches('\n');
+    if source.ends_with('\n') {
+        format!("{}\n", body)
+    } else {
+        body.to_string()
+    }
+}
+
+/// 按原文的缩进序列化JSON，原文只有一行时输出紧凑格式，原文以换行结尾时保留结尾换行
+pub(crate) fn serialize_like(value: &Value, original: &str) -> Result<String> {
+    let error = |e: serde_json::Error| TranslationError::ParseError(format!("无法序列化JSON: {}", e));
+    let mut output = match original.trim_end().split_once('\n') {
+        None => serde_json::to_string(value).map_err(error)?,
+        Some((_, rest)) => {
+            let indent = &rest[..rest.len() - rest.trim_start_matches([' ', '\t']).len()];
+            let indent = if indent.is_empty() { " " } else { indent };
+            let mut output = Vec::new();
+            let mut serializer =
+                serde_json::Serializer::with_formatter(&mut output, PrettyFormatter::with_indent(indent.as_bytes()));
+            value.serialize(&mut serializer).map_err(error)?;
+            String::from_utf8(output).expect("serde_json always writes valid UTF-8")
+        }
+    };
+    if original.ends_with('\n') {
+        output.push('\n');
+    }
+    Ok(output)
+}
@@ -0,0 +1,372 @@
+//! gettext PO模块
+//!
+//! 解析PO文件的条目（msgctxt、msgid、msgid_plural、msgstr和标记行），只重新生成需要填写的 `msgstr`，
+//! 注释、引用和其他条目原样保留；生成的 `msgstr` 遵循文件原有的折行约定，
+//! 带 `c-format` 标记的条目以占位符保护printf格式说明符。
+
+use crate::error::{Result, TranslationError};
+use crate::protect::Placeholders;
+use crate::translator::{redact, CallContext, TranslationService, MAX_CONCURRENT_CHUNKS};
+use crate::types::PoOptions;
+use futures::stream::{self, StreamExt};
+use regex::{Captures, Regex};
+use std::sync::LazyLock;
+
+/// printf格式说明符占位符的类型标记
+const PRINTF_PLACEHOLDER: char = 'P';
+
+/// msgcat默认的折行宽度，包括关键字和引号
+const WRAP_WIDTH: usize = 79;
+
+/// 头部没有声明 `Plural-Forms` 时使用的复数形式数量
+const DEFAULT_PLURALS: usize = 2;
+
+/// printf格式说明符，例如 `%s`、`%1$d`、`%-8.2f`、`%%`
+static PRINTF: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"%(?:\d+\$)?[-+ #0']*(?:\d+|\*)?(?:\.(?:\d+|\*))?(?:hh|ll|[hlLqjzt])?[diouxXeEfFgGaAcspn%]").unwrap()
+});
+
+/// 头部 `Plural-Forms` 中声明的复数形式数量
+static NPLURALS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"nplurals\s*=\s*(\d+)").unwrap());
+
+/// PO文件的组成部分，按顺序拼接后与原文完全一致
+enum PoPart<'a> {
+    /// 条目之间的空行
+    Blank(&'a str),
+    /// 一个条目
+    Entry(PoEntry<'a>),
+}
+
+/// PO文件中的条目
+#[derive(Default)]
+struct PoEntry<'a> {
+    /// 条目的原始行，包括换行符
+    lines: Vec<&'a str>,
+    msgctxt: Option<String>,
+    msgid: String,
+    msgid_plural: Option<String>,
+    /// 按序号排列的 `msgstr`，单数条目只有一项
+    msgstr: Vec<String>,
+    /// 第一行 `msgstr` 的位置，之后的行都属于译文
+    msgstr_line: Option<usize>,
+    /// `#,` 行中的标记
+    flags: Vec<String>,
+    /// 以 `#~` 开头的废弃条目
+    obsolete: bool,
+}
+
+/// 正在读取的字段，续行追加到该字段
+#[derive(Clone, Copy)]
+enum Field {
+    Msgctxt,
+    Msgid,
+    MsgidPlural,
+    Msgstr(usize),
+}
+
+impl PoEntry<'_> {
+    /// 是否为头部条目（没有上下文的空 `msgid`）
+    fn is_header(&self) -> bool {
+        self.msgid.is_empty() && self.msgctxt.is_none()
+    }
+
+    fn has_flag(&self, flag: &str) -> bool {
+        self.flags.iter().any(|f| f == flag)
+    }
+
+    /// 条目是否需要翻译
+    fn needs_translation(&self, translate_all: bool) -> bool {
+        !self.obsolete
+            && !self.is_header()
+            && self.msgstr_line.is_some()
+            && (translate_all || self.msgstr.iter().all(String::is_empty))
+    }
+}
+
+impl TranslationService {
+    /// 翻译gettext PO文件
+    ///
+    /// 默认只填写 `msgstr` 为空的条目，`translate_all` 时重新翻译除头部和废弃条目外的所有条目。
+    /// 带 `c-format` 标记的条目中的printf格式说明符（`%s`、`%1$d` 等）以占位符保护。
+    /// 复数条目按头部 `Plural-Forms` 声明的数量生成 `msgstr[n]`：`msgstr[0]` 为 `msgid` 的译文，
+    /// 其余为 `msgid_plural` 的译文，只有一种复数形式时使用 `msgid_plural` 的译文。
+    /// 注释、引用和未翻译的条目原样保留；文件中没有超过79列的字符串行时，生成的 `msgstr` 按msgcat的
+    /// 约定折行，否则只在换行符处断开。
+    ///
+    /// # 返回
+    ///
+    /// * `Ok(String)` - 翻译后的PO文件
+    /// * `Err(TranslationError::ParseError)` - 存在无法识别的行或不完整的字符串
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{FnTranslator, PoOptions, TranslationServiceBuilder};
+    ///
+    /// let service = TranslationServiceBuilder::new()
+    ///     .enabled(true)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let po = "msgid \"Quit\"\nmsgstr \"\"\n";
+    /// # tokio_test::block_on(async {
+    /// let translated = service.translate_po(po, &PoOptions::new()).await.unwrap();
+    /// assert_eq!(translated, "msgid \"Quit\"\nmsgstr \"QUIT\"\n");
+    /// # });
+    /// ```
+    pub async fn translate_po(&self, content: &str, options: &PoOptions) -> Result<String> {
+        let parts = parse_po(content, self.config().log_content)?;
+        let context = self.new_context(&options.translate_options)?;
+        if !context.components.config.enabled {
+            return Ok(content.to_string());
+        }
+
+        let plurals = parts
+            .iter()
+            .find_map(|part| match part {
+                PoPart::Entry(entry) if entry.is_header() => Some(entry),
+                _ => None,
+            })
+            .and_then(|header| NPLURALS.captures(header.msgstr.first()?))
+            .and_then(|found| found[1].parse().ok())
+            .filter(|&plurals| plurals > 0)
+            .unwrap_or(DEFAULT_PLURALS);
+        let wrap = !content
+            .lines()
+            .any(|line| (line.starts_with('"') || line.starts_with("msg")) && line.chars().count() > WRAP_WIDTH);
+
+        let translations: Vec<_> = parts
+            .iter()
+            .filter_map(|part| match part {
+                PoPart::Entry(entry) if entry.needs_translation(options.translate_all) => {
+                    Some(self.translate_po_entry(entry, plurals, &context))
+                }
+                _ => None,
+            })
+            .collect();
+        let mut translations = stream::iter(translations).buffered(MAX_CONCURRENT_CHUNKS);
+
+        let mut output = String::with_capacity(content.len());
+        for part in &parts {
+            match part {
+                PoPart::Blank(line) => output.push_str(line),
+                PoPart::Entry(entry) if entry.needs_translation(options.translate_all) => {
+                    let msgstr = translations.next().await.expect("one translation per translated entry")?;
+                    write_entry(&mut output, entry, &msgstr, options.mark_fuzzy, wrap);
+                }
+                PoPart::Entry(entry) => entry.lines.iter().for_each(|line| output.push_str(line)),
+            }
+        }
+        Ok(output)
+    }
+
+    /// 翻译一个条目，返回按序号排列的 `msgstr`
+    async fn translate_po_entry(&self, entry: &PoEntry<'_>, plurals: usize, context: &CallContext) -> Result<Vec<String>> {
+        let c_format = entry.has_flag("c-format");
+        let singular = self.translate_po_string(&entry.msgid, c_format, context).await?;
+        let Some(msgid_plural) = &entry.msgid_plural else {
+            return Ok(vec![singular]);
+        };
+        let plural = self.translate_po_string(msgid_plural, c_format, context).await?;
+        if plurals == 1 {
+            return Ok(vec![plural]);
+        }
+        Ok(std::iter::once(singular).chain(std::iter::repeat_n(plural, plurals - 1)).collect())
+    }
+
+    /// 翻译单个字符串，保留首尾空白和换行
+    async fn translate_po_string(&self, text: &str, c_format: bool, context: &CallContext) -> Result<String> {
+        let body = text.trim();
+        if !body.chars().any(char::is_alphanumeric) {
+            return Ok(text.to_string());
+        }
+        let leading = &text[..text.len() - text.trim_start().len()];
+        let trailing = &text[text.trim_end().len()..];
+
+        let translated = if c_format {
+            let mut placeholders = Placeholders::default();
+            let protected = PRINTF.replace_all(body, |found: &Captures| {
+                placeholders.insert(PRINTF_PLACEHOLDER, found[0].to_string())
+            });
+            self.translate_protected(&protected, &placeholders, "c-format", context).await?
+        } else {
+            self.translate_chunk(body, context).await?
+        };
+        Ok(format!("{}{}{}", leading, translated.trim(), trailing))
+    }
+}
+
+/// 将PO文件拆分为空行和条目
+fn parse_po(content: &str, log_content: bool) -> Result<Vec<PoPart<'_>>> {
+    let mut parts = Vec::new();
+    let mut entry = PoEntry::default();
+    let mut field = None;
+
+    for (number, line) in content.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            if !entry.lines.is_empty() {
+                parts.push(PoPart::Entry(std::mem::take(&mut entry)));
+                field = None;
+            }
+            parts.push(PoPart::Blank(line));
+            continue;
+        }
+
+        let index = entry.lines.len();
+        entry.lines.push(line);
+        let error = || {
+            TranslationError::ParseError(format!("无法解析PO文件第{}行: {}", number + 1, redact(trimmed, log_content)))
+        };
+
+        if let Some(flags) = trimmed.strip_prefix("#,") {
+            entry.flags.extend(flags.split(',').map(|flag| flag.trim().to_string()));
+            continue;
+        }
+        if trimmed.starts_with("#~") {
+            entry.obsolete = true;
+            continue;
+        }
+        if trimmed.starts_with('#') {
+            continue;
+        }
+
+        let (next, rest) = if let Some(rest) = trimmed.strip_prefix("msgctxt") {
+            (Field::Msgctxt, rest)
+        } else if let Some(rest) = trimmed.strip_prefix("msgid_plural") {
+            (Field::MsgidPlural, rest)
+        } else if let Some(rest) = trimmed.strip_prefix("msgid") {
+            (Field::Msgid, rest)
+        } else if let Some(rest) = trimmed.strip_prefix("msgstr[") {
+            let (slot, rest) = rest.split_once(']').ok_or_else(error)?;
+            (Field::Msgstr(slot.parse().map_err(|_| error())?), rest)
+        } else if let Some(rest) = trimmed.strip_prefix("msgstr") {
+            (Field::Msgstr(0), rest)
+        } else if trimmed.starts_with('"') {
+            (field.ok_or_else(error)?, trimmed)
+        } else {
+            return Err(error());
+        };
+
+        let value = unescape(rest.trim()).ok_or_else(error)?;
+        match next {
+            Field::Msgctxt => entry.msgctxt.get_or_insert_with(String::new).push_str(&value),
+            Field::Msgid => entry.msgid.push_str(&value),
+            Field::MsgidPlural => entry.msgid_plural.get_or_insert_with(String::new).push_str(&value),
+            Field::Msgstr(slot) => {
+                entry.msgstr_line.get_or_insert(index);
+                if entry.msgstr.len() <= slot {
+                    entry.msgstr.resize(slot + 1, String::new());
+                }
+                entry.msgstr[slot].push_str(&value);
+            }
+        }
+        field = Some(next);
+    }
+
+    if !entry.lines.is_empty() {
+        parts.push(PoPart::Entry(entry));
+    }
+    Ok(parts)
+}
+
+/// 写出翻译后的条目：`msgstr` 之前的行原样保留，需要时添加 `fuzzy` 标记
+fn write_entry(output: &mut String, entry: &PoEntry<'_>, msgstr: &[String], mark_fuzzy: bool, wrap: bool) {
+    let msgstr_line = entry.msgstr_line.expect("translated entries have msgstr");
+    let newline = if entry.lines[msgstr_line].ends_with("\r\n") { "\r\n" } else { "\n" };
+    let mut fuzzy_pending = mark_fuzzy && !entry.has_flag("fuzzy");
+
+    for line in &entry.lines[..msgstr_line] {
+        if fuzzy_pending {
+            if let Some(flags) = line.strip_prefix("#,") {
+                output.push_str("#, fuzzy,");
+                output.push_str(flags);
+                fuzzy_pending = false;
+                continue;
+            }
+            // 标记行位于注释和引用之后、上一版本msgid（`#|`）和关键字之前
+            if !entry.lines[..msgstr_line].iter().any(|line| line.starts_with("#,"))
+                && (line.starts_with("#|") || !line.starts_with('#'))
+            {
+                output.push_str("#, fuzzy");
+                output.push_str(newline);
+                fuzzy_pending = false;
+            }
+        }
+        output.push_str(line);
+    }
+
+    for (slot, value) in msgstr.iter().enumerate() {
+        let keyword = if entry.msgid_plural.is_some() {
+            format!("msgstr[{}]", slot)
+        } else {
+            "msgstr".to_string()
+        };
+        write_string(output, &keyword, value, wrap, newline);
+    }
+}
+
+/// 按msgcat的约定写出关键字和字符串：字符串在换行符处断开，`wrap` 时超过79列的行在空格后断开
+fn write_string(output: &mut String, keyword: &str, value: &str, wrap: bool, newline: &str) {
+    let pieces: Vec<String> = value.split_inclusive('\n').map(escape).collect();
+    let single_width = keyword.chars().count() + pieces.first().map_or(0, |piece| piece.chars().count()) + 3;
+    if pieces.len() <= 1 && (!wrap || single_width <= WRAP_WIDTH) {
+        output.push_str(&format!("{} \"{}\"{}", keyword, pieces.concat(), newline));
+        return;
+    }
+
+    output.push_str(&format!("{} \"\"{}", keyword, newline));
+    for piece in &pieces {
+        let mut line = String::new();
+        for word in piece.split_inclusive(' ') {
+            let width = line.chars().count() + word.chars().count() + 2;
+            if wrap && !line.is_empty() && width > WRAP_WIDTH {
+                output.push_str(&format!("\"{}\"{}", line, newline));
+                line.clear();
+            }
+            line.push_str(word);
+        }
+        output.push_str(&format!("\"{}\"{}", line, newline));
+    }
+}
+
+/// 解析带引号的PO字符串，处理C风格的转义
+fn unescape(quoted: &str) -> Option<String> {
+    let inner = quoted.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        value.push(match chars.next()? {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            'a' => '\u{7}',
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'v' => '\u{b}',
+            other => other,
+        });
+    }
+    Some(value)
+}
+
+/// 按PO的约定转义字符串
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,178 @@
+//! 占位符保护模块
+//!
+//! 在发送请求前将需要保护的文本替换为占位符令牌，译文返回后再还原，
+//! 供术语表、保留术语等需要精确控制输出的功能使用。
+
+use crate::error::{Result, TranslationError};
+use crate::glossary::GLOSSARY_PLACEHOLDER;
+use crate::types::ProtectionStats;
+use regex::Regex;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+/// 保留术语占位符的类型标记
+pub(crate) const KEEP_PLACEHOLDER: char = 'K';
+
+/// 以该前缀开头的保留术语按正则表达式解析
+const REGEX_PREFIX: &str = "re:";
+
+/// 行内代码和URL，其中出现的保留术语不单独替换，整体交由后端处理
+static PROTECTED_SPANS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"`[^`\n]+`|https?://[^\s)>\]]+").unwrap());
+
+/// 单个块内登记的占位符
+#[derive(Debug, Default)]
+pub(crate) struct Placeholders {
+    entries: Vec<(char, String, String)>,
+}
+
+/// 占位符还原结果
+#[derive(Debug)]
+pub(crate) struct Restored {
+    /// 还原后的文本
+    pub(crate) text: String,
+    /// 成功还原的占位符的类型标记
+    pub(crate) restored: Vec<char>,
+    /// 译文中丢失的占位符的类型标记及对应内容
+    pub(crate) missing: Vec<(char, String)>,
+}
+
+impl Placeholders {
+    /// 登记一段需要在译文中替换为 `replacement` 的内容，返回发送给API的令牌
+    ///
+    /// 令牌形如 `{{G0}}`，`kind` 区分不同的保护来源，序号在同一块内唯一。
+    pub(crate) fn insert(&mut self, kind: char, replacement: String) -> String {
+        let token = format!("{{{{{}{}}}}}", kind, self.entries.len());
+        self.entries.push((kind, token.clone(), replacement));
+        token
+    }
+
+    /// 是否没有登记任何占位符
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 将登记的保留术语和行内标记计入统计，术语表条目不计入
+    pub(crate) fn count_into(&self, stats: &mut ProtectionStats) {
+        for (kind, _, replacement) in &self.entries {
+            match *kind {
+                GLOSSARY_PLACEHOLDER => continue,
+                KEEP_PLACEHOLDER => stats.keep_terms += 1,
+                _ => stats.inline_markup += 1,
+            }
+            stats.protected_chars += replacement.chars().count();
+        }
+    }
+
+    /// 将译文中的令牌还原为登记的内容
+    pub(crate) fn restore(&self, text: &str) -> Restored {
+        let mut restored_text = text.to_string();
+        let mut restored = Vec::new();
+        let mut missing = Vec::new();
+
+        for (kind, token, replacement) in &self.entries {
+            if restored_text.contains(token.as_str()) {
+                restored_text = restored_text.replace(token.as_str(), replacement);
+                restored.push(*kind);
+            } else {
+                missing.push((*kind, replacement.clone()));
+            }
+        }
+
+        Restored {
+            text: restored_text,
+            restored,
+            missing,
+        }
+    }
+}
+
+/// 保留术语：必须原样出现在译文中的词，例如品牌名、命令行参数和缩写
+///
+/// 普通条目按整词匹配，以 `re:` 开头的条目按正则表达式匹配。
+/// 多个条目重叠时取最靠前的匹配，同一位置优先较长的普通条目；
+/// 位于行内代码或URL中的匹配会被跳过。
+#[derive(Debug, Default)]
+pub(crate) struct KeepTerms {
+    pattern: Option<Regex>,
+}
+
+impl KeepTerms {
+    /// 编译保留术语列表，正则条目无效时返回 `ConfigError`
+    pub(crate) fn new(terms: &[String]) -> Result<Self> {
+        let mut literals: Vec<&str> = Vec::new();
+        let mut patterns: Vec<String> = Vec::new();
+        for term in terms {
+            match term.strip_prefix(REGEX_PREFIX) {
+                Some(pattern) => {
+                    Regex::new(pattern).map_err(|e| {
+                        TranslationError::ConfigError(format!("保留术语正则 {:?} 无效: {}", pattern, e))
+                    })?;
+                    patterns.push(format!("(?:{})", pattern));
+                }
+                None if !term.trim().is_empty() => literals.push(term),
+                None => {}
+            }
+        }
+        if literals.is_empty() && patterns.is_empty() {
+            return Ok(Self::default());
+        }
+
+        literals.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        let alternatives: Vec<String> = literals
+            .into_iter()
+            .map(whole_word_pattern)
+            .chain(patterns)
+            .collect();
+        let pattern = Regex::new(&alternatives.join("|"))
+            .map_err(|e| TranslationError::ConfigError(format!("保留术语无法编译: {}", e)))?;
+        Ok(Self { pattern: Some(pattern) })
+    }
+
+    /// 将保留术语替换为占位符，还原时恢复为原文
+    pub(crate) fn protect(&self, text: &str, placeholders: &mut Placeholders) -> String {
+        let Some(pattern) = &self.pattern else {
+            return text.to_string();
+        };
+
+        let mut result = String::with_capacity(text.len());
+        let mut last = 0;
+        for found in find_terms(pattern, text) {
+            result.push_str(&text[last..found.start]);
+            result.push_str(&placeholders.insert(KEEP_PLACEHOLDER, text[found.clone()].to_string()));
+            last = found.end;
+        }
+        result.push_str(&text[last..]);
+        result
+    }
+
+    /// 文本中出现的保留术语，与 `protect` 替换的内容一致
+    pub(crate) fn matches<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        match &self.pattern {
+            Some(pattern) => find_terms(pattern, text).into_iter().map(|range| &text[range]).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// 查找保留术语的位置，跳过空匹配以及位于行内代码或URL中的匹配
+fn find_terms(pattern: &Regex, text: &str) -> Vec<Range<usize>> {
+    let spans: Vec<Range<usize>> = PROTECTED_SPANS.find_iter(text).map(|m| m.range()).collect();
+    let overlaps_span = |range: &Range<usize>| {
+        spans.iter().any(|span| range.start < span.end && span.start < range.end)
+    };
+    pattern
+        .find_iter(text)
+        .map(|found| found.range())
+        .filter(|range| !range.is_empty() && !overlaps_span(range))
+        .collect()
+}
+
+/// 普通条目的整词正则；只在术语两端为单词字符的一侧添加 `\b`，
+/// 使 `--dry-run` 这类以符号开头的条目也能匹配
+pub(crate) fn whole_word_pattern(term: &str) -> String {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let prefix = if term.chars().next().is_some_and(is_word) { r"\b" } else { "" };
+    let suffix = if term.chars().last().is_some_and(is_word) { r"\b" } else { "" };
+    format!("{}{}{}", prefix, regex::escape(term), suffix)
+}
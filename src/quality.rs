@@ -0,0 +1,145 @@
+//! 质量检查模块
+//!
+//! 块翻译完成后检查译文是否可疑（空译文、未翻译、长度异常、缺少目标语言文字、保留术语丢失），
+//! 并运行用户提供的检查钩子。可疑的块重新翻译一次，仍不合格时交给失败处理策略。
+
+use crate::error::{Result, TranslationError};
+use crate::translator::{CallContext, TranslationService};
+use crate::types::{QualityVerdict, TranslationScope};
+
+/// 内置长度与文字检查要求原文至少包含的字母数，过短的文本比例波动太大
+const MIN_CHECKED_LETTERS: usize = 20;
+
+impl TranslationService {
+    /// 翻译一个非代码段并执行质量检查
+    ///
+    /// 未启用内置检查且没有检查钩子时直接翻译。首次译文被拒绝时，从端点池中的下一个端点开始
+    /// 重新翻译一次（启用 `quality_retry_split` 时将段拆成两半分别翻译），结果记录在报告中；
+    /// 重新翻译的结果仍被拒绝时返回 `QualityCheckFailed`，由调用方按失败处理策略处理。
+    pub(crate) async fn translate_checked(
+        &self,
+        index: usize,
+        text: &str,
+        context: &CallContext,
+    ) -> Result<(String, Vec<String>)> {
+        let config = &context.components.config;
+        if !config.quality_checks && self.hooks.quality_check.is_none() && context.quality_check.is_none() {
+            return self.translate_in_scope(text, context).await;
+        }
+
+        let first = self.translate_in_scope(text, context).await?;
+        let Some(reason) = self.quality_issue(text, &first.0, context) else {
+            return Ok(first);
+        };
+
+        let retry_context = CallContext {
+            endpoint_offset: context.endpoint_offset + 1,
+            fresh: true,
+            ..context.clone()
+        };
+        let second = match split_in_half(text).filter(|_| config.quality_retry_split) {
+            Some((head, tail)) => {
+                let (head, _) = self.translate_in_scope(head, &retry_context).await?;
+                let (tail, _) = self.translate_in_scope(tail, &retry_context).await?;
+                (format!("{}\n\n{}", head.trim_end(), tail.trim_start()), Vec::new())
+            }
+            None => self.translate_in_scope(text, &retry_context).await?,
+        };
+
+        let issue = self.quality_issue(text, &second.0, context);
+        context.record_retranslation(index, reason, issue.is_none());
+        match issue {
+            None => Ok(second),
+            Some(reason) => Err(TranslationError::QualityCheckFailed { index, reason }),
+        }
+    }
+
+    /// 依次运行内置检查、服务级钩子和调用级钩子，返回第一个拒绝原因
+    fn quality_issue(&self, source: &str, translated: &str, context: &CallContext) -> Option<String> {
+        if context.components.config.quality_checks {
+            if let Some(reason) = builtin_issue(source, translated, context) {
+                return Some(reason);
+            }
+        }
+        [&self.hooks.quality_check, &context.quality_check]
+            .into_iter()
+            .flatten()
+            .find_map(|check| match check.check(source, translated) {
+                QualityVerdict::Accept => None,
+                QualityVerdict::Reject(reason) => Some(reason),
+            })
+    }
+}
+
+/// 内置检查
+///
+/// 按范围或块类型过滤翻译时，段中有部分内容本就保持原文，只检查空译文和保留术语。
+fn builtin_issue(source: &str, translated: &str, context: &CallContext) -> Option<String> {
+    let config = &context.components.config;
+    if translated.trim().is_empty() && !source.trim().is_empty() {
+        return Some("译文为空".to_string());
+    }
+    if let Some(term) = context
+        .keep_terms
+        .matches(source)
+        .into_iter()
+        .find(|term| !translated.contains(term))
+    {
+        return Some(format!("保留术语 {:?} 在译文中丢失", term));
+    }
+
+    let filtered = context.scope != TranslationScope::All || context.translate_kinds.is_some();
+    let letters = source.chars().filter(|c| c.is_alphabetic()).count();
+    if filtered || letters == 0 {
+        return None;
+    }
+    if translated.trim() == source.trim() && !context.source_lang.eq_ignore_ascii_case(&context.target_lang) {
+        return Some("译文与原文相同".to_string());
+    }
+    if letters < MIN_CHECKED_LETTERS {
+        return None;
+    }
+
+    let ratio = translated.trim().chars().count() as f64 / source.trim().chars().count() as f64;
+    if ratio < config.quality_min_length_ratio || ratio > config.quality_max_length_ratio {
+        return Some(format!(
+            "译文长度是原文的 {:.2} 倍，超出 {}–{} 的范围",
+            ratio, config.quality_min_length_ratio, config.quality_max_length_ratio
+        ));
+    }
+    match target_script(&context.target_lang) {
+        Some(in_script) if !translated.chars().any(in_script) => {
+            Some(format!("译文中没有目标语言 {} 的文字", context.target_lang))
+        }
+        _ => None,
+    }
+}
+
+/// 目标语言使用的文字，无法从语言代码判断（例如拉丁字母语言）时返回 `None`
+fn target_script(lang: &str) -> Option<fn(char) -> bool> {
+    let lang = lang.split(['-', '_']).next().unwrap_or(lang).to_ascii_lowercase();
+    let script: fn(char) -> bool = match lang.as_str() {
+        "zh" => |c| matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}'),
+        "ja" => |c| matches!(c, '\u{3040}'..='\u{30FF}' | '\u{4E00}'..='\u{9FFF}'),
+        "ko" => |c| matches!(c, '\u{AC00}'..='\u{D7AF}' | '\u{1100}'..='\u{11FF}'),
+        "ru" | "uk" | "bg" | "be" | "sr" | "mk" | "kk" => |c| matches!(c, '\u{0400}'..='\u{04FF}'),
+        "el" => |c| matches!(c, '\u{0370}'..='\u{03FF}'),
+        "ar" | "fa" | "ur" => |c| matches!(c, '\u{0600}'..='\u{06FF}'),
+        "he" => |c| matches!(c, '\u{0590}'..='\u{05FF}'),
+        "th" => |c| matches!(c, '\u{0E00}'..='\u{0E7F}'),
+        "hi" | "mr" | "ne" => |c| matches!(c, '\u{0900}'..='\u{097F}'),
+        _ => return None,
+    };
+    Some(script)
+}
+
+/// 在最接近中点的空行处将文本拆成两半，没有空行时返回 `None`
+fn split_in_half(text: &str) -> Option<(&str, &str)> {
+    let middle = text.len() / 2;
+    let split = text
+        .match_indices("\n\n")
+        .map(|(position, _)| position)
+        .filter(|&position| position > 0 && position + 2 < text.len())
+        .min_by_key(|&position| position.abs_diff(middle))?;
+    Some((&text[..split], &text[split + 2..]))
+}
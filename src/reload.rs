@@ -0,0 +1,187 @@
+//! 配置热加载模块
+//!
+//! 监视配置文件所在的目录，文件内容变化时重新读取、校验，并通过
+//! [`TranslationService::update_config`] 应用到运行中的服务。需要启用 `hot-reload` 特性。
+
+use crate::config::TranslationLibConfig;
+use crate::error::{Result, TranslationError};
+use crate::translator::TranslationService;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 收到文件变化后等待写入完成的时间，期间的后续变化合并为一次加载
+const SETTLE_DELAY: Duration = Duration::from_millis(200);
+
+/// 一次热加载的结果
+#[derive(Debug)]
+pub enum ReloadEvent {
+    /// 新配置已生效
+    Applied {
+        /// 配置文件路径
+        path: PathBuf,
+    },
+    /// 新配置无法读取、解析或未通过校验，原配置保持不变
+    Rejected {
+        /// 配置文件路径
+        path: PathBuf,
+        /// 拒绝的原因，校验失败时一次列出所有问题
+        error: TranslationError,
+    },
+}
+
+/// 配置文件监视器
+///
+/// 由 [`TranslationService::enable_hot_reload`] 返回，被丢弃时停止监视。
+pub struct ConfigWatcher {
+    /// 底层的文件系统监视器
+    _watcher: RecommendedWatcher,
+    /// 被监视的配置文件
+    path: PathBuf,
+}
+
+impl ConfigWatcher {
+    /// 被监视的配置文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl fmt::Debug for ConfigWatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigWatcher").field("path", &self.path).finish()
+    }
+}
+
+impl TranslationService {
+    /// 监视配置文件，文件修改后自动应用新配置
+    ///
+    /// 文件变化后等待写入完成，按 [`TranslationLibConfig::from_file`] 的严格模式解析，
+    /// 应用环境变量覆盖，再通过 [`update_config`](Self::update_config) 校验并生效，
+    /// 因此所有克隆都会使用新配置，进行中的调用不受影响。配置文件不含API密钥时沿用当前的密钥。
+    ///
+    /// 每次加载的结果通过 `on_event` 回调报告，不会打印到标准输出：
+    /// 新配置无效时原配置保持不变，回调收到 [`ReloadEvent::Rejected`]，其中列出所有问题。
+    /// 内容与上次加载时相同、文件为空或暂时不存在（例如编辑器先删除再写入）时不做处理。
+    /// 回调在监视线程上调用。需要启用 `hot-reload` 特性。
+    ///
+    /// 监视的是配置文件所在的目录，以便感知编辑器通过重命名替换文件的保存方式。
+    /// 返回的 [`ConfigWatcher`] 被丢弃时停止监视。
+    ///
+    /// # 返回
+    ///
+    /// * `Ok(ConfigWatcher)` - 已开始监视
+    /// * `Err(TranslationError::ConfigError)` - 路径没有文件名，或无法监视所在目录
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{ReloadEvent, TranslationConfig, TranslationService};
+    /// use std::time::Duration;
+    ///
+    /// let dir = std::env::temp_dir().join("markdown-translator-doc-hot-reload");
+    /// std::fs::create_dir_all(&dir).unwrap();
+    /// let path = dir.join("translation-config.toml");
+    /// std::fs::write(&path, "[translation]\ntarget_lang = \"zh\"\n").unwrap();
+    ///
+    /// let service = TranslationService::new(TranslationConfig::default());
+    /// let (sender, events) = std::sync::mpsc::channel();
+    /// let _watcher = service
+    ///     .enable_hot_reload(&path, move |event| {
+    ///         let _ = sender.send(event);
+    ///     })
+    ///     .unwrap();
+    ///
+    /// std::fs::write(&path, "[translation]\ntarget_lang = \"ja\"\nmax_requests_per_second = 5.0\n").unwrap();
+    /// let event = events.recv_timeout(Duration::from_secs(10)).unwrap();
+    /// assert!(matches!(event, ReloadEvent::Applied { .. }));
+    /// assert_eq!(service.config().target_lang, "ja");
+    ///
+    /// // 无效的配置被拒绝，原配置保持不变
+    /// std::fs::write(&path, "[translation]\ntarget_lang = \"ko\"\nmax_text_length = 0\n").unwrap();
+    /// match events.recv_timeout(Duration::from_secs(10)).unwrap() {
+    ///     ReloadEvent::Rejected { error, .. } => assert!(error.to_string().contains("max_text_length")),
+    ///     event => panic!("unexpected event: {:?}", event),
+    /// }
+    /// assert_eq!(service.config().target_lang, "ja");
+    /// ```
+    pub fn enable_hot_reload(
+        &self,
+        path: impl AsRef<Path>,
+        mut on_event: impl FnMut(ReloadEvent) + Send + 'static,
+    ) -> Result<ConfigWatcher> {
+        let path = path.as_ref().to_path_buf();
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| TranslationError::ConfigError(format!("配置文件路径无效: {}", path.display())))?
+            .to_os_string();
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let service = self.clone();
+        let watched = path.clone();
+        let mut last = std::fs::read_to_string(&path).ok();
+        let handler = move |event: notify::Result<notify::Event>| {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    on_event(ReloadEvent::Rejected {
+                        path: watched.clone(),
+                        error: TranslationError::ConfigError(format!("监视配置文件失败: {}", e)),
+                    });
+                    return;
+                }
+            };
+            let relevant = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                && event.paths.iter().any(|path| path.file_name() == Some(file_name.as_os_str()));
+            if !relevant {
+                return;
+            }
+
+            std::thread::sleep(SETTLE_DELAY);
+            let content = match std::fs::read_to_string(&watched) {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+                Err(source) => {
+                    on_event(ReloadEvent::Rejected {
+                        path: watched.clone(),
+                        error: TranslationError::Io { path: watched.clone(), source },
+                    });
+                    return;
+                }
+            };
+            if content.trim().is_empty() || last.as_deref() == Some(content.as_str()) {
+                return;
+            }
+
+            let result = reload(&service, &watched, &content);
+            last = Some(content);
+            on_event(match result {
+                Ok(()) => ReloadEvent::Applied { path: watched.clone() },
+                Err(error) => ReloadEvent::Rejected { path: watched.clone(), error },
+            });
+        };
+
+        let watch_error =
+            |e: notify::Error| TranslationError::ConfigError(format!("无法监视配置文件 {}: {}", path.display(), e));
+        let mut watcher = notify::recommended_watcher(handler).map_err(watch_error)?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive).map_err(watch_error)?;
+        Ok(ConfigWatcher { _watcher: watcher, path })
+    }
+}
+
+/// 解析新的配置内容并应用到服务，失败时原配置保持不变
+fn reload(service: &TranslationService, path: &Path, content: &str) -> Result<()> {
+    let mut lib_config = TranslationLibConfig::parse_file(path, content)?;
+    lib_config.apply_env_overrides()?;
+    let mut config = lib_config.translation;
+    service.update_config(move |current| {
+        if config.api_key.is_none() {
+            config.api_key = current.api_key.take();
+        }
+        *current = config;
+    })
+}
@@ -0,0 +1,145 @@
+//! 审校对照模块
+//!
+//! 将已翻译的段渲染为原文与译文逐段对齐的审校视图，不涉及网络请求。
+
+use crate::html::escape;
+use crate::types::{ReviewFormat, TranslatedSegment};
+
+/// 将已翻译的段渲染为原文与译文逐段对齐的审校视图
+///
+/// 每个段占一行，与 `translate_stream` 产出的段一一对应，只含空白的段被跳过。
+/// 代码段不翻译，只输出一次：Markdown表格中放在原文列、译文列留空，
+/// HTML页面中合并为一个跨两列的单元格，JSONL记录的 `kind` 为 `"code"`（其他段为 `"text"`）。
+///
+/// * [`ReviewFormat::Markdown`] - 三列表格（序号、原文、译文），单元格内的换行渲染为 `<br>`，`|` 被转义
+/// * [`ReviewFormat::Html`] - 完整的HTML页面，每段一个 `<tr>`，单元格保留原文的换行
+/// * [`ReviewFormat::Jsonl`] - 每段一行 `{index, source, translation, kind}`，`index` 与段的序号一致
+///
+/// 表格中的序号从1开始。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{render_review, ReviewFormat, TranslatedSegment};
+///
+/// let segments = vec![
+///     TranslatedSegment {
+///         index: 0,
+///         source: "Hello | world".to_string(),
+///         translated: "你好 | 世界".to_string(),
+///         is_code_block: false,
+///         alternatives: Vec::new(),
+///         chunk_id: "doc-0".to_string(),
+///     },
+///     TranslatedSegment {
+///         index: 1,
+///         source: "```\nlet x = 1;\n```".to_string(),
+///         translated: "```\nlet x = 1;\n```".to_string(),
+///         is_code_block: true,
+///         alternatives: Vec::new(),
+///         chunk_id: "doc-1".to_string(),
+///     },
+/// ];
+///
+/// assert_eq!(
+///     render_review(&segments, ReviewFormat::Markdown),
+///     "| 序号 | 原文 | 译文 |\n\
+///      | --- | --- | --- |\n\
+///      | 1 | Hello \\| world | 你好 \\| 世界 |\n\
+///      | 2 | <pre><code>```<br>let x = 1;<br>```</code></pre> | |\n"
+/// );
+///
+/// let html = render_review(&segments, ReviewFormat::Html);
+/// assert!(html.contains("<tr data-index=\"0\"><td>1</td><td>Hello | world</td><td>你好 | 世界</td></tr>"));
+/// assert!(html.contains("<td colspan=\"2\"><pre><code>```\nlet x = 1;\n```</code></pre></td>"));
+///
+/// let jsonl = render_review(&segments, ReviewFormat::Jsonl);
+/// let first: serde_json::Value = serde_json::from_str(jsonl.lines().next().unwrap()).unwrap();
+/// assert_eq!(first, serde_json::json!({
+///     "index": 0,
+///     "source": "Hello | world",
+///     "translation": "你好 | 世界",
+///     "kind": "text",
+/// }));
+/// assert_eq!(jsonl.lines().count(), 2);
+/// ```
+pub fn render_review(segments: &[TranslatedSegment], format: ReviewFormat) -> String {
+    let segments = segments.iter().filter(|segment| !segment.source.trim().is_empty());
+    match format {
+        ReviewFormat::Markdown => render_markdown(segments),
+        ReviewFormat::Html => render_html(segments),
+        ReviewFormat::Jsonl => render_jsonl(segments),
+    }
+}
+
+fn render_markdown<'a>(segments: impl Iterator<Item = &'a TranslatedSegment>) -> String {
+    let mut output = String::from("| 序号 | 原文 | 译文 |\n| --- | --- | --- |\n");
+    for segment in segments {
+        let row = if segment.is_code_block {
+            let code = escape(segment.source.trim_matches('\n'), None).replace('|', "&#124;");
+            format!("| {} | <pre><code>{}</code></pre> | |\n", segment.index + 1, markdown_lines(&code))
+        } else {
+            format!(
+                "| {} | {} | {} |\n",
+                segment.index + 1,
+                markdown_cell(&segment.source),
+                markdown_cell(&segment.translated)
+            )
+        };
+        output.push_str(&row);
+    }
+    output
+}
+
+/// 转义表格单元格中的 `|` 并将换行渲染为 `<br>`
+fn markdown_cell(text: &str) -> String {
+    markdown_lines(&text.trim().replace('|', "\\|"))
+}
+
+fn markdown_lines(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\n', "<br>")
+}
+
+fn render_html<'a>(segments: impl Iterator<Item = &'a TranslatedSegment>) -> String {
+    let mut output = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>翻译审校</title>\n<style>\n\
+         table { border-collapse: collapse; width: 100%; }\n\
+         th, td { border: 1px solid #ccc; padding: 6px; vertical-align: top; white-space: pre-wrap; }\n\
+         </style>\n</head>\n<body>\n<table>\n<tr><th>序号</th><th>原文</th><th>译文</th></tr>\n",
+    );
+    for segment in segments {
+        let row = if segment.is_code_block {
+            format!(
+                "<tr data-index=\"{}\" class=\"code\"><td>{}</td><td colspan=\"2\"><pre><code>{}</code></pre></td></tr>\n",
+                segment.index,
+                segment.index + 1,
+                escape(segment.source.trim_matches('\n'), None)
+            )
+        } else {
+            format!(
+                "<tr data-index=\"{}\"><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                segment.index,
+                segment.index + 1,
+                escape(segment.source.trim(), None),
+                escape(segment.translated.trim(), None)
+            )
+        };
+        output.push_str(&row);
+    }
+    output.push_str("</table>\n</body>\n</html>\n");
+    output
+}
+
+fn render_jsonl<'a>(segments: impl Iterator<Item = &'a TranslatedSegment>) -> String {
+    segments
+        .map(|segment| {
+            let record = serde_json::json!({
+                "index": segment.index,
+                "source": segment.source,
+                "translation": segment.translated,
+                "kind": if segment.is_code_block { "code" } else { "text" },
+            });
+            format!("{}\n", record)
+        })
+        .collect()
+}
@@ -0,0 +1,350 @@
+//! reStructuredText模块
+//!
+//! 按行识别rst的块级结构：指令及其缩进内容、注释和链接目标、字面量块、表格原样保留，
+//! 段落中的角色、行内字面量、替换引用和链接引用以占位符保护，
+//! 节标题翻译后按译文的显示宽度重新生成上下划线。
+
+use crate::error::Result;
+use crate::protect::Placeholders;
+use crate::translator::{split_at_boundaries, CallContext, TranslationService, MAX_CONCURRENT_CHUNKS};
+use futures::stream::{self, StreamExt};
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// rst占位符的类型标记
+const RST_PLACEHOLDER: char = 'R';
+
+/// 正文需要翻译的指令（提示框类），只保留指令行和选项行
+const ADMONITIONS: [&str; 12] = [
+    "attention", "caution", "danger", "error", "hint", "important", "note", "tip", "warning", "seealso",
+    "versionadded", "versionchanged",
+];
+
+/// 行内需要原样保留的标记：角色与解释文本、行内字面量、链接引用、替换引用、脚注引用和命名引用
+static INLINE_MARKUP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"(?s)(?::[\w.+:-]+:)?``.+?``",
+        r"|(?::[\w.+:-]+:)?`[^`]+`(?::[\w.+:-]+:)?_{0,2}",
+        r"|\|[^|\s](?:[^|]*[^|\s])?\|_{0,2}",
+        r"|\[[#*\w-]*\]_",
+        r"|\b\w+__?\b",
+    ))
+    .unwrap()
+});
+
+/// rst中的块
+#[derive(Debug, PartialEq)]
+enum RstBlock<'a> {
+    /// 原样保留的内容：空行、指令、注释、字面量块、表格和过渡线
+    Verbatim(&'a str),
+    /// 需要翻译的段落，包括列表项和提示框正文
+    Paragraph(&'a str),
+    /// 节标题
+    Title {
+        /// 是否带上划线
+        overline: bool,
+        /// 划线字符
+        adornment: char,
+        /// 标题行，不含换行
+        title: &'a str,
+        /// 下划线之后的换行
+        newline: &'a str,
+    },
+}
+
+impl TranslationService {
+    /// 翻译一段rst文本，保留指令、字面量和行内标记，重新生成节标题的划线
+    pub(crate) async fn translate_rst(&self, text: &str, context: &CallContext) -> Result<(String, Vec<String>)> {
+        let blocks: Vec<_> = segment_rst(text)
+            .into_iter()
+            .map(|block| self.translate_rst_block(block, context))
+            .collect();
+        let translated: Vec<Result<String>> = stream::iter(blocks)
+            .buffered(MAX_CONCURRENT_CHUNKS)
+            .collect()
+            .await;
+        Ok((translated.into_iter().collect::<Result<String>>()?, Vec::new()))
+    }
+
+    /// 翻译一个块，节标题按译文重新生成划线
+    async fn translate_rst_block(&self, block: RstBlock<'_>, context: &CallContext) -> Result<String> {
+        match block {
+            RstBlock::Verbatim(raw) => Ok(raw.to_string()),
+            RstBlock::Paragraph(raw) => self.translate_rst_paragraph(raw, context).await,
+            RstBlock::Title { overline, adornment, title, newline } => {
+                let indent = &title[..title.len() - title.trim_start().len()];
+                let translated = self.translate_rst_inline(title.trim(), context).await?;
+                let translated = format!("{}{}", indent, translated.replace('\n', " "));
+                let line = adornment.to_string().repeat(display_width(&translated).max(1));
+                Ok(if overline {
+                    format!("{line}\n{translated}\n{line}{newline}")
+                } else {
+                    format!("{translated}\n{line}{newline}")
+                })
+            }
+        }
+    }
+
+    /// 翻译一个段落：去掉公共缩进后翻译，译文每行重新缩进；结尾的 `::` 原样保留
+    async fn translate_rst_paragraph(&self, raw: &str, context: &CallContext) -> Result<String> {
+        let body = raw.trim_end_matches(['\n', '\r']);
+        let newline = &raw[body.len()..];
+        let indent = body
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(indentation)
+            .min()
+            .unwrap_or(0);
+        let dedented = body.lines().map(|line| line.get(indent..).unwrap_or("")).collect::<Vec<_>>().join("\n");
+
+        // `text::` 表示后面是字面量块，标记本身不交给后端
+        let (content, marker) = match dedented.strip_suffix("::") {
+            Some(content) => (content.trim_end(), &dedented[content.trim_end().len()..]),
+            None => (dedented.as_str(), ""),
+        };
+        if !content.chars().any(char::is_alphanumeric) {
+            return Ok(raw.to_string());
+        }
+
+        let translated = self.translate_rst_inline(content, context).await?;
+        let padding = " ".repeat(indent);
+        let reindented = format!("{}{}", translated.trim_end(), marker)
+            .lines()
+            .map(|line| if line.is_empty() { String::new() } else { format!("{}{}", padding, line) })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Ok(format!("{}{}", reindented, newline))
+    }
+
+    /// 以占位符保护行内标记后翻译
+    async fn translate_rst_inline(&self, text: &str, context: &CallContext) -> Result<String> {
+        let mut placeholders = Placeholders::default();
+        let protected = INLINE_MARKUP.replace_all(text, |found: &regex::Captures| {
+            placeholders.insert(RST_PLACEHOLDER, found[0].to_string())
+        });
+        self.translate_protected(&protected, &placeholders, "rst", context).await
+    }
+}
+
+/// 将rst文本拆分为块，所有块按顺序拼接后与输入完全一致
+fn segment_rst(text: &str) -> Vec<RstBlock<'_>> {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let offsets: Vec<usize> = lines
+        .iter()
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some(start)
+        })
+        .chain(std::iter::once(text.len()))
+        .collect();
+    let slice = |start: usize, end: usize| &text[offsets[start]..offsets[end]];
+
+    let mut blocks = Vec::new();
+    // 以 `::` 结尾的段落之后，缩进更深的块是字面量块
+    let mut literal_after: Option<usize> = None;
+    let mut index = 0;
+
+    while index < lines.len() {
+        let line = lines[index];
+        if is_blank(line) {
+            blocks.push(RstBlock::Verbatim(line));
+            index += 1;
+            continue;
+        }
+        let indent = indentation(line);
+
+        if let Some(parent) = literal_after.take() {
+            if indent > parent {
+                let end = indented_block_end(&lines, index, parent);
+                blocks.push(RstBlock::Verbatim(slice(index, end)));
+                index = end;
+                continue;
+            }
+        }
+
+        let trimmed = line.trim();
+        if trimmed == ".." || trimmed.starts_with(".. ") {
+            if let Some(end) = admonition_header_end(&lines, index) {
+                blocks.push(RstBlock::Verbatim(slice(index, end)));
+                index = end;
+            } else {
+                let end = indented_block_end(&lines, index + 1, indent);
+                blocks.push(RstBlock::Verbatim(slice(index, end)));
+                index = end;
+            }
+            continue;
+        }
+
+        if let Some(adornment) = adornment_char(line) {
+            let next = lines.get(index + 1).copied();
+            let after = lines.get(index + 2).copied();
+            if let (Some(title), Some(underline)) = (next, after) {
+                if !is_blank(title) && adornment_char(underline) == Some(adornment) {
+                    blocks.push(RstBlock::Title {
+                        overline: true,
+                        adornment,
+                        title: title.trim_end_matches(['\n', '\r']),
+                        newline: line_ending(underline),
+                    });
+                    index += 3;
+                    continue;
+                }
+            }
+            // 过渡线或无法识别的划线
+            blocks.push(RstBlock::Verbatim(line));
+            index += 1;
+            continue;
+        }
+
+        if let Some(end) = table_end(&lines, index) {
+            blocks.push(RstBlock::Verbatim(slice(index, end)));
+            index = end;
+            continue;
+        }
+
+        if let Some(underline) = lines.get(index + 1) {
+            if let Some(adornment) = adornment_char(underline) {
+                if indent == 0 && underline.trim().chars().count() >= 2 {
+                    blocks.push(RstBlock::Title {
+                        overline: false,
+                        adornment,
+                        title: line.trim_end_matches(['\n', '\r']),
+                        newline: line_ending(underline),
+                    });
+                    index += 2;
+                    continue;
+                }
+            }
+        }
+
+        let end = (index + 1..lines.len()).find(|&i| is_blank(lines[i])).unwrap_or(lines.len());
+        let paragraph = slice(index, end);
+        if paragraph.trim_end().ends_with("::") {
+            literal_after = Some(indent);
+        }
+        blocks.push(RstBlock::Paragraph(paragraph));
+        index = end;
+    }
+    blocks
+}
+
+/// 按块边界将rst文本拆分为不超过 `max_length` 的块，各块按顺序拼接后与原文完全一致
+///
+/// 只在空行之后、顶格开始的行处断开，指令内容和字面量块不会被拆到两个块中；
+/// 没有合适断点时允许块超过上限。
+pub(crate) fn split_rst_text(text: &str, max_length: usize) -> Vec<&str> {
+    let mut boundaries = Vec::new();
+    let mut offset = 0;
+    let mut previous_blank = false;
+    let mut previous_literal = false;
+    for line in text.split_inclusive('\n') {
+        if previous_blank && !is_blank(line) && indentation(line) == 0 && !previous_literal {
+            boundaries.push(offset);
+        }
+        if !is_blank(line) {
+            previous_literal = line.trim_end().ends_with("::");
+        }
+        previous_blank = is_blank(line);
+        offset += line.len();
+    }
+
+    split_at_boundaries(text, &boundaries, max_length)
+}
+
+/// 提示框指令的指令行和紧随其后的选项行的结束行（不含），其他指令返回 `None`
+fn admonition_header_end(lines: &[&str], start: usize) -> Option<usize> {
+    let name = lines[start].trim().strip_prefix(".. ")?.split("::").next()?.trim();
+    if !ADMONITIONS.contains(&name.to_ascii_lowercase().as_str()) || !lines[start].contains("::") {
+        return None;
+    }
+    let indent = indentation(lines[start]);
+    let end = (start + 1..lines.len())
+        .find(|&i| {
+            let line = lines[i];
+            is_blank(line) || indentation(line) <= indent || !line.trim_start().starts_with(':')
+        })
+        .unwrap_or(lines.len());
+    Some(end)
+}
+
+/// 从 `start` 开始、缩进深于 `parent` 的块的结束行（不含）；块末尾的空行不计入
+fn indented_block_end(lines: &[&str], start: usize, parent: usize) -> usize {
+    let mut end = start;
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        if is_blank(line) {
+            continue;
+        }
+        if indentation(line) <= parent {
+            break;
+        }
+        end = i + 1;
+    }
+    end
+}
+
+/// 网格表格或简单表格的结束行（不含），`start` 行不是表格边框时返回 `None`
+fn table_end(lines: &[&str], start: usize) -> Option<usize> {
+    let first = lines[start].trim();
+    if first.starts_with('+') && first.ends_with('+') && first.chars().all(|c| matches!(c, '+' | '-' | '=')) {
+        return Some((start + 1..lines.len()).find(|&i| is_blank(lines[i])).unwrap_or(lines.len()));
+    }
+
+    let is_border = |line: &str| {
+        let line = line.trim();
+        line.starts_with('=') && line.contains(' ') && line.chars().all(|c| c == '=' || c == ' ')
+    };
+    if !is_border(first) {
+        return None;
+    }
+    let mut borders = 1;
+    for i in start + 1..lines.len() {
+        if is_border(lines[i]) {
+            borders += 1;
+            let closed = lines.get(i + 1).is_none_or(|next| is_blank(next));
+            if borders >= 2 && closed {
+                return Some(i + 1);
+            }
+        }
+    }
+    Some(lines.len())
+}
+
+/// 行是否为节标题划线或过渡线：至少两个相同的非字母数字ASCII标点
+fn adornment_char(line: &str) -> Option<char> {
+    let line = line.trim_end();
+    let first = line.chars().next()?;
+    let valid = first.is_ascii_punctuation()
+        && line.len() >= 2
+        && line.chars().all(|c| c == first);
+    valid.then_some(first)
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+fn line_ending(line: &str) -> &str {
+    &line[line.trim_end_matches(['\n', '\r']).len()..]
+}
+
+/// 文本的显示宽度，东亚宽字符计为两列，与docutils检查划线长度的方式一致
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x1100..=0x115F
+            | 0x2E80..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x20000..=0x3FFFD => 2,
+            _ => 1,
+        })
+        .sum()
+}
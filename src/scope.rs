@@ -0,0 +1,79 @@
+//! 翻译范围模块
+//!
+//! 按 [`TranslationScope`] 和 `translate_kinds` 过滤段中的Markdown块，
+//! 只有范围内的块会发送给翻译API，其余块原样保留。
+
+use crate::error::Result;
+use crate::segment::segment_markdown;
+use crate::translator::{CallContext, TranslationService};
+use crate::types::{SegmentKind, TextFormat, TranslationScope};
+
+impl TranslationService {
+    /// 按调用的翻译范围和块类型过滤翻译一个非代码段
+    ///
+    /// 未设置范围和块类型时整段作为一个块翻译；否则将段拆分为Markdown块，
+    /// 相邻的范围内块合并为一个请求，块两端的空白原样保留，范围外的块不发送请求。
+    /// 只有整段作为一个块翻译时才返回备选译文。纯文本、rst和AsciiDoc格式不做Markdown过滤，
+    /// 分别见 `translate_plain`、`translate_rst` 和 `translate_asciidoc`。
+    pub(crate) async fn translate_in_scope(&self, text: &str, context: &CallContext) -> Result<(String, Vec<String>)> {
+        match context.format {
+            TextFormat::Markdown => {}
+            TextFormat::Plain => return self.translate_plain(text, context).await,
+            TextFormat::Rst => return self.translate_rst(text, context).await,
+            TextFormat::Asciidoc => return self.translate_asciidoc(text, context).await,
+        }
+        let Some(kinds) = context.translate_kinds.as_deref().or(
+            (context.scope != TranslationScope::All).then_some(SegmentKind::DEFAULT_TRANSLATABLE),
+        ) else {
+            return self.translate_chunk_with_alternatives(text, context).await;
+        };
+        let in_scope = |kind: SegmentKind| {
+            kinds.contains(&kind)
+                && match context.scope {
+                    TranslationScope::All => true,
+                    TranslationScope::HeadingsOnly => kind == SegmentKind::Heading,
+                    TranslationScope::BodyOnly => kind != SegmentKind::Heading,
+                }
+        };
+
+        // 将相邻且范围状态相同的块合并为连续的片段
+        let mut runs: Vec<(bool, usize, usize)> = Vec::new();
+        let mut offset = 0;
+        for block in segment_markdown(text) {
+            let selected = in_scope(block.kind);
+            let end = offset + block.content.len();
+            match runs.last_mut() {
+                Some((last, _, last_end)) if *last == selected => *last_end = end,
+                _ => runs.push((selected, offset, end)),
+            }
+            offset = end;
+        }
+
+        let mut translated = String::with_capacity(text.len());
+        for (selected, start, end) in runs {
+            let piece = &text[start..end];
+            let content = piece.trim();
+            if !selected || !content.chars().any(char::is_alphanumeric) {
+                translated.push_str(piece);
+                continue;
+            }
+            let leading = piece.len() - piece.trim_start().len();
+            translated.push_str(&piece[..leading]);
+            translated.push_str(self.translate_chunk(content, context).await?.trim());
+            translated.push_str(&piece[leading + content.len()..]);
+        }
+        Ok((translated, Vec::new()))
+    }
+
+    /// 整段翻译纯文本，段两端的空白原样保留，使各段可以直接拼接
+    async fn translate_plain(&self, text: &str, context: &CallContext) -> Result<(String, Vec<String>)> {
+        let content = text.trim();
+        if !content.chars().any(char::is_alphanumeric) {
+            return Ok((text.to_string(), Vec::new()));
+        }
+        let leading = text.len() - text.trim_start().len();
+        let (translated, alternatives) = self.translate_chunk_with_alternatives(content, context).await?;
+        let translated = format!("{}{}{}", &text[..leading], translated.trim(), &text[leading + content.len()..]);
+        Ok((translated, alternatives))
+    }
+}
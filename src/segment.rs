@@ -0,0 +1,359 @@
+//! Markdown分段模块
+//!
+//! 按块级结构将Markdown拆分为带类型的块，用于按块类型过滤需要翻译的内容。
+//! 识别规则参照CommonMark，但只做翻译所需的近似：不解析行内语法，也不处理嵌套容器的细节。
+
+use crate::types::{canonical_language, MarkdownBlock, SegmentKind};
+use std::ops::Range;
+
+/// 将Markdown文本拆分为带类型的块
+///
+/// 所有块的 `content` 按顺序拼接后与输入完全一致：块之间的空行归属于前一个块，
+/// 文档开头的空行归属于第一个块。只有文档开头以 `---`（或 `+++`）包围的部分才会识别为前置元数据。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{segment_markdown, SegmentKind};
+///
+/// let document = "---\ntitle: Guide\n---\n# Install\n\n- step one\n- step two\n\n```sh\n# not a heading\n```\n";
+/// let kinds: Vec<SegmentKind> = segment_markdown(document).iter().map(|block| block.kind).collect();
+/// assert_eq!(
+///     kinds,
+///     [SegmentKind::Frontmatter, SegmentKind::Heading, SegmentKind::List, SegmentKind::Code]
+/// );
+///
+/// let rebuilt: String = segment_markdown(document).into_iter().map(|block| block.content).collect();
+/// assert_eq!(rebuilt, document);
+/// ```
+pub fn segment_markdown(text: &str) -> Vec<MarkdownBlock> {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let mut blocks: Vec<MarkdownBlock> = Vec::new();
+    let mut leading = String::new();
+    let mut index = 0;
+
+    if let Some(end) = frontmatter_end(&lines) {
+        blocks.push(MarkdownBlock {
+            kind: SegmentKind::Frontmatter,
+            content: lines[..end].concat(),
+        });
+        index = end;
+    }
+
+    while index < lines.len() {
+        let line = lines[index];
+        if is_blank(line) {
+            match blocks.last_mut() {
+                Some(last) => last.content.push_str(line),
+                None => leading.push_str(line),
+            }
+            index += 1;
+            continue;
+        }
+
+        let (kind, end) = classify(&lines, index);
+        let mut content = std::mem::take(&mut leading);
+        content.push_str(&lines[index..end].concat());
+        blocks.push(MarkdownBlock { kind, content });
+        index = end;
+    }
+
+    if !leading.is_empty() {
+        blocks.push(MarkdownBlock {
+            kind: SegmentKind::Paragraph,
+            content: leading,
+        });
+    }
+    blocks
+}
+
+/// 识别从 `start` 行开始的块，返回块类型和结束行（不含）
+fn classify(lines: &[&str], start: usize) -> (SegmentKind, usize) {
+    let line = lines[start];
+    let until_blank = || (start + 1..lines.len()).find(|&i| is_blank(lines[i])).unwrap_or(lines.len());
+
+    if let Some(fence) = fence_marker(line) {
+        let end = (start + 1..lines.len())
+            .find(|&i| closes_fence(lines[i], fence))
+            .map_or(lines.len(), |i| i + 1);
+        return (SegmentKind::Code, end);
+    }
+    if is_atx_heading(line) {
+        return (SegmentKind::Heading, start + 1);
+    }
+    if is_indented_code(line) {
+        let mut end = start + 1;
+        while end < lines.len() && (is_indented_code(lines[end]) || is_blank(lines[end]) && next_is_indented_code(lines, end)) {
+            end += 1;
+        }
+        return (SegmentKind::Code, end);
+    }
+    if is_blockquote(line) {
+        return (SegmentKind::Blockquote, until_blank());
+    }
+    if is_html_start(line) {
+        return (SegmentKind::Html, until_blank());
+    }
+    if is_table_start(lines, start) {
+        let end = (start + 1..lines.len())
+            .find(|&i| is_blank(lines[i]) || !lines[i].contains('|'))
+            .unwrap_or(lines.len());
+        return (SegmentKind::Table, end);
+    }
+    if is_thematic_break(line) {
+        return (SegmentKind::Paragraph, start + 1);
+    }
+    if is_list_item(line) {
+        return (SegmentKind::List, list_end(lines, start));
+    }
+
+    let mut end = start + 1;
+    while end < lines.len() && !is_blank(lines[end]) {
+        if is_setext_underline(lines[end]) {
+            return (SegmentKind::Heading, end + 1);
+        }
+        if interrupts_paragraph(lines[end]) {
+            break;
+        }
+        end += 1;
+    }
+    (SegmentKind::Paragraph, end)
+}
+
+/// 列表的结束行：空行之后只有缩进的续行或新的列表项才属于同一列表
+fn list_end(lines: &[&str], start: usize) -> usize {
+    let mut end = start + 1;
+    while end < lines.len() {
+        if !is_blank(lines[end]) {
+            end += 1;
+            continue;
+        }
+        let Some(next) = (end..lines.len()).find(|&i| !is_blank(lines[i])) else {
+            break;
+        };
+        if indentation(lines[next]) >= 2 || is_list_item(lines[next]) {
+            end = next;
+        } else {
+            break;
+        }
+    }
+    end
+}
+
+/// 文档开头前置元数据的结束行（不含），没有前置元数据时返回 `None`
+pub(crate) fn frontmatter_end(lines: &[&str]) -> Option<usize> {
+    let delimiter = lines.first()?.trim_end();
+    if delimiter != "---" && delimiter != "+++" {
+        return None;
+    }
+    (1..lines.len())
+        .find(|&i| {
+            let line = lines[i].trim_end();
+            line == delimiter || (delimiter == "---" && line == "...")
+        })
+        .map(|i| i + 1)
+}
+
+/// 围栏代码块中行注释正文的字节范围，不含注释符号及其后的空白
+///
+/// 注释符号按开始围栏信息字符串的语言确定（`#`、`//` 或 `--`），未知语言和没有行注释的语言
+/// （如JSON、CSS）返回空列表。只识别行注释：注释符号位于行首或前面是空白，且不在引号内；
+/// `#!` 开头的行视为shebang，不作为注释。
+pub(crate) fn code_comments(block: &str) -> Vec<Range<usize>> {
+    let mut lines = block.split_inclusive('\n');
+    let Some(first) = lines.next() else {
+        return Vec::new();
+    };
+    let Some(fence) = fence_marker(first) else {
+        return Vec::new();
+    };
+    let info = strip_indent(first).unwrap_or(first).trim_start_matches(fence.0).trim();
+    let language = info.split(|c: char| c.is_whitespace() || c == '{' || c == ',').next().unwrap_or_default();
+    let language = canonical_language(language);
+    let markers: &[&str] = match language {
+        Some("bash" | "python" | "ruby" | "perl" | "r" | "yaml" | "toml" | "dockerfile" | "makefile" | "powershell") => {
+            &["#"]
+        }
+        Some("ini") => &["#", ";"],
+        Some("php") => &["//", "#"],
+        Some(
+            "javascript" | "typescript" | "rust" | "go" | "c" | "cpp" | "csharp" | "java" | "kotlin" | "swift",
+        ) => &["//"],
+        Some("sql" | "lua") => &["--"],
+        _ => return Vec::new(),
+    };
+    // Rust的 `'a` 是生命周期而不是字符串
+    let quotes: &[char] = if language == Some("rust") { &['"'] } else { &['"', '\''] };
+
+    let mut comments = Vec::new();
+    let mut offset = first.len();
+    for line in lines {
+        let start = offset;
+        offset += line.len();
+        if closes_fence(line, fence) {
+            break;
+        }
+        if line.trim_start().starts_with("#!") {
+            continue;
+        }
+        if let Some(range) = line_comment(line, markers, quotes) {
+            comments.push(start + range.start..start + range.end);
+        }
+    }
+    comments
+}
+
+/// 一行中注释正文的字节范围：跳过引号内的内容，注释符号之后连续的符号字符和空白不计入正文
+fn line_comment(line: &str, markers: &[&str], quotes: &[char]) -> Option<Range<usize>> {
+    let mut quote = None;
+    let mut escaped = false;
+    let mut previous = None;
+    for (index, c) in line.char_indices() {
+        match quote {
+            Some(open) => {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == open {
+                    quote = None;
+                }
+            }
+            None if quotes.contains(&c) => quote = Some(c),
+            None => {
+                let marker = markers.iter().find(|marker| line[index..].starts_with(**marker));
+                if let Some(marker) = marker.filter(|_| previous.is_none_or(char::is_whitespace)) {
+                    let symbol = marker.chars().next()?;
+                    let body = &line[index + marker.len()..];
+                    let body_start = line.len() - body.trim_start_matches([symbol, '!']).trim_start().len();
+                    let body_end = line.trim_end().len();
+                    return (body_start < body_end).then_some(body_start..body_end);
+                }
+            }
+        }
+        previous = Some(c);
+    }
+    None
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+/// 行首空格数，制表符按4个空格计
+fn indentation(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// 去掉最多3个空格的缩进，缩进超过3个空格时返回 `None`
+fn strip_indent(line: &str) -> Option<&str> {
+    (indentation(line) <= 3).then(|| line.trim_start_matches(' '))
+}
+
+/// 围栏起始行的标记字符和长度
+fn fence_marker(line: &str) -> Option<(char, usize)> {
+    let rest = strip_indent(line)?;
+    let marker = rest.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let length = rest.chars().take_while(|c| *c == marker).count();
+    (length >= 3).then_some((marker, length))
+}
+
+/// 是否为与起始标记匹配的围栏结束行
+fn closes_fence(line: &str, (marker, length): (char, usize)) -> bool {
+    let Some(rest) = strip_indent(line) else {
+        return false;
+    };
+    let rest = rest.trim_end();
+    rest.len() >= length && rest.chars().all(|c| c == marker)
+}
+
+/// ATX标题：1到6个 `#` 后跟空白或行尾
+fn is_atx_heading(line: &str) -> bool {
+    let Some(rest) = strip_indent(line) else {
+        return false;
+    };
+    let level = rest.chars().take_while(|c| *c == '#').count();
+    (1..=6).contains(&level) && rest[level..].chars().next().is_none_or(char::is_whitespace)
+}
+
+/// Setext标题的下划线：只由 `=` 或 `-` 组成
+fn is_setext_underline(line: &str) -> bool {
+    let Some(rest) = strip_indent(line) else {
+        return false;
+    };
+    let rest = rest.trim_end();
+    !rest.is_empty() && (rest.chars().all(|c| c == '=') || rest.chars().all(|c| c == '-'))
+}
+
+fn is_indented_code(line: &str) -> bool {
+    !is_blank(line) && indentation(line) >= 4
+}
+
+fn next_is_indented_code(lines: &[&str], index: usize) -> bool {
+    (index..lines.len())
+        .find(|&i| !is_blank(lines[i]))
+        .is_some_and(|i| is_indented_code(lines[i]))
+}
+
+fn is_blockquote(line: &str) -> bool {
+    strip_indent(line).is_some_and(|rest| rest.starts_with('>'))
+}
+
+/// HTML块起始行：`<` 后跟字母、`/`、`!` 或 `?`
+fn is_html_start(line: &str) -> bool {
+    let Some(rest) = strip_indent(line) else {
+        return false;
+    };
+    let mut chars = rest.chars();
+    chars.next() == Some('<') && chars.next().is_some_and(|c| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?'))
+}
+
+/// 分隔线：至少3个相同的 `-`、`*` 或 `_`，中间可以有空格
+fn is_thematic_break(line: &str) -> bool {
+    let Some(rest) = strip_indent(line) else {
+        return false;
+    };
+    let marks: Vec<char> = rest.chars().filter(|c| !c.is_whitespace()).collect();
+    marks.len() >= 3 && matches!(marks[0], '-' | '*' | '_') && marks.iter().all(|c| *c == marks[0])
+}
+
+/// 列表项：`-`、`*`、`+` 或 `1.`、`1)` 后跟空白或行尾
+fn is_list_item(line: &str) -> bool {
+    let Some(rest) = strip_indent(line) else {
+        return false;
+    };
+    let digits = rest.chars().take_while(char::is_ascii_digit).count();
+    let marker_len = match rest.chars().next() {
+        Some('-' | '*' | '+') => 1,
+        Some(_) if (1..=9).contains(&digits) && matches!(rest[digits..].chars().next(), Some('.' | ')')) => digits + 1,
+        _ => return false,
+    };
+    rest[marker_len..].chars().next().is_none_or(char::is_whitespace)
+}
+
+/// 表格起始行：包含 `|`，且下一行是分隔行
+fn is_table_start(lines: &[&str], index: usize) -> bool {
+    let Some(next) = lines.get(index + 1) else {
+        return false;
+    };
+    if !lines[index].contains('|') || !next.contains('-') {
+        return false;
+    }
+    let delimiter = next.trim().trim_start_matches('|').trim_end_matches('|');
+    delimiter.split('|').all(|cell| {
+        let cell = cell.trim().trim_start_matches(':').trim_end_matches(':');
+        !cell.is_empty() && cell.chars().all(|c| c == '-')
+    })
+}
+
+/// 能打断段落的行
+fn interrupts_paragraph(line: &str) -> bool {
+    fence_marker(line).is_some()
+        || is_atx_heading(line)
+        || is_blockquote(line)
+        || is_thematic_break(line)
+        || strip_indent(line).is_some_and(|rest| matches!(rest.chars().next(), Some('-' | '*' | '+')) && is_list_item(line))
+}
@@ -0,0 +1,221 @@
+//! 字幕模块
+//!
+//! 解析SRT和WebVTT字幕，只翻译cue文本：序号、cue标识、时间轴和VTT的cue设置原样保留，
+//! `<i>` 等行内标签和对白开头的破折号以占位符保护。多个cue以分隔行打包在一个请求中翻译，
+//! 返回后校验条目数，超过 `subtitle_max_line_length` 的行在词边界处重新折行。
+
+use crate::error::{Result, TranslationError};
+use crate::protect::Placeholders;
+use crate::translator::TranslationService;
+use crate::types::{SubtitleFormat, TranslateOptions};
+use regex::{Captures, Regex};
+use std::sync::LazyLock;
+
+/// 字幕占位符的类型标记
+const SUBTITLE_PLACEHOLDER: char = 'S';
+
+/// 行内标签：SRT的 `<i>`、`<font color="...">`、`{\an8}`，VTT的 `<v Speaker>`、`<c.yellow>` 和时间戳标签
+static INLINE_TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"</?[a-zA-Z][^<>]*>|<\d{2}:[\d:.]+>|\{\\[^{}]*\}").unwrap());
+
+/// 对白开头的破折号
+static SPEAKER_DASH: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?m)^[-‐–—][ \t]*").unwrap());
+
+/// 字幕文件的组成部分，按顺序拼接后与原文完全一致
+enum SubtitlePart<'a> {
+    /// 原样保留的内容：空行、VTT头部、NOTE/STYLE/REGION块以及无法识别的块
+    Verbatim(&'a str),
+    /// 一个cue
+    Cue {
+        /// 序号或cue标识以及时间轴行
+        head: &'a str,
+        /// cue文本，不含最后一行的换行符
+        text: &'a str,
+        /// cue文本之后的换行符
+        newline: &'a str,
+    },
+}
+
+impl TranslationService {
+    /// 翻译SRT或WebVTT字幕
+    ///
+    /// 只有cue文本会发送给翻译API，序号、cue标识、时间轴和VTT的cue设置（`align:start` 等）、
+    /// 头部以及NOTE、STYLE、REGION块原样保留。`<i>`、`<font>`、`<v Speaker>` 等行内标签和对白开头的
+    /// 破折号在译文中保持原样。相邻的cue以分隔行打包在一个请求中翻译（总长不超过 `max_text_length`），
+    /// 返回的条目数与cue数不一致时改为逐条翻译。译文中超过 `subtitle_max_line_length` 个字符的行
+    /// 在词边界处重新折行，折行后各行长度尽量均衡。
+    ///
+    /// # 返回
+    ///
+    /// * `Ok(String)` - 翻译后的字幕
+    /// * `Err(TranslationError::ParseError)` - WebVTT字幕缺少 `WEBVTT` 头部
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{FnTranslator, SubtitleFormat, TranslationServiceBuilder};
+    ///
+    /// let service = TranslationServiceBuilder::new()
+    ///     .enabled(true)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let srt = "1\n00:00:01,000 --> 00:00:03,500\n<i>Where are you going?</i>\n";
+    /// # tokio_test::block_on(async {
+    /// let translated = service.translate_subtitles(srt, SubtitleFormat::Srt).await.unwrap();
+    /// assert_eq!(translated, "1\n00:00:01,000 --> 00:00:03,500\n<i>WHERE ARE YOU GOING?</i>\n");
+    /// # });
+    /// ```
+    pub async fn translate_subtitles(&self, content: &str, format: SubtitleFormat) -> Result<String> {
+        let parts = segment_subtitles(content, format)?;
+        let context = self.new_context(&TranslateOptions::default())?;
+        if !context.components.config.enabled {
+            return Ok(content.to_string());
+        }
+
+        let mut protected = Vec::new();
+        let mut placeholders = Vec::new();
+        for part in &parts {
+            if let SubtitlePart::Cue { text, .. } = part {
+                let mut cue_placeholders = Placeholders::default();
+                let text = text.replace("\r\n", "\n");
+                let text = INLINE_TAG.replace_all(&text, |found: &Captures| {
+                    cue_placeholders.insert(SUBTITLE_PLACEHOLDER, found[0].to_string())
+                });
+                let text = SPEAKER_DASH.replace_all(&text, |found: &Captures| {
+                    cue_placeholders.insert(SUBTITLE_PLACEHOLDER, found[0].to_string())
+                });
+                protected.push(text.into_owned());
+                placeholders.push(cue_placeholders);
+            }
+        }
+
+        let max_line_length = context.components.config.subtitle_max_line_length;
+        let mut translations = self
+            .translate_items(&protected, true, &context)
+            .await
+            .into_iter()
+            .zip(placeholders);
+
+        let mut output = String::with_capacity(content.len());
+        for part in &parts {
+            match part {
+                SubtitlePart::Verbatim(raw) => output.push_str(raw),
+                SubtitlePart::Cue { head, text, newline } => {
+                    let (translated, cue_placeholders) = translations.next().expect("one translation per cue");
+                    let translated = translated?;
+                    context.record_protection(&cue_placeholders);
+                    let restored = cue_placeholders.restore(translated.trim());
+                    for (_, tag) in &restored.missing {
+                        context.warn(format!("字幕标签的占位符在译文中丢失，{:?} 未出现在译文中", tag));
+                    }
+                    let line_ending = if text.contains("\r\n") || head.ends_with("\r\n") { "\r\n" } else { "\n" };
+                    let lines: Vec<String> = restored
+                        .text
+                        .lines()
+                        .map(|line| wrap_line(line.trim_end(), max_line_length))
+                        .collect();
+                    output.push_str(head);
+                    output.push_str(&lines.join("\n").replace('\n', line_ending));
+                    output.push_str(newline);
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// 将字幕拆分为cue和原样保留的部分
+///
+/// 以空行分隔块，包含 `-->` 时间轴行的块是cue：时间轴行及之前的行原样保留，之后的行是cue文本。
+fn segment_subtitles(content: &str, format: SubtitleFormat) -> Result<Vec<SubtitlePart<'_>>> {
+    if format == SubtitleFormat::WebVtt && !content.trim_start_matches('\u{feff}').starts_with("WEBVTT") {
+        return Err(TranslationError::ParseError("WebVTT字幕必须以WEBVTT头部开始".to_string()));
+    }
+
+    let mut parts = Vec::new();
+    let mut offset = 0;
+    let mut block_start = None;
+    for line in content.split_inclusive('\n').chain(std::iter::once("")) {
+        let end = offset + line.len();
+        if line.trim().is_empty() {
+            if let Some(start) = block_start.take() {
+                parts.push(segment_block(&content[start..offset], format));
+            }
+            if !line.is_empty() {
+                parts.push(SubtitlePart::Verbatim(line));
+            }
+        } else if block_start.is_none() {
+            block_start = Some(offset);
+        }
+        offset = end;
+    }
+    Ok(parts)
+}
+
+/// 识别单个块是否为cue
+fn segment_block(block: &str, format: SubtitleFormat) -> SubtitlePart<'_> {
+    let is_vtt_metadata = format == SubtitleFormat::WebVtt
+        && ["WEBVTT", "NOTE", "STYLE", "REGION"]
+            .iter()
+            .any(|keyword| block.trim_start_matches('\u{feff}').starts_with(keyword));
+    let mut offset = 0;
+    let head_end = block.split_inclusive('\n').find_map(|line| {
+        offset += line.len();
+        line.contains("-->").then_some(offset)
+    });
+    let Some(head_end) = head_end.filter(|_| !is_vtt_metadata) else {
+        return SubtitlePart::Verbatim(block);
+    };
+
+    let rest = &block[head_end..];
+    let text = rest.trim_end_matches(['\n', '\r']);
+    if text.trim().is_empty() {
+        return SubtitlePart::Verbatim(block);
+    }
+    SubtitlePart::Cue {
+        head: &block[..head_end],
+        text,
+        newline: &rest[text.len()..],
+    }
+}
+
+/// 行的可见宽度，不计行内标签
+fn visible_width(line: &str) -> usize {
+    INLINE_TAG.replace_all(line, "").chars().count()
+}
+
+/// 超过 `max_length` 的行在词边界处折行，在行数不变的前提下使各行长度尽量均衡
+fn wrap_line(line: &str, max_length: usize) -> String {
+    let width = visible_width(line);
+    if max_length == 0 || width <= max_length {
+        return line.to_string();
+    }
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let greedy = fill(&words, max_length);
+    (width.div_ceil(greedy.len())..max_length)
+        .map(|limit| fill(&words, limit))
+        .find(|lines| lines.len() <= greedy.len())
+        .unwrap_or(greedy)
+        .join("\n")
+}
+
+/// 贪心地将单词填入不超过 `limit` 的行，超长的单词单独成行
+fn fill(words: &[&str], limit: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for word in words {
+        if !current.is_empty() && visible_width(&current) + 1 + visible_width(word) > limit {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
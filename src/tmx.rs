@@ -0,0 +1,371 @@
+//! TMX翻译记忆模块
+//!
+//! 将一次翻译得到的段导出为TMX 1.4文件，供CAT工具使用；也可以把已有的TMX文件导入翻译缓存，
+//! 与文件中原文完全相同的块直接使用其译文，不再请求API。
+//! 解析时兼容OmegaT和Trados等工具产生的文件：TMX 1.1的 `lang` 属性、UTF-16编码、
+//! `<prop>`/`<note>` 元素、CDATA以及 `<bpt>`、`<ph>` 等行内标记。
+
+use crate::cache::{CacheKey, RequestScope};
+use crate::error::{Result, TranslationError};
+use crate::file::io_error;
+use crate::html;
+use crate::protect::Placeholders;
+use crate::translator::{Reply, TranslationService};
+use crate::types::{TranslatedSegment, Verbosity};
+use regex::Regex;
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// 翻译单元 `<tu>`
+static TU: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<tu[\s>].*?</tu>").unwrap());
+
+/// 语言变体 `<tuv>` 的属性和内容
+static TUV: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<tuv\b([^>]*)>(.*?)</tuv>").unwrap());
+
+/// 语言变体中的文本 `<seg>`
+static SEG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<seg\b[^>]*>(.*?)</seg>").unwrap());
+
+/// 语言属性：TMX 1.4的 `xml:lang` 或TMX 1.1的 `lang`
+static LANG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?:^|\s)(?:xml:)?lang\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+
+/// 头部声明的源语言
+static SRCLANG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?s)<header\b[^>]*?\ssrclang\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+
+/// 注释
+static COMMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+
+/// 连同内容一起去除的行内标记：其内容是原始格式代码，不属于文本
+static NATIVE_CODE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<(bpt|ept|ph|it|ut)\b[^>]*?(?:/>|>.*?</(?:bpt|ept|ph|it|ut)>)").unwrap());
+
+/// 其余标签（`<hi>`、`<sub>` 等），只去除标签、保留文本
+static TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").unwrap());
+
+/// 一对原文和译文
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TmxEntry {
+    /// 原文
+    pub source: String,
+    /// 译文
+    pub target: String,
+}
+
+/// 将已翻译的段导出为TMX 1.4文件
+///
+/// 每个段生成一个 `<tu>`（`tuid` 为块ID），包含源语言和目标语言两个 `<tuv>`；
+/// 代码块和只含空白的段被跳过。文本中的 `&`、`<`、`>` 被转义，XML不允许的控制字符被去除。
+/// `source_lang` 为 `auto` 时头部的 `srclang` 写为 `*all*`。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{tmx, TranslatedSegment};
+///
+/// let segment = |index: usize, source: &str, translated: &str| TranslatedSegment {
+///     index,
+///     source: source.to_string(),
+///     translated: translated.to_string(),
+///     is_code_block: false,
+///     alternatives: Vec::new(),
+///     chunk_id: format!("doc-{}", index),
+/// };
+/// let segments = vec![
+///     segment(0, "Use <b> & \"quotes\".", "使用 <b> 和“引号”。"),
+///     segment(1, "Line one\nline two", "第一行\n第二行"),
+/// ];
+///
+/// let xml = tmx::export(&segments, "en", "zh");
+/// assert!(xml.contains(r#"<tmx version="1.4">"#));
+/// assert!(xml.contains("<seg>Use &lt;b&gt; &amp; \"quotes\".</seg>"));
+///
+/// // 导出后再解析得到同样的原文和译文
+/// let entries = tmx::parse(&xml, "en", "zh").unwrap();
+/// assert_eq!(entries.len(), 2);
+/// for (entry, segment) in entries.iter().zip(&segments) {
+///     assert_eq!(entry.source, segment.source);
+///     assert_eq!(entry.target, segment.translated);
+/// }
+/// ```
+pub fn export(segments: &[TranslatedSegment], source_lang: &str, target_lang: &str) -> String {
+    let srclang = if source_lang.eq_ignore_ascii_case("auto") { "*all*" } else { source_lang };
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<tmx version=\"1.4\">\n");
+    xml.push_str(&format!(
+        "  <header creationtool=\"markdown-translator\" creationtoolversion=\"{}\" datatype=\"plaintext\" \
+         segtype=\"block\" adminlang=\"en\" srclang=\"{}\" o-tmf=\"markdown-translator\"/>\n  <body>\n",
+        env!("CARGO_PKG_VERSION"),
+        escape(srclang, true)
+    ));
+    for segment in segments {
+        if segment.is_code_block || segment.source.trim().is_empty() {
+            continue;
+        }
+        xml.push_str(&format!("    <tu tuid=\"{}\">\n", escape(&segment.chunk_id, true)));
+        for (lang, text) in [(source_lang, &segment.source), (target_lang, &segment.translated)] {
+            xml.push_str(&format!(
+                "      <tuv xml:lang=\"{}\"><seg>{}</seg></tuv>\n",
+                escape(lang, true),
+                escape(text, false)
+            ));
+        }
+        xml.push_str("    </tu>\n");
+    }
+    xml.push_str("  </body>\n</tmx>\n");
+    xml
+}
+
+/// 从TMX文件中读取源语言到目标语言的原文和译文
+///
+/// 语言按主语言子标签比较且忽略大小写，例如 `en` 匹配 `EN-US`。`source_lang` 为 `auto` 时
+/// 使用头部 `srclang` 声明的语言，头部也未声明时取第一个不是目标语言的变体。
+/// 缺少任一语言或文本为空的翻译单元被跳过。`<bpt>`、`<ept>`、`<ph>`、`<it>` 等行内标记连同其中的
+/// 格式代码一起去除，`<hi>` 等标记只去除标签；实体和CDATA按XML规则还原。
+///
+/// # 错误
+///
+/// 文本中没有 `<tmx>` 根元素时返回 `ParseError`。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::tmx;
+///
+/// // Trados风格：带地区的语言代码和行内格式标记
+/// let trados = r#"<?xml version="1.0" encoding="utf-8"?>
+/// <tmx version="1.4"><header creationtool="SDL Language Platform" srclang="en-US" segtype="sentence" o-tmf="SDL TM8 Format" adminlang="en-US" datatype="xml"/>
+/// <body><tu creationdate="20240101T000000Z">
+///   <prop type="x-Context">0, 0</prop>
+///   <tuv xml:lang="EN-US"><seg>Click <bpt i="1">&lt;b&gt;</bpt>Save<ept i="1">&lt;/b&gt;</ept>.</seg></tuv>
+///   <tuv xml:lang="ZH-CN"><seg>单击<bpt i="1">&lt;b&gt;</bpt>保存<ept i="1">&lt;/b&gt;</ept>。</seg></tuv>
+/// </tu></body></tmx>"#;
+/// let entries = tmx::parse(trados, "auto", "zh").unwrap();
+/// assert_eq!(entries[0].source, "Click Save.");
+/// assert_eq!(entries[0].target, "单击保存。");
+///
+/// // OmegaT风格：TMX 1.1的 lang 属性、注释和CDATA
+/// let omegat = r#"<tmx version="1.1"><header srclang="EN"/><body>
+/// <!-- exported by OmegaT -->
+/// <tu><tuv lang="EN"><seg><![CDATA[Fish & chips]]></seg></tuv><tuv lang="DE"><seg>Fisch &amp; Pommes</seg></tuv></tu>
+/// <tu><tuv lang="EN"><seg>Untranslated</seg></tuv></tu>
+/// </body></tmx>"#;
+/// let entries = tmx::parse(omegat, "en", "de").unwrap();
+/// assert_eq!(entries, vec![tmx::TmxEntry { source: "Fish & chips".into(), target: "Fisch & Pommes".into() }]);
+///
+/// assert!(tmx::parse("<html></html>", "en", "de").is_err());
+/// ```
+pub fn parse(xml: &str, source_lang: &str, target_lang: &str) -> Result<Vec<TmxEntry>> {
+    let xml = COMMENT.replace_all(xml, "");
+    if !xml.contains("<tmx") {
+        return Err(TranslationError::ParseError("不是TMX文件：缺少 <tmx> 根元素".to_string()));
+    }
+    let declared = SRCLANG
+        .captures(&xml)
+        .and_then(|found| found.get(1).or_else(|| found.get(2)))
+        .map(|found| found.as_str().to_string())
+        .filter(|lang| lang != "*all*");
+    let source_lang = match source_lang {
+        lang if lang.eq_ignore_ascii_case("auto") => declared.as_deref(),
+        lang => Some(lang),
+    };
+
+    let mut entries = Vec::new();
+    for unit in TU.find_iter(&xml) {
+        let variants: Vec<(String, String)> = TUV
+            .captures_iter(unit.as_str())
+            .filter_map(|found| {
+                let attributes = LANG.captures(&found[1])?;
+                let lang = attributes.get(1).or_else(|| attributes.get(2))?.as_str().to_string();
+                let seg = SEG.captures(&found[2])?;
+                Some((lang, seg_text(&seg[1])))
+            })
+            .collect();
+        let target = variants.iter().find(|(lang, _)| same_language(lang, target_lang));
+        let source = variants.iter().find(|(lang, _)| match source_lang {
+            Some(source_lang) => same_language(lang, source_lang),
+            None => !same_language(lang, target_lang),
+        });
+        if let (Some((_, source)), Some((_, target))) = (source, target) {
+            if !source.trim().is_empty() && !target.trim().is_empty() {
+                entries.push(TmxEntry {
+                    source: source.clone(),
+                    target: target.clone(),
+                });
+            }
+        }
+    }
+    Ok(entries)
+}
+
+impl TranslationService {
+    /// 将TMX文件中的原文和译文导入翻译缓存
+    ///
+    /// 按服务配置的源语言和目标语言读取文件（见 [`tmx::parse`](crate::tmx::parse)），
+    /// 之后发送的块与某条原文完全相同时直接使用其译文，不请求API。文件可以是UTF-8或带BOM的UTF-16编码。
+    /// 原文中含有保留术语或术语表条目的记录会被跳过：这些块发送前会替换为占位符，与原文不再相同。
+    ///
+    /// # 返回
+    ///
+    /// 导入的记录数。
+    ///
+    /// # 错误
+    ///
+    /// 未启用翻译缓存（`[translation.cache]`）时返回 `ConfigError`，文件无法读取时返回 `Io`，
+    /// 文件不是TMX时返回 `ParseError`。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{tmx, CacheConfig, TranslatedSegment, TranslationService};
+    ///
+    /// let path = std::env::temp_dir().join("markdown-translator-doc-import.tmx");
+    /// let segments = vec![TranslatedSegment {
+    ///     index: 0,
+    ///     source: "Hello, world!".to_string(),
+    ///     translated: "你好，世界！".to_string(),
+    ///     is_code_block: false,
+    ///     alternatives: Vec::new(),
+    ///     chunk_id: "doc-0".to_string(),
+    /// }];
+    /// std::fs::write(&path, tmx::export(&segments, "auto", "zh")).unwrap();
+    ///
+    /// let service = TranslationService::builder()
+    ///     .enabled(true)
+    ///     .configure(|config| config.cache = Some(CacheConfig::default()))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// # tokio_test::block_on(async {
+    /// assert_eq!(service.import_tmx(&path).await.unwrap(), 1);
+    /// let (translated, report) = service.translate_with_report("Hello, world!").await.unwrap();
+    /// assert_eq!(translated, "你好，世界！");
+    /// assert_eq!(report.metrics.requests, 0);
+    /// # });
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub async fn import_tmx(&self, path: &Path) -> Result<usize> {
+        let components = self.components();
+        let Some(cache) = &components.cache else {
+            return Err(TranslationError::ConfigError(
+                "导入TMX需要启用翻译缓存（[translation.cache]）".to_string(),
+            ));
+        };
+        let bytes = tokio::fs::read(path).await.map_err(io_error(path))?;
+        let config = &components.config;
+        let entries = parse(&decode(&bytes), &config.source_lang, &config.target_lang)?;
+
+        let mut imported = 0;
+        for entry in &entries {
+            let mut placeholders = Placeholders::default();
+            let protected = components.keep_terms.protect(&entry.source, &mut placeholders);
+            components.glossary.protect(&protected, &mut placeholders);
+            if !placeholders.is_empty() {
+                continue;
+            }
+            let request = RequestScope {
+                custom_backend: self.hooks.backend.is_some(),
+                source_lang: &config.source_lang,
+                target_lang: &config.target_lang,
+                ..Default::default()
+            };
+            let key = CacheKey::for_request(config, &components.glossary, &request, &entry.source);
+            cache.insert(key, &entry.source, &Reply::text(entry.target.clone()));
+            imported += 1;
+        }
+        if imported < entries.len() {
+            config.verbosity.log(
+                Verbosity::Info,
+                format_args!("TMX中有 {} 条记录含保留术语或术语表条目，未导入", entries.len() - imported),
+            );
+        }
+        Ok(imported)
+    }
+}
+
+/// 按BOM解码文件内容：UTF-16（LE或BE）或UTF-8，无效的字节替换为U+FFFD
+fn decode(bytes: &[u8]) -> String {
+    let utf16 = |bytes: &[u8], from: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes.chunks_exact(2).map(|pair| from([pair[0], pair[1]])).collect();
+        String::from_utf16_lossy(&units)
+    };
+    match bytes {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+/// 语言代码的主语言子标签是否相同，忽略大小写
+fn same_language(a: &str, b: &str) -> bool {
+    let primary = |code: &str| code.trim().split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+    primary(a) == primary(b)
+}
+
+/// `<seg>` 的文本：CDATA原样保留，其余部分去除行内标记并还原实体
+fn seg_text(content: &str) -> String {
+    let mut text = String::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("<![CDATA[") {
+        text.push_str(&markup_text(&rest[..start]));
+        let section = &rest[start + "<![CDATA[".len()..];
+        let end = section.find("]]>").unwrap_or(section.len());
+        text.push_str(&section[..end]);
+        rest = section.get(end + "]]>".len()..).unwrap_or_default();
+    }
+    text.push_str(&markup_text(rest));
+    text
+}
+
+/// 去除行内标记并还原实体
+fn markup_text(markup: &str) -> String {
+    let without_code = NATIVE_CODE.replace_all(markup, "");
+    unescape(&TAG.replace_all(&without_code, ""))
+}
+
+/// 还原XML的预定义实体和字符引用，无法识别的实体原样保留
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';').filter(|&end| end <= 10) else {
+            result.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let decoded = match &rest[1..end] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            name => name
+                .strip_prefix("#x")
+                .or_else(|| name.strip_prefix("#X"))
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| name.strip_prefix('#').map(str::parse))
+                .and_then(|code| code.ok())
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                result.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// 转义XML特殊字符并去除XML 1.0不允许的控制字符，属性值中还需转义双引号
+fn escape(text: &str, attribute: bool) -> String {
+    let allowed: String = text.chars().filter(|&c| !c.is_control() || matches!(c, '\t' | '\n' | '\r')).collect();
+    html::escape(&allowed, attribute.then_some('"'))
+}
@@ -0,0 +1,123 @@
+//! 翻译器抽象模块
+//!
+//! 定义对象安全的 [`Translator`] trait，使下游代码可以接受任意翻译实现，
+//! 并在测试中用 [`NoopTranslator`] 或 [`FnTranslator`] 替换真实服务。
+//! 实现也可以通过 [`TranslationServiceBuilder::backend`](crate::TranslationServiceBuilder::backend)
+//! 作为服务的后端，由服务完成分块、缓存和重试，只把单个块交给实现翻译。
+
+use crate::error::Result;
+use crate::translator::TranslationService;
+use crate::types::TranslateOptions;
+use futures::future::{BoxFuture, FutureExt};
+use std::fmt;
+use std::sync::Arc;
+
+/// 可翻译文本的对象
+///
+/// trait是对象安全的，可以以 `Arc<dyn Translator>` 或 `&dyn Translator` 的形式注入。
+/// [`TranslationService`] 的实现走完整的翻译流程（分块、代码块保护、术语表、重试等）。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{FnTranslator, NoopTranslator, Translator};
+/// use std::sync::Arc;
+///
+/// async fn localize(translator: &dyn Translator, title: &str) -> String {
+///     translator.translate(title).await.unwrap_or_else(|_| title.to_string())
+/// }
+///
+/// # tokio_test::block_on(async {
+/// let fake: Arc<dyn Translator> = Arc::new(FnTranslator::new(|text| Ok(text.to_uppercase())));
+/// assert_eq!(localize(fake.as_ref(), "Settings").await, "SETTINGS");
+/// assert_eq!(localize(&NoopTranslator, "Settings").await, "Settings");
+/// # });
+/// ```
+pub trait Translator: Send + Sync {
+    /// 翻译文本
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>>;
+
+    /// 使用单次调用选项翻译文本
+    ///
+    /// 默认实现忽略选项并调用 [`translate`](Self::translate)；
+    /// [`TranslationService`] 会按选项覆盖目标语言、超时、失败策略等。
+    fn translate_with<'a>(&'a self, text: &'a str, options: &'a TranslateOptions) -> BoxFuture<'a, Result<String>> {
+        let _ = options;
+        self.translate(text)
+    }
+}
+
+impl fmt::Debug for dyn Translator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Translator")
+    }
+}
+
+impl Translator for TranslationService {
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>> {
+        TranslationService::translate(self, text).boxed()
+    }
+
+    fn translate_with<'a>(&'a self, text: &'a str, options: &'a TranslateOptions) -> BoxFuture<'a, Result<String>> {
+        TranslationService::translate_with(self, text, options).boxed()
+    }
+}
+
+impl<T: Translator + ?Sized> Translator for Arc<T> {
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>> {
+        (**self).translate(text)
+    }
+
+    fn translate_with<'a>(&'a self, text: &'a str, options: &'a TranslateOptions) -> BoxFuture<'a, Result<String>> {
+        (**self).translate_with(text, options)
+    }
+}
+
+/// 原样返回输入的翻译器，用于关闭翻译或测试
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopTranslator;
+
+impl Translator for NoopTranslator {
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>> {
+        futures::future::ready(Ok(text.to_string())).boxed()
+    }
+}
+
+/// 以闭包实现翻译的翻译器，用于测试中模拟译文或错误
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{FnTranslator, TranslationError, Translator};
+///
+/// let failing = FnTranslator::new(|_| Err(TranslationError::Custom("backend down".to_string())));
+/// assert!(tokio_test::block_on(failing.translate("Hello")).is_err());
+/// ```
+#[derive(Clone)]
+pub struct FnTranslator {
+    translate: Arc<TranslateFn>,
+}
+
+/// [`FnTranslator`] 包装的闭包类型
+type TranslateFn = dyn Fn(&str) -> Result<String> + Send + Sync;
+
+impl FnTranslator {
+    /// 由闭包创建翻译器
+    pub fn new(translate: impl Fn(&str) -> Result<String> + Send + Sync + 'static) -> Self {
+        Self {
+            translate: Arc::new(translate),
+        }
+    }
+}
+
+impl fmt::Debug for FnTranslator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("FnTranslator")
+    }
+}
+
+impl Translator for FnTranslator {
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>> {
+        futures::future::ready((self.translate)(text)).boxed()
+    }
+}
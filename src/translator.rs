@@ -2,29 +2,91 @@
 //! 
 //! 提供主要的翻译功能，包括并行处理、速率限制和智能文本分块。
 
-use crate::types::{TranslationConfig, DeepLXRequest, DeepLXResponse, DpTransRequest, RetryConfig, TextSegment};
-use crate::error::{Result, TranslationError};
-use reqwest::Client;
-use std::sync::Arc;
+use crate::types::{TranslationConfig, ChunkingConfig, ChunkStrategy, LengthUnit, UnclosedFence, Verbosity, DeepLXRequest, DeepLXResponse, DpTransRequest, ChunkTiming, TimingSummary, Estimate, ProtectionStats, FailurePolicy, CacheMode, OutputMode, Progress, ProgressCallback, ProgressEvent, TranslationEvent, QualityCheck, Retranslation, RetryConfig, SegmentKind, TextFormat, TextHook, TextSegment, TranslateOptions, TranslationScope, TranslatedSegment, TranslationReport, TranslationResult};
+use crate::error::{RateLimitHeaders, Result, TranslationError};
+use crate::bilingual::render_segment;
+use crate::checkpoint::Checkpoint;
+use crate::builder::{validate_config, TranslationServiceBuilder};
+use crate::config::TranslationLibConfig;
+use crate::audit::{AuditEntry, AuditLog};
+use crate::cache::{stable_hash, CacheKey, RequestScope, TranslationCache, WarmState};
+use crate::dump::DebugDump;
+use crate::endpoint::{Endpoint, EndpointPool};
+use crate::glossary::{Glossary, GLOSSARY_PLACEHOLDER};
+use crate::language::check_language;
+use crate::metrics::{self, Metrics, MetricsRecorder, MetricsSnapshot};
+use crate::protect::{KeepTerms, Placeholders, KEEP_PLACEHOLDER};
+use crate::asciidoc::split_asciidoc_text;
+use crate::rst::split_rst_text;
+use crate::segment::{code_comments, segment_markdown};
+use crate::traits::Translator;
+use futures::future::Either;
+use futures::stream::{self, Stream, StreamExt};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE, USER_AGENT};
+use reqwest::{Client, StatusCode};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::ops::Range;
 use std::time::Duration;
-use tokio::sync::Semaphore;
-use tokio::time::sleep;
+use tokio::sync::{mpsc, watch, Mutex};
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::task_tracker::TaskTrackerToken;
+use tokio_util::task::TaskTracker;
+use tokio::time::{sleep, timeout, Instant};
+
+/// 单次调用内同时翻译的最大块数
+pub(crate) const MAX_CONCURRENT_CHUNKS: usize = 5;
+
+/// 未配置 `user_agent` 时内部客户端使用的 `User-Agent`
+const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (compatible; MarkdownDownloader/1.0)";
+
+/// 未配置 `user_agent` 时dptrans请求使用的 `User-Agent`
+const DPTRANS_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36";
+
+/// 启用 `log_content` 时错误信息和日志中保留的最大字符数
+const LOGGED_CONTENT_CHARS: usize = 200;
+
+/// 启用 `log_content` 时块级错误中保留的块开头字符数
+const PREVIEW_CHARS: usize = 80;
+
+/// `subscribe` 返回的事件通道容量
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// 关闭时中止在途调用后，等待它们退出的最长时间
+const ABORT_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
 
 /// 速率限制器
 /// 
 /// 用于控制API请求频率，防止超出服务提供商的速率限制。
-/// 支持并发请求和自适应延迟。
-#[derive(Clone)]
+/// 基于令牌桶实现：桶容量 `burst_size` 决定可以立即发出的突发请求数，
+/// 令牌按 `requests_per_second` 的速率补充，决定持续请求频率。
+#[derive(Clone, Debug)]
 pub struct RateLimiter {
-    /// 信号量，用于控制并发请求数量
-    semaphore: Arc<Semaphore>,
-    /// 请求间隔延迟
-    delay: Duration,
+    /// 令牌桶状态
+    bucket: Arc<Mutex<TokenBucket>>,
+    /// 每秒补充的令牌数
+    rate: f64,
+    /// 桶容量，即允许的最大突发请求数
+    capacity: f64,
+    /// 上级限制器（如全局上限），取得本限制器的令牌后再从上级限制器获取许可
+    parent: Option<Arc<RateLimiter>>,
+}
+
+/// 令牌桶状态
+#[derive(Debug)]
+struct TokenBucket {
+    /// 当前可用令牌数
+    tokens: f64,
+    /// 上次补充令牌的时间
+    last_refill: Instant,
 }
 
 impl RateLimiter {
     /// 创建新的速率限制器
     /// 
+    /// 突发容量默认为 `ceil(requests_per_second * 2)`。
+    /// 
     /// # 参数
     /// 
     /// * `requests_per_second` - 每秒允许的最大请求数
@@ -37,38 +99,133 @@ impl RateLimiter {
     /// let limiter = RateLimiter::new(1.0); // 每秒1个请求
     /// ```
     pub fn new(requests_per_second: f64) -> Self {
-        // 允许更多并发，减少延迟
-        let permits = (requests_per_second * 2.0).ceil() as usize;
-        let delay = Duration::from_millis((500.0 / requests_per_second) as u64); // 减少延迟
+        Self::with_burst(requests_per_second, Self::default_burst(requests_per_second))
+    }
 
+    /// 创建指定突发容量的速率限制器
+    ///
+    /// 桶初始为满，前 `burst_size` 个请求立即放行，之后按 `requests_per_second`
+    /// 的速率放行；空闲期间令牌最多累积到 `burst_size`，不会形成更大的突发。
+    ///
+    /// # 参数
+    ///
+    /// * `requests_per_second` - 令牌补充速率，即持续请求频率
+    /// * `burst_size` - 桶容量，即允许立即发出的最大请求数（至少为1）
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::RateLimiter;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let limiter = RateLimiter::with_burst(10.0, 3);
+    ///
+    ///     // 前3个请求无需等待
+    ///     let start = Instant::now();
+    ///     for _ in 0..3 {
+    ///         limiter.acquire().await.unwrap();
+    ///     }
+    ///     assert!(start.elapsed() < Duration::from_millis(50));
+    ///
+    ///     // 第4个请求需要等待一个令牌补充（约100ms）
+    ///     limiter.acquire().await.unwrap();
+    ///     assert!(start.elapsed() >= Duration::from_millis(80));
+    /// }
+    /// ```
+    pub fn with_burst(requests_per_second: f64, burst_size: usize) -> Self {
+        let capacity = burst_size.max(1) as f64;
         Self {
-            semaphore: Arc::new(Semaphore::new(permits)),
-            delay,
+            bucket: Arc::new(Mutex::new(TokenBucket {
+                tokens: capacity,
+                last_refill: Instant::now(),
+            })),
+            rate: requests_per_second,
+            capacity,
+            parent: None,
         }
     }
 
+    /// 未配置突发容量时的默认值
+    pub(crate) fn default_burst(requests_per_second: f64) -> usize {
+        (requests_per_second * 2.0).ceil().max(1.0) as usize
+    }
+
+    /// 挂接上级限制器
+    ///
+    /// 挂接后每次 `acquire` 都会先从本限制器获取令牌，再从上级限制器获取许可，
+    /// 用于在各端点独立限速的同时保留一个全局请求上限。被本限制器限速而等待的请求
+    /// 不会提前占用上级的配额，其他子限制器仍可使用。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::RateLimiter;
+    ///
+    /// // 两个端点各自每秒10个请求，共享每秒1个请求的全局上限
+    /// let global = RateLimiter::with_burst(1.0, 1);
+    /// let primary = RateLimiter::with_burst(10.0, 10).with_parent(global.clone());
+    /// let backup = RateLimiter::with_burst(10.0, 10).with_parent(global);
+    ///
+    /// # tokio_test::block_on(async {
+    /// primary.acquire().await.unwrap();
+    /// backup.acquire().await.unwrap();
+    /// # });
+    /// ```
+    pub fn with_parent(mut self, parent: RateLimiter) -> Self {
+        self.parent = Some(Arc::new(parent));
+        self
+    }
+
     /// 获取请求许可
     /// 
     /// 在发起API请求前调用此方法，确保不超过配置的速率限制。
+    /// 桶中没有令牌时等待下一个令牌补充，等待期间不持有任何锁。
     /// 
     /// # 返回
     /// 
     /// * `Ok(())` - 成功获取许可
     /// * `Err(TranslationError)` - 获取许可失败
     pub async fn acquire(&self) -> Result<()> {
-        let _permit = self.semaphore.acquire().await
-            .map_err(|e| TranslationError::RateLimitError(format!("Rate limiter error: {}", e)))?;
-        // 在并发环境下减少固定延迟
-        if self.delay > Duration::from_millis(100) {
-            sleep(self.delay).await;
+        self.take_token().await;
+        if let Some(parent) = &self.parent {
+            Box::pin(parent.acquire()).await?;
         }
         Ok(())
     }
+
+    /// 从本限制器的令牌桶取出一个令牌，不经过上级限制器
+    async fn take_token(&self) {
+        // 非正的速率视为不限速
+        if self.rate <= 0.0 {
+            return;
+        }
+
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().await;
+                let now = Instant::now();
+                let refill = now.duration_since(bucket.last_refill).as_secs_f64() * self.rate;
+                bucket.tokens = (bucket.tokens + refill).min(self.capacity);
+                bucket.last_refill = now;
+
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate)
+            };
+            sleep(wait).await;
+        }
+    }
 }
 
 /// 带指数退避的重试机制
 /// 
 /// 为API调用提供可靠的重试机制，在失败时按指数增长的延迟重试。
+/// 每次实际发出的尝试恰好获取一次速率限制许可；不可重试的错误
+/// （如4xx客户端错误、解析错误）立即返回，不再获取许可。
 /// 
 /// # 参数
 /// 
@@ -80,6 +237,34 @@ impl RateLimiter {
 /// 
 /// * `Ok(T)` - 操作成功的结果
 /// * `Err(TranslationError)` - 所有重试尝试失败后的错误
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{retry_with_backoff, RateLimiter, RetryConfig, TranslationError};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// let limiter = RateLimiter::new(10.0);
+/// let config = RetryConfig { max_retries: 2, initial_delay_ms: 1, max_delay_ms: 1, ..Default::default() };
+/// let attempts = AtomicUsize::new(0);
+///
+/// # tokio_test::block_on(async {
+/// // 第一次尝试失败，重试后成功
+/// let result = retry_with_backoff(
+///     || async {
+///         match attempts.fetch_add(1, Ordering::SeqCst) {
+///             0 => Err(TranslationError::Custom("connection reset".into())),
+///             _ => Ok("done"),
+///         }
+///     },
+///     &config,
+///     &limiter,
+/// )
+/// .await;
+/// assert_eq!(result.unwrap(), "done");
+/// assert_eq!(attempts.load(Ordering::SeqCst), 2);
+/// # });
+/// ```
 pub async fn retry_with_backoff<F, Fut, T>(
     mut operation: F,
     config: &RetryConfig,
@@ -88,27 +273,1082 @@ pub async fn retry_with_backoff<F, Fut, T>(
 where
     F: FnMut() -> Fut,
     Fut: std::future::Future<Output = Result<T>>,
+{
+    retry_with_failover(|_| (rate_limiter.clone(), ()), |()| operation(), config, None).await
+}
+
+/// 单次翻译调用的共享状态
+///
+/// 在一次调用的所有块任务之间共享。
+#[derive(Clone, Debug)]
+pub(crate) struct CallContext {
+    /// 调用开始时的服务组件快照，调用期间的配置更新不影响本次调用
+    pub(crate) components: Arc<Components>,
+    /// 文档级重试预算
+    pub(crate) budget: RetryBudget,
+    /// 调用方传入的取消令牌
+    pub(crate) cancel: CancellationToken,
+    /// 本次调用的源语言
+    pub(crate) source_lang: String,
+    /// 本次调用的目标语言
+    pub(crate) target_lang: String,
+    /// 本次调用的整体时限
+    pub(crate) overall_timeout: Option<Duration>,
+    /// 本次调用的单块时限
+    pub(crate) chunk_timeout: Option<Duration>,
+    /// 翻译失败时的处理策略
+    pub(crate) on_error: FailurePolicy,
+    /// 单块超时时的处理策略
+    pub(crate) on_chunk_timeout: FailurePolicy,
+    /// 输出模式
+    pub(crate) output_mode: OutputMode,
+    /// 本次调用的保留术语
+    pub(crate) keep_terms: Arc<KeepTerms>,
+    /// 随请求发送的文档上下文
+    pub(crate) document_context: Option<String>,
+    /// 本次调用的翻译范围
+    pub(crate) scope: TranslationScope,
+    /// 本次调用的输入格式
+    pub(crate) format: TextFormat,
+    /// 请求的备选译文数量，`None` 时不保留备选译文
+    pub(crate) num_alternatives: Option<usize>,
+    /// 本次调用的质量检查钩子
+    pub(crate) quality_check: Option<QualityCheck>,
+    /// 选择端点时的起始偏移，重新翻译时加一以优先使用另一个端点
+    pub(crate) endpoint_offset: usize,
+    /// 是否必须重新请求：因质量检查重新翻译时为 `true`，不使用缓存和文档内去重的结果
+    pub(crate) fresh: bool,
+    /// 本次调用使用翻译缓存的方式
+    pub(crate) cache_mode: CacheMode,
+    /// 缓存预热的统计，只在 `warm_cache` 中设置
+    pub(crate) warm: Option<Arc<WarmState>>,
+    /// 请求中的标签处理方式，由 `translate_html` 在交给后端解析HTML时设置
+    pub(crate) tag_handling: Option<&'static str>,
+    /// 本次调用需要翻译的块类型，`None` 表示不按块类型过滤
+    pub(crate) translate_kinds: Option<Arc<[SegmentKind]>>,
+    /// 本次调用的翻译前钩子
+    pub(crate) pre_process: Option<TextHook>,
+    /// 本次调用的翻译后钩子
+    pub(crate) post_process: Option<TextHook>,
+    /// 断点续译的检查点，未设置检查点文件时为 `None`
+    pub(crate) checkpoint: Option<Arc<Checkpoint>>,
+    /// 服务关闭时用于中止未完成调用的令牌
+    pub(crate) shutdown: CancellationToken,
+    /// 在途调用登记，所有克隆释放后调用才视为结束
+    pub(crate) _in_flight: TaskTrackerToken,
+    /// 本次调用的指标，写入报告
+    pub(crate) metrics: Arc<Metrics>,
+    /// 本次调用产生的警告，写入报告
+    pub(crate) warnings: Arc<std::sync::Mutex<Vec<String>>>,
+    /// 服务级累计指标
+    pub(crate) service_metrics: Arc<Metrics>,
+    /// 外部指标记录器，未设置时为 `None`
+    pub(crate) recorder: Option<Arc<dyn MetricsRecorder>>,
+    /// 进度跟踪，未设置回调时为 `None`
+    pub(crate) progress: Option<ProgressTracker>,
+    /// 本次调用的块统计，写入报告
+    pub(crate) stats: Arc<CallStats>,
+    /// 本次调用的文档ID
+    pub(crate) document_id: Arc<str>,
+    /// 当前块的ID，只在翻译文档中的某个段时设置
+    pub(crate) chunk_id: Option<Arc<str>>,
+    /// 当前块的序号，与 `chunk_id` 同时设置
+    pub(crate) chunk_index: Option<usize>,
+    /// 当前块的耗时明细，与 `chunk_id` 同时设置
+    pub(crate) chunk_timing: Option<Arc<std::sync::Mutex<ChunkTiming>>>,
+    /// 报告中是否包含每个块的耗时明细
+    pub(crate) detailed_timings: bool,
+    /// 调用开始时的事件订阅者
+    pub(crate) subscribers: Arc<[mpsc::Sender<TranslationEvent>]>,
+}
+
+/// 单次调用的块统计
+#[derive(Debug)]
+pub(crate) struct CallStats {
+    /// 调用开始时间
+    started: Instant,
+    /// 分块后的总段数
+    chunks: AtomicUsize,
+    /// 翻译失败的块数
+    failed_chunks: AtomicUsize,
+    /// 每个已翻译块的耗时
+    chunk_timings: std::sync::Mutex<Vec<ChunkTiming>>,
+    /// 术语表命中次数
+    glossary_hits: AtomicUsize,
+    /// 后端报告的源语言
+    detected_source_lang: OnceLock<String>,
+    /// 第一个成功响应的端点地址
+    backend: OnceLock<String>,
+    /// 是否有请求携带了文档上下文
+    context_sent: AtomicBool,
+    /// 因质量检查不合格而重新翻译的块
+    retranslations: std::sync::Mutex<Vec<Retranslation>>,
+    /// 从检查点恢复的块数
+    resumed_chunks: AtomicUsize,
+    /// 成功翻译的块数，用于判断端点不可用时是否整篇保留原文
+    succeeded_chunks: AtomicUsize,
+    /// 因订阅通道已满而丢弃的事件数
+    dropped_events: AtomicUsize,
+    /// 翻译缓存命中的请求数
+    cache_hits: AtomicUsize,
+    /// 复用翻译缓存中相似条目的请求数
+    fuzzy_hits: AtomicUsize,
+    /// 译文来自翻译缓存的块序号
+    cached_chunks: std::sync::Mutex<Vec<usize>>,
+    /// 离线模式下缓存未命中的块序号
+    offline_misses: std::sync::Mutex<Vec<usize>>,
+    /// 本次调用中按缓存键去重的块响应，相同内容的块共用同一个结果
+    dedupe: std::sync::Mutex<HashMap<CacheKey, Arc<tokio::sync::OnceCell<Reply>>>>,
+    /// 去重后不同的块数
+    unique_chunks: AtomicUsize,
+    /// 复用文档中相同块译文的块数
+    duplicate_chunks: AtomicUsize,
+    /// 受保护和跳过内容的统计，`translated_chars` 在生成报告前包含占位符的字符数
+    protection: std::sync::Mutex<ProtectionStats>,
+}
+
+impl Default for CallStats {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            chunks: AtomicUsize::new(0),
+            failed_chunks: AtomicUsize::new(0),
+            chunk_timings: std::sync::Mutex::default(),
+            glossary_hits: AtomicUsize::new(0),
+            detected_source_lang: OnceLock::new(),
+            backend: OnceLock::new(),
+            context_sent: AtomicBool::new(false),
+            retranslations: std::sync::Mutex::default(),
+            resumed_chunks: AtomicUsize::new(0),
+            succeeded_chunks: AtomicUsize::new(0),
+            dropped_events: AtomicUsize::new(0),
+            cache_hits: AtomicUsize::new(0),
+            fuzzy_hits: AtomicUsize::new(0),
+            cached_chunks: std::sync::Mutex::default(),
+            offline_misses: std::sync::Mutex::default(),
+            dedupe: std::sync::Mutex::default(),
+            unique_chunks: AtomicUsize::new(0),
+            duplicate_chunks: AtomicUsize::new(0),
+            protection: std::sync::Mutex::default(),
+        }
+    }
+}
+
+impl CallStats {
+    /// 取得缓存键对应的共享结果，同时计入不同块数或重复块数
+    fn dedupe_slot(&self, key: CacheKey) -> Arc<tokio::sync::OnceCell<Reply>> {
+        let mut dedupe = self.dedupe.lock().unwrap();
+        let counter = if dedupe.contains_key(&key) { &self.duplicate_chunks } else { &self.unique_chunks };
+        counter.fetch_add(1, Ordering::Relaxed);
+        dedupe.entry(key).or_default().clone()
+    }
+}
+
+/// 进度跟踪
+///
+/// 计数器均为原子变量，回调在不持有任何锁的情况下调用。
+#[derive(Clone, Debug)]
+pub(crate) struct ProgressTracker {
+    callback: ProgressCallback,
+    state: Arc<ProgressState>,
+}
+
+#[derive(Debug, Default)]
+struct ProgressState {
+    total_chunks: AtomicUsize,
+    completed_chunks: AtomicUsize,
+    characters_done: AtomicUsize,
+    characters_total: AtomicUsize,
+    /// 还需要请求API的块数（不含代码块）
+    pending_requests: AtomicUsize,
+    /// 吞吐量采样：完成时刻和截至该时刻完成的字符数，只保留滑动窗口内的样本和一个基准样本
+    samples: std::sync::Mutex<VecDeque<(Instant, usize)>>,
+}
+
+/// 计算吞吐量的滑动窗口
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(30);
+
+impl ProgressState {
+    /// 记录一个吞吐量样本，丢弃窗口之外除最近一个以外的样本
+    fn sample(&self, characters_done: usize) {
+        let now = Instant::now();
+        let mut samples = self.samples.lock().unwrap();
+        samples.push_back((now, characters_done));
+        while samples.len() > 2 && now - samples[1].0 > THROUGHPUT_WINDOW {
+            samples.pop_front();
+        }
+    }
+
+    /// 滑动窗口内的吞吐量（字符/秒）
+    fn chars_per_second(&self) -> f64 {
+        let samples = self.samples.lock().unwrap();
+        match (samples.front(), samples.back()) {
+            (Some(&(start, start_chars)), Some(&(end, end_chars))) if end > start => {
+                (end_chars - start_chars) as f64 / (end - start).as_secs_f64()
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+impl CallContext {
+    /// 调用是否已被取消（调用方取消或服务关闭）
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled() || self.shutdown.is_cancelled()
+    }
+
+    /// 等待调用被取消
+    pub(crate) async fn cancelled(&self) {
+        tokio::select! {
+            _ = self.cancel.cancelled() => {}
+            _ = self.shutdown.cancelled() => {}
+        }
+    }
+
+    /// 同时记录到本次调用和服务级指标
+    pub(crate) fn record(&self, record: impl Fn(&Metrics)) {
+        record(&self.metrics);
+        record(&self.service_metrics);
+    }
+
+    /// 当前块的序号和即将发出的是第几次尝试（从1开始），只在翻译文档中的某个段时可用
+    pub(crate) fn attempt(&self) -> Option<(usize, usize)> {
+        let index = self.chunk_index?;
+        let attempts = self.chunk_timing.as_ref()?.lock().unwrap().attempts.len();
+        Some((index, attempts + 1))
+    }
+
+    /// 随请求发送的ID：翻译文档中的段时为块ID，否则为文档ID
+    pub(crate) fn request_id(&self) -> &str {
+        self.chunk_id.as_deref().unwrap_or(&self.document_id)
+    }
+
+    /// 把一次尝试的耗时计入当前块的明细
+    fn record_attempt(&self, limiter_wait: Duration, http: Duration) {
+        if let Some(timing) = &self.chunk_timing {
+            let mut timing = timing.lock().unwrap();
+            timing.limiter_wait += limiter_wait;
+            timing.attempts.push(http);
+        }
+    }
+
+    /// 外部记录器的计数器增加 `value`
+    pub(crate) fn count(&self, name: &'static str, labels: &[(&'static str, &str)], value: u64) {
+        if let Some(recorder) = &self.recorder {
+            recorder.increment_counter(name, labels, value);
+        }
+    }
+
+    /// 外部记录器的直方图记录一次耗时
+    pub(crate) fn observe(&self, name: &'static str, labels: &[(&'static str, &str)], elapsed: Duration) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record_histogram(name, labels, elapsed.as_secs_f64());
+        }
+    }
+
+    /// 记录一条警告
+    pub(crate) fn warn(&self, warning: String) {
+        self.warnings.lock().unwrap().push(warning);
+    }
+
+    /// 分块完成后记录总量并通知 `PlanComplete`
+    pub(crate) fn progress_planned(&self, segments: &[TextSegment]) {
+        self.stats.chunks.store(segments.len(), Ordering::Relaxed);
+        self.emit(|| TranslationEvent::PlanReady { chunks: segments.len() });
+        if let Some(progress) = &self.progress {
+            let characters: usize = segments.iter().map(|segment| segment.content.chars().count()).sum();
+            let requests = segments.iter().filter(|segment| !segment.is_code_block).count();
+            progress.state.total_chunks.store(segments.len(), Ordering::Relaxed);
+            progress.state.characters_total.store(characters, Ordering::Relaxed);
+            progress.state.pending_requests.store(requests, Ordering::Relaxed);
+            progress.state.sample(progress.state.characters_done.load(Ordering::Relaxed));
+            self.notify(progress, ProgressEvent::PlanComplete, None);
+        }
+    }
+
+    /// 通知某个块开始翻译
+    pub(crate) fn progress_started(&self, index: usize) {
+        self.emit(|| TranslationEvent::ChunkStarted { index });
+        if let Some(progress) = &self.progress {
+            self.notify(progress, ProgressEvent::ChunkStarted, Some(index));
+        }
+    }
+
+    /// 记录某个块的结果并通知 `ChunkFinished` 或 `ChunkFailed`
+    pub(crate) fn progress_chunk_done(&self, index: usize, segment: &TextSegment, succeeded: bool) {
+        if let Some(progress) = &self.progress {
+            if !segment.is_code_block {
+                let _ = progress.state.pending_requests.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |pending| {
+                    pending.checked_sub(1)
+                });
+            }
+            let event = if succeeded {
+                progress.state.completed_chunks.fetch_add(1, Ordering::Relaxed);
+                let characters = segment.content.chars().count();
+                let done = progress.state.characters_done.fetch_add(characters, Ordering::Relaxed) + characters;
+                progress.state.sample(done);
+                ProgressEvent::ChunkFinished
+            } else {
+                ProgressEvent::ChunkFailed
+            };
+            self.notify(progress, event, Some(index));
+        }
+    }
+
+    /// 通知整个文档翻译完成
+    pub(crate) fn progress_finished(&self) {
+        self.emit(|| TranslationEvent::DocumentCompleted { report: Box::new(self.report()) });
+        if let Some(progress) = &self.progress {
+            self.notify(progress, ProgressEvent::DocumentFinished, None);
+        }
+    }
+
+    /// 向所有订阅者发送事件，通道已满时丢弃并计数；没有订阅者时不构造事件
+    pub(crate) fn emit(&self, event: impl FnOnce() -> TranslationEvent) {
+        if self.subscribers.is_empty() {
+            return;
+        }
+        let event = event();
+        for subscriber in self.subscribers.iter() {
+            if let Err(mpsc::error::TrySendError::Full(_)) = subscriber.try_send(event.clone()) {
+                self.stats.dropped_events.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn notify(&self, progress: &ProgressTracker, event: ProgressEvent, chunk_index: Option<usize>) {
+        let state = &progress.state;
+        let total_chunks = state.total_chunks.load(Ordering::Relaxed);
+        let completed_chunks = state.completed_chunks.load(Ordering::Relaxed);
+        let characters_done = state.characters_done.load(Ordering::Relaxed);
+        let characters_total = state.characters_total.load(Ordering::Relaxed);
+        let percent_complete = match (characters_total, total_chunks) {
+            (0, 0) => 0.0,
+            (0, chunks) => completed_chunks as f64 * 100.0 / chunks as f64,
+            (characters, _) => (characters_done as f64 * 100.0 / characters as f64).min(100.0),
+        };
+        let chars_per_second = state.chars_per_second();
+        let eta = match event {
+            ProgressEvent::DocumentFinished => Some(Duration::ZERO),
+            _ if total_chunks == 0 => None,
+            _ => Some(self.estimate_remaining(state, characters_total.saturating_sub(characters_done), chars_per_second)),
+        };
+        (progress.callback.0)(Progress {
+            event,
+            chunk_index,
+            total_chunks,
+            completed_chunks,
+            characters_done,
+            characters_total,
+            retries: self.budget.used(),
+            percent_complete,
+            chars_per_second,
+            eta,
+        });
+    }
+
+    /// 估算剩余时间：按吞吐量估算的时间与剩余请求按速率限制所需的最短时间取较大者
+    fn estimate_remaining(&self, state: &ProgressState, remaining_characters: usize, chars_per_second: f64) -> Duration {
+        let config = &self.components.config;
+        let rate = config
+            .global_max_requests_per_second
+            .map_or(config.max_requests_per_second, |global| global.min(config.max_requests_per_second));
+        let pending = state.pending_requests.load(Ordering::Relaxed);
+        let floor = if rate > 0.0 { pending as f64 / rate } else { 0.0 };
+        let by_throughput = if chars_per_second > 0.0 {
+            remaining_characters as f64 / chars_per_second
+        } else {
+            0.0
+        };
+        Duration::from_secs_f64(floor.max(by_throughput))
+    }
+
+    /// 生成本次调用的报告
+    pub(crate) fn report(&self) -> TranslationReport {
+        let mut chunk_timings = self.stats.chunk_timings.lock().unwrap().clone();
+        chunk_timings.sort_by_key(|timing| timing.index);
+        let timing_summary = TimingSummary::new(&chunk_timings);
+        if !self.detailed_timings {
+            chunk_timings.clear();
+        }
+        let mut retranslated_chunks = self.stats.retranslations.lock().unwrap().clone();
+        retranslated_chunks.sort_by_key(|retranslation| retranslation.index);
+        let mut protection = self.stats.protection.lock().unwrap().clone();
+        protection.translated_chars = protection
+            .translated_chars
+            .saturating_sub(protection.protected_chars.saturating_sub(protection.code_block_chars));
+        TranslationReport {
+            document_id: self.document_id.to_string(),
+            retries_used: self.budget.used(),
+            retry_budget: self.budget.limit,
+            metrics: self.metrics.snapshot(),
+            warnings: self.warnings.lock().unwrap().clone(),
+            chunks: self.stats.chunks.load(Ordering::Relaxed),
+            failed_chunks: self.stats.failed_chunks.load(Ordering::Relaxed),
+            cache_hits: self.stats.cache_hits.load(Ordering::Relaxed),
+            fuzzy_hits: self.stats.fuzzy_hits.load(Ordering::Relaxed),
+            cached_chunks: sorted_indices(&self.stats.cached_chunks),
+            offline_misses: sorted_indices(&self.stats.offline_misses),
+            unique_chunks: self.stats.unique_chunks.load(Ordering::Relaxed),
+            duplicate_chunks: self.stats.duplicate_chunks.load(Ordering::Relaxed),
+            elapsed: self.stats.started.elapsed(),
+            timing_summary,
+            chunk_timings,
+            detected_source_lang: self.stats.detected_source_lang.get().cloned(),
+            backend: self.stats.backend.get().cloned(),
+            glossary_hits: self.stats.glossary_hits.load(Ordering::Relaxed),
+            protection,
+            context_sent: self.stats.context_sent.load(Ordering::Relaxed),
+            retranslated_chunks,
+            reused_segments: 0,
+            resumed_chunks: self.stats.resumed_chunks.load(Ordering::Relaxed),
+            dropped_events: self.stats.dropped_events.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 记录成功响应的端点及其报告的源语言，只保留第一次记录的值
+    pub(crate) fn record_response(&self, backend: &str, detected_source_lang: Option<&str>) {
+        self.stats.backend.get_or_init(|| backend.to_string());
+        if let Some(lang) = detected_source_lang {
+            self.stats.detected_source_lang.get_or_init(|| lang.to_lowercase());
+        }
+    }
+
+    /// 将当前块的序号加入列表，不属于文档分块的请求不记录
+    fn record_chunk_index(&self, indices: &std::sync::Mutex<Vec<usize>>) {
+        if let Some(index) = self.chunk_index {
+            let mut indices = indices.lock().unwrap();
+            if !indices.contains(&index) {
+                indices.push(index);
+            }
+        }
+    }
+
+    /// 记录一次成功请求中替换为占位符的保留术语和行内标记
+    pub(crate) fn record_protection(&self, placeholders: &Placeholders) {
+        placeholders.count_into(&mut self.stats.protection.lock().unwrap());
+    }
+
+    /// 记录一次因质量检查不合格而进行的重新翻译
+    pub(crate) fn record_retranslation(&self, index: usize, reason: String, passed: bool) {
+        self.stats.retranslations.lock().unwrap().push(Retranslation { index, reason, passed });
+    }
+
+    /// 记录一个已翻译块的耗时及是否失败，连同请求过程中累计的耗时明细
+    pub(crate) fn record_chunk(&self, index: usize, elapsed: Duration, failed: bool) {
+        if failed {
+            self.stats.failed_chunks.fetch_add(1, Ordering::Relaxed);
+        }
+        let timing = match &self.chunk_timing {
+            Some(timing) => std::mem::take(&mut *timing.lock().unwrap()),
+            None => ChunkTiming::default(),
+        };
+        self.stats.chunk_timings.lock().unwrap().push(ChunkTiming { index, elapsed, ..timing });
+        let outcome = if failed { "error" } else { "ok" };
+        self.observe(metrics::CHUNK_DURATION_SECONDS, &[("outcome", outcome)], elapsed);
+    }
+}
+
+/// 文档级重试预算
+///
+/// 在一次 `translate()` 调用的所有块之间共享。每次重试消耗一个单位，
+/// 预算耗尽后后续失败不再重试，直接交给失败处理策略。
+#[derive(Clone, Debug)]
+pub(crate) struct RetryBudget {
+    used: Arc<AtomicUsize>,
+    limit: Option<usize>,
+}
+
+impl RetryBudget {
+    /// 创建重试预算，`limit` 为 `None` 时只计数不限制
+    pub(crate) fn new(limit: Option<usize>) -> Self {
+        Self {
+            used: Arc::new(AtomicUsize::new(0)),
+            limit,
+        }
+    }
+
+    /// 尝试消耗一次重试，预算已耗尽时返回 `false`
+    pub(crate) fn try_consume(&self) -> bool {
+        self.used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| match self.limit {
+                Some(limit) if used >= limit => None,
+                _ => Some(used + 1),
+            })
+            .is_ok()
+    }
+
+    /// 已使用的重试次数
+    pub(crate) fn used(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+}
+
+/// 带故障转移的重试机制
+///
+/// 与 [`retry_with_backoff`] 相同，但每次尝试前通过 `select` 选择本次尝试的目标
+/// 及其速率限制器，因此故障转移后的重试会从新端点的限制器获取许可。
+/// 传入调用上下文时，每次重试都会消耗文档级重试预算，并在每次尝试前检查取消令牌。
+pub(crate) async fn retry_with_failover<S, R, F, Fut, T>(
+    mut select: S,
+    mut operation: F,
+    config: &RetryConfig,
+    context: Option<&CallContext>,
+) -> Result<T>
+where
+    S: FnMut(usize) -> (RateLimiter, R),
+    F: FnMut(R) -> Fut,
+    Fut: std::future::Future<Output = Result<T>>,
 {
     let mut delay = config.initial_delay_ms;
+    let mut attempt = 0;
 
-    for attempt in 0..=config.max_retries {
+    loop {
+        if context.is_some_and(CallContext::is_cancelled) {
+            return Err(TranslationError::Cancelled { completed: 0, total: 1 });
+        }
+
+        // 只为实际发出的尝试获取许可
+        let (rate_limiter, target) = select(attempt);
+        let wait_start = Instant::now();
         rate_limiter.acquire().await?;
+        let request_start = Instant::now();
+        let waited = request_start - wait_start;
 
-        match operation().await {
+        let result = operation(target).await;
+        if let Some(context) = context {
+            let elapsed = request_start.elapsed();
+            context.record(|metrics| {
+                metrics.record_limiter_wait(waited);
+                metrics.record_request(elapsed);
+            });
+            context.observe(metrics::RATE_LIMITER_WAIT_SECONDS, &[], waited);
+            context.record_attempt(waited, elapsed);
+        }
+        let error = match result {
             Ok(result) => return Ok(result),
-            Err(e) if attempt == config.max_retries => return Err(e),
-            Err(e) => {
-                eprintln!("Attempt {} failed: {}. Retrying in {}ms...", attempt + 1, e, delay);
-                sleep(Duration::from_millis(delay)).await;
-                delay = std::cmp::min(
-                    (delay as f64 * config.backoff_multiplier) as u64,
-                    config.max_delay_ms,
-                );
+            Err(e) => e,
+        };
+        if let Some(context) = context.filter(|_| error.is_rate_limited()) {
+            context.record(Metrics::record_rate_limited);
+        }
+
+        if attempt >= config.max_retries || !error.is_retryable() {
+            return Err(error);
+        }
+        let verbosity = context.map_or(Verbosity::Quiet, |context| context.components.config.verbosity);
+        if context.is_some_and(|context| !context.budget.try_consume()) {
+            verbosity.log(Verbosity::Warn, format_args!("Attempt {} failed: {}. Retry budget exhausted", attempt + 1, error));
+            return Err(error);
+        }
+
+        verbosity.log(
+            Verbosity::Warn,
+            format_args!("Attempt {} failed: {}. Retrying in {}ms...", attempt + 1, error, delay),
+        );
+        let backoff = Duration::from_millis(delay);
+        if let Some(context) = context {
+            if let Some(index) = context.chunk_index {
+                context.emit(|| TranslationEvent::ChunkRetried { index, attempt: attempt + 1, error: error.to_string() });
+            }
+            context.record(|metrics| metrics.record_retry(backoff));
+            context.count(metrics::RETRIES_TOTAL, &[], 1);
+            if let Some(timing) = &context.chunk_timing {
+                timing.lock().unwrap().backoff += backoff;
             }
         }
+        sleep(backoff).await;
+        delay = std::cmp::min(
+            (delay as f64 * config.backoff_multiplier) as u64,
+            config.max_delay_ms,
+        );
+        attempt += 1;
+    }
+}
+
+/// 由配置构建的服务组件
+///
+/// 配置更新时整体替换；每次调用开始时取一份快照，保证同一调用内使用一致的配置。
+#[derive(Debug)]
+pub(crate) struct Components {
+    /// 翻译配置
+    pub(crate) config: Arc<TranslationConfig>,
+    /// HTTP客户端，用于API调用
+    client: Client,
+    /// 客户端是否由调用方提供，调用方提供的客户端不会因配置修改而重建
+    custom_client: bool,
+    /// 端点池，每个端点拥有独立的速率限制器和熔断器
+    endpoints: EndpointPool,
+    /// 由配置编译的术语表
+    pub(crate) glossary: Arc<Glossary>,
+    /// 由配置编译的保留术语
+    pub(crate) keep_terms: Arc<KeepTerms>,
+    /// 请求转储，未设置 `debug_dump_dir` 或目录不可用时为 `None`
+    dump: Option<Arc<DebugDump>>,
+    /// 审计日志，未设置 `audit_log_path` 或文件无法打开时为 `None`
+    audit: Option<Arc<AuditLog>>,
+    /// 翻译缓存，未配置或未启用时为 `None`
+    pub(crate) cache: Option<Arc<TranslationCache>>,
+}
+
+impl Components {
+    /// 是否可以将HTML交给后端解析：配置声明了支持，且故障转移可能用到的端点都使用标准DeepLX格式
+    pub(crate) fn supports_html_tag_handling(&self) -> bool {
+        self.config.html_tag_handling
+            && self.endpoints.endpoints().iter().all(|endpoint| !endpoint.url.contains("dptrans"))
+    }
+}
+
+/// 端点地址、速率限制或熔断参数是否变化，变化时需要重建端点池
+fn endpoint_settings_changed(old: &TranslationConfig, new: &TranslationConfig) -> bool {
+    old.deeplx_api_url != new.deeplx_api_url
+        || old.fallback_api_urls != new.fallback_api_urls
+        || old.max_requests_per_second != new.max_requests_per_second
+        || old.burst_size != new.burst_size
+        || old.global_max_requests_per_second != new.global_max_requests_per_second
+        || old.circuit_breaker_threshold != new.circuit_breaker_threshold
+        || old.circuit_breaker_cooldown_secs != new.circuit_breaker_cooldown_secs
+}
+
+/// HTTP客户端相关的超时是否变化，变化时需要重建客户端
+fn client_settings_changed(old: &TranslationConfig, new: &TranslationConfig) -> bool {
+    old.request_timeout_secs != new.request_timeout_secs
+        || old.connect_timeout_secs != new.connect_timeout_secs
+        || old.user_agent != new.user_agent
+        || old.default_headers != new.default_headers
+        || old.http != new.http
+}
+
+/// 配置的 `user_agent` 和 `default_headers`，无效的请求头被忽略（由 `validate` 报告）
+fn configured_headers(config: &TranslationConfig) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(user_agent) = config.user_agent.as_deref().and_then(|value| HeaderValue::from_str(value).ok()) {
+        headers.insert(USER_AGENT, user_agent);
+    }
+    for (name, value) in &config.default_headers {
+        if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(value)) {
+            headers.insert(name, value);
+        }
     }
+    headers
+}
 
-    unreachable!()
+/// 构建单个请求的请求头：先放入内置的请求头，再用配置的请求头覆盖，最后附加请求ID
+///
+/// 调用方提供的客户端不带配置的默认请求头，因此每个请求都显式携带。
+fn request_headers(context: &CallContext, builtin: &[(HeaderName, &'static str)]) -> HeaderMap {
+    let config = &context.components.config;
+    let mut headers: HeaderMap = builtin
+        .iter()
+        .map(|(name, value)| (name.clone(), HeaderValue::from_static(value)))
+        .collect();
+    headers.extend(configured_headers(config));
+    if let (Ok(name), Ok(value)) = (
+        HeaderName::from_bytes(config.request_id_header.as_bytes()),
+        HeaderValue::from_str(context.request_id()),
+    ) {
+        headers.insert(name, value);
+    }
+    headers
+}
+
+/// 排序后的块序号列表
+fn sorted_indices(indices: &std::sync::Mutex<Vec<usize>>) -> Vec<usize> {
+    let mut indices = indices.lock().unwrap().clone();
+    indices.sort_unstable();
+    indices
+}
+
+/// 生成UUID（第4版）格式的文档ID
+///
+/// 随机性来自标准库 `RandomState` 的随机种子，再混入时间、进程ID和计数器，
+/// 同一进程内不会重复，足以在日志中区分文档。
+fn generate_document_id() -> String {
+    use std::hash::{BuildHasher, Hasher};
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let state = std::collections::hash_map::RandomState::new();
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    let mut hasher = state.build_hasher();
+    hasher.write_u128(nanos);
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    let high = hasher.finish();
+    hasher.write_u32(std::process::id());
+    let low = hasher.finish();
+
+    let high = (high & !0xF000) | 0x4000;
+    let low = (low & 0x3FFF_FFFF_FFFF_FFFF) | 0x8000_0000_0000_0000;
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        high >> 32,
+        (high >> 16) & 0xFFFF,
+        high & 0xFFFF,
+        low >> 48,
+        low & 0xFFFF_FFFF_FFFF
+    )
+}
+
+/// 请求转储相关字段是否变化，变化时需要重新打开转储目录（已写入的大小重新计算）
+fn dump_settings_changed(old: &TranslationConfig, new: &TranslationConfig) -> bool {
+    old.debug_dump_dir != new.debug_dump_dir
+        || old.debug_dump_max_bytes != new.debug_dump_max_bytes
+        || old.verbosity != new.verbosity
+}
+
+/// 按配置打开请求转储，目录不可用时记录警告并不启用
+fn open_dump(config: &TranslationConfig) -> Option<Arc<DebugDump>> {
+    match DebugDump::from_config(config) {
+        Ok(dump) => dump.map(Arc::new),
+        Err(message) => {
+            config.verbosity.log(Verbosity::Warn, format_args!("请求转储未启用: {}", message));
+            None
+        }
+    }
+}
+
+/// 审计日志相关字段是否变化，变化时需要重新打开审计日志
+fn audit_settings_changed(old: &TranslationConfig, new: &TranslationConfig) -> bool {
+    old.audit_log_path != new.audit_log_path
+        || old.audit_include_text != new.audit_include_text
+        || old.verbosity != new.verbosity
+}
+
+/// 按配置打开审计日志，无法打开时记录警告并不启用
+fn open_audit(config: &TranslationConfig) -> Option<Arc<AuditLog>> {
+    match AuditLog::from_config(config) {
+        Ok(audit) => audit.map(Arc::new),
+        Err(message) => {
+            config.verbosity.log(Verbosity::Warn, format_args!("审计日志未启用: {}", message));
+            None
+        }
+    }
+}
+
+/// 术语表相关字段是否变化，变化时需要重新加载术语表
+fn glossary_settings_changed(old: &TranslationConfig, new: &TranslationConfig) -> bool {
+    old.glossary != new.glossary
+        || old.glossary_file != new.glossary_file
+        || old.glossary_case_sensitive != new.glossary_case_sensitive
+        || old.glossary_whole_word != new.glossary_whole_word
+        || old.glossary_id != new.glossary_id
+}
+
+/// 单次请求的后端响应
+#[derive(Debug, Clone)]
+pub(crate) struct Reply {
+    /// 译文
+    pub(crate) text: String,
+    /// 后端检测到的源语言，只有标准DeepLX响应会携带
+    pub(crate) detected_source_lang: Option<String>,
+    /// 备选译文，未请求时为空
+    pub(crate) alternatives: Vec<String>,
+}
+
+impl Reply {
+    /// 只有译文的响应
+    pub(crate) fn text(text: String) -> Self {
+        Self {
+            text,
+            detected_source_lang: None,
+            alternatives: Vec::new(),
+        }
+    }
+}
+
+/// 创建服务时按配置加载的组件，加载失败时由调用方决定返回错误还是禁用对应功能
+#[derive(Debug)]
+pub(crate) struct LoadedParts {
+    /// 术语表
+    pub(crate) glossary: Glossary,
+    /// 保留术语
+    pub(crate) keep_terms: KeepTerms,
+    /// 翻译缓存，未启用时为 `None`
+    pub(crate) cache: Option<TranslationCache>,
+}
+
+/// 服务级文本处理钩子
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Hooks {
+    /// 翻译前钩子
+    pub(crate) pre_process: Option<TextHook>,
+    /// 翻译后钩子
+    pub(crate) post_process: Option<TextHook>,
+    /// 质量检查钩子
+    pub(crate) quality_check: Option<QualityCheck>,
+    /// 外部指标记录器
+    pub(crate) metrics_recorder: Option<Arc<dyn MetricsRecorder>>,
+    /// 代替HTTP请求翻译每个块的后端
+    pub(crate) backend: Option<Arc<dyn Translator>>,
+}
+
+/// 通过构建器设置了自定义后端时，指标和审计日志中的后端标签
+const CUSTOM_BACKEND: &str = "custom";
+
+/// 指标中的后端标签：端点的主机和端口，不含路径和凭据；地址无效时使用原始地址
+fn metrics_backend(endpoint: &Endpoint) -> String {
+    match endpoint.parsed_url() {
+        Ok(url) => match (url.host_str(), url.port_or_known_default()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            _ => endpoint.url.clone(),
+        },
+        Err(_) => endpoint.url.clone(),
+    }
+}
+
+/// 将文档内容或响应体转换为可以写入错误信息和日志的形式
+///
+/// 默认只保留字节数和哈希值，便于关联同一内容而不泄露文本。哈希为FNV-1a，不同版本的构建记录的值可以互相比对，
+/// 与审计日志中的 `chunk_hash` 也一致；`log_content` 为 `true` 时
+/// 保留前 [`LOGGED_CONTENT_CHARS`] 个字符。
+pub(crate) fn redact(text: &str, log_content: bool) -> String {
+    redact_to(text, log_content, LOGGED_CONTENT_CHARS)
+}
+
+/// 与 [`redact`] 相同，但 `log_content` 为 `true` 时保留前 `max_chars` 个字符
+fn redact_to(text: &str, log_content: bool, max_chars: usize) -> String {
+    if log_content {
+        return match text.char_indices().nth(max_chars) {
+            Some((end, _)) => format!("{}…（共 {} 字节）", &text[..end], text.len()),
+            None => text.to_string(),
+        };
+    }
+    format!("<{} 字节，哈希 {:016x}>", text.len(), stable_hash(text.as_bytes()))
+}
+
+/// 端点无法连接或拒绝凭据：第一个块就遇到时，其余块也会以同样的方式失败
+fn is_catastrophic(error: &TranslationError) -> bool {
+    matches!(
+        error.root_cause(),
+        TranslationError::ConnectionFailed { .. } | TranslationError::AuthError { .. }
+    )
+}
+
+/// 响应是否是HTML或XML标记，而不是纯文本译文
+///
+/// 按 `tag_handling` 翻译标记时译文本身可能以 `<` 开头，此时只看 `Content-Type`。
+fn is_markup(body: &str, headers: &HeaderMap, tag_handling: bool) -> bool {
+    let html = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.to_ascii_lowercase().contains("html"));
+    html || (!tag_handling && body.trim_start().starts_with('<'))
+}
+
+/// 由非成功响应构建 `ApiError`，保留状态码、速率限制相关的响应头和按配置截断的响应体
+fn api_error(status: u16, headers: &HeaderMap, body: &str, config: &TranslationConfig) -> TranslationError {
+    let header = |name: &str| headers.get(name)?.to_str().ok()?.trim().parse::<u64>().ok();
+    TranslationError::ApiError {
+        status,
+        retry_after: header("retry-after").map(Duration::from_secs),
+        rate_limit: RateLimitHeaders {
+            limit: header("x-ratelimit-limit"),
+            remaining: header("x-ratelimit-remaining"),
+            reset: header("x-ratelimit-reset"),
+        },
+        body_excerpt: redact_to(body, config.log_content, config.error_body_max_chars),
+    }
+}
+
+/// 块级错误中的内容预览：启用 `log_content` 时为前 [`PREVIEW_CHARS`] 个字符，否则同 [`redact`]
+fn preview(text: &str, log_content: bool) -> String {
+    if !log_content {
+        return redact(text, false);
+    }
+    match text.char_indices().nth(PREVIEW_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// 将短于 `chunking.min_chunk_length` 的块与下一个块以 `separator` 连接，合并后不超过 `max_text_length`；代码块不参与合并
+fn merge_short_segments(segments: Vec<TextSegment>, config: &TranslationConfig, separator: &str) -> Vec<TextSegment> {
+    let min_length = config.chunking.min_chunk_length;
+    if min_length == 0 {
+        return segments;
+    }
+    let measure = |text: &str| config.chunking.length_unit.measure(text);
+    let mut merged: Vec<TextSegment> = Vec::with_capacity(segments.len());
+    for segment in segments {
+        if let Some(last) = merged.last_mut() {
+            let length = measure(&last.content);
+            if !last.is_code_block
+                && !segment.is_code_block
+                && length < min_length
+                && length + measure(separator) + measure(&segment.content) <= config.max_text_length
+            {
+                last.content.push_str(separator);
+                last.content.push_str(&segment.content);
+                continue;
+            }
+        }
+        merged.push(segment);
+    }
+    merged
+}
+
+/// 各块的重叠上下文：前一个非代码块末尾 `chunking.overlap` 个长度单位的原文，第一个块和代码块为 `None`
+pub(crate) fn overlap_contexts(segments: &[TextSegment], chunking: &ChunkingConfig) -> Vec<Option<String>> {
+    let mut previous: Option<&str> = None;
+    segments
+        .iter()
+        .map(|segment| {
+            if segment.is_code_block {
+                return None;
+            }
+            let overlap = previous
+                .filter(|_| chunking.overlap > 0)
+                .map(|previous| {
+                    let start = match chunking.length_unit {
+                        LengthUnit::Bytes => {
+                            let mut start = previous.len().saturating_sub(chunking.overlap);
+                            while !previous.is_char_boundary(start) {
+                                start += 1;
+                            }
+                            start
+                        }
+                        LengthUnit::Chars => previous
+                            .char_indices()
+                            .rev()
+                            .nth(chunking.overlap - 1)
+                            .map_or(0, |(offset, _)| offset),
+                    };
+                    previous[start..].trim().to_string()
+                })
+                .filter(|overlap| !overlap.is_empty());
+            previous = Some(&segment.content);
+            overlap
+        })
+        .collect()
+}
+
+/// 定位各段在源文本中的字节范围
+///
+/// 依次从上一段结束处向后查找段内容；合并段时段落间的空白可能被规范化，
+/// 找不到完整内容时按首行和末行定位，仍找不到时为 `None`。
+pub(crate) fn locate_segments(text: &str, segments: &[TextSegment]) -> Vec<Option<Range<usize>>> {
+    let mut cursor = 0;
+    segments
+        .iter()
+        .map(|segment| {
+            let range = locate(text, cursor, segment.content.trim())?;
+            cursor = range.end;
+            Some(range)
+        })
+        .collect()
+}
+
+/// 从 `cursor` 开始查找内容，先查找完整内容，再按首行和末行查找
+fn locate(text: &str, cursor: usize, content: &str) -> Option<Range<usize>> {
+    let rest = text.get(cursor..)?;
+    if content.is_empty() {
+        return None;
+    }
+    if let Some(offset) = rest.find(content) {
+        return Some(cursor + offset..cursor + offset + content.len());
+    }
+    let first = content.lines().next()?;
+    let last = content.lines().last()?;
+    let start = cursor + rest.find(first)?;
+    let last_start = start + first.len() + text[start + first.len()..].find(last)?;
+    Some(start..last_start + last.len())
+}
+
+/// 依次应用已设置的钩子
+fn apply_hooks(text: &str, hooks: [&Option<TextHook>; 2]) -> String {
+    hooks
+        .into_iter()
+        .flatten()
+        .fold(text.to_string(), |text, hook| hook.apply(&text))
+}
+
+/// 按长度将纯文本拆分为块，各块按顺序拼接后与原文完全一致
+///
+/// 优先在 `chunking.sentence_breaks` 中的句末字符（ASCII字符需后跟空白）或换行之后断开，
+/// 其次在空白处断开，都找不到时按字符边界硬性截断。长度按 `chunking.length_unit` 计算，
+/// 断点处的空白归属于下一个块。
+fn split_plain_text<'a>(text: &'a str, max_length: usize, chunking: &ChunkingConfig) -> Vec<&'a str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    while chunking.length_unit.measure(&text[start..]) > max_length {
+        let limit = match chunking.length_unit {
+            LengthUnit::Bytes => {
+                let mut limit = start + max_length;
+                while !text.is_char_boundary(limit) {
+                    limit -= 1;
+                }
+                limit
+            }
+            LengthUnit::Chars => text[start..]
+                .char_indices()
+                .nth(max_length)
+                .map_or(text.len(), |(offset, _)| start + offset),
+        };
+        let window = &text[start..limit];
+        let mut sentence_end = None;
+        let mut whitespace = None;
+        let mut chars = window.char_indices().peekable();
+        while let Some((offset, c)) = chars.next() {
+            let next = chars.peek().map(|&(_, next)| next);
+            let end = offset + c.len_utf8();
+            let after_sentence = match c {
+                '\n' => true,
+                c if chunking.sentence_breaks.contains(&c) => !c.is_ascii() || next.is_none_or(char::is_whitespace),
+                _ => false,
+            };
+            if after_sentence {
+                sentence_end = Some(end);
+            } else if c.is_whitespace() && offset > 0 {
+                whitespace = Some(offset);
+            }
+        }
+
+        let end = match sentence_end.or(whitespace) {
+            Some(end) if end > 0 => start + end,
+            // 整个窗口没有可用断点，按字符边界截断；单个字符超过上限时至少前进一个字符
+            _ if limit > start => limit,
+            _ => start + text[start..].chars().next().map_or(1, char::len_utf8),
+        };
+        chunks.push(&text[start..end]);
+        start = end;
+    }
+
+    if start < text.len() {
+        chunks.push(&text[start..]);
+    }
+    chunks
+}
+
+/// 在给定的断点处将文本拆分为不超过 `max_length` 的块，各块按顺序拼接后与原文完全一致
+///
+/// 取上限内最靠后的断点；上限内没有断点时取之后的第一个断点，允许块超过上限。
+pub(crate) fn split_at_boundaries<'a>(text: &'a str, boundaries: &[usize], max_length: usize) -> Vec<&'a str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while text.len() - start > max_length {
+        let end = boundaries
+            .iter()
+            .copied()
+            .rfind(|&boundary| boundary > start && boundary <= start + max_length)
+            .or_else(|| boundaries.iter().copied().find(|&boundary| boundary > start));
+        let Some(end) = end else { break };
+        chunks.push(&text[start..end]);
+        start = end;
+    }
+    chunks.push(&text[start..]);
+    chunks
+}
+
+/// 服务生命周期状态
+#[derive(Debug, Default)]
+struct Lifecycle {
+    /// 在途调用登记，关闭后不再接受新调用
+    tracker: TaskTracker,
+    /// 宽限期结束后用于中止剩余调用
+    abort: CancellationToken,
 }
 
 /// 翻译服务主类
@@ -134,12 +1374,20 @@ where
 /// ```
 #[derive(Clone)]
 pub struct TranslationService {
-    /// HTTP客户端，用于API调用
-    client: Client,
-    /// 速率限制器
-    rate_limiter: RateLimiter,
-    /// 翻译配置
-    config: TranslationConfig,
+    /// 可在运行时替换的配置及由其构建的组件，所有克隆共享
+    components: Arc<RwLock<Arc<Components>>>,
+    /// 暂停状态，所有克隆共享
+    paused: Arc<watch::Sender<bool>>,
+    /// 服务级累计指标，所有克隆共享
+    metrics: Arc<Metrics>,
+    /// 在途调用与关闭状态，所有克隆共享
+    lifecycle: Arc<Lifecycle>,
+    /// 单个块的重试配置
+    retry_config: RetryConfig,
+    /// 服务级翻译前后钩子和质量检查钩子
+    pub(crate) hooks: Hooks,
+    /// 事件订阅者，所有克隆共享
+    subscribers: Arc<std::sync::Mutex<Vec<mpsc::Sender<TranslationEvent>>>>,
 }
 
 impl TranslationService {
@@ -150,47 +1398,588 @@ impl TranslationService {
     /// * `config` - 翻译配置，包含API地址、语言设置等
     /// 
     /// # 示例
-    /// 
+    /// 
+    /// ```rust
+    /// use markdown_translator::{TranslationService, TranslationConfig};
+    /// 
+    /// let config = TranslationConfig {
+    ///     enabled: true,
+    ///     source_lang: "en".to_string(),
+    ///     target_lang: "zh".to_string(),
+    ///     deeplx_api_url: "http://localhost:1188/translate".to_string(),
+    ///     max_requests_per_second: 1.0,
+    ///     max_text_length: 3000,
+    ///     max_paragraphs_per_request: 10,
+    ///     ..Default::default()
+    /// };
+    /// 
+    /// let service = TranslationService::new(config);
+    /// ```
+    ///
+    /// `new` 不会失败：配置无效时只在 `verbosity` 不低于 `warn` 时向标准错误输出打印 [`TranslationConfig::validate`] 发现的问题，
+    /// 需要在配置无效时得到错误请使用 [`try_new`](Self::try_new)。
+    pub fn new(config: TranslationConfig) -> Self {
+        if let Err(errors) = config.validate() {
+            for error in errors {
+                config.verbosity.log(Verbosity::Warn, format_args!("Invalid configuration: {}", error));
+            }
+        }
+        let client = Self::build_client(&config).unwrap_or_else(|e| {
+            config.verbosity.log(Verbosity::Warn, format_args!("Failed to create optimized client: {}, using default", e));
+            Client::new()
+        });
+        Self::with_parts(config, client, false)
+    }
+
+    /// 校验配置并创建翻译服务
+    ///
+    /// 与 [`new`](Self::new) 不同，配置无效、术语表或保留术语无法加载时返回错误，
+    /// 错误信息一次列出 [`TranslationConfig::validate`] 发现的所有问题及字段名。
+    ///
+    /// # 返回
+    ///
+    /// * `Ok(TranslationService)` - 创建成功
+    /// * `Err(TranslationError::InvalidLanguage)` - 源语言或目标语言不是已知的语言代码
+    /// * `Err(TranslationError::ConfigError)` - 配置无效（含术语表无法解析）
+    /// * `Err(TranslationError::Io)` - 术语表文件无法读取，或 `disk` 缓存目录无法创建、条目文件无法读写
+    /// * `Err(TranslationError::Http)` - HTTP客户端构建失败
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{TranslationError, TranslationService, TranslationConfig};
+    ///
+    /// let config = TranslationConfig {
+    ///     max_text_length: 0,
+    ///     deeplx_api_url: String::new(),
+    ///     ..Default::default()
+    /// };
+    /// match TranslationService::try_new(config) {
+    ///     Err(TranslationError::ConfigError(message)) => {
+    ///         assert!(message.contains("deeplx_api_url"));
+    ///         assert!(message.contains("max_text_length"));
+    ///     }
+    ///     _ => panic!("invalid configuration accepted"),
+    /// }
+    ///
+    /// // "cn" 是常见的错误写法，错误中附带建议的代码
+    /// let config = TranslationConfig { target_lang: "cn".to_string(), ..Default::default() };
+    /// match TranslationService::try_new(config) {
+    ///     Err(TranslationError::InvalidLanguage { given, suggestions }) => {
+    ///         assert_eq!(given, "cn");
+    ///         assert_eq!(suggestions, ["zh", "cs", "en"]);
+    ///     }
+    ///     _ => panic!("invalid language accepted"),
+    /// }
+    /// ```
+    pub fn try_new(config: TranslationConfig) -> Result<Self> {
+        TranslationServiceBuilder::from_config(config).build()
+    }
+
+    /// 按配置文件中的配置档创建翻译服务
+    ///
+    /// 合并基础配置和配置档（见 [`TranslationLibConfig::profile`]）后按 [`try_new`](Self::try_new) 校验并创建服务。
+    ///
+    /// # 返回
+    ///
+    /// * `Ok(TranslationService)` - 创建成功
+    /// * `Err(TranslationError::ConfigError)` - 配置档不存在（错误信息列出所有配置档）或合并后的配置无效
+    ///
+    /// # 示例
+    ///
+    /// ```rust,no_run
+    /// use markdown_translator::{TranslationLibConfig, TranslationService};
+    ///
+    /// let config = TranslationLibConfig::load_from_default_locations();
+    /// let ja = TranslationService::from_profile(&config, "ja").unwrap();
+    /// ```
+    pub fn from_profile(config: &TranslationLibConfig, name: &str) -> Result<Self> {
+        Self::try_new(config.profile(name)?)
+    }
+
+    /// 使用调用方提供的HTTP客户端创建翻译服务
+    ///
+    /// 客户端原样使用，不经过内部的客户端构建，调用方可以复用已配置好DNS解析、代理或中间件的客户端，
+    /// 与应用共享连接池。此时以下事项由调用方在构建客户端时负责，配置中的对应字段不再生效：
+    ///
+    /// * 请求超时和连接超时（`request_timeout_secs`、`connect_timeout_secs`），
+    ///   整体时限和单块时限（`overall_timeout_secs`、`chunk_timeout_secs`）仍由服务控制
+    /// * 默认的User-Agent、连接池大小和TCP keepalive；配置了 `user_agent` 或 `default_headers` 时，
+    ///   它们仍随每个请求发送
+    ///
+    /// 之后通过 [`update_config`](Self::update_config) 修改超时设置也不会替换该客户端。
+    /// 与 [`new`](Self::new) 相同，术语表或保留术语无效时打印错误并禁用对应功能；
+    /// 需要在这些情况下返回错误时使用构建器的 [`client`](TranslationServiceBuilder::client)。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{TranslationService, TranslationConfig};
+    /// use std::time::Duration;
+    ///
+    /// let client = reqwest::Client::builder()
+    ///     .timeout(Duration::from_secs(10))
+    ///     .user_agent("my-app/1.0")
+    ///     .build()
+    ///     .unwrap();
+    /// let config = TranslationConfig { enabled: true, ..Default::default() };
+    /// let _service = TranslationService::with_client(config, client);
+    /// ```
+    pub fn with_client(config: TranslationConfig, client: Client) -> Self {
+        Self::with_parts(config, client, true)
+    }
+
+    /// 由配置和客户端创建服务，术语表、保留术语或翻译缓存无效时禁用对应功能
+    fn with_parts(config: TranslationConfig, client: Client, custom_client: bool) -> Self {
+        let glossary = Glossary::from_config(&config).unwrap_or_else(|e| {
+            config.verbosity.log(Verbosity::Warn, format_args!("Failed to load glossary: {}, glossary disabled", e));
+            Glossary::default()
+        });
+        let keep_terms = KeepTerms::new(&config.keep_terms).unwrap_or_else(|e| {
+            config.verbosity.log(Verbosity::Warn, format_args!("Invalid keep_terms: {}, keep_terms disabled", e));
+            KeepTerms::default()
+        });
+        let cache = TranslationCache::from_config(&config).unwrap_or_else(|e| {
+            config.verbosity.log(Verbosity::Warn, format_args!("Failed to open translation cache: {}, cache disabled", e));
+            None
+        });
+
+        let parts = LoadedParts {
+            glossary,
+            keep_terms,
+            cache,
+        };
+        Self::from_parts(config, client, custom_client, RetryConfig::default(), parts, Hooks::default())
+    }
+
+    /// 创建服务构建器
+    ///
+    /// 未设置的字段使用 `TranslationConfig::default()` 的值（注意默认未启用翻译）。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{RetryConfig, TranslationService};
+    ///
+    /// let service = TranslationService::builder()
+    ///     .enabled(true)
+    ///     .target_lang("zh")
+    ///     .api_url("http://localhost:1188/translate")
+    ///     .max_rps(2.0)
+    ///     .retry(RetryConfig { max_retries: 3, ..Default::default() })
+    ///     .build()
+    ///     .unwrap();
+    /// let config = service.config();
+    /// assert!(config.enabled);
+    /// assert_eq!(config.deeplx_api_url, "http://localhost:1188/translate");
+    /// assert_eq!((config.target_lang.as_str(), config.max_requests_per_second), ("zh", 2.0));
+    ///
+    /// // 未设置的字段保持默认值
+    /// assert_eq!(config.max_text_length, markdown_translator::TranslationConfig::default().max_text_length);
+    /// ```
+    pub fn builder() -> TranslationServiceBuilder {
+        TranslationServiceBuilder::new()
+    }
+
+    /// 按配置构建HTTP客户端
+    pub(crate) fn build_client(config: &TranslationConfig) -> reqwest::Result<Client> {
+        let mut builder = Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_secs));
+        if let Some(connect_timeout) = config.connect_timeout_secs {
+            builder = builder.connect_timeout(Duration::from_secs(connect_timeout));
+        }
+        let http = &config.http;
+        let keepalive = (http.tcp_keepalive_secs > 0).then(|| Duration::from_secs(http.tcp_keepalive_secs));
+        if !http.http2 {
+            builder = builder.http1_only();
+        }
+        builder
+            .pool_idle_timeout(Duration::from_secs(http.pool_idle_timeout_secs))
+            .pool_max_idle_per_host(http.pool_max_idle_per_host)
+            .tcp_keepalive(keepalive)
+            .http1_title_case_headers()
+            .http2_keep_alive_interval(None)
+            .user_agent(DEFAULT_USER_AGENT)
+            .default_headers(configured_headers(config))
+            .build()
+    }
+
+    /// 由已构建好的组件创建服务
+    pub(crate) fn from_parts(
+        config: TranslationConfig,
+        client: Client,
+        custom_client: bool,
+        retry_config: RetryConfig,
+        parts: LoadedParts,
+        hooks: Hooks,
+    ) -> Self {
+        for warning in config.warnings() {
+            config.verbosity.log(Verbosity::Warn, format_args!("Configuration warning: {}", warning));
+        }
+        let components = Components {
+            endpoints: EndpointPool::from_config(&config),
+            client,
+            custom_client,
+            glossary: Arc::new(parts.glossary),
+            keep_terms: Arc::new(parts.keep_terms),
+            dump: open_dump(&config),
+            audit: open_audit(&config),
+            cache: parts.cache.map(Arc::new),
+            config: Arc::new(config),
+        };
+        Self {
+            components: Arc::new(RwLock::new(Arc::new(components))),
+            paused: Arc::new(watch::Sender::new(false)),
+            metrics: Arc::new(Metrics::default()),
+            lifecycle: Arc::new(Lifecycle::default()),
+            retry_config,
+            hooks,
+            subscribers: Arc::default(),
+        }
+    }
+
+    /// 当前组件的快照
+    pub(crate) fn components(&self) -> Arc<Components> {
+        self.components.read().unwrap().clone()
+    }
+
+    /// 获取当前生效的配置
+    ///
+    /// 返回的是调用时刻的快照，之后通过 [`update_config`](Self::update_config) 所做的修改不会反映在其中。
+    pub fn config(&self) -> Arc<TranslationConfig> {
+        self.components().config.clone()
+    }
+
+    /// 在运行时修改配置，修改对服务的所有克隆生效
+    ///
+    /// 闭包在当前配置的副本上修改，校验通过后整体替换。
+    /// 每次翻译调用在开始时取一份配置快照，因此正在进行的调用继续使用旧配置，
+    /// 之后开始的调用使用新配置：
+    ///
+    /// * 语言、`max_text_length`、失败策略、超时等字段直接生效
+    /// * 修改端点地址、速率限制或熔断参数时重建端点池，新端点池的限制器和熔断器从初始状态开始
+    /// * 修改 `request_timeout_secs` 或 `connect_timeout_secs` 时按新配置重建HTTP客户端；
+    ///   调用方提供的客户端（[`with_client`](Self::with_client) 或构建器的 `client`）保持不变
+    /// * 修改术语表或保留术语相关字段时重新编译
+    ///
+    /// 服务级钩子和重试配置不属于 `TranslationConfig`，不受影响。
+    ///
+    /// # 返回
+    ///
+    /// * `Ok(())` - 新配置已生效
+    /// * `Err(TranslationError)` - 新配置无效、术语表无法加载或HTTP客户端构建失败，原配置保持不变
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{TranslationService, TranslationConfig};
+    ///
+    /// let service = TranslationService::new(TranslationConfig::default());
+    /// let handle = service.clone();
+    ///
+    /// service.update_config(|config| {
+    ///     config.target_lang = "ja".to_string();
+    ///     config.max_requests_per_second = 5.0;
+    /// }).unwrap();
+    /// assert_eq!(handle.config().target_lang, "ja");
+    ///
+    /// // 无效的修改被拒绝，原配置保持不变
+    /// assert!(service.update_config(|config| config.max_text_length = 0).is_err());
+    /// assert_eq!(handle.config().max_text_length, 3000);
+    /// ```
+    pub fn update_config(&self, update: impl FnOnce(&mut TranslationConfig)) -> Result<()> {
+        let mut components = self.components.write().unwrap();
+        let mut config = TranslationConfig::clone(&components.config);
+        update(&mut config);
+        validate_config(&config)?;
+
+        let old = &components.config;
+        let endpoints = if endpoint_settings_changed(old, &config) {
+            EndpointPool::from_config(&config)
+        } else {
+            components.endpoints.clone()
+        };
+        let client = if !components.custom_client && client_settings_changed(old, &config) {
+            Self::build_client(&config)?
+        } else {
+            components.client.clone()
+        };
+        let glossary = if glossary_settings_changed(old, &config) {
+            Arc::new(Glossary::from_config(&config)?)
+        } else {
+            components.glossary.clone()
+        };
+        let keep_terms = if old.keep_terms == config.keep_terms {
+            components.keep_terms.clone()
+        } else {
+            Arc::new(KeepTerms::new(&config.keep_terms)?)
+        };
+        let dump = if dump_settings_changed(old, &config) {
+            open_dump(&config)
+        } else {
+            components.dump.clone()
+        };
+        let audit = if audit_settings_changed(old, &config) {
+            open_audit(&config)
+        } else {
+            components.audit.clone()
+        };
+        let cache = if old.cache != config.cache {
+            TranslationCache::from_config(&config)?.map(Arc::new)
+        } else {
+            components.cache.clone()
+        };
+
+        *components = Arc::new(Components {
+            config: Arc::new(config),
+            client,
+            custom_client: components.custom_client,
+            endpoints,
+            glossary,
+            keep_terms,
+            dump,
+            audit,
+            cache,
+        });
+        Ok(())
+    }
+
+    /// 获取服务级累计指标
+    ///
+    /// 累计自服务创建以来所有调用的请求数、重试次数、429次数，
+    /// 以及速率限制等待、退避等待和HTTP耗时。所有克隆共享同一份指标。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{FnTranslator, TranslationService};
+    ///
+    /// let service = TranslationService::builder()
+    ///     .enabled(true)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// # tokio_test::block_on(async {
+    /// let (_, report) = service.translate_with_report("Hello").await.unwrap();
+    /// // 服务级指标跨调用累计，单次报告只包含本次调用
+    /// service.translate("World").await.unwrap();
+    /// assert_eq!((report.metrics.requests, service.metrics().requests), (1, 2));
+    /// # });
+    /// ```
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// 订阅结构化翻译事件
+    ///
+    /// 返回的接收端收到之后开始的所有调用（包括服务的其他克隆发起的调用）的事件，
+    /// 订阅时已在进行的调用不会发送给它。事件以 `try_send` 发送：接收方处理不及时、
+    /// 通道（容量256）已满时事件被丢弃，翻译不会因此等待，丢弃数记录在
+    /// `TranslationReport::dropped_events` 中。丢弃接收端即取消订阅。
+    ///
+    /// # 示例
+    ///
     /// ```rust
-    /// use markdown_translator::{TranslationService, TranslationConfig};
-    /// 
-    /// let config = TranslationConfig {
-    ///     enabled: true,
-    ///     source_lang: "en".to_string(),
-    ///     target_lang: "zh".to_string(),
-    ///     deeplx_api_url: "http://localhost:1188/translate".to_string(),
-    ///     max_requests_per_second: 1.0,
-    ///     max_text_length: 3000,
-    ///     max_paragraphs_per_request: 10,
-    /// };
-    /// 
+    /// use markdown_translator::{TranslationConfig, TranslationEvent, TranslationService};
+    ///
+    /// # tokio_test::block_on(async {
+    /// let config = TranslationConfig { enabled: true, max_text_length: 10, ..Default::default() };
     /// let service = TranslationService::new(config);
+    /// let mut events = service.subscribe();
+    ///
+    /// // 只包含代码块的文档无需请求API
+    /// service.translate("```\nfn main() {}\n```").await.unwrap();
+    ///
+    /// assert!(matches!(events.recv().await, Some(TranslationEvent::PlanReady { chunks: 1 })));
+    /// assert!(matches!(events.recv().await, Some(TranslationEvent::ChunkStarted { index: 0 })));
+    /// assert!(matches!(events.recv().await, Some(TranslationEvent::ChunkCompleted { index: 0, .. })));
+    /// match events.recv().await {
+    ///     Some(TranslationEvent::DocumentCompleted { report }) => assert_eq!(report.dropped_events, 0),
+    ///     other => panic!("unexpected event: {:?}", other),
+    /// }
+    /// # });
     /// ```
-    pub fn new(config: TranslationConfig) -> Self {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .pool_max_idle_per_host(5)
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .http1_title_case_headers()
-            .http2_keep_alive_interval(None)
-            .user_agent("Mozilla/5.0 (compatible; MarkdownDownloader/1.0)")
-            .build()
-            .unwrap_or_else(|e| {
-                eprintln!("Failed to create optimized client: {}, using default", e);
-                Client::new()
-            });
-            
-        Self {
-            client,
-            rate_limiter: RateLimiter::new(config.max_requests_per_second),
-            config,
+    pub fn subscribe(&self) -> mpsc::Receiver<TranslationEvent> {
+        let (sender, receiver) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|subscriber| !subscriber.is_closed());
+        subscribers.push(sender);
+        receiver
+    }
+
+    /// 当前仍在接收的订阅者
+    fn subscribers(&self) -> Arc<[mpsc::Sender<TranslationEvent>]> {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|subscriber| !subscriber.is_closed());
+        subscribers.iter().cloned().collect()
+    }
+
+    /// 创建单次调用的上下文并登记为在途调用，服务关闭后返回 `ShuttingDown`
+    ///
+    /// 选项中未设置的字段使用服务配置中的值；选项覆盖的语言不是已知代码时返回 `InvalidLanguage`。
+    pub(crate) fn new_context(&self, options: &TranslateOptions) -> Result<CallContext> {
+        if let Some(lang) = &options.source_lang {
+            check_language(lang, true)?;
+        }
+        if let Some(lang) = &options.target_lang {
+            check_language(lang, false)?;
+        }
+
+        // 先登记再检查，保证 shutdown 要么拒绝本次调用，要么等待它完成
+        let in_flight = self.lifecycle.tracker.token();
+        if self.lifecycle.tracker.is_closed() {
+            return Err(TranslationError::ShuttingDown);
+        }
+
+        let components = self.components();
+        let config = &components.config;
+        let document_id = match &options.document_id {
+            Some(id) if HeaderValue::from_str(id).is_err() => {
+                return Err(TranslationError::ConfigError(format!("document_id 不是有效的请求头值: {:?}", id)));
+            }
+            Some(id) => id.clone(),
+            None => generate_document_id(),
+        };
+        Ok(CallContext {
+            budget: RetryBudget::new(options.max_total_retries.or(config.max_total_retries)),
+            cancel: options.cancellation_token.clone().unwrap_or_default(),
+            source_lang: options.source_lang.clone().unwrap_or_else(|| config.source_lang.clone()),
+            target_lang: options.target_lang.clone().unwrap_or_else(|| config.target_lang.clone()),
+            overall_timeout: options.overall_timeout.or(config.overall_timeout_secs.map(Duration::from_secs)),
+            chunk_timeout: options.chunk_timeout.or(config.chunk_timeout_secs.map(Duration::from_secs)),
+            on_error: options.on_error.unwrap_or(config.on_error),
+            on_chunk_timeout: options.on_chunk_timeout.unwrap_or(config.on_chunk_timeout),
+            output_mode: options.output_mode.clone(),
+            keep_terms: match &options.keep_terms {
+                Some(terms) => Arc::new(KeepTerms::new(terms)?),
+                None => components.keep_terms.clone(),
+            },
+            document_context: options.context.clone(),
+            scope: options.scope,
+            format: options.format.unwrap_or(config.format),
+            num_alternatives: options.num_alternatives,
+            quality_check: options.quality_check.clone(),
+            endpoint_offset: 0,
+            fresh: false,
+            cache_mode: options.cache_mode,
+            warm: None,
+            tag_handling: None,
+            translate_kinds: options.translate_kinds.as_deref().or(config.translate_kinds.as_deref()).map(Arc::from),
+            pre_process: options.pre_process.clone(),
+            post_process: options.post_process.clone(),
+            checkpoint: options.checkpoint_path.clone().map(|path| Arc::new(Checkpoint::new(path))),
+            shutdown: self.lifecycle.abort.clone(),
+            _in_flight: in_flight,
+            metrics: Arc::new(Metrics::default()),
+            warnings: Arc::default(),
+            service_metrics: self.metrics.clone(),
+            recorder: self.hooks.metrics_recorder.clone(),
+            document_id: Arc::from(document_id),
+            chunk_id: None,
+            chunk_index: None,
+            chunk_timing: None,
+            detailed_timings: options.detailed_timings,
+            subscribers: self.subscribers(),
+            stats: Arc::default(),
+            progress: options.on_progress.clone().map(|callback| ProgressTracker {
+                callback,
+                state: Arc::default(),
+            }),
+            components,
+        })
+    }
+
+    /// 优雅关闭服务
+    ///
+    /// 立即拒绝新的翻译调用（返回 `TranslationError::ShuttingDown`），
+    /// 等待在途调用在 `grace` 时间内完成；超时后中止剩余调用，
+    /// 它们会返回 `TranslationError::Cancelled`，并在它们退出（最多再等1秒）后才返回。
+    /// 关闭状态在服务的所有克隆之间共享。
+    /// 配置了审计日志时，返回前等待已提交的记录写入磁盘。
+    ///
+    /// # 返回
+    ///
+    /// 所有在途调用都在宽限期内完成时返回 `true`，有调用被中止时返回 `false`。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{TranslationError, TranslationService, TranslationConfig};
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let service = TranslationService::new(TranslationConfig::default());
+    ///
+    ///     assert!(service.shutdown(Duration::from_secs(5)).await);
+    ///     assert!(matches!(
+    ///         service.translate("Hello").await,
+    ///         Err(TranslationError::ShuttingDown)
+    ///     ));
+    /// }
+    /// ```
+    pub async fn shutdown(&self, grace: Duration) -> bool {
+        self.lifecycle.tracker.close();
+        let drained = timeout(grace, self.lifecycle.tracker.wait()).await.is_ok();
+        if !drained {
+            self.lifecycle.abort.cancel();
+            // 被中止的调用还在退出，等它们写完最后的审计记录再刷新
+            let _ = timeout(ABORT_DRAIN_TIMEOUT, self.lifecycle.tracker.wait()).await;
         }
+        if let Some(audit) = &self.components().audit {
+            audit.flush().await;
+        }
+        drained
+    }
+
+    /// 服务是否已进入关闭状态
+    pub fn is_shutting_down(&self) -> bool {
+        self.lifecycle.tracker.is_closed()
+    }
+
+    /// 暂停翻译
+    ///
+    /// 暂停后不再派发新的块请求，已在进行中的请求（包括其重试）会继续完成，
+    /// 已完成的结果不会丢失。暂停状态在服务的所有克隆之间共享。
+    /// 暂停期间速率限制器的令牌最多累积到 `burst_size`，恢复后不会形成更大的突发。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{TranslationService, TranslationConfig};
+    ///
+    /// let service = TranslationService::new(TranslationConfig::default());
+    /// service.pause();
+    /// assert!(service.is_paused());
+    /// service.resume();
+    /// assert!(!service.is_paused());
+    /// ```
+    pub fn pause(&self) {
+        self.paused.send_replace(true);
+    }
+
+    /// 恢复翻译，等待中的块请求将继续派发
+    pub fn resume(&self) {
+        self.paused.send_replace(false);
+    }
+
+    /// 当前是否处于暂停状态
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
+    /// 暂停期间等待恢复
+    async fn wait_until_resumed(&self) {
+        let mut receiver = self.paused.subscribe();
+        // 发送端由 self 持有，等待期间不会关闭
+        let _ = receiver.wait_for(|paused| !*paused).await;
     }
 
     /// 翻译文本
     /// 
     /// 主要的翻译接口，支持智能分块、并行处理和代码块跳过。
+    /// 各块并发翻译，完成的先后不定，译文仍按原文顺序拼接。
     /// 
     /// # 参数
     /// 
@@ -237,77 +2026,595 @@ impl TranslationService {
     /// }
     /// ```
     pub async fn translate(&self, text: &str) -> Result<String> {
-        if !self.config.enabled {
-            return Ok(text.to_string());
-        }
+        self.translate_with(text, &TranslateOptions::default()).await
+    }
+
+    /// 翻译文本并返回运行统计
+    ///
+    /// 与 [`translate`](Self::translate) 相同，额外返回 [`TranslationReport`]，
+    /// 其中记录了收发字符数、块数、请求数、重试与失败次数、总耗时以及每个块的耗时。
+    ///
+    /// # 示例
+    ///
+    /// 文档中所有块共享 `max_total_retries` 重试预算，预算用尽后失败的块不再重试：
+    ///
+    /// ```rust
+    /// use markdown_translator::{FnTranslator, TranslationService};
+    ///
+    /// let service = TranslationService::builder()
+    ///     .enabled(true)
+    ///     .max_text_length(20)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// # tokio_test::block_on(async {
+    /// let (translated, report) = service.translate_with_report("First paragraph.\n\nSecond paragraph.").await.unwrap();
+    /// assert_eq!(translated, "FIRST PARAGRAPH.\n\nSECOND PARAGRAPH.");
+    /// assert_eq!((report.chunks, report.failed_chunks), (2, 0));
+    /// # });
+    /// ```
+    pub async fn translate_with_report(&self, text: &str) -> Result<(String, TranslationReport)> {
+        self.run_translation(text, &TranslateOptions::default()).await
+    }
+
+    /// 使用单次调用选项翻译文本
+    ///
+    /// 与 [`translate`](Self::translate) 相同，但接受 [`TranslateOptions`]，
+    /// 例如传入取消令牌以便在调用方放弃时中止翻译，或覆盖目标语言、时限和失败处理策略。
+    /// 未设置的选项使用服务配置中的值。
+    ///
+    /// # 示例
+    ///
+    /// 取消后调用立即返回 `Cancelled`（带已完成的块数），进行中的请求随之中止：
+    ///
+    /// ```rust
+    /// use markdown_translator::{CancellationToken, FnTranslator, TranslateOptions, TranslationError, TranslationService};
+    ///
+    /// let service = TranslationService::builder()
+    ///     .enabled(true)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let token = CancellationToken::new();
+    /// let options = TranslateOptions::new().cancellation_token(token.clone());
+    /// token.cancel();
+    /// # tokio_test::block_on(async {
+    /// let error = service.translate_with("Hello", &options).await.unwrap_err();
+    /// assert!(matches!(error, TranslationError::Cancelled { .. }));
+    /// # });
+    /// ```
+    pub async fn translate_with(&self, text: &str, options: &TranslateOptions) -> Result<String> {
+        self.run_translation(text, options).await.map(|(translated, _)| translated)
+    }
+
+    /// 翻译为指定目标语言
+    ///
+    /// 复用同一个HTTP客户端和速率限制器，无需为每种语言单独创建服务。
+    /// 等价于 `translate_with(text, &TranslateOptions::new().target_lang(target_lang))`。
+    ///
+    /// # 示例
+    ///
+    /// ```rust,no_run
+    /// use markdown_translator::TranslationService;
+    ///
+    /// let service = TranslationService::builder().enabled(true).target_lang("zh").build().unwrap();
+    ///
+    /// # tokio_test::block_on(async {
+    /// for lang in ["ja", "ko", "de"] {
+    ///     println!("{}: {}", lang, service.translate_to("Hello, world!", lang).await.unwrap());
+    /// }
+    /// # });
+    /// ```
+    pub async fn translate_to(&self, text: &str, target_lang: &str) -> Result<String> {
+        self.translate_with(text, &TranslateOptions::new().target_lang(target_lang)).await
+    }
+
+    /// 翻译文本并返回带元数据的结果
+    ///
+    /// 与 [`translate_with`](Self::translate_with) 相同，但返回 [`TranslationResult`]：
+    /// 除译文外还包含后端检测到的源语言、提供译文的端点、是否来自缓存，
+    /// 以及保留原文等回退处理产生的警告。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{FnTranslator, TranslateOptions, TranslationService};
+    ///
+    /// let service = TranslationService::builder()
+    ///     .enabled(true)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// # tokio_test::block_on(async {
+    /// let result = service.translate_detailed("Hello there.", &TranslateOptions::new()).await.unwrap();
+    /// assert_eq!(result.text, "HELLO THERE.");
+    /// assert_eq!(result.backend.as_deref(), Some("custom"));
+    /// assert!(!result.from_cache);
+    /// assert!(result.warnings.is_empty());
+    /// # });
+    /// ```
+    pub async fn translate_detailed(&self, text: &str, options: &TranslateOptions) -> Result<TranslationResult> {
+        let (translated, report) = self.run_translation(text, options).await?;
+        Ok(TranslationResult::new(translated, report))
+    }
+
+    /// 以流的形式翻译文本
+    ///
+    /// 按文档顺序逐段产出翻译结果：某一段及其之前的所有段完成后立即产出，
+    /// 调用方可以边翻译边写出结果或显示进度。内部重排缓冲区受并发上限约束，
+    /// 内存中最多保留并发上限个未产出的段。将各段的 `translated` 以 `"\n\n"`
+    /// 连接即得到与 [`translate`](Self::translate) 相同的结果。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use futures::StreamExt;
+    /// use markdown_translator::{FnTranslator, TranslationService};
+    ///
+    /// let service = TranslationService::builder()
+    ///     .enabled(true)
+    ///     .max_text_length(25)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// # tokio_test::block_on(async {
+    /// let mut segments = Box::pin(service.translate_stream("First paragraph.\n\nSecond paragraph."));
+    /// while let Some(segment) = segments.next().await {
+    ///     let segment = segment.unwrap();
+    ///     println!("{}: {}", segment.index, segment.translated);
+    /// }
+    /// # });
+    /// ```
+    pub fn translate_stream<'a>(&'a self, text: &'a str) -> impl Stream<Item = Result<TranslatedSegment>> + 'a {
+        self.translate_stream_with(text, &TranslateOptions::default())
+    }
+
+    /// 使用单次调用选项以流的形式翻译文本
+    ///
+    /// 与 [`translate_stream`](Self::translate_stream) 相同，但按 `options` 覆盖服务配置。
+    /// 设置 `num_alternatives` 时，每个段的 `alternatives` 中包含后端提供的备选译文。
+    ///
+    /// # 示例
+    ///
+    /// ```rust,no_run
+    /// use futures::StreamExt;
+    /// use markdown_translator::{TranslateOptions, TranslationService};
+    ///
+    /// let service = TranslationService::builder().enabled(true).build().unwrap();
+    /// let options = TranslateOptions::new().num_alternatives(2);
+    ///
+    /// # tokio_test::block_on(async {
+    /// let mut segments = Box::pin(service.translate_stream_with("Hello, world!", &options));
+    /// let segment = segments.next().await.unwrap().unwrap();
+    /// println!("{} ({:?})", segment.translated, segment.alternatives);
+    /// # });
+    /// ```
+    pub fn translate_stream_with<'a>(
+        &'a self,
+        text: &'a str,
+        options: &TranslateOptions,
+    ) -> impl Stream<Item = Result<TranslatedSegment>> + 'a {
+        let context = match self.new_context(options) {
+            Ok(context) => context,
+            Err(e) => return Either::Left(stream::once(async { Err(e) })),
+        };
+
+        let segments = self.plan_segments(text, context.format, &context.components.config);
+        let ranges = locate_segments(text, &segments);
+        let overlaps = overlap_contexts(&segments, &context.components.config.chunking);
+        Either::Right(
+            stream::iter(segments.into_iter().zip(ranges).zip(overlaps).enumerate())
+                .map(move |(index, ((segment, range), overlap))| {
+                    let context = context.clone();
+                    async move { self.translate_segment(index, segment, range, overlap, &context).await }
+                })
+                .buffered(MAX_CONCURRENT_CHUNKS),
+        )
+    }
+
+    /// 预估翻译文本所需的请求数和耗时
+    ///
+    /// 执行与 [`translate`](Self::translate) 相同的分块流程，但不发送任何请求。
+    /// 耗时按主端点的速率限制（及全局上限）估算：突发容量内的请求立即发出，
+    /// 其余请求按令牌补充速率排队。翻译未启用时预计请求数为0。
+    /// `protection` 统计跳过的代码块和各块中的保留术语。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{TranslationService, TranslationConfig};
+    ///
+    /// let config = TranslationConfig {
+    ///     enabled: true,
+    ///     max_text_length: 20,
+    ///     max_requests_per_second: 1.0,
+    ///     burst_size: Some(1),
+    ///     keep_terms: vec!["First".to_string()],
+    ///     ..Default::default()
+    /// };
+    /// let service = TranslationService::new(config);
+    ///
+    /// let estimate = service.estimate("First paragraph.\n\n```\ncode\n```\n\nSecond paragraph.");
+    /// assert_eq!(estimate.chunks, 2);
+    /// assert_eq!(estimate.skipped_segments, 1);
+    /// assert_eq!(estimate.estimated_requests, 2);
+    /// assert_eq!(estimate.estimated_duration.as_secs(), 1);
+    /// assert_eq!(estimate.protection.code_blocks, 1);
+    /// assert_eq!(estimate.protection.keep_terms, 1);
+    /// assert_eq!(estimate.protection.protected_chars, estimate.protection.code_block_chars + "First".len());
+    /// println!("{}", serde_json::to_string(&estimate).unwrap());
+    /// ```
+    pub fn estimate(&self, text: &str) -> Estimate {
+        let config = self.config();
+        let segments = self.plan_segments(text, config.format, &config);
+        let (skipped, translatable): (Vec<_>, Vec<_>) =
+            segments.iter().partition(|segment| segment.is_code_block);
+
+        let chunks = translatable.len();
+        let estimated_requests = if config.enabled { chunks } else { 0 };
 
-        println!("文本总长度: {} 字符", text.len());
+        let rate = match config.global_max_requests_per_second {
+            Some(global) if global > 0.0 => global.min(config.max_requests_per_second),
+            _ => config.max_requests_per_second,
+        };
+        let estimated_duration = if rate > 0.0 {
+            let burst = config.burst_size.unwrap_or_else(|| RateLimiter::default_burst(rate));
+            Duration::from_secs_f64(estimated_requests.saturating_sub(burst) as f64 / rate)
+        } else {
+            Duration::ZERO
+        };
 
-        if text.len() <= self.config.max_text_length {
-            println!("文本较短，直接翻译");
-            return self.translate_chunk(text).await;
+        let mut protection = ProtectionStats {
+            code_blocks: skipped.len(),
+            code_block_chars: skipped.iter().map(|segment| segment.content.chars().count()).sum(),
+            ..ProtectionStats::default()
+        };
+        protection.protected_chars = protection.code_block_chars;
+        let keep_terms = self.components().keep_terms.clone();
+        for segment in &translatable {
+            let mut placeholders = Placeholders::default();
+            keep_terms.protect(&segment.content, &mut placeholders);
+            placeholders.count_into(&mut protection);
+            protection.translated_chars += segment.content.chars().count();
         }
+        protection.translated_chars = protection
+            .translated_chars
+            .saturating_sub(protection.protected_chars.saturating_sub(protection.code_block_chars));
 
-        let chunks = self.split_text_into_chunks(text);
-        println!("文本较长，分为 {} 块进行翻译", chunks.len());
+        Estimate {
+            total_segments: segments.len(),
+            chunks,
+            characters: translatable.iter().map(|segment| segment.content.chars().count()).sum(),
+            skipped_segments: skipped.len(),
+            estimated_requests,
+            estimated_duration,
+            protection,
+        }
+    }
 
-        let mut translated_chunks = Vec::new();
+    /// 将文本划分为待翻译的段
+    ///
+    /// 短文本整体作为一个文本段；长Markdown文本按 `split_text_into_chunks` 分块，
+    /// 并识别出代码块段；长纯文本按 `split_plain_text` 在句子边界处分块，分块方式由 `chunking` 配置决定；
+    /// 长rst和AsciiDoc文本分别按 `split_rst_text` 和 `split_asciidoc_text` 在块边界处分块。
+    /// 分块只读取传入的 `config`：翻译调用传入上下文中的配置快照，分块与请求使用同一份配置。
+    pub(crate) fn plan_segments(&self, text: &str, format: TextFormat, config: &TranslationConfig) -> Vec<TextSegment> {
+        let splits_short = format == TextFormat::Markdown && config.chunking.strategy == ChunkStrategy::Paragraph;
+        if !config.enabled || (config.chunking.length_unit.measure(text) <= config.max_text_length && !splits_short) {
+            return vec![TextSegment {
+                content: text.to_string(),
+                is_code_block: false,
+            }];
+        }
+        let chunks = match format {
+            TextFormat::Markdown => Vec::new(),
+            TextFormat::Plain => split_plain_text(text, config.max_text_length, &config.chunking),
+            TextFormat::Rst => split_rst_text(text, config.max_text_length),
+            TextFormat::Asciidoc => split_asciidoc_text(text, config.max_text_length),
+        };
+        let segments = if !chunks.is_empty() {
+            chunks
+                .into_iter()
+                .map(|content| TextSegment {
+                    content: content.to_string(),
+                    is_code_block: false,
+                })
+                .collect()
+        } else {
+            self.split_text_into_chunks(text, config)
+                .into_iter()
+                .map(|chunk| {
+                    let is_code_block = self.is_code_block_chunk(&chunk);
+                    let content = match chunk.strip_prefix("__CODE_BLOCK__") {
+                        Some(code) => code.to_string(),
+                        None => chunk,
+                    };
+                    TextSegment { content, is_code_block }
+                })
+                .collect()
+        };
+        merge_short_segments(segments, config, format.separator())
+    }
 
-        // 并行处理所有块
-        let mut futures = Vec::new();
-        
-        for (i, chunk) in chunks.iter().enumerate() {
-            println!("准备翻译第 {} 块，长度: {} 字符", i + 1, chunk.len());
-            
-            if self.is_code_block_chunk(chunk) {
-                // 代码块直接返回结果
-                let result = chunk.strip_prefix("__CODE_BLOCK__").unwrap_or(chunk).to_string();
-                futures.push(Box::pin(async move { Ok(result) }) as std::pin::Pin<Box<dyn std::future::Future<Output = Result<String>> + Send>>);
+    /// 翻译单个段，代码块和未启用翻译时原样返回
+    ///
+    /// 块翻译失败且未按策略保留原文时，错误包装为 `ChunkFailed`，附带 `source_range` 等块的位置信息；
+    /// 取消和服务关闭不包装。
+    pub(crate) async fn translate_segment(
+        &self,
+        index: usize,
+        segment: TextSegment,
+        source_range: Option<Range<usize>>,
+        overlap: Option<String>,
+        context: &CallContext,
+    ) -> Result<TranslatedSegment> {
+        let chunk_id: Arc<str> = Arc::from(format!("{}-{}", context.document_id, index));
+        let timing = ChunkTiming {
+            queue_wait: context.stats.started.elapsed(),
+            ..ChunkTiming::default()
+        };
+        let context = &CallContext {
+            chunk_id: Some(chunk_id.clone()),
+            chunk_index: Some(index),
+            chunk_timing: Some(Arc::new(std::sync::Mutex::new(timing))),
+            document_context: match (&context.document_context, overlap) {
+                (Some(document), Some(overlap)) => Some(format!("{}\n\n{}", document, overlap)),
+                (document, overlap) => overlap.or_else(|| document.clone()),
+            },
+            ..context.clone()
+        };
+        context.progress_started(index);
+        let mut alternatives = Vec::new();
+        let mut attempts = 0;
+        let resumed = context.checkpoint.as_ref().and_then(|checkpoint| checkpoint.take(index));
+        let skipped = || TranslationEvent::ChunkCompleted {
+            index,
+            chars: segment.content.chars().count(),
+            duration: Duration::ZERO,
+        };
+        let translated = if segment.is_code_block || !context.components.config.enabled {
+            if segment.is_code_block {
+                let mut protection = context.stats.protection.lock().unwrap();
+                protection.code_blocks += 1;
+                let chars = segment.content.chars().count();
+                protection.code_block_chars += chars;
+                protection.protected_chars += chars;
+            }
+            let config = &context.components.config;
+            let info = segment.content.lines().next().unwrap_or_default().trim_start().trim_start_matches(['`', '~']);
+            if segment.is_code_block && config.enabled && config.chunking.translates_comments(info) {
+                let result = self.translate_code_comments(&segment.content, context).await;
+                if result.is_ok() {
+                    context.emit(skipped);
+                }
+                self.apply_failure_policy(result, &segment.content, context.on_error, context)
             } else {
-                // 翻译任务
-                let chunk_clone = chunk.clone();
-                let translator_clone = self.clone();
-                futures.push(Box::pin(async move {
-                    translator_clone.translate_chunk(&chunk_clone).await
-                }) as std::pin::Pin<Box<dyn std::future::Future<Output = Result<String>> + Send>>);
+                context.emit(skipped);
+                Ok(segment.content.clone())
+            }
+        } else if let Some(translated) = resumed {
+            context.stats.resumed_chunks.fetch_add(1, Ordering::Relaxed);
+            context.emit(skipped);
+            Ok(translated)
+        } else {
+            let verbosity = context.components.config.verbosity;
+            verbosity.log(
+                Verbosity::Info,
+                format_args!("开始翻译第 {} 块（{}），长度: {} 字符", index + 1, chunk_id, segment.content.len()),
+            );
+            let started = Instant::now();
+            let chunk = self.translate_checked(index, &segment.content, context);
+            let (result, policy): (Result<(String, Vec<String>)>, _) = match context.chunk_timeout {
+                Some(limit) => match timeout(limit, chunk).await {
+                    Ok(result) => (result, context.on_error),
+                    Err(_) => (
+                        Err(TranslationError::ChunkTimeout { index, elapsed: limit }),
+                        context.on_chunk_timeout,
+                    ),
+                },
+                None => (chunk.await, context.on_error),
+            };
+            verbosity.log(Verbosity::Info, format_args!("完成翻译第 {} 块（{}）", index + 1, chunk_id));
+            let elapsed = started.elapsed();
+            attempts = context.attempt().map_or(0, |(_, next)| next - 1);
+            context.record_chunk(index, elapsed, result.is_err());
+            match &result {
+                Ok(_) => context.emit(|| TranslationEvent::ChunkCompleted {
+                    index,
+                    chars: segment.content.chars().count(),
+                    duration: elapsed,
+                }),
+                Err(error) => context.emit(|| TranslationEvent::ChunkFailed { index, error: error.to_string() }),
+            }
+            if result.is_ok() {
+                context.stats.succeeded_chunks.fetch_add(1, Ordering::Relaxed);
+                context.stats.protection.lock().unwrap().translated_chars += segment.content.chars().count();
+            }
+            let result = result.map(|(translated, chunk_alternatives)| {
+                alternatives = chunk_alternatives;
+                translated
+            });
+            if let (Some(checkpoint), Ok(translated)) = (&context.checkpoint, &result) {
+                checkpoint.record(index, &segment.content, translated, context).await;
+            }
+            // 还没有任何块成功时端点不可用，其余块也会失败：交给文档级处理，不再逐块重试
+            let policy = match &result {
+                Err(error) if is_catastrophic(error) && context.stats.succeeded_chunks.load(Ordering::Relaxed) == 0 => {
+                    FailurePolicy::Fail
+                }
+                _ => policy,
+            };
+            self.apply_failure_policy(result, &segment.content, policy, context)
+        };
+        context.progress_chunk_done(index, &segment, translated.is_ok());
+        let translated = translated.map_err(|error| match error {
+            TranslationError::Cancelled { .. } | TranslationError::ShuttingDown => error,
+            source => TranslationError::ChunkFailed {
+                index,
+                byte_range: source_range,
+                preview: preview(&segment.content, context.components.config.log_content),
+                attempts,
+                source: Box::new(source),
+            },
+        })?;
+
+        Ok(TranslatedSegment {
+            index,
+            source: segment.content,
+            translated,
+            is_code_block: segment.is_code_block,
+            alternatives,
+            chunk_id: chunk_id.to_string(),
+        })
+    }
+
+    /// 翻译流程的公共实现
+    pub(crate) async fn run_translation(&self, text: &str, options: &TranslateOptions) -> Result<(String, TranslationReport)> {
+        let context = self.new_context(options)?;
+        let translated = self.translate_document(text, &context).await?;
+        Ok((translated, context.report()))
+    }
+
+    /// 在给定调用上下文中翻译一篇文档：分块、并发翻译并拼接
+    ///
+    /// 一次调用包含多篇文档时（例如notebook的各个Markdown单元格）共享同一个上下文，
+    /// 统计汇总到同一份报告中。
+    pub(crate) async fn translate_document(&self, text: &str, context: &CallContext) -> Result<String> {
+        let config = context.components.config.clone();
+
+        if !config.enabled {
+            return Ok(text.to_string());
+        }
+
+        if config.skip_if_already_target {
+            match self.detect_with_context(text, context).await {
+                Ok(detected) if detected.matches(&context.target_lang) => {
+                    context.stats.detected_source_lang.get_or_init(|| detected.code.clone());
+                    context.warn(format!("文档已是目标语言 {}，跳过翻译", context.target_lang));
+                    return Ok(text.to_string());
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    context.warn(format!("语言检测失败，继续翻译: {}", e));
+                }
             }
         }
 
-        // 并发执行所有翻译任务，但有并发限制
-        let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(5)); // 最多5个并发请求
-        let mut handles = Vec::new();
-        
-        for (i, future) in futures.into_iter().enumerate() {
-            let semaphore_clone = semaphore.clone();
-            let handle = tokio::spawn(async move {
-                let _permit = semaphore_clone.acquire().await.unwrap();
-                println!("开始翻译第 {} 块", i + 1);
-                let result = future.await;
-                println!("完成翻译第 {} 块", i + 1);
-                result
-            });
-            handles.push(handle);
+        let length = config.chunking.length_unit.measure(text);
+        let unit = match config.chunking.length_unit {
+            LengthUnit::Bytes => "字节",
+            LengthUnit::Chars => "字符",
+        };
+        config.verbosity.log(Verbosity::Info, format_args!("文本总长度: {} {}", length, unit));
+
+        let deadline = context.overall_timeout;
+        let segments = self.plan_segments(text, context.format, &config);
+        let total = segments.len();
+        if length <= config.max_text_length {
+            config.verbosity.log(Verbosity::Info, format_args!("文本较短，直接翻译"));
+        } else {
+            config.verbosity.log(Verbosity::Info, format_args!("文本较长，分为 {} 块进行翻译", total));
         }
 
-        // 收集所有结果
-        for handle in handles {
-            let result = handle.await.map_err(|e| TranslationError::Custom(e.to_string()))??;
-            translated_chunks.push(result);
+        context.progress_planned(&segments);
+
+        // 超时且策略为保留原文时需要用原文填充未完成的块
+        let sources: Vec<String> = segments.iter().map(|segment| segment.content.clone()).collect();
+        if let Some(checkpoint) = &context.checkpoint {
+            checkpoint.load(&sources, context).await;
+        }
+
+        // 按顺序并发翻译所有块，最多同时翻译 MAX_CONCURRENT_CHUNKS 块。
+        // 所有块的 future 都归属于本次调用，调用返回或被丢弃时一并取消
+        let mut translated_chunks = Vec::with_capacity(total);
+        let outcome = {
+            let ranges = locate_segments(text, &segments);
+            let overlaps = overlap_contexts(&segments, &context.components.config.chunking);
+            let mut translated_segments = stream::iter(segments.into_iter().zip(ranges).zip(overlaps).enumerate())
+                .map(|(index, ((segment, range), overlap))| self.translate_segment(index, segment, range, overlap, context))
+                .buffered(MAX_CONCURRENT_CHUNKS);
+            let collect = async {
+                while let Some(segment) = translated_segments.next().await {
+                    translated_chunks.push(render_segment(&segment?, &context.output_mode));
+                }
+                Ok::<_, TranslationError>(())
+            };
+            let cancellable = async {
+                tokio::select! {
+                    result = collect => Some(result),
+                    _ = context.cancelled() => None,
+                }
+            };
+            match deadline {
+                Some(limit) => timeout(limit, cancellable).await,
+                None => Ok(cancellable.await),
+            }
+        };
+
+        let completed = translated_chunks.len();
+        if context.is_cancelled() {
+            return Err(TranslationError::Cancelled { completed, total });
+        }
+
+        match outcome {
+            Ok(Some(Err(error))) if context.on_error == FailurePolicy::KeepOriginal && is_catastrophic(&error) => {
+                context.warn(format!("翻译失败，整篇文档保留原文: {}", error));
+                context.progress_finished();
+                return Ok(text.to_string());
+            }
+            Ok(Some(result)) => result?,
+            Ok(None) => unreachable!("cancellation is handled above"),
+            Err(_) if context.on_error == FailurePolicy::Fail => {
+                return Err(TranslationError::DeadlineExceeded { completed, total });
+            }
+            Err(_) => {
+                context.warn(format!("翻译超过整体时限，{}/{} 块已完成，其余保留原文", completed, total));
+                translated_chunks.extend(sources.into_iter().skip(completed));
+            }
         }
 
-        Ok(translated_chunks.join("\n\n"))
+        context.progress_finished();
+        Ok(translated_chunks.join(context.format.separator()))
+    }
+
+    /// 按失败处理策略处理单个块的翻译结果，取消不受策略影响
+    ///
+    /// 保留原文时会在报告中记录一条警告。
+    pub(crate) fn apply_failure_policy(
+        &self,
+        result: Result<String>,
+        source: &str,
+        policy: FailurePolicy,
+        context: &CallContext,
+    ) -> Result<String> {
+        match result {
+            Err(TranslationError::Cancelled { completed, total }) => {
+                Err(TranslationError::Cancelled { completed, total })
+            }
+            Err(e) if policy == FailurePolicy::KeepOriginal => {
+                context.warn(format!("翻译失败，保留原文: {}", e));
+                Ok(source.to_string())
+            }
+            result => result,
+        }
     }
 
-    fn split_text_into_chunks(&self, text: &str) -> Vec<String> {
+    fn split_text_into_chunks(&self, text: &str, config: &TranslationConfig) -> Vec<String> {
         let mut chunks = Vec::new();
-        let max_length = self.config.max_text_length;
+        let max_length = config.max_text_length;
+        let measure = |text: &str| config.chunking.length_unit.measure(text);
 
-        if text.len() <= max_length {
+        let strategy = config.chunking.strategy;
+        if measure(text) <= max_length && strategy == ChunkStrategy::Pack {
             chunks.push(text.to_string());
             return chunks;
         }
 
-        let protected_sections = self.identify_code_blocks(text);
+        let protected_sections = self.identify_code_blocks(text, config.chunking.unclosed_fence);
         let segments = self.split_by_code_blocks(text, &protected_sections);
 
         let mut current_chunk = String::new();
@@ -322,7 +2629,16 @@ impl TranslationService {
                 // 给代码块添加特殊标记，便于后续识别
                 chunks.push(format!("__CODE_BLOCK__{}", segment.content));
             } else {
-                let paragraphs = self.split_text_by_empty_lines(&segment.content);
+                let paragraphs = match strategy {
+                    ChunkStrategy::Pack => self.split_text_by_empty_lines(&segment.content, config),
+                    ChunkStrategy::Paragraph => segment
+                        .content
+                        .split("\n\n")
+                        .map(str::trim)
+                        .filter(|paragraph| !paragraph.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                };
                 
                 for paragraph in paragraphs {
                     if paragraph.trim().is_empty() {
@@ -330,12 +2646,13 @@ impl TranslationService {
                     }
 
                     let potential_length = if current_chunk.is_empty() {
-                        paragraph.len()
+                        measure(&paragraph)
                     } else {
-                        current_chunk.len() + 2 + paragraph.len()
+                        measure(&current_chunk) + 2 + measure(&paragraph)
                     };
 
-                    if potential_length <= max_length {
+                    let packs = current_chunk.is_empty() || strategy == ChunkStrategy::Pack;
+                    if potential_length <= max_length && packs {
                         if !current_chunk.is_empty() {
                             current_chunk.push_str("\n\n");
                         }
@@ -346,8 +2663,8 @@ impl TranslationService {
                             current_chunk.clear();
                         }
 
-                        if paragraph.len() > max_length {
-                            let sub_chunks = self.split_long_paragraph(&paragraph, max_length);
+                        if measure(&paragraph) > max_length && !self.is_atomic(&paragraph, &config.chunking) {
+                            let sub_chunks = self.split_long_paragraph(&paragraph, max_length, &config.chunking);
                             chunks.extend(sub_chunks);
                         } else {
                             current_chunk = paragraph;
@@ -368,7 +2685,7 @@ impl TranslationService {
         chunks
     }
 
-    fn identify_code_blocks(&self, text: &str) -> Vec<(usize, usize)> {
+    fn identify_code_blocks(&self, text: &str, unclosed_fence: UnclosedFence) -> Vec<(usize, usize)> {
         let mut code_blocks = Vec::new();
         let mut in_code_block = false;
         let mut current_start = 0;
@@ -376,7 +2693,7 @@ impl TranslationService {
         let lines: Vec<&str> = text.lines().collect();
         let mut char_pos = 0;
         
-        for (_i, line) in lines.iter().enumerate() {
+        for line in lines.iter() {
             if line.starts_with("```") {
                 if in_code_block {
                     let end_pos = char_pos + line.len();
@@ -390,7 +2707,7 @@ impl TranslationService {
             char_pos += line.len() + 1;
         }
         
-        if in_code_block {
+        if in_code_block && unclosed_fence == UnclosedFence::CodeToEnd {
             code_blocks.push((current_start, text.len()));
         }
         
@@ -441,10 +2758,11 @@ impl TranslationService {
         segments
     }
 
-    fn split_text_by_empty_lines(&self, text: &str) -> Vec<String> {
-        let max_length = self.config.max_text_length;
-        
-        if text.len() <= max_length {
+    fn split_text_by_empty_lines(&self, text: &str, config: &TranslationConfig) -> Vec<String> {
+        let max_length = config.max_text_length;
+        let measure = |text: &str| config.chunking.length_unit.measure(text);
+
+        if measure(text) <= max_length {
             return vec![text.to_string()];
         }
         
@@ -459,7 +2777,7 @@ impl TranslationService {
                 continue;
             }
             
-            let para_len = paragraph.len();
+            let para_len = measure(paragraph);
             
             let potential_length = if current_group.is_empty() {
                 para_len
@@ -476,8 +2794,8 @@ impl TranslationService {
                     current_group.clear();
                 }
                 
-                if para_len > max_length {
-                    let sub_parts = self.split_long_paragraph(paragraph, max_length);
+                if para_len > max_length && !self.is_atomic(paragraph, &config.chunking) {
+                    let sub_parts = self.split_long_paragraph(paragraph, max_length, &config.chunking);
                     result.extend(sub_parts);
                     current_length = 0;
                 } else {
@@ -494,173 +2812,481 @@ impl TranslationService {
         result
     }
 
-    fn split_long_paragraph(&self, paragraph: &str, max_length: usize) -> Vec<String> {
-        let mut chunks = Vec::new();
-        let mut start = 0;
-
-        while start < paragraph.len() {
-            let end = std::cmp::min(start + max_length, paragraph.len());
-            let mut actual_end = end;
-
-            if end < paragraph.len() {
-                for i in (start..end).rev() {
-                    let ch = paragraph.chars().nth(i).unwrap_or(' ');
-                    if ch == '.' || ch == '!' || ch == '?' || ch == '。' || ch == '！' || ch == '？' {
-                        actual_end = i + 1;
-                        break;
-                    }
-                }
+    /// 将超长段落按 `split_plain_text` 拆分，去掉块首尾的空白
+    fn split_long_paragraph(&self, paragraph: &str, max_length: usize, chunking: &ChunkingConfig) -> Vec<String> {
+        split_plain_text(paragraph, max_length, chunking)
+            .into_iter()
+            .map(str::trim)
+            .filter(|chunk| !chunk.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
 
-                if actual_end == end {
-                    for i in (start..end).rev() {
-                        let ch = paragraph.chars().nth(i).unwrap_or(' ');
-                        if ch == ' ' || ch == '\n' || ch == '\t' {
-                            actual_end = i + 1;
-                            break;
-                        }
-                    }
-                }
+    /// 段落是否只由 `chunking.atomic_kinds` 中的块组成，这样的段落超长时也不拆分
+    fn is_atomic(&self, paragraph: &str, chunking: &ChunkingConfig) -> bool {
+        let atomic_kinds = &chunking.atomic_kinds;
+        !atomic_kinds.is_empty()
+            && segment_markdown(paragraph)
+                .iter()
+                .all(|block| atomic_kinds.contains(&block.kind))
+    }
 
-                if actual_end == end && end - start < max_length / 2 {
-                    actual_end = end;
-                }
+    /// 翻译围栏代码块中的行注释，代码本身原样保留
+    ///
+    /// 块中的所有注释合并为一个请求，每条注释一行；译文的行数与注释数不一致时无法逐条对应，
+    /// 保留原文并记录警告。
+    async fn translate_code_comments(&self, block: &str, context: &CallContext) -> Result<String> {
+        let comments = code_comments(block);
+        if comments.is_empty() {
+            return Ok(block.to_string());
+        }
+        let joined = comments.iter().map(|range| &block[range.clone()]).collect::<Vec<_>>().join("\n");
+        let translated = self.translate_chunk(&joined, context).await?;
+        let lines: Vec<&str> = translated.lines().collect();
+        if lines.len() != comments.len() {
+            context.warn(format!(
+                "代码注释的译文有 {} 行，与 {} 条注释不一致，保留原文",
+                lines.len(),
+                comments.len()
+            ));
+            return Ok(block.to_string());
+        }
+
+        let mut result = String::with_capacity(block.len());
+        let mut last = 0;
+        for (range, line) in comments.iter().zip(lines) {
+            result.push_str(&block[last..range.start]);
+            result.push_str(line.trim());
+            last = range.end;
+        }
+        result.push_str(&block[last..]);
+        Ok(result)
+    }
+
+    pub(crate) async fn translate_chunk(&self, text: &str, context: &CallContext) -> Result<String> {
+        self.translate_chunk_with_alternatives(text, context)
+            .await
+            .map(|(translated, _)| translated)
+    }
+
+    /// 翻译已将标记替换为占位符的文本并还原占位符
+    ///
+    /// 供rst、AsciiDoc等格式保护行内标记使用，译文中丢失的占位符以 `format` 标注记录为警告。
+    pub(crate) async fn translate_protected(
+        &self,
+        protected: &str,
+        placeholders: &Placeholders,
+        format: &str,
+        context: &CallContext,
+    ) -> Result<String> {
+        let translated = self.translate_chunk(protected, context).await?;
+        context.record_protection(placeholders);
+        let restored = placeholders.restore(&translated);
+        for (_, markup) in &restored.missing {
+            context.warn(format!("{}行内标记的占位符在译文中丢失，{:?} 未出现在译文中", format, markup));
+        }
+        Ok(restored.text)
+    }
+
+    /// 翻译单个块，同时返回后端提供的备选译文
+    ///
+    /// 只有设置了 `num_alternatives` 时才会保留备选译文，否则返回空列表。
+    pub(crate) async fn translate_chunk_with_alternatives(
+        &self,
+        text: &str,
+        context: &CallContext,
+    ) -> Result<(String, Vec<String>)> {
+        // 先保护保留术语，术语表只作用于剩余文本
+        let mut placeholders = Placeholders::default();
+        let protected = context.keep_terms.protect(text, &mut placeholders);
+        let protected = context.components.glossary.protect(&protected, &mut placeholders);
+        let reply = self.translate_chunk_detecting(&protected, context).await?;
+        context.record_protection(&placeholders);
+        if placeholders.is_empty() {
+            return Ok((reply.text, reply.alternatives));
+        }
+
+        let alternatives = reply
+            .alternatives
+            .iter()
+            .map(|alternative| placeholders.restore(alternative).text)
+            .collect();
+        let restored = placeholders.restore(&reply.text);
+        let glossary_hits = restored.restored.iter().filter(|&&kind| kind == GLOSSARY_PLACEHOLDER).count();
+        context.stats.glossary_hits.fetch_add(glossary_hits, Ordering::Relaxed);
+        for (kind, term) in restored.missing {
+            if kind == KEEP_PLACEHOLDER {
+                context.warn(format!("保留术语占位符在译文中丢失，{:?} 未出现在译文中", term));
+            } else {
+                context.warn(format!("术语占位符在译文中丢失，术语 {:?} 未能写入译文", term));
+            }
+        }
+        Ok((restored.text, alternatives))
+    }
+
+    /// 翻译单个块，同时返回端点报告的源语言（如果响应中包含）
+    pub(crate) async fn translate_chunk_detecting(
+        &self,
+        text: &str,
+        context: &CallContext,
+    ) -> Result<Reply> {
+        tokio::select! {
+            _ = self.wait_until_resumed() => {}
+            _ = context.cancelled() => {
+                return Err(TranslationError::Cancelled { completed: 0, total: 1 });
+            }
+        }
+        let verbosity = context.components.config.verbosity;
+        verbosity.log(Verbosity::Debug, format_args!("翻译文本长度: {} 字符", text.len()));
+
+        let processed = apply_hooks(text, [&self.hooks.pre_process, &context.pre_process]);
+        let text = processed.as_str();
+        let components = &context.components;
+        let request = RequestScope {
+            custom_backend: self.hooks.backend.is_some(),
+            source_lang: &context.source_lang,
+            target_lang: &context.target_lang,
+            context: context.document_context.as_deref(),
+            num_alternatives: context.num_alternatives,
+            tag_handling: context.tag_handling,
+        };
+        let key = CacheKey::for_request(&components.config, &components.glossary, &request, text);
+        if let Some(warm) = &context.warm {
+            if !context.fresh {
+                let cache = components.cache.as_deref().filter(|_| context.cache_mode.reads());
+                warm.record(key, text, cache);
             }
+            if warm.dry_run {
+                return Ok(Reply::text(text.to_string()));
+            }
+        }
+        let reply = if context.fresh {
+            self.fetch_reply(text, key, context).await?
+        } else {
+            // 同一调用中内容相同的块只请求一次，其余出现处等待并复用该结果
+            let slot = context.stats.dedupe_slot(key);
+            slot.get_or_try_init(|| self.fetch_reply(text, key, context)).await?.clone()
+        };
+        Ok(self.post_process(reply, context))
+    }
 
-            let chunk = paragraph[start..actual_end].trim().to_string();
-            if !chunk.is_empty() {
-                chunks.push(chunk);
+    /// 获取一个块的后端响应：先查翻译缓存，未命中时经速率限制器发送请求并写入缓存
+    ///
+    /// `fresh` 调用和 `CacheMode::Refresh` 跳过缓存查找，但仍用新结果覆盖缓存条目；`CacheMode::Bypass` 不读也不写。
+    async fn fetch_reply(&self, text: &str, key: CacheKey, context: &CallContext) -> Result<Reply> {
+        let cache = context.components.cache.as_deref();
+        let lookup = cache.filter(|_| !context.fresh && context.cache_mode.reads());
+        if let Some(reply) = lookup.and_then(|cache| cache.get(key)) {
+            let verbosity = context.components.config.verbosity;
+            verbosity.log(Verbosity::Debug, format_args!("翻译缓存命中（{}）", context.request_id()));
+            context.stats.cache_hits.fetch_add(1, Ordering::Relaxed);
+            context.record_chunk_index(&context.stats.cached_chunks);
+            context.count(metrics::CACHE_HITS_TOTAL, &[], 1);
+            if let Some(lang) = &reply.detected_source_lang {
+                context.stats.detected_source_lang.get_or_init(|| lang.to_lowercase());
             }
+            return Ok(reply);
+        }
+        if let Some((reply, score)) = lookup.and_then(|cache| cache.get_fuzzy(key, text)) {
+            let verbosity = context.components.config.verbosity;
+            verbosity.log(
+                Verbosity::Debug,
+                format_args!("翻译缓存模糊匹配（{}），相似度 {:.3}", context.request_id(), score),
+            );
+            context.stats.fuzzy_hits.fetch_add(1, Ordering::Relaxed);
+            context.record_chunk_index(&context.stats.cached_chunks);
+            return Ok(reply);
+        }
+        if context.components.config.offline {
+            context.record_chunk_index(&context.stats.offline_misses);
+            return Err(TranslationError::OfflineCacheMiss);
+        }
+        let reply = retry_with_failover(
+            |attempt| {
+                let endpoint = context.components.endpoints.select(attempt + context.endpoint_offset);
+                (endpoint.rate_limiter.clone(), endpoint)
+            },
+            |endpoint: Arc<Endpoint>| async move {
+                let sent = text.chars().count();
+                context.record(|metrics| metrics.record_characters_sent(sent));
+                context.count(metrics::CHARACTERS_TOTAL, &[("direction", "sent")], sent as u64);
+                let started = Instant::now();
+                let result = match (&self.hooks.backend, endpoint.parsed_url()) {
+                    (Some(backend), _) => {
+                        let options = TranslateOptions::new()
+                            .source_lang(context.source_lang.clone())
+                            .target_lang(context.target_lang.clone());
+                        backend.translate_with(text, &options).await.map(Reply::text)
+                    }
+                    (None, Ok(url)) => self.request_translation(url, text, context).await,
+                    (None, Err(message)) => Err(TranslationError::ConfigError(format!("API地址无效: {}", message))),
+                };
+                let backend = match &self.hooks.backend {
+                    Some(_) => CUSTOM_BACKEND.to_string(),
+                    None => metrics_backend(&endpoint),
+                };
+                let elapsed = started.elapsed();
+                context.observe(metrics::REQUEST_DURATION_SECONDS, &[("backend", &backend)], elapsed);
+                let status = match &result {
+                    Ok(_) => "ok".to_string(),
+                    Err(TranslationError::ApiError { status, .. }) => status.to_string(),
+                    Err(TranslationError::Timeout { .. }) => "timeout".to_string(),
+                    Err(TranslationError::ConnectionFailed { .. }) => "connection_failed".to_string(),
+                    Err(_) => "error".to_string(),
+                };
+                context.count(metrics::REQUESTS_TOTAL, &[("backend", &backend), ("status", &status)], 1);
+                if let Some(audit) = &context.components.audit {
+                    audit.record(AuditEntry {
+                        document_id: &context.document_id,
+                        chunk: context.attempt(),
+                        source_lang: &context.source_lang,
+                        target_lang: &context.target_lang,
+                        backend: &backend,
+                        endpoint: &endpoint.url,
+                        status: &status,
+                        duration_ms: elapsed.as_millis(),
+                        source_text: text,
+                        translated_text: result.as_ref().ok().map(|reply| reply.text.as_str()),
+                    });
+                }
+                match &result {
+                    Ok(reply) => {
+                        endpoint.circuit_breaker.record_success();
+                        let reported = if self.hooks.backend.is_some() { CUSTOM_BACKEND } else { &endpoint.url };
+                        context.record_response(reported, reply.detected_source_lang.as_deref());
+                        let received = reply.text.chars().count();
+                        context.record(|metrics| metrics.record_characters_received(received));
+                        context.count(metrics::CHARACTERS_TOTAL, &[("direction", "received")], received as u64);
+                    }
+                    Err(_) => endpoint.circuit_breaker.record_failure(),
+                }
+                result
+            },
+            &self.retry_config,
+            Some(context),
+        )
+        .await?;
+        if let Some(cache) = cache.filter(|_| context.cache_mode.writes()) {
+            cache.insert(key, text, &reply);
+        }
+        Ok(reply)
+    }
 
-            start = actual_end;
+    /// 对后端响应的译文和备选译文应用翻译后钩子
+    fn post_process(&self, reply: Reply, context: &CallContext) -> Reply {
+        let post_process = [&context.post_process, &self.hooks.post_process];
+        Reply {
+            text: apply_hooks(&reply.text, post_process),
+            alternatives: reply
+                .alternatives
+                .iter()
+                .map(|alternative| apply_hooks(alternative, post_process))
+                .collect(),
+            detected_source_lang: reply.detected_source_lang,
         }
+    }
 
-        chunks
+    /// 配置了 `api_key` 时附加 `Authorization: Bearer` 请求头
+    fn authorize(&self, request: reqwest::RequestBuilder, context: &CallContext) -> reqwest::RequestBuilder {
+        match &context.components.config.api_key {
+            Some(key) => request.bearer_auth(key.expose()),
+            None => request,
+        }
     }
 
-    async fn translate_chunk(&self, text: &str) -> Result<String> {
-        println!("发送翻译请求到: {}", self.config.deeplx_api_url);
-        println!("翻译文本长度: {} 字符", text.len());
-
-        let retry_config = RetryConfig::default();
-        let client = &self.client;
-        let config = &self.config;
-        let text_clone = text.to_string();
-
-        let result = retry_with_backoff(
-            || {
-                let client = client.clone();
-                let config = config.clone();
-                let text = text_clone.clone();
-
-                Box::pin(async move {
-                    let response = if config.deeplx_api_url.contains("dptrans") {
-                        println!("使用dptrans API格式请求");
-
-                        let request = DpTransRequest {
-                            text: text.clone(),
-                            source_lang: if config.source_lang == "auto" { "auto".to_string() } else { config.source_lang.clone() },
-                            target_lang: config.target_lang.clone(),
-                        };
-
-                        client
-                            .post(&config.deeplx_api_url)
-                            .header("Content-Type", "application/json")
-                            .header("Accept", "application/json, text/plain, */*")
-                            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
-                            .json(&request)
-                            .send()
-                            .await
-                            .map_err(|e| {
-                                TranslationError::Custom(format!("DeepLX网络请求失败: {}", e))
-                            })?
-                    } else {
-                        println!("使用标准DeepLX API格式请求");
-
-                        let request = DeepLXRequest {
-                            text: text.clone(),
-                            source_lang: config.source_lang.clone(),
-                            target_lang: config.target_lang.clone(),
-                        };
-
-                        client
-                            .post(&config.deeplx_api_url)
-                            .header("Content-Type", "application/json")
-                            .header("Accept", "application/json")
-                            .json(&request)
-                            .send()
-                            .await
-                            .map_err(|e| {
-                                TranslationError::Custom(format!("DeepLX网络请求失败: {}", e))
-                            })?
-                    };
+    /// 向指定端点发送一次翻译请求
+    ///
+    /// 返回译文以及端点检测到的源语言；只有标准DeepLX响应会携带源语言。
+    async fn request_translation(
+        &self,
+        url: &reqwest::Url,
+        text: &str,
+        context: &CallContext,
+    ) -> Result<Reply> {
+        let verbosity = context.components.config.verbosity;
+        verbosity.log(Verbosity::Debug, format_args!("发送翻译请求到: {}（{}）", url, context.request_id()));
 
-                    let status = response.status();
-                    println!("DeepLX响应状态: {}", status);
-
-                    if response.status().is_success() {
-                        let response_text = response
-                            .text()
-                            .await
-                            .map_err(|e| TranslationError::Custom(format!("读取响应文本失败: {}", e)))?;
-
-                        if let Ok(result) = serde_json::from_str::<DeepLXResponse>(&response_text) {
-                            if result.code == 200 {
-                                if result.data.is_empty() {
-                                    Err(TranslationError::Custom("DeepLX返回了空的翻译结果".to_string()))
-                                } else {
-                                    Ok(result.data)
-                                }
-                            } else {
-                                Err(TranslationError::ApiError {
-                                    code: result.code,
-                                    message: format!("DeepLX翻译失败，返回代码: {}", result.code)
-                                })
-                            }
+        let backend = if url.as_str().contains("dptrans") { "dptrans" } else { "DeepLX" };
+        let (headers, body) = if backend == "dptrans" {
+            verbosity.log(Verbosity::Debug, format_args!("使用dptrans API格式请求"));
+
+            let request = DpTransRequest {
+                text: text.to_string(),
+                source_lang: context.source_lang.clone(),
+                target_lang: context.target_lang.clone(),
+            };
+
+            let headers = request_headers(
+                context,
+                &[
+                    (CONTENT_TYPE, "application/json"),
+                    (ACCEPT, "application/json, text/plain, */*"),
+                    (USER_AGENT, DPTRANS_USER_AGENT),
+                ],
+            );
+            let body = serde_json::to_value(&request)?;
+            (headers, body)
+        } else {
+            verbosity.log(Verbosity::Debug, format_args!("使用标准DeepLX API格式请求"));
+
+            let request = DeepLXRequest {
+                text: text.to_string(),
+                source_lang: context.source_lang.clone(),
+                target_lang: context.target_lang.clone(),
+                glossary_id: context.components.config.glossary_id.clone(),
+                context: context.document_context.clone(),
+                num_alternatives: context.num_alternatives,
+                tag_handling: context.tag_handling.map(str::to_string),
+            };
+            if request.context.is_some() {
+                context.stats.context_sent.store(true, Ordering::Relaxed);
+            }
+
+            let headers = request_headers(
+                context,
+                &[(CONTENT_TYPE, "application/json"), (ACCEPT, "application/json")],
+            );
+            let body = serde_json::to_value(&request)?;
+            (headers, body)
+        };
+
+        let dump = context.components.dump.as_deref().map(|dump| {
+            let stem = dump.stem(&context.document_id, context.attempt());
+            dump.request(&stem, url, &headers, &body);
+            (dump, stem)
+        });
+        let response = self
+            .authorize(context.components.client.post(url.clone()), context)
+            .headers(headers)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| {
+                if let Some((dump, stem)) = &dump {
+                    dump.failure(stem, &e.to_string());
+                }
+                Self::map_http_error(e, &context.components.config)
+            })?;
+
+        let status = response.status();
+        verbosity.log(Verbosity::Debug, format_args!("DeepLX响应状态: {}", status));
+        let response_headers = response.headers().clone();
+        let record_response = |body: &str| {
+            if let Some((dump, stem)) = &dump {
+                dump.response(stem, status, &response_headers, body);
+            }
+        };
+
+        if response.status().is_success() {
+            let response_text = response
+                .text()
+                .await
+                .map_err(|e| Self::map_http_error(e, &context.components.config))?;
+            record_response(&response_text);
+
+            if let Ok(result) = serde_json::from_str::<DeepLXResponse>(&response_text) {
+                if result.code == 200 {
+                    if result.data.is_empty() {
+                        Err(TranslationError::Custom("DeepLX返回了空的翻译结果".to_string()))
+                    } else {
+                        let mut alternatives = result.alternatives;
+                        alternatives.truncate(context.num_alternatives.unwrap_or(0));
+                        Ok(Reply {
+                            text: result.data,
+                            detected_source_lang: result.source_lang,
+                            alternatives,
+                        })
+                    }
+                } else if matches!(result.code, 401 | 403) {
+                    Err(TranslationError::AuthError {
+                        status: result.code as u16,
+                        message: format!("DeepLX翻译失败，返回代码: {}", result.code),
+                    })
+                } else {
+                    // 响应体中的错误代码不是合法的状态码时按网关错误处理
+                    let code = u16::try_from(result.code).unwrap_or(StatusCode::BAD_GATEWAY.as_u16());
+                    Err(api_error(code, &response_headers, &response_text, &context.components.config))
+                }
+            } else {
+                let parse_error = |expected: &str| TranslationError::ResponseParseError {
+                    backend: backend.to_string(),
+                    expected: expected.to_string(),
+                    content_type: response_headers
+                        .get(CONTENT_TYPE)
+                        .and_then(|value| value.to_str().ok())
+                        .map(str::to_string),
+                    body_excerpt: redact_to(
+                        &response_text,
+                        context.components.config.log_content,
+                        context.components.config.error_body_max_chars,
+                    ),
+                };
+                if response_text.trim().is_empty() {
+                    Err(TranslationError::Custom("API返回了空的翻译结果".to_string()))
+                } else if response_text.starts_with("{") {
+                    if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(&response_text) {
+                        if let Some(translated) = json_value
+                            .get("translated_text")
+                            .or_else(|| json_value.get("result"))
+                            .or_else(|| json_value.get("translation"))
+                            .or_else(|| json_value.get("data"))
+                            .and_then(|v| v.as_str())
+                        {
+                            Ok(Reply::text(translated.to_string()))
                         } else {
-                            if response_text.trim().is_empty() {
-                                Err(TranslationError::Custom("API返回了空的翻译结果".to_string()))
-                            } else if response_text.starts_with("{") {
-                                if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(&response_text) {
-                                    if let Some(translated) = json_value
-                                        .get("translated_text")
-                                        .or_else(|| json_value.get("result"))
-                                        .or_else(|| json_value.get("translation"))
-                                        .or_else(|| json_value.get("data"))
-                                        .and_then(|v| v.as_str())
-                                    {
-                                        Ok(translated.to_string())
-                                    } else {
-                                        Err(TranslationError::ParseError(format!(
-                                            "无法从JSON响应中提取翻译结果: {}",
-                                            response_text
-                                        )))
-                                    }
-                                } else {
-                                    Err(TranslationError::ParseError(format!("无法解析JSON响应: {}", response_text)))
-                                }
-                            } else {
-                                println!("假设响应是纯文本翻译结果");
-                                Ok(response_text)
-                            }
+                            Err(parse_error("JSON object with translated_text, result, translation or data"))
                         }
                     } else {
-                        let error_text = response
-                            .text()
-                            .await
-                            .unwrap_or_else(|_| "无法读取错误信息".to_string());
-                        Err(TranslationError::ApiError {
-                            code: status.as_u16() as i32,
-                            message: format!("DeepLX API请求失败: {} - {}", status, error_text)
-                        })
+                        Err(parse_error("JSON"))
                     }
-                })
-            },
-            &retry_config,
-            &self.rate_limiter,
-        )
-        .await?;
+                } else if is_markup(&response_text, &response_headers, context.tag_handling.is_some()) {
+                    // HTML错误页（代理、网关或登录页）不是译文，不能写入文档
+                    Err(parse_error("plain text translation, got HTML"))
+                } else {
+                    verbosity.log(Verbosity::Debug, format_args!("假设响应是纯文本翻译结果"));
+                    Ok(Reply::text(response_text))
+                }
+            }
+        } else {
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "无法读取错误信息".to_string());
+            record_response(&error_text);
+            if matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+                return Err(TranslationError::AuthError {
+                    status: status.as_u16(),
+                    message: format!(
+                        "DeepLX API请求失败: {} - {}",
+                        status,
+                        redact(&error_text, context.components.config.log_content)
+                    ),
+                });
+            }
+            Err(api_error(status.as_u16(), &response_headers, &error_text, &context.components.config))
+        }
+    }
 
-        Ok(result)
+    /// 将 `reqwest` 错误转换为翻译错误，连接失败和超时单独归类，其余保留原始错误
+    ///
+    /// 连接阶段的超时（`connect_timeout_secs`）归为连接失败，其余超时为请求超时。
+    fn map_http_error(error: reqwest::Error, config: &TranslationConfig) -> TranslationError {
+        if error.is_connect() {
+            let mut message = error.to_string();
+            let mut source = std::error::Error::source(&error);
+            while let Some(cause) = source {
+                message.push_str(": ");
+                message.push_str(&cause.to_string());
+                source = cause.source();
+            }
+            TranslationError::ConnectionFailed { message, source: error }
+        } else if error.is_timeout() {
+            TranslationError::Timeout {
+                elapsed: Duration::from_secs(config.request_timeout_secs),
+            }
+        } else {
+            TranslationError::Http(error)
+        }
     }
 
     /// 检测chunk是否为代码块
@@ -2,7 +2,16 @@
 //! 
 //! 定义翻译库中使用的所有数据结构和配置类型。
 
+use crate::error::{ConfigError, Result, TranslationError};
+use crate::language::check_language;
+use crate::metrics::MetricsSnapshot;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 
 /// 翻译配置
 /// 
@@ -14,39 +23,776 @@ use serde::{Deserialize, Serialize};
 /// * `source_lang` - 源语言代码，"auto"表示自动检测
 /// * `target_lang` - 目标语言代码
 /// * `deeplx_api_url` - DeepLX API地址
-/// * `max_requests_per_second` - 每秒最大请求数
+/// * `max_requests_per_second` - 每秒最大请求数（令牌补充速率）
+/// * `burst_size` - 允许立即发出的最大突发请求数（令牌桶容量）
 /// * `max_text_length` - 单次翻译的最大文本长度
 /// * `max_paragraphs_per_request` - 单次请求的最大段落数
+/// * `fallback_api_urls` - 备用API地址，按顺序故障转移
+/// * `global_max_requests_per_second` - 所有端点合计的每秒最大请求数
+/// * `circuit_breaker_threshold` - 触发端点熔断的连续失败次数
+/// * `circuit_breaker_cooldown_secs` - 端点熔断的冷却时间（秒）
+/// * `overall_timeout_secs` - 单次 `translate()` 调用的整体时限（秒）
+/// * `on_error` - 翻译失败时的处理策略
+/// * `request_timeout_secs` - 单个HTTP请求的超时时间（秒）
+/// * `connect_timeout_secs` - 建立连接的超时时间（秒）
+/// * `max_total_retries` - 单次 `translate()` 调用内所有块共享的重试预算
+/// * `chunk_timeout_secs` - 单个块（含重试）的超时时间（秒）
+/// * `on_chunk_timeout` - 单个块超时时的处理策略
+/// * `pack_batch_items` - 批量翻译时是否将多个短条目合并为一个请求
+/// * `max_file_size_bytes` - `translate_file` 允许读取的最大文件大小（字节）
+/// * `skip_if_already_target` - 文档已是目标语言时跳过翻译
+/// * `glossary` - 术语表，源术语到目标术语的映射
+/// * `keep_terms` - 必须原样保留的术语
+/// * `format` - 输入文本的格式（`markdown`、`plain`、`rst` 或 `asciidoc`），非Markdown格式不做Markdown处理
+/// * `translate_kinds` - 需要翻译的Markdown块类型，未设置时按分块结果翻译全部非代码内容
+/// * `quality_checks` - 是否对译文运行内置质量检查，不合格的块会重新翻译一次
+/// * `quality_min_length_ratio` / `quality_max_length_ratio` - 译文与原文字符数之比的合理范围
+/// * `quality_retry_split` - 重新翻译时是否将块拆成两半分别翻译
+/// * `html_tag_handling` - 翻译API是否原生支持HTML（`tag_handling=html`），支持时 `translate_html` 将文档整体交给后端
+/// * `subtitle_max_line_length` - 字幕译文每行的最大字符数，超过时在词边界处重新折行，0表示不折行
+/// * `target_langs_key` - 前置元数据中声明文件目标语言的键，设置后 `translate_file` 为每种语言各输出一个文件
+/// * `target_lang_file_pattern` - 按文件目标语言输出时的文件名模式，支持 `{stem}`、`{lang}` 和 `{ext}`
+/// * `glossary_file` - 术语表文件路径（TOML或CSV）
+/// * `glossary_case_sensitive` - 术语匹配是否区分大小写
+/// * `glossary_whole_word` - 术语是否按整词匹配
+/// * `glossary_id` - 后端原生术语表ID，设置后不做本地术语替换
+/// * `cache` - 翻译缓存配置（`[translation.cache]`），未设置时不使用缓存
+/// * `offline` - 离线模式，只使用翻译缓存中的译文，不发送任何请求
+/// * `chunking` - 分块行为（`[translation.chunking]`）：长度单位、断句字符、不拆分的块类型和未闭合代码围栏的处理
+/// * `http` - HTTP连接池设置（`[translation.http]`）：每个主机的空闲连接数、空闲超时、TCP keepalive和HTTP/2
+/// * `user_agent` - 所有请求的 `User-Agent`，未设置时使用内置的默认值
+/// * `default_headers` - 随每个请求发送的附加请求头
+/// * `request_id_header` - 携带块ID的请求头名称，默认 `X-Request-Id`，为空时不发送
+/// * `verbosity` - 写入标准错误输出的日志详细程度（`quiet`、`warn`、`info` 或 `debug`），默认不输出
+/// * `log_content` - 日志和错误信息是否包含文档内容和响应体（截断），默认只记录长度和哈希值
+/// * `error_body_max_chars` - 启用 `log_content` 时 `ApiError` 中保留的响应体字符数，默认200
+/// * `debug_dump_dir` - 请求转储目录，设置后每次API请求的原始请求和响应写入该目录，目录必须已存在且可写
+/// * `debug_dump_max_bytes` - 请求转储的总大小上限，默认100 MiB
+/// * `audit_log_path` - 审计日志文件，设置后每次API请求追加一行JSON记录，用于合规留存
+/// * `audit_include_text` - 审计日志是否记录原文和译文，默认只记录哈希值
+/// * `api_key` - 翻译API的密钥，只能通过代码或 `TRANSLATION_API_KEY` 环境变量设置
+///
+/// # 部分配置
+///
+/// 每个字段都有与 [`Default`] 一致的默认值，配置文件只需列出需要修改的字段，
+/// 旧版本写入的、缺少新字段的配置文件也能直接加载。
+///
+/// ```rust
+/// use markdown_translator::{TranslationConfig, TranslationLibConfig};
+///
+/// // 空的 [translation] 表与默认配置完全一致
+/// let empty = TranslationLibConfig::parse("[translation]\n", true).unwrap();
+/// assert_eq!(
+///     serde_json::to_value(&empty.translation).unwrap(),
+///     serde_json::to_value(TranslationConfig::default()).unwrap()
+/// );
+///
+/// // 早期版本只包含最初的几个字段
+/// let old = r#"
+/// [translation]
+/// enabled = true
+/// source_lang = "en"
+/// target_lang = "ja"
+/// deeplx_api_url = "http://localhost:1188/translate"
+/// max_requests_per_second = 2.0
+/// max_text_length = 5000
+/// max_paragraphs_per_request = 20
+/// "#;
+/// let config = TranslationLibConfig::parse(old, true).unwrap().translation;
+/// assert!(config.enabled);
+/// assert_eq!(config.target_lang, "ja");
+/// assert_eq!(config.max_text_length, 5000);
+/// assert_eq!(config.circuit_breaker_threshold, TranslationConfig::default().circuit_breaker_threshold);
+/// assert!(config.glossary.is_empty());
+/// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranslationConfig {
     /// 是否启用翻译功能
+    #[serde(default)]
     pub enabled: bool,
     /// 源语言代码，"auto"表示自动检测
+    #[serde(default = "default_source_lang")]
     pub source_lang: String,
     /// 目标语言代码
+    #[serde(default = "default_target_lang")]
     pub target_lang: String,
     /// DeepLX API地址
+    #[serde(default = "default_deeplx_api_url")]
     pub deeplx_api_url: String,
-    /// 每秒最大请求数
+    /// 每秒最大请求数，即令牌桶的补充速率，决定持续请求频率
+    #[serde(default = "default_max_requests_per_second")]
     pub max_requests_per_second: f64,
+    /// 令牌桶容量，即空闲后可以立即发出的最大请求数；
+    /// `None` 时为 `ceil(max_requests_per_second * 2)`。
+    /// 例如 `max_requests_per_second = 1.0`、`burst_size = 10` 表示前10个请求立即发出，之后每秒1个
+    #[serde(default)]
+    pub burst_size: Option<usize>,
     /// 单次翻译的最大文本长度
+    #[serde(default = "default_max_text_length")]
     pub max_text_length: usize,
     /// 单次请求的最大段落数
+    #[serde(default = "default_max_paragraphs_per_request")]
     pub max_paragraphs_per_request: usize,
+    /// 备用API地址，主地址失败时按顺序故障转移，每个端点独立限速
+    #[serde(default)]
+    pub fallback_api_urls: Vec<String>,
+    /// 所有端点合计的每秒最大请求数，`None` 表示不设全局上限
+    ///
+    /// 全局上限的突发容量为 `ceil(global_max_requests_per_second * 2)`，不受单个端点的 `burst_size` 影响。
+    #[serde(default)]
+    pub global_max_requests_per_second: Option<f64>,
+    /// 触发端点熔断的连续失败次数，0表示禁用熔断
+    #[serde(default = "default_circuit_breaker_threshold")]
+    pub circuit_breaker_threshold: u32,
+    /// 端点熔断的冷却时间（秒）
+    #[serde(default = "default_circuit_breaker_cooldown_secs")]
+    pub circuit_breaker_cooldown_secs: u64,
+    /// 单次 `translate()` 调用的整体时限（秒），`None` 表示不限制
+    #[serde(default)]
+    pub overall_timeout_secs: Option<u64>,
+    /// 翻译失败（含整体超时）时的处理策略
+    ///
+    /// `keep_original` 时重试耗尽的块保留原文；还没有任何块成功时端点就无法连接或拒绝凭据，
+    /// 则不再逐块重试，整篇文档保留原文。两种情况都返回 `Ok`，经过记录在报告的 `warnings` 中。
+    #[serde(default)]
+    pub on_error: FailurePolicy,
+    /// 单个HTTP请求（含读取响应）的超时时间（秒）
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    /// 建立连接的超时时间（秒），`None` 表示只受请求超时约束
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+    /// 单次 `translate()` 调用内所有块共享的重试次数上限，`None` 表示不限制
+    #[serde(default)]
+    pub max_total_retries: Option<usize>,
+    /// 单个块（含其所有重试）的超时时间（秒），`None` 表示不限制
+    #[serde(default)]
+    pub chunk_timeout_secs: Option<u64>,
+    /// 单个块超时时的处理策略，默认保留该块原文并在报告中记录警告
+    #[serde(default = "default_on_chunk_timeout")]
+    pub on_chunk_timeout: FailurePolicy,
+    /// `translate_batch` 是否将相邻的短条目合并为一个请求（每个请求最多
+    /// `max_paragraphs_per_request` 条、总长不超过 `max_text_length`）
+    #[serde(default)]
+    pub pack_batch_items: bool,
+    /// `translate_file` 允许读取的最大文件大小（字节），超过时拒绝翻译
+    #[serde(default = "default_max_file_size_bytes")]
+    pub max_file_size_bytes: u64,
+    /// 翻译前先检测文档语言，已是目标语言时原样返回（检测需要额外一次请求）
+    #[serde(default)]
+    pub skip_if_already_target: bool,
+    /// 术语表，源术语会在请求前替换为占位符，译文中再替换为目标术语
+    #[serde(default)]
+    pub glossary: HashMap<String, String>,
+    /// 术语表文件路径；`.csv` 文件每行为 `源术语,目标术语`，其他文件按TOML键值对解析。
+    /// 与 `glossary` 合并，`glossary` 中的条目优先
+    #[serde(default)]
+    pub glossary_file: Option<String>,
+    /// 术语匹配是否区分大小写
+    #[serde(default = "default_true")]
+    pub glossary_case_sensitive: bool,
+    /// 术语是否按整词匹配，避免 "node" 匹配 "nodes"
+    #[serde(default = "default_true")]
+    pub glossary_whole_word: bool,
+    /// 后端原生术语表ID（例如DeepL的 `glossary_id`），设置后随请求发送，不做本地术语替换
+    #[serde(default)]
+    pub glossary_id: Option<String>,
+    /// 必须原样保留的术语（品牌名、命令行参数、缩写等），按整词匹配；
+    /// 以 `re:` 开头的条目按正则表达式匹配。行内代码和URL中的匹配会被跳过
+    #[serde(default)]
+    pub keep_terms: Vec<String>,
+    /// 输入文本的格式，默认为 `markdown`；`plain` 时关闭代码块识别、范围和块类型过滤，
+    /// 只在句子边界处按长度分块，原文中的空白原样保留；`rst` 和 `asciidoc` 时按相应格式的结构翻译
+    #[serde(default)]
+    pub format: TextFormat,
+    /// 需要翻译的Markdown块类型，未列出的类型原样保留；
+    /// 未设置时沿用分块结果，翻译除代码块外的全部内容
+    #[serde(default)]
+    pub translate_kinds: Option<Vec<SegmentKind>>,
+    /// 是否对译文运行内置质量检查（空译文、未翻译、长度比例、目标文字、保留术语完整性），
+    /// 不合格的块会重新翻译一次，仍不合格时按失败处理策略处理
+    #[serde(default)]
+    pub quality_checks: bool,
+    /// 译文与原文字符数之比的下限，低于该值视为可疑
+    #[serde(default = "default_quality_min_length_ratio")]
+    pub quality_min_length_ratio: f64,
+    /// 译文与原文字符数之比的上限，超过该值视为可疑
+    #[serde(default = "default_quality_max_length_ratio")]
+    pub quality_max_length_ratio: f64,
+    /// 重新翻译时将块在最接近中点的空行处拆成两半分别翻译
+    #[serde(default)]
+    pub quality_retry_split: bool,
+    /// 翻译API原生支持HTML标签处理（例如DeepL的 `tag_handling=html`）。
+    /// 启用后 `translate_html` 将不超过 `max_text_length` 的文档整体交给后端，dptrans格式的端点不支持该参数
+    #[serde(default)]
+    pub html_tag_handling: bool,
+    /// 字幕译文每行的最大字符数，超过时在词边界处重新折行，0表示不折行
+    #[serde(default = "default_subtitle_max_line_length")]
+    pub subtitle_max_line_length: usize,
+    /// 前置元数据中声明文件目标语言的键，例如 `translate_to`（值为 `[ja, ko]` 或单个语言）。
+    /// 文件声明了该键时 `translate_file` 和 `translate_dir` 为每种语言各输出一个文件，未声明时使用 `target_lang`
+    #[serde(default)]
+    pub target_langs_key: Option<String>,
+    /// 按文件目标语言输出时的文件名模式，`{stem}`、`{lang}` 和 `{ext}` 分别替换为输出文件名的主干、语言和扩展名
+    #[serde(default = "default_target_lang_file_pattern")]
+    pub target_lang_file_pattern: String,
+    /// 翻译缓存配置，对应配置文件中的 `[translation.cache]` 表，未设置时不使用缓存
+    ///
+    /// 缓存按 [`CacheKey`](crate::CacheKey)（后端、语言、术语表和发送的块文本）查找，在获取速率限制令牌之前进行，
+    /// 命中的块不发送请求、不消耗速率限制。缓存由服务的所有克隆共享，
+    /// 条目数超过 `max_entries` 时淘汰最久未使用的条目；命中次数记录在 `TranslationReport::cache_hits` 中。
+    #[serde(default)]
+    pub cache: Option<CacheConfig>,
+    /// 离线模式：只使用翻译缓存中的译文（包括模糊匹配），不建立任何网络连接
+    ///
+    /// 未命中的块返回 `TranslationError::OfflineCacheMiss`，按 `on_error` 处理：`keep_original` 时保留原文，
+    /// `fail` 时整个调用失败。命中和未命中的块序号分别记录在 `TranslationReport::cached_chunks` 和
+    /// `TranslationReport::offline_misses` 中。需要启用 `[translation.cache]`，可以先用 `import_tmx` 或 `warm_cache` 填充。
+    ///
+    /// `memory` 后端的缓存只在当前服务（及其克隆）中有效，重建服务或重启进程后离线模式不会命中任何块；
+    /// 需要先联网预热、之后在另一个进程中离线翻译时使用 `disk` 后端（[`CacheBackend::Disk`]）。
+    #[serde(default)]
+    pub offline: bool,
+    /// 分块行为，对应配置文件中的 `[translation.chunking]` 表，默认值与未配置时的分块方式一致
+    #[serde(default)]
+    pub chunking: ChunkingConfig,
+    /// HTTP连接池设置，对应配置文件中的 `[translation.http]` 表
+    #[serde(default)]
+    pub http: HttpConfig,
+    /// 所有请求的 `User-Agent`；`None` 时使用内置的默认值
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// 随每个请求发送的附加请求头，优先于内置的 `Content-Type`、`Accept` 和 `User-Agent`
+    #[serde(default)]
+    pub default_headers: HashMap<String, String>,
+    /// 携带块ID（`<文档ID>-<块序号>`）的请求头名称，用于在后端日志中关联具体的块；为空时不发送
+    #[serde(default = "default_request_id_header")]
+    pub request_id_header: String,
+    /// 日志详细程度，日志只写入标准错误输出；默认不输出任何内容
+    #[serde(default)]
+    pub verbosity: Verbosity,
+    /// 是否允许日志和错误信息包含文档内容和响应体（截断到200个字符），只应在本地调试时开启；
+    /// 默认只记录长度、哈希值和状态码
+    #[serde(default)]
+    pub log_content: bool,
+    /// 启用 `log_content` 时 `ApiError::body_excerpt` 保留的响应体字符数，超出部分截断
+    #[serde(default = "default_error_body_max_chars")]
+    pub error_body_max_chars: usize,
+    /// 请求转储目录；设置后每次API请求写出请求（去掉凭据）和原始响应，用于向上游报告问题。
+    /// 目录必须已存在且可写，否则校验失败、转储不启用。转储包含文档内容
+    #[serde(default)]
+    pub debug_dump_dir: Option<String>,
+    /// 请求转储的总大小上限（字节），达到后不再写入
+    #[serde(default = "default_debug_dump_max_bytes")]
+    pub debug_dump_max_bytes: u64,
+    /// 审计日志文件，`None` 表示不记录
+    ///
+    /// 每次发往翻译后端的请求（包括重试）追加一行JSON：时间戳（UTC）、文档ID、块序号和尝试次数、
+    /// 发送内容的哈希、源语言和目标语言、后端、端点地址、结果和耗时。文件以追加模式打开，
+    /// 由后台线程写入，不会阻塞翻译；[`shutdown`](crate::TranslationService::shutdown) 会等待记录写入磁盘。
+    /// 所在目录必须已存在且可写。
+    #[serde(default)]
+    pub audit_log_path: Option<String>,
+    /// 审计日志是否记录发送的原文和返回的译文，默认 `false`，只记录内容的哈希值
+    #[serde(default)]
+    pub audit_include_text: bool,
+    /// 翻译API的密钥，以 `Authorization: Bearer` 请求头发送
+    ///
+    /// 只能通过代码或 `TRANSLATION_API_KEY` 环境变量设置，不会从配置文件读取，也不会写入配置文件或调试输出。
+    #[serde(skip)]
+    pub api_key: Option<ApiKey>,
+}
+
+/// 翻译API的密钥
+///
+/// `Debug` 和 `Display` 只输出 `***`，避免密钥出现在日志中。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::ApiKey;
+///
+/// let key = ApiKey::new("secret-token");
+/// assert_eq!(key.expose(), "secret-token");
+/// assert_eq!(format!("{:?}", key), "ApiKey(***)");
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    /// 由密钥字符串创建
+    pub fn new(key: impl Into<String>) -> Self {
+        Self(key.into())
+    }
+
+    /// 取出密钥原文，只应在构造请求时使用
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ApiKey(***)")
+    }
+}
+
+impl fmt::Display for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+/// Markdown块类型
+///
+/// 由 [`segment_markdown`](crate::segment_markdown) 识别，也用于 `translate_kinds` 过滤需要翻译的块。
+/// 在配置文件中使用 snake_case 名称，`list_item` 和 `table_cell` 分别是 `list` 和 `table` 的别名。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentKind {
+    /// 普通段落（包括分隔线）
+    Paragraph,
+    /// ATX或Setext标题
+    Heading,
+    /// 有序或无序列表，包括列表项中的续行
+    #[serde(alias = "list_item")]
+    List,
+    /// 表格
+    #[serde(alias = "table_cell")]
+    Table,
+    /// 引用块
+    Blockquote,
+    /// 围栏或缩进代码块
+    Code,
+    /// HTML块
+    Html,
+    /// 文档开头的YAML前置元数据
+    Frontmatter,
+}
+
+impl SegmentKind {
+    /// 未设置 `translate_kinds` 时翻译的块类型：除代码、HTML和前置元数据外的全部类型
+    pub const DEFAULT_TRANSLATABLE: &'static [SegmentKind] = &[
+        SegmentKind::Paragraph,
+        SegmentKind::Heading,
+        SegmentKind::List,
+        SegmentKind::Table,
+        SegmentKind::Blockquote,
+    ];
+}
+
+/// Markdown块
+///
+/// 所有块的 `content` 按顺序拼接后与原文完全一致，块之间的空行归属于前一个块。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MarkdownBlock {
+    /// 块类型
+    pub kind: SegmentKind,
+    /// 块原文，包括结尾的换行和空行
+    pub content: String,
+}
+
+/// 翻译失败处理策略
+///
+/// 决定某个块在重试耗尽或整体超时后如何处理。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailurePolicy {
+    /// 返回错误，整个调用失败
+    #[default]
+    Fail,
+    /// 保留原文，调用继续并返回部分翻译结果
+    KeepOriginal,
+}
+
+fn default_source_lang() -> String {
+    "auto".to_string()
+}
+
+fn default_target_lang() -> String {
+    "zh".to_string()
+}
+
+fn default_deeplx_api_url() -> String {
+    "http://localhost:1188/translate".to_string()
+}
+
+fn default_max_requests_per_second() -> f64 {
+    0.5
+}
+
+fn default_max_text_length() -> usize {
+    3000
+}
+
+fn default_max_paragraphs_per_request() -> usize {
+    10
+}
+
+fn default_circuit_breaker_threshold() -> u32 {
+    5
+}
+
+fn default_circuit_breaker_cooldown_secs() -> u64 {
+    30
+}
+
+fn default_request_timeout_secs() -> u64 {
+    30
+}
+
+fn default_on_chunk_timeout() -> FailurePolicy {
+    FailurePolicy::KeepOriginal
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_quality_min_length_ratio() -> f64 {
+    0.1
+}
+
+fn default_quality_max_length_ratio() -> f64 {
+    4.0
+}
+
+fn default_subtitle_max_line_length() -> usize {
+    42
+}
+
+fn default_target_lang_file_pattern() -> String {
+    "{stem}.{lang}.{ext}".to_string()
+}
+
+fn default_request_id_header() -> String {
+    "X-Request-Id".to_string()
+}
+
+fn default_error_body_max_chars() -> usize {
+    200
+}
+
+fn default_debug_dump_max_bytes() -> u64 {
+    100 * 1024 * 1024
+}
+
+fn default_max_file_size_bytes() -> u64 {
+    10 * 1024 * 1024
 }
 
 impl Default for TranslationConfig {
     fn default() -> Self {
         Self {
             enabled: false,
-            source_lang: "auto".to_string(),
-            target_lang: "zh".to_string(),
-            deeplx_api_url: "http://localhost:1188/translate".to_string(),
-            max_requests_per_second: 0.5,
-            max_text_length: 3000,
-            max_paragraphs_per_request: 10,
+            source_lang: default_source_lang(),
+            target_lang: default_target_lang(),
+            deeplx_api_url: default_deeplx_api_url(),
+            max_requests_per_second: default_max_requests_per_second(),
+            burst_size: None,
+            max_text_length: default_max_text_length(),
+            max_paragraphs_per_request: default_max_paragraphs_per_request(),
+            fallback_api_urls: Vec::new(),
+            global_max_requests_per_second: None,
+            circuit_breaker_threshold: default_circuit_breaker_threshold(),
+            circuit_breaker_cooldown_secs: default_circuit_breaker_cooldown_secs(),
+            overall_timeout_secs: None,
+            on_error: FailurePolicy::default(),
+            request_timeout_secs: default_request_timeout_secs(),
+            connect_timeout_secs: None,
+            max_total_retries: None,
+            chunk_timeout_secs: None,
+            on_chunk_timeout: default_on_chunk_timeout(),
+            pack_batch_items: false,
+            max_file_size_bytes: default_max_file_size_bytes(),
+            skip_if_already_target: false,
+            glossary: HashMap::new(),
+            glossary_file: None,
+            glossary_case_sensitive: true,
+            glossary_whole_word: true,
+            glossary_id: None,
+            keep_terms: Vec::new(),
+            format: TextFormat::default(),
+            translate_kinds: None,
+            quality_checks: false,
+            quality_min_length_ratio: default_quality_min_length_ratio(),
+            quality_max_length_ratio: default_quality_max_length_ratio(),
+            quality_retry_split: false,
+            html_tag_handling: false,
+            subtitle_max_line_length: default_subtitle_max_line_length(),
+            target_langs_key: None,
+            target_lang_file_pattern: default_target_lang_file_pattern(),
+            cache: None,
+            offline: false,
+            chunking: ChunkingConfig::default(),
+            http: HttpConfig::default(),
+            user_agent: None,
+            default_headers: HashMap::new(),
+            request_id_header: default_request_id_header(),
+            verbosity: Verbosity::default(),
+            log_content: false,
+            error_body_max_chars: default_error_body_max_chars(),
+            debug_dump_dir: None,
+            debug_dump_max_bytes: default_debug_dump_max_bytes(),
+            audit_log_path: None,
+            audit_include_text: false,
+            api_key: None,
+        }
+    }
+}
+
+impl TranslationConfig {
+    /// 校验配置，一次返回所有问题
+    ///
+    /// 检查请求频率、长度和超时等数值的范围，API地址（去除首尾空白后）能否解析且为 `http`/`https`，
+    /// 语言代码不为空且是已知代码（见 [`supported_languages`](crate::supported_languages)），质量检查的比例区间和文件名模式是否合理。
+    /// `TranslationService::try_new`、构建器和 `update_config` 使用同样的校验。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{ChunkingConfig, TranslationConfig};
+    ///
+    /// assert!(TranslationConfig::default().validate().is_ok());
+    ///
+    /// let config = TranslationConfig {
+    ///     max_requests_per_second: 0.0,
+    ///     deeplx_api_url: "localhost:1188".to_string(),
+    ///     target_lang: String::new(),
+    ///     ..Default::default()
+    /// };
+    /// let errors = config.validate().unwrap_err();
+    /// let fields: Vec<_> = errors.iter().map(|error| error.field.as_str()).collect();
+    /// assert_eq!(fields, ["target_lang", "deeplx_api_url", "max_requests_per_second"]);
+    ///
+    /// // 分块的重叠长度和最小块长度不能超出块长度上限
+    /// let config = TranslationConfig {
+    ///     max_text_length: 100,
+    ///     chunking: ChunkingConfig { overlap: 100, min_chunk_length: 101, ..Default::default() },
+    ///     ..Default::default()
+    /// };
+    /// let errors = config.validate().unwrap_err();
+    /// let fields: Vec<_> = errors.iter().map(|error| error.field.as_str()).collect();
+    /// assert_eq!(fields, ["chunking.overlap", "chunking.min_chunk_length"]);
+    /// assert!(errors[0].message.contains("max_text_length"));
+    /// ```
+    pub fn validate(&self) -> std::result::Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, field: &str, message: String| {
+            if !ok {
+                errors.push(ConfigError::new(field, message));
+            }
+        };
+
+        check(!self.source_lang.trim().is_empty(), "source_lang", "不能为空，自动检测请使用 \"auto\"".to_string());
+        check(!self.target_lang.trim().is_empty(), "target_lang", "不能为空".to_string());
+        for (field, lang, allow_auto) in [("source_lang", &self.source_lang, true), ("target_lang", &self.target_lang, false)] {
+            if let (false, Err(error)) = (lang.trim().is_empty(), check_language(lang, allow_auto)) {
+                check(false, field, error.to_string());
+            }
+        }
+        if let Err(message) = parse_api_url(&self.deeplx_api_url) {
+            check(false, "deeplx_api_url", message);
+        }
+        for (index, url) in self.fallback_api_urls.iter().enumerate() {
+            if let Err(message) = parse_api_url(url) {
+                check(false, &format!("fallback_api_urls[{}]", index), message);
+            }
+        }
+
+        let positive = |value: f64| value.is_finite() && value > 0.0;
+        check(
+            positive(self.max_requests_per_second),
+            "max_requests_per_second",
+            format!("必须是大于0的有限数，当前为 {}", self.max_requests_per_second),
+        );
+        if let Some(rate) = self.global_max_requests_per_second {
+            check(
+                positive(rate),
+                "global_max_requests_per_second",
+                format!("必须是大于0的有限数，当前为 {}", rate),
+            );
+        }
+        check(self.burst_size != Some(0), "burst_size", "必须大于0，不设置时按请求频率计算".to_string());
+        check(self.max_text_length > 0, "max_text_length", "必须大于0".to_string());
+        check(self.request_timeout_secs > 0, "request_timeout_secs", "必须大于0".to_string());
+        for (field, value) in [
+            ("connect_timeout_secs", self.connect_timeout_secs),
+            ("overall_timeout_secs", self.overall_timeout_secs),
+            ("chunk_timeout_secs", self.chunk_timeout_secs),
+        ] {
+            check(value != Some(0), field, "必须大于0，不限时请不设置".to_string());
+        }
+        check(self.max_file_size_bytes > 0, "max_file_size_bytes", "必须大于0".to_string());
+
+        let ratio = |value: f64| value.is_finite() && value >= 0.0;
+        check(
+            ratio(self.quality_min_length_ratio),
+            "quality_min_length_ratio",
+            format!("必须是不小于0的有限数，当前为 {}", self.quality_min_length_ratio),
+        );
+        check(
+            ratio(self.quality_max_length_ratio) && self.quality_max_length_ratio >= self.quality_min_length_ratio,
+            "quality_max_length_ratio",
+            format!(
+                "必须是不小于 quality_min_length_ratio（{}）的有限数，当前为 {}",
+                self.quality_min_length_ratio, self.quality_max_length_ratio
+            ),
+        );
+        check(
+            self.target_lang_file_pattern.contains("{lang}"),
+            "target_lang_file_pattern",
+            format!("必须包含 {{lang}}，否则各语言写入同一个文件，当前为 {:?}", self.target_lang_file_pattern),
+        );
+        if let Some(user_agent) = &self.user_agent {
+            check(
+                reqwest::header::HeaderValue::from_str(user_agent).is_ok(),
+                "user_agent",
+                format!("不是有效的请求头值: {:?}", user_agent),
+            );
+        }
+        for (name, value) in &self.default_headers {
+            let field = format!("default_headers.{}", name);
+            check(
+                reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_ok(),
+                &field,
+                format!("不是有效的请求头名称: {:?}", name),
+            );
+            check(
+                reqwest::header::HeaderValue::from_str(value).is_ok(),
+                &field,
+                format!("不是有效的请求头值: {:?}", value),
+            );
+        }
+        check(
+            self.request_id_header.is_empty()
+                || reqwest::header::HeaderName::from_bytes(self.request_id_header.as_bytes()).is_ok(),
+            "request_id_header",
+            format!("不是有效的请求头名称: {:?}", self.request_id_header),
+        );
+        if let Some(dir) = &self.debug_dump_dir {
+            if let Err(message) = crate::dump::check_writable(std::path::Path::new(dir)) {
+                errors.push(ConfigError::new("debug_dump_dir", message));
+            }
+        }
+        if let Some(path) = &self.audit_log_path {
+            let path = std::path::Path::new(path);
+            let dir = match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => std::path::Path::new("."),
+            };
+            if path.file_name().is_none() || path.is_dir() {
+                errors.push(ConfigError::new("audit_log_path", format!("不是文件路径: {}", path.display())));
+            } else if let Err(message) = crate::dump::check_writable(dir) {
+                errors.push(ConfigError::new("audit_log_path", message));
+            }
+        }
+        if self.offline && !self.cache.as_ref().is_some_and(|cache| cache.enabled) {
+            errors.push(ConfigError::new("offline", "离线模式需要启用翻译缓存（[translation.cache]）"));
+        }
+        if let Some(Err(cache_errors)) = self.cache.as_ref().map(CacheConfig::validate) {
+            errors.extend(cache_errors);
+        }
+        if let Err(chunking_errors) = self.chunking.validate() {
+            errors.extend(chunking_errors);
+        }
+        if self.max_text_length > 0 {
+            if self.chunking.overlap >= self.max_text_length {
+                errors.push(ConfigError::new(
+                    "chunking.overlap",
+                    format!("必须小于 max_text_length（{}），当前为 {}", self.max_text_length, self.chunking.overlap),
+                ));
+            }
+            if self.chunking.min_chunk_length > self.max_text_length {
+                errors.push(ConfigError::new(
+                    "chunking.min_chunk_length",
+                    format!(
+                        "不能大于 max_text_length（{}），当前为 {}",
+                        self.max_text_length, self.chunking.min_chunk_length
+                    ),
+                ));
+            }
+        }
+        if let Err(http_errors) = self.http.validate() {
+            errors.extend(http_errors);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
         }
     }
+
+    /// 返回不影响使用但可能是配置错误的问题
+    ///
+    /// 目前检查API地址的路径是否以 `translate` 结尾（dptrans格式的端点除外），
+    /// 例如 `http://localhost:1188` 缺少 `/translate` 时请求会被后端拒绝；
+    /// 以及 [`ChunkingConfig::warnings`] 报告的问题。
+    /// 无法解析的地址由 [`validate`](Self::validate) 报告，这里不重复。
+    /// 构建服务时，`verbosity` 不低于 `warn` 则在标准错误输出中打印这些警告。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::TranslationConfig;
+    ///
+    /// let config = TranslationConfig {
+    ///     deeplx_api_url: " http://localhost:1188 ".to_string(),
+    ///     fallback_api_urls: vec!["https://backup.example.com/v2/translate".to_string()],
+    ///     ..Default::default()
+    /// };
+    /// // 首尾空白会被去除，地址本身有效
+    /// assert!(config.validate().is_ok());
+    ///
+    /// let warnings = config.warnings();
+    /// assert_eq!(warnings.len(), 1);
+    /// assert_eq!(warnings[0].field, "deeplx_api_url");
+    /// assert!(TranslationConfig::default().warnings().is_empty());
+    /// ```
+    pub fn warnings(&self) -> Vec<ConfigError> {
+        let urls = std::iter::once(("deeplx_api_url".to_string(), &self.deeplx_api_url)).chain(
+            self.fallback_api_urls
+                .iter()
+                .enumerate()
+                .map(|(index, url)| (format!("fallback_api_urls[{}]", index), url)),
+        );
+        let mut warnings: Vec<ConfigError> = urls
+            .filter_map(|(field, url)| {
+                let parsed = parse_api_url(url).ok()?;
+                (!is_known_endpoint(&parsed)).then(|| {
+                    ConfigError::new(
+                        field,
+                        format!("地址 {:?} 的路径不以 /translate 结尾，DeepLX的地址通常形如 http://localhost:1188/translate", parsed.as_str()),
+                    )
+                })
+            })
+            .collect();
+        warnings.extend(self.chunking.warnings());
+        warnings
+    }
+}
+
+/// 去除首尾空白后解析API地址，只接受 `http` 和 `https`
+pub(crate) fn parse_api_url(url: &str) -> std::result::Result<reqwest::Url, String> {
+    let url = url.trim();
+    if url.is_empty() {
+        return Err("不能为空".to_string());
+    }
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("无法解析地址 {:?}: {}", url, e))?;
+    match parsed.scheme() {
+        "http" | "https" => Ok(parsed),
+        scheme => Err(format!("地址 {:?} 的协议 {:?} 不受支持，只支持 http 和 https", url, scheme)),
+    }
+}
+
+/// 地址的路径是否指向已知的翻译接口：以 `translate` 结尾，或是dptrans格式的端点
+fn is_known_endpoint(url: &reqwest::Url) -> bool {
+    url.as_str().contains("dptrans")
+        || url
+            .path_segments()
+            .and_then(|mut segments| segments.rfind(|segment| !segment.is_empty()))
+            == Some("translate")
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,11 +814,1546 @@ impl Default for RetryConfig {
     }
 }
 
+impl RetryConfig {
+    /// 校验重试参数，一次返回所有问题
+    ///
+    /// 退避倍数必须是不小于1的有限数，初始延迟不能大于最大延迟。
+    pub fn validate(&self) -> std::result::Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+        if !(self.backoff_multiplier.is_finite() && self.backoff_multiplier >= 1.0) {
+            errors.push(ConfigError::new(
+                "retry.backoff_multiplier",
+                format!("不能小于1，当前为 {}", self.backoff_multiplier),
+            ));
+        }
+        if self.initial_delay_ms > self.max_delay_ms {
+            errors.push(ConfigError::new(
+                "retry.initial_delay_ms",
+                format!("不能大于 max_delay_ms（{}），当前为 {}", self.max_delay_ms, self.initial_delay_ms),
+            ));
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// 翻译缓存的存储后端
+///
+/// `disk` 后端的条目在重建服务（或重启进程）后仍然可用。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheBackend {
+    /// 进程内存，服务销毁后清空
+    #[default]
+    Memory,
+    /// `path` 目录下的条目文件，重建服务或重启进程后仍然可用
+    ///
+    /// 条目同时保存在内存中，每次写入追加一行到 `entries-v2.jsonl`（文件名中的数字为 [`CacheKey::VERSION`](crate::CacheKey::VERSION)），
+    /// 创建服务时读取该文件恢复未过期的条目。淘汰和过期的条目在 `compact_cache` 时从文件中删除。
+    /// 同一目录同时只应由一个服务写入。
+    Disk,
+}
+
+/// 翻译缓存配置
+///
+/// 对应配置文件中的 `[translation.cache]` 表。`TranslationConfig::cache` 为 `None`（配置文件中没有该表）时不使用缓存；
+/// 有该表时 `enabled` 默认为 `true`，其余未列出的项不设上限。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{CacheBackend, TranslationLibConfig};
+///
+/// let config = TranslationLibConfig::parse(r#"
+///     [translation]
+///     target_lang = "ja"
+///
+///     [translation.cache]
+///     backend = "disk"
+///     path = "target/translation-cache"
+///     max_size_mb = 256
+///     ttl_hours = 720
+/// "#, true).unwrap().translation;
+///
+/// let cache = config.cache.as_ref().unwrap();
+/// assert!(cache.enabled);
+/// assert_eq!(cache.backend, CacheBackend::Disk);
+/// assert_eq!(cache.max_entries, None);
+/// assert!(config.validate().is_ok());
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// 是否启用缓存
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 存储后端，`memory` 或 `disk`
+    #[serde(default)]
+    pub backend: CacheBackend,
+    /// 磁盘缓存的目录，`disk` 后端必须设置，创建服务时不存在则自动创建
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    /// 最多缓存的条目数，`None` 表示不限制
+    #[serde(default)]
+    pub max_entries: Option<usize>,
+    /// 条目的原文和译文最多占用的空间（MB），`None` 表示不限制；写入后超过时淘汰最久未使用的条目
+    #[serde(default)]
+    pub max_size_mb: Option<u64>,
+    /// 条目的有效期（小时），`None` 表示永不过期；过期的条目在查找时视为未命中并删除
+    #[serde(default)]
+    pub ttl_hours: Option<u64>,
+    /// 模糊匹配的相似度阈值（0到1），`None` 表示只复用原文完全相同的条目
+    ///
+    /// 精确匹配未命中时，在后端、语言、术语表和请求选项相同的条目中查找最相似的原文，
+    /// 相似度（1 减去字符级编辑距离与较长文本长度之比）不低于阈值时直接复用其译文，
+    /// 复用次数记录在 `TranslationReport::fuzzy_hits` 中，低于阈值时照常发送请求。
+    /// 目前的后端只接受原文，相似条目不能作为参考译文随请求发送，因此阈值不宜设得过低。
+    #[serde(default)]
+    pub fuzzy_threshold: Option<f64>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            backend: CacheBackend::default(),
+            path: None,
+            max_entries: None,
+            max_size_mb: None,
+            ttl_hours: None,
+            fuzzy_threshold: None,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// 校验缓存配置，一次返回所有问题，字段名带 `cache.` 前缀
+    ///
+    /// 检查 `disk` 后端设置了目录且目录（或其最近的已存在的上级目录）可写，以及各上限不为0。
+    pub fn validate(&self) -> std::result::Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+        if self.backend == CacheBackend::Disk {
+            match &self.path {
+                None => errors.push(ConfigError::new("cache.path", "disk 后端必须指定缓存目录")),
+                Some(path) => {
+                    if let Err(message) = check_writable_dir(path) {
+                        errors.push(ConfigError::new("cache.path", message));
+                    }
+                }
+            }
+        }
+        for (field, value) in [
+            ("cache.max_entries", self.max_entries.map(|value| value as u64)),
+            ("cache.max_size_mb", self.max_size_mb),
+            ("cache.ttl_hours", self.ttl_hours),
+        ] {
+            if value == Some(0) {
+                errors.push(ConfigError::new(field, "必须大于0，不限制请不设置"));
+            }
+        }
+        if let Some(threshold) = self.fuzzy_threshold {
+            if !(threshold > 0.0 && threshold <= 1.0) {
+                errors.push(ConfigError::new("cache.fuzzy_threshold", "必须大于0且不超过1"));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// 检查目录可写；目录不存在时检查最近的已存在的上级目录
+fn check_writable_dir(path: &Path) -> std::result::Result<(), String> {
+    let Some(existing) = path.ancestors().find(|dir| dir.exists()) else {
+        // 相对路径的上级目录最终为当前目录
+        return Ok(());
+    };
+    if !existing.is_dir() {
+        return Err(format!("{} 不是目录", existing.display()));
+    }
+    match std::fs::metadata(existing) {
+        Ok(metadata) if metadata.permissions().readonly() => Err(format!("目录 {} 不可写", existing.display())),
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("无法访问目录 {}: {}", existing.display(), e)),
+    }
+}
+
+/// 日志详细程度
+///
+/// 翻译服务和配置加载的日志只写入标准错误输出，不会混入调用方写到标准输出的译文。
+/// 默认为 `Quiet`，适合作为库使用；每个级别包含其之前级别的日志。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{TranslationLibConfig, Verbosity};
+///
+/// let config = TranslationLibConfig::parse("[translation]\nverbosity = \"info\"\n", true).unwrap();
+/// assert_eq!(config.translation.verbosity, Verbosity::Info);
+/// assert!(Verbosity::Info > Verbosity::Warn);
+/// assert_eq!(Verbosity::default(), Verbosity::Quiet);
+/// ```
+///
+/// 错误信息默认不包含后端返回的响应体，只包含其长度和哈希值；`log_content` 为 `true` 时保留前200个字符。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verbosity {
+    /// 不输出任何日志
+    #[default]
+    Quiet,
+    /// 只输出警告：无效配置、被忽略的配置文件、重试
+    Warn,
+    /// 另外输出每次调用的分块情况和每个块的开始与完成
+    Info,
+    /// 另外输出每个请求的地址、格式和响应状态
+    Debug,
+}
+
+impl Verbosity {
+    /// 详细程度不低于 `level` 时向标准错误输出写入一行日志
+    pub(crate) fn log(self, level: Verbosity, message: fmt::Arguments<'_>) {
+        if level != Verbosity::Quiet && self >= level {
+            eprintln!("{}", message);
+        }
+    }
+}
+
+/// 分块时计算长度的单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LengthUnit {
+    /// UTF-8字节数，与API按字节计算的请求大小一致
+    #[default]
+    Bytes,
+    /// 字符数，中日韩文本的块不会因每个字符占3个字节而过小
+    Chars,
+}
+
+impl LengthUnit {
+    /// 按该单位计算文本长度
+    pub fn measure(self, text: &str) -> usize {
+        match self {
+            LengthUnit::Bytes => text.len(),
+            LengthUnit::Chars => text.chars().count(),
+        }
+    }
+}
+
+/// 没有结束围栏的代码块的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnclosedFence {
+    /// 从开始围栏到文档末尾都视为代码，不翻译
+    #[default]
+    CodeToEnd,
+    /// 忽略该围栏，按普通文本翻译
+    Text,
+}
+
+/// Markdown文本的分块策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChunkStrategy {
+    /// 把相邻段落合并到不超过 `max_text_length` 的块中，请求数最少；不超过上限的文档整体发送
+    #[default]
+    Pack,
+    /// 每个段落单独成块（超长时仍按句拆分），修改文档后未改动的段落更容易命中翻译缓存
+    Paragraph,
+}
+
+/// 分块配置
+///
+/// 对应配置文件中的 `[translation.chunking]` 表，控制长Markdown和纯文本如何拆分为不超过 `max_text_length` 的块。
+/// 所有项的默认值与未配置时的分块方式一致。rst和AsciiDoc按块结构分块，只使用 `max_text_length`。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{ChunkStrategy, LengthUnit, TranslationLibConfig};
+///
+/// let config = TranslationLibConfig::parse(r#"
+///     [translation.chunking]
+///     length_unit = "chars"
+///     strategy = "paragraph"
+///     min_chunk_length = 20
+/// "#, true).unwrap();
+/// let chunking = &config.translation.chunking;
+/// assert_eq!((chunking.length_unit, chunking.strategy), (LengthUnit::Chars, ChunkStrategy::Paragraph));
+/// ```
+///
+/// 分块效果可以通过 [`estimate`](crate::TranslationService::estimate) 观察：
+///
+/// ```rust
+/// use markdown_translator::{ChunkingConfig, LengthUnit, TranslationConfig, TranslationService};
+///
+/// let service = |chunking: ChunkingConfig| {
+///     TranslationService::new(TranslationConfig { enabled: true, max_text_length: 12, chunking, ..Default::default() })
+/// };
+///
+/// // 12个字符的中文句子占36个字节，按字符计算时不需要拆分
+/// let text = "第一句话。第二句话完了。";
+/// assert!(service(ChunkingConfig::default()).estimate(text).chunks > 1);
+/// let chars = ChunkingConfig { length_unit: LengthUnit::Chars, ..Default::default() };
+/// assert_eq!(service(chars).estimate(text).chunks, 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkingConfig {
+    /// 计算块长度（与 `max_text_length` 比较）的单位
+    #[serde(default)]
+    pub length_unit: LengthUnit,
+    /// 优先在其后断开的句末字符。ASCII字符只在其后是空白或文本末尾时断开（避免拆开 `3.14`），
+    /// 其他字符（如 `。`）总是可以断开；换行总是可以断开，不需要列出
+    #[serde(default = "default_sentence_breaks")]
+    pub sentence_breaks: Vec<char>,
+    /// 超过长度上限时也不拆分的Markdown块类型，例如 `["table", "list"]`；这些块整体作为一个块发送，可能超过上限
+    #[serde(default)]
+    pub atomic_kinds: Vec<SegmentKind>,
+    /// 没有结束围栏的代码块的处理方式
+    #[serde(default)]
+    pub unclosed_fence: UnclosedFence,
+    /// 注释需要翻译的围栏代码块语言，按信息字符串的第一个词匹配，不区分大小写，
+    /// 同一语言的别名（如 `sh`、`bash`、`shell`）视为相同；默认为空，即不翻译代码注释。
+    ///
+    /// 分块后匹配的代码块中，行注释的正文（`#`、`//` 或 `--` 之后的文本，按语言确定）合并为一个请求翻译，
+    /// 代码、注释符号和缩进原样保留。译文行数与注释数不一致时保留原文并记录警告。
+    /// 未知语言和没有行注释的语言不翻译；不需要分块的短文本整体发送，不单独处理代码块。
+    #[serde(default)]
+    pub code_comment_languages: Vec<String>,
+    /// Markdown文本的分块策略，默认合并相邻段落；其他格式按各自的结构分块，不使用该项
+    #[serde(default)]
+    pub strategy: ChunkStrategy,
+    /// 随每个块的请求发送前一个块末尾多少个长度单位的原文作为上下文，0表示不发送
+    ///
+    /// 上下文只帮助后端理解跨块的指代，不出现在译文中，也不参与缓存键；与 `TranslateOptions::context`
+    /// 同时使用时两者以空行连接。只有标准DeepLX请求携带上下文，必须小于 `max_text_length`。
+    #[serde(default)]
+    pub overlap: usize,
+    /// 短于该长度的块与下一个块合并（合并后不超过 `max_text_length` 时），0表示不合并；
+    /// 例如在 `paragraph` 策略下让标题与其后的段落一起发送。不能大于 `max_text_length`
+    #[serde(default)]
+    pub min_chunk_length: usize,
+}
+
+/// 已知的代码块语言及其别名，每组第一个为规范名称
+const CODE_LANGUAGES: &[&[&str]] = &[
+    &["bash", "sh", "shell", "zsh", "console", "shell-session"],
+    &["python", "py", "python3", "py3"],
+    &["javascript", "js", "jsx", "mjs", "cjs", "node"],
+    &["typescript", "ts", "tsx"],
+    &["rust", "rs"],
+    &["go", "golang"],
+    &["c", "h"],
+    &["cpp", "c++", "cc", "cxx", "hpp"],
+    &["csharp", "cs", "c#"],
+    &["java"],
+    &["kotlin", "kt", "kts"],
+    &["swift"],
+    &["ruby", "rb"],
+    &["php"],
+    &["perl", "pl"],
+    &["lua"],
+    &["r"],
+    &["sql", "mysql", "postgresql", "psql"],
+    &["powershell", "ps1", "pwsh"],
+    &["dockerfile", "docker"],
+    &["makefile", "make"],
+    &["yaml", "yml"],
+    &["toml"],
+    &["ini", "cfg"],
+    &["css", "scss", "less"],
+    &["html", "xml", "svg"],
+    &["json", "jsonc", "json5"],
+    &["diff", "patch"],
+    &["markdown", "md"],
+    &["text", "txt", "plain", "plaintext"],
+];
+
+/// 代码块语言的规范名称，未知语言返回 `None`
+pub(crate) fn canonical_language(name: &str) -> Option<&'static str> {
+    let name = name.trim().to_ascii_lowercase();
+    CODE_LANGUAGES
+        .iter()
+        .find(|aliases| aliases.contains(&name.as_str()))
+        .map(|aliases| aliases[0])
+}
+
+fn default_sentence_breaks() -> Vec<char> {
+    vec!['.', '!', '?', '。', '！', '？']
+}
+
+impl Default for ChunkingConfig {
+    fn default() -> Self {
+        Self {
+            length_unit: LengthUnit::default(),
+            sentence_breaks: default_sentence_breaks(),
+            atomic_kinds: Vec::new(),
+            unclosed_fence: UnclosedFence::default(),
+            code_comment_languages: Vec::new(),
+            strategy: ChunkStrategy::default(),
+            overlap: 0,
+            min_chunk_length: 0,
+        }
+    }
+}
+
+impl ChunkingConfig {
+    /// 围栏代码块的注释是否需要翻译
+    ///
+    /// `info` 为开始围栏后的信息字符串（如 `python title="demo.py"`），取第一个词与
+    /// `code_comment_languages` 比较，不区分大小写，已知语言的别名视为相同。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::ChunkingConfig;
+    ///
+    /// let chunking = ChunkingConfig {
+    ///     code_comment_languages: vec!["python".to_string(), "Bash".to_string()],
+    ///     ..Default::default()
+    /// };
+    /// assert!(chunking.translates_comments("py"));
+    /// assert!(chunking.translates_comments("Python title=\"demo.py\""));
+    /// assert!(chunking.translates_comments("sh"));
+    /// assert!(chunking.translates_comments("shell"));
+    /// assert!(!chunking.translates_comments("diff"));
+    /// assert!(!chunking.translates_comments(""));
+    /// assert!(!ChunkingConfig::default().translates_comments("python"));
+    ///
+    /// // 未知语言只产生警告
+    /// let unknown = ChunkingConfig {
+    ///     code_comment_languages: vec!["pythn".to_string()],
+    ///     ..Default::default()
+    /// };
+    /// assert!(unknown.validate().is_ok());
+    /// assert_eq!(unknown.warnings()[0].field, "chunking.code_comment_languages[0]");
+    /// ```
+    pub fn translates_comments(&self, info: &str) -> bool {
+        let Some(language) = info.split(|c: char| c.is_whitespace() || c == '{' || c == ',').next() else {
+            return false;
+        };
+        if language.is_empty() {
+            return false;
+        }
+        let language = language.to_ascii_lowercase();
+        let canonical = canonical_language(&language);
+        self.code_comment_languages.iter().any(|configured| {
+            let configured = configured.trim().to_ascii_lowercase();
+            configured == language || (canonical.is_some() && canonical_language(&configured) == canonical)
+        })
+    }
+
+    /// 返回不影响使用但可能是配置错误的问题，字段名带 `chunking.` 前缀
+    ///
+    /// 目前报告 `code_comment_languages` 中的未知语言：它们只能按原样匹配信息字符串，不会匹配别名。
+    pub fn warnings(&self) -> Vec<ConfigError> {
+        self.code_comment_languages
+            .iter()
+            .enumerate()
+            .filter(|(_, language)| canonical_language(language).is_none())
+            .map(|(index, language)| {
+                ConfigError::new(
+                    format!("chunking.code_comment_languages[{}]", index),
+                    format!("未知的语言 {:?}，只匹配信息字符串完全相同的代码块", language),
+                )
+            })
+            .collect()
+    }
+
+    /// 校验分块配置，一次返回所有问题，字段名带 `chunking.` 前缀
+    pub fn validate(&self) -> std::result::Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+        for (index, c) in self.sentence_breaks.iter().enumerate() {
+            if c.is_whitespace() {
+                errors.push(ConfigError::new(
+                    format!("chunking.sentence_breaks[{}]", index),
+                    "不能是空白字符，换行总是可以断开，其他空白作为次选断点",
+                ));
+            }
+        }
+        for (index, kind) in self.atomic_kinds.iter().enumerate() {
+            if matches!(kind, SegmentKind::Code | SegmentKind::Html | SegmentKind::Frontmatter) {
+                errors.push(ConfigError::new(
+                    format!("chunking.atomic_kinds[{}]", index),
+                    format!("{:?} 块不翻译，本来就不会被拆分", kind),
+                ));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// HTTP连接池设置
+///
+/// 对应配置文件中的 `[translation.http]` 表，用于构建服务内部的HTTP客户端；
+/// 通过 [`TranslationServiceBuilder::client`](crate::TranslationServiceBuilder::client) 提供客户端时不使用。
+/// 自托管的高吞吐后端可以增大空闲连接数；无服务器后端会较快关闭空闲连接，
+/// 可以缩短空闲超时，避免每轮突发的第一个请求使用已失效的连接。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{HttpConfig, TranslationConfig, TranslationLibConfig, TranslationService};
+///
+/// let config = TranslationLibConfig::parse(r#"
+/// [translation.http]
+/// pool_max_idle_per_host = 32
+/// pool_idle_timeout_secs = 5
+/// tcp_keepalive_secs = 0
+/// http2 = false
+/// "#, true).unwrap();
+/// let http = &config.translation.http;
+/// assert_eq!(http.pool_max_idle_per_host, 32);
+/// assert_eq!(http.tcp_keepalive_secs, 0);
+/// assert!(TranslationService::try_new(config.translation).is_ok());
+///
+/// // 未配置时与之前内置的设置一致
+/// assert_eq!(HttpConfig::default().pool_max_idle_per_host, 5);
+/// assert_eq!(HttpConfig::default().pool_idle_timeout_secs, 30);
+///
+/// let invalid = TranslationConfig {
+///     http: HttpConfig { pool_idle_timeout_secs: 0, ..Default::default() },
+///     ..Default::default()
+/// };
+/// let errors = invalid.validate().unwrap_err();
+/// assert_eq!(errors[0].field, "http.pool_idle_timeout_secs");
+/// assert!(TranslationService::try_new(invalid).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpConfig {
+    /// 每个主机保留的最大空闲连接数，0表示不复用连接
+    #[serde(default = "default_pool_max_idle_per_host")]
+    pub pool_max_idle_per_host: usize,
+    /// 空闲连接保留的时间（秒），超过后关闭
+    #[serde(default = "default_pool_idle_timeout_secs")]
+    pub pool_idle_timeout_secs: u64,
+    /// TCP keepalive探测间隔（秒），0表示不启用
+    #[serde(default = "default_tcp_keepalive_secs")]
+    pub tcp_keepalive_secs: u64,
+    /// 是否允许与支持的服务器协商HTTP/2，`false` 时只使用HTTP/1.1
+    #[serde(default = "default_true")]
+    pub http2: bool,
+}
+
+fn default_pool_max_idle_per_host() -> usize {
+    5
+}
+
+fn default_pool_idle_timeout_secs() -> u64 {
+    30
+}
+
+fn default_tcp_keepalive_secs() -> u64 {
+    60
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            pool_max_idle_per_host: default_pool_max_idle_per_host(),
+            pool_idle_timeout_secs: default_pool_idle_timeout_secs(),
+            tcp_keepalive_secs: default_tcp_keepalive_secs(),
+            http2: true,
+        }
+    }
+}
+
+impl HttpConfig {
+    /// 校验连接池设置，一次返回所有问题，字段名带 `http.` 前缀
+    pub fn validate(&self) -> std::result::Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+        if self.pool_idle_timeout_secs == 0 {
+            errors.push(ConfigError::new(
+                "http.pool_idle_timeout_secs",
+                "必须大于0，不复用连接请将 pool_max_idle_per_host 设为0",
+            ));
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// 单次翻译调用的选项
+///
+/// 传给 `TranslationService::translate_with`，只影响本次调用。
+/// 未设置的选项使用服务的 `TranslationConfig` 中对应的值。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{CancellationToken, FailurePolicy, TranslateOptions};
+/// use std::time::Duration;
+///
+/// let token = CancellationToken::new();
+/// let options = TranslateOptions::new()
+///     .cancellation_token(token.clone())
+///     .target_lang("ja")
+///     .overall_timeout(Duration::from_secs(60))
+///     .on_error(FailurePolicy::KeepOriginal);
+/// assert_eq!(options.target_lang.as_deref(), Some("ja"));
+/// assert_eq!(options.overall_timeout, Some(Duration::from_secs(60)));
+/// assert_eq!(options.on_error, Some(FailurePolicy::KeepOriginal));
+///
+/// // 选项持有令牌的克隆，在其他任务中调用 token.cancel() 即可中止翻译
+/// token.cancel();
+/// assert!(options.cancellation_token.as_ref().is_some_and(|token| token.is_cancelled()));
+/// ```
+///
+/// 选项优先于服务配置：
+///
+/// ```rust
+/// use markdown_translator::{FailurePolicy, RetryConfig, TranslateOptions, TranslationService};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     // 指向一个不可达的端点，配置为失败即返回错误
+///     let service = TranslationService::builder()
+///         .enabled(true)
+///         .api_url("http://127.0.0.1:9/translate")
+///         .retry(RetryConfig { max_retries: 0, ..Default::default() })
+///         .configure(|config| config.on_error = FailurePolicy::Fail)
+///         .build()
+///         .unwrap();
+///
+///     assert!(service.translate("Hello").await.is_err());
+///
+///     let options = TranslateOptions::new().on_error(FailurePolicy::KeepOriginal);
+///     assert_eq!(service.translate_with("Hello", &options).await.unwrap(), "Hello");
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TranslateOptions {
+    /// 取消令牌，取消后停止派发新块、中止进行中的任务并返回 `Cancelled`
+    pub cancellation_token: Option<CancellationToken>,
+    /// 覆盖配置中的源语言
+    pub source_lang: Option<String>,
+    /// 覆盖配置中的目标语言
+    pub target_lang: Option<String>,
+    /// 覆盖配置中的 `overall_timeout_secs`
+    pub overall_timeout: Option<Duration>,
+    /// 覆盖配置中的 `chunk_timeout_secs`
+    pub chunk_timeout: Option<Duration>,
+    /// 覆盖配置中的 `on_error`
+    pub on_error: Option<FailurePolicy>,
+    /// 覆盖配置中的 `on_chunk_timeout`
+    pub on_chunk_timeout: Option<FailurePolicy>,
+    /// 覆盖配置中的 `max_total_retries`
+    pub max_total_retries: Option<usize>,
+    /// 进度回调，未设置时不产生任何开销
+    pub on_progress: Option<ProgressCallback>,
+    /// 输出模式，默认只输出译文
+    pub output_mode: OutputMode,
+    /// 覆盖配置中的 `keep_terms`
+    pub keep_terms: Option<Vec<String>>,
+    /// 随每个块请求发送的文档上下文或领域提示
+    pub context: Option<String>,
+    /// 翻译范围，默认翻译全部内容
+    pub scope: TranslationScope,
+    /// 覆盖配置中的 `format`
+    pub format: Option<TextFormat>,
+    /// 覆盖配置中的 `translate_kinds`
+    pub translate_kinds: Option<Vec<SegmentKind>>,
+    /// 每个段最多保留的备选译文数量，未设置时不保留备选译文
+    pub num_alternatives: Option<usize>,
+    /// 本次调用的质量检查钩子，在服务级钩子之后运行
+    pub quality_check: Option<QualityCheck>,
+    /// 本次调用的翻译前钩子，在服务级钩子之后运行
+    pub pre_process: Option<TextHook>,
+    /// 本次调用的翻译后钩子，在服务级钩子之前运行
+    pub post_process: Option<TextHook>,
+    /// 断点续译的检查点文件，未设置时不读写检查点
+    pub checkpoint_path: Option<PathBuf>,
+    /// 文档ID，`None` 时自动生成
+    pub document_id: Option<String>,
+    /// 是否在报告中包含每个块的耗时明细
+    pub detailed_timings: bool,
+    /// 本次调用如何使用翻译缓存，默认读写缓存
+    pub cache_mode: CacheMode,
+}
+
+impl TranslateOptions {
+    /// 创建默认选项
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置取消令牌
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// 设置本次调用的源语言
+    pub fn source_lang(mut self, lang: impl Into<String>) -> Self {
+        self.source_lang = Some(lang.into());
+        self
+    }
+
+    /// 设置本次调用的目标语言
+    ///
+    /// 不是已知的语言代码时调用直接返回 `TranslationError::InvalidLanguage`，不发送请求：
+    ///
+    /// ```rust
+    /// use markdown_translator::{TranslateOptions, TranslationError, TranslationServiceBuilder};
+    ///
+    /// # tokio_test::block_on(async {
+    /// let service = TranslationServiceBuilder::new().enabled(true).build().unwrap();
+    /// let error = service
+    ///     .translate_with("Hello", &TranslateOptions::new().target_lang("jp"))
+    ///     .await
+    ///     .unwrap_err();
+    /// assert!(matches!(&error, TranslationError::InvalidLanguage { suggestions, .. } if suggestions[0] == "ja"));
+    /// assert_eq!(error.to_string(), "Invalid language code \"jp\" (did you mean: ja?)");
+    /// # });
+    /// ```
+    pub fn target_lang(mut self, lang: impl Into<String>) -> Self {
+        self.target_lang = Some(lang.into());
+        self
+    }
+
+    /// 设置本次调用的整体时限
+    ///
+    /// 超过时限时，失败策略为 `Fail` 则返回 `TranslationError::DeadlineExceeded`（带已完成的块数），
+    /// 为 `KeepOriginal` 则按顺序保留已完成的译文，其余块保留原文。
+    ///
+    /// ```rust
+    /// use markdown_translator::{FailurePolicy, TranslateOptions};
+    /// use std::time::Duration;
+    ///
+    /// let options = TranslateOptions::new()
+    ///     .overall_timeout(Duration::from_secs(30))
+    ///     .on_error(FailurePolicy::KeepOriginal);
+    /// ```
+    pub fn overall_timeout(mut self, limit: Duration) -> Self {
+        self.overall_timeout = Some(limit);
+        self
+    }
+
+    /// 设置本次调用的单块时限
+    ///
+    /// 时限包含该块的重试。单块超时默认（`on_chunk_timeout` 为 `KeepOriginal`）只让该块保留原文并记录警告，
+    /// 其余块照常翻译。
+    pub fn chunk_timeout(mut self, limit: Duration) -> Self {
+        self.chunk_timeout = Some(limit);
+        self
+    }
+
+    /// 设置本次调用翻译失败时的处理策略
+    pub fn on_error(mut self, policy: FailurePolicy) -> Self {
+        self.on_error = Some(policy);
+        self
+    }
+
+    /// 设置本次调用单块超时时的处理策略
+    pub fn on_chunk_timeout(mut self, policy: FailurePolicy) -> Self {
+        self.on_chunk_timeout = Some(policy);
+        self
+    }
+
+    /// 设置本次调用的重试预算
+    pub fn max_total_retries(mut self, max_total_retries: usize) -> Self {
+        self.max_total_retries = Some(max_total_retries);
+        self
+    }
+
+    /// 设置进度回调
+    pub fn on_progress(mut self, callback: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        self.on_progress = Some(ProgressCallback(Arc::new(callback)));
+        self
+    }
+
+    /// 设置随每个块请求发送的文档上下文，例如 `"Kubernetes documentation"`
+    ///
+    /// 标准DeepLX格式的请求会携带 `context` 字段，由支持该参数的后端使用，其他后端会忽略它；
+    /// dptrans格式不支持上下文。是否实际发送记录在 `TranslationReport::context_sent` 中。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::TranslateOptions;
+    ///
+    /// let options = TranslateOptions::new().context("Kubernetes documentation");
+    /// ```
+    pub fn context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+
+    /// 设置文档ID，替代自动生成的ID
+    ///
+    /// 文档ID是每个块ID（`<文档ID>-<序号>`）的前缀，块ID随请求在 `request_id_header` 中发送，
+    /// 并记录在 `TranslatedSegment::chunk_id` 和 `TranslationReport::document_id` 中。
+    /// 传入调用方自己的追踪ID即可把后端日志与上游请求关联起来。ID必须是有效的请求头值。
+    pub fn document_id(mut self, id: impl Into<String>) -> Self {
+        self.document_id = Some(id.into());
+        self
+    }
+
+    /// 在 `TranslationReport::chunk_timings` 中包含每个块的耗时明细
+    ///
+    /// 未设置时报告只包含汇总的分位数（`TranslationReport::timing_summary`），
+    /// 长文档的报告不会因逐块明细而变大。
+    pub fn detailed_timings(mut self, enabled: bool) -> Self {
+        self.detailed_timings = enabled;
+        self
+    }
+
+    /// 设置本次调用使用翻译缓存的方式
+    pub fn cache_mode(mut self, mode: CacheMode) -> Self {
+        self.cache_mode = mode;
+        self
+    }
+
+    /// 设置翻译范围，范围外的标题或正文原样保留
+    pub fn scope(mut self, scope: TranslationScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// 设置输入文本的格式，见 [`TextFormat`]
+    pub fn format(mut self, format: TextFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// 设置本次调用需要翻译的Markdown块类型，未列出的类型原样保留
+    ///
+    /// 与 [`scope`](Self::scope) 同时设置时，块需要同时满足两者才会被翻译。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{SegmentKind, TranslateOptions};
+    ///
+    /// // 标题和引用块保持原文
+    /// let options = TranslateOptions::new().translate_kinds([SegmentKind::Paragraph, SegmentKind::List, SegmentKind::Table]);
+    /// ```
+    pub fn translate_kinds(mut self, kinds: impl IntoIterator<Item = SegmentKind>) -> Self {
+        self.translate_kinds = Some(kinds.into_iter().collect());
+        self
+    }
+
+    /// 设置每个段最多保留的备选译文数量
+    ///
+    /// 数量会随请求发送给支持该参数的后端；后端返回的备选译文经过与译文相同的占位符还原和后处理钩子，
+    /// 通过 [`TranslatedSegment::alternatives`] 提供。按块类型或范围拆分翻译的段没有备选译文。
+    /// 未设置时丢弃后端返回的备选译文，不产生额外开销。
+    pub fn num_alternatives(mut self, count: usize) -> Self {
+        self.num_alternatives = Some(count);
+        self
+    }
+
+    /// 设置本次调用的质量检查钩子，见 [`QualityCheck`]
+    pub fn quality_check(mut self, check: impl Fn(&str, &str) -> QualityVerdict + Send + Sync + 'static) -> Self {
+        self.quality_check = Some(QualityCheck::new(check));
+        self
+    }
+
+    /// 设置输出模式
+    pub fn output_mode(mut self, output_mode: OutputMode) -> Self {
+        self.output_mode = output_mode;
+        self
+    }
+
+    /// 设置本次调用的翻译前钩子，见 [`TextHook`] 的调用顺序
+    pub fn pre_process(mut self, hook: impl Fn(&str) -> String + Send + Sync + 'static) -> Self {
+        self.pre_process = Some(TextHook::new(hook));
+        self
+    }
+
+    /// 设置本次调用的翻译后钩子，见 [`TextHook`] 的调用顺序
+    pub fn post_process(mut self, hook: impl Fn(&str) -> String + Send + Sync + 'static) -> Self {
+        self.post_process = Some(TextHook::new(hook));
+        self
+    }
+
+    /// 设置本次调用必须原样保留的术语，以 `re:` 开头的条目按正则表达式匹配
+    ///
+    /// 正则条目无效时，调用返回 `TranslationError::ConfigError`。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::TranslateOptions;
+    ///
+    /// // 命令、产品名和版本号保持原样
+    /// let options = TranslateOptions::new().keep_terms(["Kubernetes", "--dry-run", r"re:v\d+\.\d+"]);
+    /// ```
+    pub fn keep_terms<I, S>(mut self, terms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.keep_terms = Some(terms.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// 设置断点续译的检查点文件
+    ///
+    /// 每个块翻译完成后，块序号、原文哈希和译文作为一行JSON追加到文件中。以同一输入再次调用时，
+    /// 序号和哈希（含目标语言）都与当前原文一致的块直接使用记录的译文，不再请求API，
+    /// 数量记录在 `TranslationReport::resumed_chunks` 中。无法解析或与原文不一致的记录被忽略，
+    /// 并在报告中记录一条警告，不会使调用失败。按失败策略保留原文的块不写入检查点，下次调用时重新翻译。
+    ///
+    /// 检查点按单篇文档的块序号记录，适用于 `translate_with`、`translate_detailed` 等单文档调用。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::TranslateOptions;
+    ///
+    /// let options = TranslateOptions::new().checkpoint_path("guide.md.checkpoint.jsonl");
+    /// ```
+    pub fn checkpoint_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.checkpoint_path = Some(path.into());
+        self
+    }
+}
+
+/// 单次调用使用翻译缓存的方式
+///
+/// 只影响翻译缓存（包括模糊匹配），同一调用中内容相同的块仍然只请求一次。服务未启用缓存时各模式没有区别。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{CacheMode, TranslateOptions};
+///
+/// // 修改术语表之后重新请求，并用新的译文覆盖缓存条目
+/// let options = TranslateOptions::new().cache_mode(CacheMode::Refresh);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheMode {
+    /// 先查缓存，未命中时请求并写入
+    #[default]
+    Default,
+    /// 不读取也不写入缓存，例如比较不同后端的译文质量
+    Bypass,
+    /// 不读取缓存，用新的译文覆盖缓存条目，例如修改术语表之后
+    Refresh,
+}
+
+impl CacheMode {
+    /// 是否读取缓存
+    pub(crate) fn reads(self) -> bool {
+        self == Self::Default
+    }
+
+    /// 是否写入缓存
+    pub(crate) fn writes(self) -> bool {
+        self != Self::Bypass
+    }
+}
+
+/// 输出模式
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{BilingualOrder, OutputMode, TranslateOptions};
+///
+/// // 每段译文后紧跟以引用块呈现的原文
+/// let options = TranslateOptions::new().output_mode(OutputMode::bilingual());
+///
+/// // 原文在前，使用自定义模板
+/// let options = TranslateOptions::new().output_mode(OutputMode::Bilingual {
+///     order: BilingualOrder::SourceFirst,
+///     source_format: "<!-- {source} -->".to_string(),
+/// });
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum OutputMode {
+    /// 只输出译文
+    #[default]
+    Translated,
+    /// 双语对照：每个段落的译文与原文相邻输出
+    ///
+    /// 代码块和未改变的段落只输出一次；原文中的标题渲染为加粗文本，
+    /// 避免重复的标题产生冲突的锚点。译文段落数与原文不一致时，
+    /// 以整个块为单位对照。
+    Bilingual {
+        /// 译文与原文的先后顺序
+        order: BilingualOrder,
+        /// 原文段落的模板，`{source}` 会被替换为原文
+        source_format: String,
+    },
+}
+
+/// 翻译范围
+///
+/// 决定段中的哪些部分发送给翻译API，范围外的部分原样保留，输出始终是完整的文档。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{TranslateOptions, TranslationScope};
+///
+/// // 只翻译标题，用于生成翻译后的导航栏
+/// let options = TranslateOptions::new().scope(TranslationScope::HeadingsOnly);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TranslationScope {
+    /// 翻译全部内容
+    #[default]
+    All,
+    /// 只翻译标题（ATX `#` 标题和Setext下划线标题），正文原样保留
+    HeadingsOnly,
+    /// 只翻译正文，标题原样保留以保持锚点稳定
+    BodyOnly,
+}
+
+/// 输入文本的格式
+///
+/// 在配置文件中写作 `format = "markdown"`、`"plain"`、`"rst"` 或 `"asciidoc"`。
+/// 翻译日志片段、终端记录或纯文本时使用 `Plain`，避免 `#` 提示符、`>` 开头的行和
+/// 三个反引号被当作Markdown处理。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{TextFormat, TranslateOptions};
+///
+/// let options = TranslateOptions::new().format(TextFormat::Plain);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextFormat {
+    /// Markdown文档：识别代码块，支持翻译范围和块类型过滤
+    #[default]
+    Markdown,
+    /// 纯文本：不做任何Markdown处理，只在句子边界处按长度分块
+    Plain,
+    /// reStructuredText：保留指令、字面量块、表格和行内标记，按译文宽度重新生成节标题划线
+    Rst,
+    /// AsciiDoc：保留分隔块、属性条目、块宏和行内标记，交叉引用只翻译可见文本
+    Asciidoc,
+}
+
+/// 字幕文件格式
+///
+/// 传给 `TranslationService::translate_subtitles`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleFormat {
+    /// SubRip（`.srt`）：序号、`00:00:01,000 --> 00:00:02,000` 时间轴和文本
+    Srt,
+    /// WebVTT（`.vtt`）：`WEBVTT` 头部、可选的cue标识、带设置的时间轴和文本
+    WebVtt,
+}
+
+impl TextFormat {
+    /// 拼接各段译文的分隔符：Markdown段之间以空行分隔，纯文本段自带原文中的空白
+    pub(crate) fn separator(self) -> &'static str {
+        match self {
+            TextFormat::Markdown => "\n\n",
+            TextFormat::Plain | TextFormat::Rst | TextFormat::Asciidoc => "",
+        }
+    }
+}
+
+impl OutputMode {
+    /// 译文在前、原文以 `> {source}` 引用块呈现的双语模式
+    pub fn bilingual() -> Self {
+        OutputMode::Bilingual {
+            order: BilingualOrder::default(),
+            source_format: "> {source}".to_string(),
+        }
+    }
+}
+
+/// 双语对照的先后顺序
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BilingualOrder {
+    /// 译文在前，原文在后
+    #[default]
+    TranslationFirst,
+    /// 原文在前，译文在后
+    SourceFirst,
+}
+
+/// 审校对照的输出格式，见 [`render_review`](crate::render_review)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewFormat {
+    /// 两列的Markdown表格
+    Markdown,
+    /// 以表格行对齐原文和译文的HTML页面
+    Html,
+    /// 每段一行的JSON记录：`{index, source, translation, kind}`
+    Jsonl,
+}
+
+/// 进度回调
+///
+/// 由 [`TranslateOptions::on_progress`] 设置。回调在翻译任务中同步调用，
+/// 调用时不持有任何内部锁，但应尽快返回以免拖慢翻译。
+#[derive(Clone)]
+pub struct ProgressCallback(pub Arc<dyn Fn(Progress) + Send + Sync>);
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+/// 文本处理钩子
+///
+/// 接收一个块的文本并返回处理后的文本，用于翻译前的规范化、脱敏，
+/// 或翻译后的风格修正。只作用于需要翻译的块，代码块不会经过钩子；
+/// 保留术语和术语表的占位符在钩子运行前已替换，钩子不应修改形如 `{{K0}}` 的占位符。
+///
+/// # 调用顺序
+///
+/// 1. 保留术语、术语表替换为占位符
+/// 2. 服务级 `pre_process`，然后调用级 `pre_process`
+/// 3. 发送请求（包括重试时的每次请求，使用同一份处理后的文本）
+/// 4. 调用级 `post_process`，然后服务级 `post_process`
+/// 5. 还原占位符
+#[derive(Clone)]
+pub struct TextHook(pub Arc<dyn Fn(&str) -> String + Send + Sync>);
+
+impl TextHook {
+    /// 由闭包创建钩子
+    pub fn new(hook: impl Fn(&str) -> String + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    /// 对文本应用钩子
+    pub(crate) fn apply(&self, text: &str) -> String {
+        (self.0)(text)
+    }
+}
+
+impl fmt::Debug for TextHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TextHook")
+    }
+}
+
+/// 质量检查结论
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QualityVerdict {
+    /// 译文可以接受
+    Accept,
+    /// 译文可疑，附带原因，会被记录在报告中
+    Reject(String),
+}
+
+/// 质量检查钩子
+///
+/// 接收一个块的原文和译文，返回 [`QualityVerdict`]。钩子在内置检查之后运行，
+/// 服务级钩子先于调用级钩子；任一检查拒绝时该块会重新翻译一次，
+/// 重新翻译的结果仍被拒绝时按失败处理策略处理。
+#[derive(Clone)]
+pub struct QualityCheck(pub Arc<QualityCheckFn>);
+
+/// 质量检查钩子包装的闭包类型
+pub type QualityCheckFn = dyn Fn(&str, &str) -> QualityVerdict + Send + Sync;
+
+impl QualityCheck {
+    /// 由闭包创建钩子
+    pub fn new(check: impl Fn(&str, &str) -> QualityVerdict + Send + Sync + 'static) -> Self {
+        Self(Arc::new(check))
+    }
+
+    /// 检查一个块的译文
+    pub(crate) fn check(&self, source: &str, translated: &str) -> QualityVerdict {
+        (self.0)(source, translated)
+    }
+}
+
+impl fmt::Debug for QualityCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("QualityCheck")
+    }
+}
+
+/// 一次因质量检查不合格而进行的重新翻译
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Retranslation {
+    /// 块序号
+    pub index: usize,
+    /// 首次译文被拒绝的原因
+    pub reason: String,
+    /// 重新翻译的结果是否通过检查
+    pub passed: bool,
+}
+
+/// 进度事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// 分块完成，`total_chunks` 与 `characters_total` 已确定
+    PlanComplete,
+    /// 开始翻译某个块
+    ChunkStarted,
+    /// 某个块翻译完成（包括按策略保留原文的块）
+    ChunkFinished,
+    /// 某个块翻译失败
+    ChunkFailed,
+    /// 整个文档翻译完成
+    DocumentFinished,
+}
+
+/// 通过 `TranslationService::subscribe` 接收的结构化事件
+///
+/// 事件以 `try_send` 发送，接收方处理不及时、通道已满时事件被丢弃而不会阻塞翻译，
+/// 丢弃的数量记录在 `TranslationReport::dropped_events` 中。
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TranslationEvent {
+    /// 分块完成
+    PlanReady {
+        /// 分块后的总段数（含代码块）
+        chunks: usize,
+    },
+    /// 开始翻译某个块
+    ChunkStarted {
+        /// 块序号
+        index: usize,
+    },
+    /// 某个块的一次请求失败，即将重试
+    ChunkRetried {
+        /// 块序号
+        index: usize,
+        /// 失败的是第几次尝试，从1开始
+        attempt: usize,
+        /// 失败原因
+        error: String,
+    },
+    /// 某个块翻译完成（包括代码块和从检查点恢复的块）
+    ChunkCompleted {
+        /// 块序号
+        index: usize,
+        /// 原文字符数
+        chars: usize,
+        /// 该块的耗时
+        duration: Duration,
+    },
+    /// 某个块翻译失败，之后按失败处理策略保留原文或中止调用
+    ChunkFailed {
+        /// 块序号
+        index: usize,
+        /// 失败原因
+        error: String,
+    },
+    /// 文档翻译完成；一次调用包含多篇文档时（例如notebook的单元格）每篇文档发送一次
+    DocumentCompleted {
+        /// 截至此时的运行报告
+        report: Box<TranslationReport>,
+    },
+}
+
+/// 翻译进度
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{ProgressEvent, TranslateOptions, TranslationService, TranslationConfig};
+/// use std::sync::{Arc, Mutex};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let config = TranslationConfig { enabled: true, max_text_length: 10, ..Default::default() };
+///     let service = TranslationService::new(config);
+///
+///     let events = Arc::new(Mutex::new(Vec::new()));
+///     let recorded = events.clone();
+///     let options = TranslateOptions::new().on_progress(move |progress| {
+///         recorded.lock().unwrap().push(progress.event);
+///     });
+///
+///     // 只包含代码块的文档无需请求API
+///     service.translate_with("```\nfn main() {}\n```", &options).await.unwrap();
+///     assert_eq!(
+///         *events.lock().unwrap(),
+///         [
+///             ProgressEvent::PlanComplete,
+///             ProgressEvent::ChunkStarted,
+///             ProgressEvent::ChunkFinished,
+///             ProgressEvent::DocumentFinished,
+///         ]
+///     );
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Progress {
+    /// 触发本次回调的事件
+    pub event: ProgressEvent,
+    /// 事件对应的块序号，文档级事件为 `None`
+    pub chunk_index: Option<usize>,
+    /// 总块数
+    pub total_chunks: usize,
+    /// 已完成的块数
+    pub completed_chunks: usize,
+    /// 已完成块的字符数
+    pub characters_done: usize,
+    /// 文档总字符数（不含块之间的分隔）
+    pub characters_total: usize,
+    /// 截至目前发生的重试次数
+    pub retries: usize,
+    /// 按字符计算的完成百分比（0到100）
+    pub percent_complete: f64,
+    /// 最近30秒内的平均吞吐量（字符/秒），还没有块完成时为0
+    pub chars_per_second: f64,
+    /// 预计剩余时间，文档没有任何块时为 `None`，文档完成时为零
+    ///
+    /// 取按吞吐量估算的时间与剩余请求数按速率限制所需的最短时间中较大的一个，
+    /// 因此开始时少数几个块完成得很快也不会给出过于乐观的估计。
+    pub eta: Option<Duration>,
+}
+
+/// 受保护和跳过内容的统计
+///
+/// 受保护的内容原样保留在译文中，不经过翻译：代码块整段跳过，保留术语和格式相关的行内标记
+/// （rst角色、AsciiDoc宏、HTML标签和实体、c-format占位符、字幕标签、ICU占位符）替换为占位符后发送。
+/// 术语表条目替换为目标术语，单独记录在 `TranslationReport::glossary_hits` 中，不计入这里。
+/// Markdown的行内代码、URL和数学公式目前没有单独的保护流程，随段落一起发送，不在统计之列。
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ProtectionStats {
+    /// 跳过的代码块数
+    pub code_blocks: usize,
+    /// 跳过的代码块字符数
+    pub code_block_chars: usize,
+    /// 替换为占位符的保留术语数
+    pub keep_terms: usize,
+    /// 替换为占位符的行内标记数
+    pub inline_markup: usize,
+    /// 受保护的字符数：代码块、保留术语和行内标记的字符数之和
+    pub protected_chars: usize,
+    /// 成功翻译的段的字符数，不含其中的保留术语和行内标记
+    pub translated_chars: usize,
+}
+
+/// 翻译运行报告
+///
+/// 由 `translate_with_report` 返回，记录单次调用的运行统计，
+/// 实现了 `Serialize`，可直接记录为JSON用于计费和监控。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{FnTranslator, TranslationService};
+///
+/// let service = TranslationService::builder()
+///     .enabled(true)
+///     .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+///     .build()
+///     .unwrap();
+///
+/// # tokio_test::block_on(async {
+/// let (_, report) = service.translate_with_report("Hello").await.unwrap();
+/// println!("{}", serde_json::to_string(&report).unwrap());
+/// # });
+/// ```
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TranslationReport {
+    /// 本次调用的文档ID，由 `TranslateOptions::document_id` 指定或自动生成
+    pub document_id: String,
+    /// 本次调用实际发生的重试次数
+    pub retries_used: usize,
+    /// 配置的重试预算，`None` 表示不限制
+    pub retry_budget: Option<usize>,
+    /// 本次调用的请求数、429次数及各类等待时间
+    pub metrics: MetricsSnapshot,
+    /// 本次调用产生的警告，例如某个块超时后保留了原文
+    pub warnings: Vec<String>,
+    /// 分块后的总段数（含代码块）
+    pub chunks: usize,
+    /// 翻译失败的块数（含按策略保留原文的块）
+    pub failed_chunks: usize,
+    /// 翻译缓存命中、未发送请求的次数
+    pub cache_hits: usize,
+    /// 复用翻译缓存中相似条目（模糊匹配）的次数，不计入 `cache_hits`
+    pub fuzzy_hits: usize,
+    /// 译文来自翻译缓存（精确或模糊匹配）的块序号，按序号排序；复用文档中相同块的块不重复记录
+    pub cached_chunks: Vec<usize>,
+    /// 离线模式下缓存未命中、未翻译的块序号，按序号排序
+    pub offline_misses: Vec<usize>,
+    /// 去重后实际翻译的不同块数：同一调用中替换占位符后内容相同的块只翻译一次
+    pub unique_chunks: usize,
+    /// 与调用中其他块内容相同、直接复用其译文的块数；与 `unique_chunks` 之和为需要翻译的块总数
+    pub duplicate_chunks: usize,
+    /// 本次调用的总耗时
+    pub elapsed: Duration,
+    /// 各块耗时的分位数汇总
+    pub timing_summary: TimingSummary,
+    /// 每个已翻译块的耗时明细，按块序号排序；只有设置了 `TranslateOptions::detailed_timings` 时才会填充
+    pub chunk_timings: Vec<ChunkTiming>,
+    /// 后端报告的源语言（小写），后端未报告时为 `None`
+    pub detected_source_lang: Option<String>,
+    /// 第一个成功响应的端点地址，通过构建器的 `backend` 使用自定义后端时为 `"custom"`
+    pub backend: Option<String>,
+    /// 术语表命中（替换为目标术语）的次数
+    pub glossary_hits: usize,
+    /// 受保护和跳过内容的统计，代码块只计入跳过的段，占位符按成功的请求计入
+    pub protection: ProtectionStats,
+    /// 是否有请求携带了 `TranslateOptions::context` 设置的文档上下文
+    pub context_sent: bool,
+    /// 因质量检查不合格而重新翻译的块，按块序号排序
+    pub retranslated_chunks: Vec<Retranslation>,
+    /// 增量翻译时直接沿用旧译文的块数
+    pub reused_segments: usize,
+    /// 从检查点恢复、未请求API的块数
+    pub resumed_chunks: usize,
+    /// 因订阅通道已满而丢弃的事件数，见 `TranslationService::subscribe`
+    pub dropped_events: usize,
+}
+
+/// 带元数据的翻译结果
+///
+/// 由 `TranslationService::translate_detailed` 返回。
+#[derive(Debug, Clone, Serialize)]
+pub struct TranslationResult {
+    /// 翻译后的文本
+    pub text: String,
+    /// 后端报告的源语言（小写），后端未报告时为 `None`
+    pub detected_source_lang: Option<String>,
+    /// 提供译文的端点地址（多个端点参与时为第一个成功响应的端点），使用自定义后端时为 `"custom"`，未发送请求时为 `None`
+    pub backend: Option<String>,
+    /// 结果是否完全来自缓存
+    pub from_cache: bool,
+    /// 本次调用产生的警告，例如保留原文的块
+    pub warnings: Vec<String>,
+    /// 本次调用的完整运行统计
+    pub report: TranslationReport,
+}
+
+impl TranslationResult {
+    /// 由译文和运行报告构建结果
+    pub(crate) fn new(text: String, report: TranslationReport) -> Self {
+        Self {
+            text,
+            detected_source_lang: report.detected_source_lang.clone(),
+            backend: report.backend.clone(),
+            from_cache: report.cache_hits + report.fuzzy_hits > 0 && report.metrics.requests == 0,
+            warnings: report.warnings.clone(),
+            report,
+        }
+    }
+}
+
+/// 单个块的耗时
+///
+/// `elapsed` 减去限速等待、各次请求和退避等待后剩下的是本地处理时间（占位符、钩子和质量检查）。
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ChunkTiming {
+    /// 块序号
+    pub index: usize,
+    /// 从开始翻译到得到结果的耗时
+    pub elapsed: Duration,
+    /// 从调用开始到该块开始翻译的时间，即在并发队列中的等待
+    pub queue_wait: Duration,
+    /// 在速率限制器上的总等待时间
+    pub limiter_wait: Duration,
+    /// 重试前的退避等待总时间
+    pub backoff: Duration,
+    /// 每次请求（含重试和重新翻译）的HTTP耗时，长度即请求次数
+    pub attempts: Vec<Duration>,
+}
+
+impl ChunkTiming {
+    /// 所有请求的HTTP耗时之和
+    pub fn http(&self) -> Duration {
+        self.attempts.iter().sum()
+    }
+}
+
+/// 一组耗时的分位数（最近秩法）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Percentiles {
+    /// 中位数
+    pub p50: Duration,
+    /// 第90百分位
+    pub p90: Duration,
+    /// 第99百分位
+    pub p99: Duration,
+    /// 最大值
+    pub max: Duration,
+}
+
+impl Percentiles {
+    /// 由一组耗时计算分位数，为空时全部为零
+    pub(crate) fn new(mut values: Vec<Duration>) -> Self {
+        values.sort_unstable();
+        let rank = |percentile: usize| {
+            let position = (values.len() * percentile).div_ceil(100).max(1);
+            values.get(position - 1).copied().unwrap_or_default()
+        };
+        Self {
+            p50: rank(50),
+            p90: rank(90),
+            p99: rank(99),
+            max: values.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// 各块耗时的分位数汇总
+///
+/// 用于判断慢在哪里：`limiter_wait` 高说明速率限制过低，`http` 高说明网络或后端慢，
+/// `backoff` 高说明重试多，`queue_wait` 高说明并发数不足。
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TimingSummary {
+    /// 参与统计的块数
+    pub chunks: usize,
+    /// 请求总次数
+    pub attempts: usize,
+    /// 每个块的总耗时
+    pub total: Percentiles,
+    /// 每个块的队列等待
+    pub queue_wait: Percentiles,
+    /// 每个块的限速等待
+    pub limiter_wait: Percentiles,
+    /// 每个块所有请求的HTTP耗时之和
+    pub http: Percentiles,
+    /// 每个块的退避等待
+    pub backoff: Percentiles,
+}
+
+impl TimingSummary {
+    /// 由各块的耗时计算汇总
+    pub(crate) fn new(timings: &[ChunkTiming]) -> Self {
+        let collect = |field: fn(&ChunkTiming) -> Duration| Percentiles::new(timings.iter().map(field).collect());
+        Self {
+            chunks: timings.len(),
+            attempts: timings.iter().map(|timing| timing.attempts.len()).sum(),
+            total: collect(|timing| timing.elapsed),
+            queue_wait: collect(|timing| timing.queue_wait),
+            limiter_wait: collect(|timing| timing.limiter_wait),
+            http: collect(ChunkTiming::http),
+            backoff: collect(|timing| timing.backoff),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DeepLXRequest {
     pub text: String,
     pub source_lang: String,
     pub target_lang: String,
+    /// 后端原生术语表ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glossary_id: Option<String>,
+    /// 文档上下文，支持的后端（例如DeepL的 `context` 参数）用它改善术语选择，不会被翻译
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// 请求的备选译文数量，由支持该参数的后端使用
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_alternatives: Option<usize>,
+    /// 文本中标签的处理方式，`"html"` 表示由后端解析HTML并只翻译文本
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag_handling: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -86,6 +2367,661 @@ pub struct DpTransRequest {
 pub struct DeepLXResponse {
     pub code: i32,
     pub data: String,
+    /// 检测到的源语言，部分DeepLX部署不返回该字段
+    #[serde(default)]
+    pub source_lang: Option<String>,
+    /// 备选译文，部分DeepLX部署不返回该字段
+    #[serde(default)]
+    pub alternatives: Vec<String>,
+}
+
+/// 翻译预估
+///
+/// 由 `TranslationService::estimate` 返回，不发送任何请求。
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Estimate {
+    /// 分块后的总段数（含代码块）
+    pub total_segments: usize,
+    /// 需要翻译的块数
+    pub chunks: usize,
+    /// 需要翻译的字符数
+    pub characters: usize,
+    /// 跳过的代码块段数
+    pub skipped_segments: usize,
+    /// 预计发出的请求数（不含重试）
+    pub estimated_requests: usize,
+    /// 按配置的速率限制估算的最短耗时（不含HTTP耗时）
+    pub estimated_duration: Duration,
+    /// 受保护和跳过内容的统计；只统计代码块和保留术语，格式相关的行内标记在翻译时才会识别
+    pub protection: ProtectionStats,
+}
+
+/// 语言检测结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DetectedLanguage {
+    /// 语言代码，小写，例如 `"en"`、`"zh"`
+    pub code: String,
+    /// 置信度（0到1）；由翻译后端检测时后端不提供置信度，固定为1
+    pub confidence: f64,
+}
+
+impl DetectedLanguage {
+    /// 是否与给定语言代码属于同一种语言
+    ///
+    /// 只比较主语言子标签且忽略大小写，例如 `"zh"` 与 `"ZH-HANS"` 视为相同。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::DetectedLanguage;
+    ///
+    /// let detected = DetectedLanguage { code: "en".to_string(), confidence: 1.0 };
+    /// assert!(detected.matches("EN-US"));
+    /// assert!(!detected.matches("de"));
+    /// ```
+    pub fn matches(&self, lang: &str) -> bool {
+        fn primary(code: &str) -> &str {
+            code.split(['-', '_']).next().unwrap_or(code)
+        }
+        primary(&self.code).eq_ignore_ascii_case(primary(lang))
+    }
+}
+
+/// 文件翻译报告
+///
+/// 由 `translate_file` 和 `translate_file_in_place` 返回。
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReport {
+    /// 输入文件路径
+    pub input: PathBuf,
+    /// 输出文件路径
+    pub output: PathBuf,
+    /// 原地翻译时的备份文件路径
+    pub backup: Option<PathBuf>,
+    /// 输入文件的字节数
+    pub bytes_read: u64,
+    /// 写入输出文件的字节数
+    pub bytes_written: u64,
+    /// 源文本的字符数（不含BOM）
+    pub characters: usize,
+    /// 输入文件是否带有UTF-8 BOM（输出保持一致）
+    pub has_bom: bool,
+    /// 输入文件是否使用CRLF换行（输出保持一致）
+    pub crlf: bool,
+    /// 本次翻译的运行统计
+    pub translation: TranslationReport,
+    /// 按前置元数据声明的目标语言输出的文件，未声明目标语言时为空；
+    /// 此时 `output`、`bytes_written` 和 `translation` 对应第一种语言
+    pub languages: Vec<LanguageOutput>,
+    /// 前置元数据中声明、但与源语言相同而跳过的语言
+    pub skipped_languages: Vec<String>,
+}
+
+/// 按前置元数据声明的一种目标语言输出的文件
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageOutput {
+    /// 目标语言
+    pub lang: String,
+    /// 输出文件路径
+    pub output: PathBuf,
+    /// 写入输出文件的字节数
+    pub bytes_written: u64,
+    /// 该语言翻译的运行统计
+    pub translation: TranslationReport,
+}
+
+/// 输出文件已存在时的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverwritePolicy {
+    /// 覆盖已存在的输出文件
+    #[default]
+    Overwrite,
+    /// 跳过已存在的输出文件
+    Skip,
+    /// 输出文件已存在时返回 `TranslationError::OutputExists`
+    Error,
+}
+
+/// 输出文件的命名规则
+///
+/// 由 `TranslationService::translate_file_named` 和 [`DirOptions::naming`] 使用，按输入路径和目标语言生成输出路径。
+/// 文件名按最后一个 `.` 拆分为主干和扩展名，已含多个点的文件名只在最后一个扩展名前插入；
+/// 没有扩展名的文件（包括 `.gitignore` 这类以点开头的文件）直接在末尾追加。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::OutputNaming;
+/// use std::path::Path;
+///
+/// let naming = OutputNaming::LangSuffix;
+/// assert_eq!(naming.resolve(Path::new("README.md"), "zh").unwrap(), Path::new("README.zh.md"));
+/// assert_eq!(naming.resolve(Path::new("docs/v1.2.notes.md"), "zh").unwrap(), Path::new("docs/v1.2.notes.zh.md"));
+/// assert_eq!(naming.resolve(Path::new("LICENSE"), "zh").unwrap(), Path::new("LICENSE.zh"));
+///
+/// let naming = OutputNaming::LangSubdir { base: "docs".into() };
+/// assert_eq!(naming.resolve(Path::new("docs/index.md"), "zh").unwrap(), Path::new("docs/zh/index.md"));
+/// assert_eq!(naming.resolve(Path::new("docs/guide/setup.md"), "ja").unwrap(), Path::new("docs/ja/guide/setup.md"));
+/// // 输入不在 `base` 下时无法生成镜像路径
+/// assert!(naming.resolve(Path::new("README.md"), "zh").is_err());
+///
+/// // 以 `.` 作为 `base` 时，树根的文件同样镜像到语言目录下
+/// let naming = OutputNaming::LangSubdir { base: ".".into() };
+/// assert_eq!(naming.resolve(Path::new("README.md"), "zh").unwrap(), Path::new("zh/README.md"));
+///
+/// let naming = OutputNaming::Suffix("_translated".to_string());
+/// assert_eq!(naming.resolve(Path::new("api.v2.md"), "zh").unwrap(), Path::new("api.v2_translated.md"));
+///
+/// let naming = OutputNaming::Explicit("out/readme.md".into());
+/// assert_eq!(naming.resolve(Path::new("README.md"), "zh").unwrap(), Path::new("out/readme.md"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputNaming {
+    /// 在扩展名前插入目标语言，`README.md` -> `README.zh.md`
+    LangSuffix,
+    /// 镜像到 `base` 下的语言目录，`docs/guide/a.md` -> `docs/zh/guide/a.md`，输入必须位于 `base` 下
+    LangSubdir {
+        /// 被镜像的目录树的根
+        base: PathBuf,
+    },
+    /// 在扩展名前插入固定后缀，`README.md` -> `README_translated.md`
+    Suffix(String),
+    /// 固定的输出路径，只能用于单个文件
+    Explicit(PathBuf),
+}
+
+impl OutputNaming {
+    /// 按命名规则生成 `input` 翻译为 `lang` 后的输出路径
+    ///
+    /// # 返回
+    ///
+    /// * `Ok(PathBuf)` - 输出路径
+    /// * `Err(TranslationError::ConfigError)` - `LangSubdir` 的输入不在 `base` 下，或输入没有文件名
+    pub fn resolve(&self, input: &Path, lang: &str) -> Result<PathBuf> {
+        let insert = |infix: &str| -> Result<PathBuf> {
+            let name = input.file_name().ok_or_else(|| {
+                TranslationError::ConfigError(format!("路径 {} 没有文件名", input.display()))
+            })?;
+            let name = name.to_string_lossy();
+            let name = match name.rfind('.').filter(|&dot| dot > 0) {
+                Some(dot) => format!("{}{}{}", &name[..dot], infix, &name[dot..]),
+                None => format!("{}{}", name, infix),
+            };
+            Ok(input.with_file_name(name))
+        };
+        match self {
+            OutputNaming::LangSuffix => insert(&format!(".{}", lang)),
+            OutputNaming::Suffix(suffix) => insert(suffix),
+            OutputNaming::Explicit(path) => Ok(path.clone()),
+            OutputNaming::LangSubdir { base } => {
+                let relative = without_current_dir(input)
+                    .strip_prefix(without_current_dir(base))
+                    .map_err(|_| {
+                        TranslationError::ConfigError(format!(
+                            "{} 不在目录 {} 下，无法镜像到语言目录",
+                            input.display(),
+                            base.display()
+                        ))
+                    })?;
+                Ok(without_current_dir(base).join(lang).join(relative))
+            }
+        }
+    }
+}
+
+/// 去掉路径开头的 `./`
+fn without_current_dir(path: &Path) -> &Path {
+    let mut path = path;
+    while let Ok(rest) = path.strip_prefix(".") {
+        path = rest;
+    }
+    path
+}
+
+/// 目录翻译选项
+///
+/// 传给 `TranslationService::translate_dir`。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{DirOptions, OverwritePolicy};
+///
+/// let options = DirOptions::new()
+///     .include(["docs/**/*.md"])
+///     .exclude(["**/CHANGELOG.md"])
+///     .copy_unmatched(true)
+///     .overwrite(OverwritePolicy::Skip)
+///     .file_concurrency(2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DirOptions {
+    /// 需要翻译的文件（相对源目录的glob），默认 `**/*.md`
+    pub include: Vec<String>,
+    /// 排除的文件（相对源目录的glob），优先于 `include`
+    pub exclude: Vec<String>,
+    /// 是否将不匹配的文件原样复制到目标目录
+    pub copy_unmatched: bool,
+    /// 同时翻译的文件数，与单个文件内的块并发相互独立
+    pub file_concurrency: usize,
+    /// 输出文件已存在时的处理策略
+    pub overwrite: OverwritePolicy,
+    /// 是否在第一个文件失败时立即返回错误
+    pub fail_fast: bool,
+    /// 翻译后文件的命名规则，未设置时写入目标目录中的相同相对路径
+    pub naming: Option<OutputNaming>,
+    /// 是否忽略目标目录中的翻译清单，重新翻译所有匹配的文件
+    pub force: bool,
+}
+
+impl Default for DirOptions {
+    fn default() -> Self {
+        Self {
+            include: vec!["**/*.md".to_string()],
+            exclude: Vec::new(),
+            copy_unmatched: false,
+            file_concurrency: 4,
+            overwrite: OverwritePolicy::default(),
+            fail_fast: false,
+            naming: None,
+            force: false,
+        }
+    }
+}
+
+impl DirOptions {
+    /// 创建默认选项
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置需要翻译的文件glob，替换默认的 `**/*.md`
+    pub fn include<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.include = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// 设置排除的文件glob
+    pub fn exclude<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.exclude = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// 设置是否复制不匹配的文件
+    pub fn copy_unmatched(mut self, copy_unmatched: bool) -> Self {
+        self.copy_unmatched = copy_unmatched;
+        self
+    }
+
+    /// 设置同时翻译的文件数
+    pub fn file_concurrency(mut self, file_concurrency: usize) -> Self {
+        self.file_concurrency = file_concurrency;
+        self
+    }
+
+    /// 设置输出文件已存在时的处理策略
+    pub fn overwrite(mut self, overwrite: OverwritePolicy) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// 设置是否在第一个文件失败时立即返回错误
+    pub fn fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    /// 设置翻译后文件的命名规则
+    ///
+    /// 规则作用于源目录中的路径，生成的路径再换到目标目录下，例如源目录为 `docs`、
+    /// 规则为 `LangSubdir { base: "docs" }` 时 `docs/guide/a.md` 写入 `<dst>/zh/guide/a.md`，
+    /// 规则为 `LangSuffix` 时写入 `<dst>/guide/a.zh.md`。原样复制的文件不受命名规则影响。
+    /// `Explicit` 只能命名单个文件，用于目录翻译时返回 `TranslationError::ConfigError`。
+    pub fn naming(mut self, naming: OutputNaming) -> Self {
+        self.naming = Some(naming);
+        self
+    }
+
+    /// 设置是否忽略翻译清单，重新翻译所有匹配的文件
+    ///
+    /// 清单仍会按本次运行的结果重新写入。
+    pub fn force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+}
+
+/// gettext PO文件翻译选项
+///
+/// 传给 `TranslationService::translate_po`。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{PoOptions, TranslateOptions};
+///
+/// let options = PoOptions::new()
+///     .translate_all(true)
+///     .mark_fuzzy(true)
+///     .translate_options(TranslateOptions::new().target_lang("ja"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PoOptions {
+    /// 是否重新翻译已有译文的条目，默认只翻译 `msgstr` 为空的条目
+    pub translate_all: bool,
+    /// 是否为机器翻译的条目添加 `fuzzy` 标记，提示译者审校
+    pub mark_fuzzy: bool,
+    /// 翻译每个条目时使用的调用选项
+    pub translate_options: TranslateOptions,
+}
+
+impl PoOptions {
+    /// 创建默认选项
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置是否重新翻译已有译文的条目
+    pub fn translate_all(mut self, translate_all: bool) -> Self {
+        self.translate_all = translate_all;
+        self
+    }
+
+    /// 设置是否为机器翻译的条目添加 `fuzzy` 标记
+    pub fn mark_fuzzy(mut self, mark_fuzzy: bool) -> Self {
+        self.mark_fuzzy = mark_fuzzy;
+        self
+    }
+
+    /// 设置翻译每个条目时使用的调用选项
+    pub fn translate_options(mut self, options: TranslateOptions) -> Self {
+        self.translate_options = options;
+        self
+    }
+}
+
+/// JSON/YAML本地化文件翻译选项
+///
+/// 传给 `TranslationService::translate_json_values` 和 `translate_yaml_values`。
+/// 键路径由各级键以 `.` 连接，数组元素使用序号，例如 `menu.items.0.label`。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::ValueOptions;
+///
+/// let options = ValueOptions::new().exclude(["*.id", "meta.*"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ValueOptions {
+    /// 不翻译的键路径（glob），例如 `*.id`、`meta.*`
+    pub exclude: Vec<String>,
+    /// 翻译字符串值时使用的调用选项
+    pub translate_options: TranslateOptions,
+}
+
+impl ValueOptions {
+    /// 创建默认选项
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置不翻译的键路径glob
+    pub fn exclude<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.exclude = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// 设置翻译字符串值时使用的调用选项
+    pub fn translate_options(mut self, options: TranslateOptions) -> Self {
+        self.translate_options = options;
+        self
+    }
+}
+
+/// CSV中需要翻译的列
+///
+/// 可以由表头名称（`"title"`）或从0开始的列序号（`2`）转换而来。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvColumn {
+    /// 按表头名称指定，要求CSV带表头
+    Name(String),
+    /// 按从0开始的列序号指定
+    Index(usize),
+}
+
+impl From<&str> for CsvColumn {
+    fn from(name: &str) -> Self {
+        CsvColumn::Name(name.to_string())
+    }
+}
+
+impl From<String> for CsvColumn {
+    fn from(name: String) -> Self {
+        CsvColumn::Name(name)
+    }
+}
+
+impl From<usize> for CsvColumn {
+    fn from(index: usize) -> Self {
+        CsvColumn::Index(index)
+    }
+}
+
+/// CSV翻译选项
+///
+/// 传给 `TranslationService::translate_csv`。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::CsvOptions;
+///
+/// // 翻译 `title` 列和第4列，译文追加为新列，原列保持不变
+/// let options = CsvOptions::new().column("title").column(3usize).append(true);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    /// 需要翻译的列
+    pub columns: Vec<CsvColumn>,
+    /// 第一行是否为表头，表头不会被翻译
+    pub has_headers: bool,
+    /// 字段分隔符，默认 `,`
+    pub delimiter: u8,
+    /// 是否将译文写入追加在行末的新列（表头为 `{原表头}_{目标语言}`），而不是替换原列
+    pub append: bool,
+    /// 翻译单元格时使用的调用选项
+    pub translate_options: TranslateOptions,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            columns: Vec::new(),
+            has_headers: true,
+            delimiter: b',',
+            append: false,
+            translate_options: TranslateOptions::default(),
+        }
+    }
+}
+
+impl CsvOptions {
+    /// 创建默认选项
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一个需要翻译的列
+    pub fn column(mut self, column: impl Into<CsvColumn>) -> Self {
+        self.columns.push(column.into());
+        self
+    }
+
+    /// 设置需要翻译的列，替换已添加的列
+    pub fn columns<I, C>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<CsvColumn>,
+    {
+        self.columns = columns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// 设置第一行是否为表头
+    pub fn has_headers(mut self, has_headers: bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
+
+    /// 设置字段分隔符
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// 设置是否将译文追加为新列
+    pub fn append(mut self, append: bool) -> Self {
+        self.append = append;
+        self
+    }
+
+    /// 设置翻译单元格时使用的调用选项
+    pub fn translate_options(mut self, options: TranslateOptions) -> Self {
+        self.translate_options = options;
+        self
+    }
+}
+
+/// CSV翻译结果
+///
+/// 由 `translate_csv` 返回。格式错误或翻译失败的行原样保留，并以行号记录在 `failed_rows` 中。
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CsvTranslation {
+    /// 翻译后的CSV
+    pub content: String,
+    /// 翻译的单元格数
+    pub cells_translated: usize,
+    /// 原样保留的行的行号（从1开始，表头为第1行）及原因
+    pub failed_rows: Vec<(u64, String)>,
+}
+
+/// 缓存预热选项
+///
+/// 传给 `TranslationService::warm_cache_with`。
+///
+/// # 示例
+///
+/// ```rust
+/// use markdown_translator::{TranslateOptions, WarmOptions};
+///
+/// // 只统计预热日语译文需要请求的块数，不发送翻译请求
+/// let options = WarmOptions::new()
+///     .dry_run(true)
+///     .translate_options(TranslateOptions::new().target_lang("ja"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct WarmOptions {
+    /// 是否只统计需要请求的块，不发送翻译请求
+    pub dry_run: bool,
+    /// 翻译时使用的调用选项，例如目标语言
+    pub translate_options: TranslateOptions,
+}
+
+impl WarmOptions {
+    /// 创建默认选项
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置是否只统计、不请求
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// 设置翻译时使用的调用选项
+    pub fn translate_options(mut self, options: TranslateOptions) -> Self {
+        self.translate_options = options;
+        self
+    }
+}
+
+/// 缓存预热报告
+///
+/// 由 `TranslationService::warm_cache` 返回。每个块只按首次出现计入 `translated` 或 `cached`，
+/// 之后在任意文本中再次出现时计入 `duplicates`。
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WarmReport {
+    /// 预热的文本数
+    pub texts: usize,
+    /// 新翻译并写入缓存的块数；`dry_run` 时为需要请求的块数
+    pub translated: usize,
+    /// 已在缓存中（包括模糊匹配）、无需请求的块数
+    pub cached: usize,
+    /// 与之前的块内容相同、不再单独计数的块数
+    pub duplicates: usize,
+    /// 翻译失败的块数，失败处理策略为保留原文时才会出现
+    pub failed: usize,
+    /// 是否为只统计的预演
+    pub dry_run: bool,
+    /// 总耗时
+    pub duration: Duration,
+}
+
+/// 目录翻译报告
+///
+/// 由 `translate_dir` 返回。
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DirReport {
+    /// 翻译成功的文件数
+    pub files_translated: usize,
+    /// 因输出已存在而跳过的文件数
+    pub files_skipped: usize,
+    /// 源内容和配置与翻译清单一致、输出仍存在而跳过的文件数
+    pub files_up_to_date: usize,
+    /// 原样复制的不匹配文件数
+    pub files_copied: usize,
+    /// 翻译失败的文件及错误信息
+    pub failed: Vec<(PathBuf, String)>,
+    /// 前置元数据中声明、但与源语言相同而跳过的文件和语言
+    pub skipped_languages: Vec<(PathBuf, String)>,
+    /// 已翻译文件的源文本字符数
+    pub characters: usize,
+    /// 总耗时
+    pub duration: Duration,
+}
+
+/// 已翻译的段
+///
+/// 由 `TranslationService::translate_stream` 按文档顺序产出。
+#[derive(Debug, Clone, Serialize)]
+pub struct TranslatedSegment {
+    /// 段在文档中的序号，从0开始
+    pub index: usize,
+    /// 原文
+    pub source: String,
+    /// 译文，代码块与原文相同
+    pub translated: String,
+    /// 是否为代码块（代码块不会被翻译）
+    pub is_code_block: bool,
+    /// 后端提供的备选译文，只有设置了 `TranslateOptions::num_alternatives` 时才会填充
+    pub alternatives: Vec<String>,
+    /// 块ID（`<文档ID>-<序号>`），与请求头 `request_id_header` 中发送的值一致
+    pub chunk_id: String,
 }
 
 #[derive(Debug, Clone)]
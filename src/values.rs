@@ -0,0 +1,224 @@
+//! 本地化文件模块
+//!
+//! 遍历JSON/YAML本地化文件的结构，只翻译字符串叶子值：键、数字、布尔值和非字符串数组原样保留，
+//! ICU MessageFormat占位符（`{name}`、`{count, plural, ...}`）以占位符保护，
+//! 所有叶子值按长度预算打包为尽可能少的请求。
+
+use crate::error::{Result, TranslationError};
+use crate::file::build_glob_set;
+use crate::notebook::serialize_like;
+use crate::protect::Placeholders;
+use crate::translator::{CallContext, TranslationService};
+use crate::types::ValueOptions;
+use globset::GlobSet;
+use serde_json::Value;
+
+/// ICU占位符的类型标记
+const ICU_PLACEHOLDER: char = 'V';
+
+impl TranslationService {
+    /// 翻译JSON本地化文件中的字符串值
+    ///
+    /// 递归遍历对象和数组，只翻译字符串叶子值，键路径匹配 `exclude` 的值原样保留。
+    /// 花括号包围的ICU MessageFormat占位符（包括嵌套的 `plural`、`select` 表达式）不会发送给翻译API，
+    /// 只由占位符组成的值不翻译。所有值以分隔行打包，在 `max_text_length` 和 `max_paragraphs_per_request`
+    /// 允许的范围内合并为尽可能少的请求。输出保持原有的键顺序和缩进。
+    ///
+    /// # 返回
+    ///
+    /// * `Ok(String)` - 翻译后的JSON
+    /// * `Err(TranslationError::ParseError)` - 不是合法的JSON
+    /// * `Err(TranslationError::ConfigError)` - `exclude` 中存在无效的glob
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{FnTranslator, TranslationServiceBuilder, ValueOptions};
+    ///
+    /// let service = TranslationServiceBuilder::new()
+    ///     .enabled(true)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let json = r#"{"meta":{"title":"Settings"},"greeting":"Hello, {name}!"}"#;
+    /// # tokio_test::block_on(async {
+    /// let options = ValueOptions::new().exclude(["meta.*"]);
+    /// let translated = service.translate_json_values(json, &options).await.unwrap();
+    /// assert_eq!(translated, json.replace("Hello, {name}!", "HELLO, {name}!"));
+    /// # });
+    /// ```
+    pub async fn translate_json_values(&self, json: &str, options: &ValueOptions) -> Result<String> {
+        let mut value: Value =
+            serde_json::from_str(json).map_err(|e| TranslationError::ParseError(format!("无法解析JSON: {}", e)))?;
+        let exclude = build_glob_set(&options.exclude)?;
+        let context = self.new_context(&options.translate_options)?;
+        if !context.components.config.enabled {
+            return Ok(json.to_string());
+        }
+
+        let mut leaves = Vec::new();
+        collect_json_leaves(&mut value, String::new(), &exclude, &mut leaves);
+        self.translate_leaves(leaves, &context).await?;
+        serialize_like(&value, json)
+    }
+
+    /// 翻译YAML本地化文件中的字符串值
+    ///
+    /// 规则与 [`translate_json_values`](Self::translate_json_values) 相同，映射的键顺序保持不变。
+    /// 输出由 `serde_yaml` 重新生成，原文中的注释、锚点和引号风格不会保留。需要启用 `yaml` 特性。
+    ///
+    /// # 返回
+    ///
+    /// * `Ok(String)` - 翻译后的YAML
+    /// * `Err(TranslationError::ParseError)` - 不是合法的YAML
+    /// * `Err(TranslationError::ConfigError)` - `exclude` 中存在无效的glob
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use markdown_translator::{FnTranslator, TranslationServiceBuilder, ValueOptions};
+    ///
+    /// let service = TranslationServiceBuilder::new()
+    ///     .enabled(true)
+    ///     .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let yaml = "menu:\n  id: main-menu\n  label: Open file\n";
+    /// # tokio_test::block_on(async {
+    /// let options = ValueOptions::new().exclude(["*.id"]);
+    /// let translated = service.translate_yaml_values(yaml, &options).await.unwrap();
+    /// assert_eq!(translated, "menu:\n  id: main-menu\n  label: OPEN FILE\n");
+    /// # });
+    /// ```
+    #[cfg(feature = "yaml")]
+    pub async fn translate_yaml_values(&self, yaml: &str, options: &ValueOptions) -> Result<String> {
+        let mut value: serde_yaml::Value =
+            serde_yaml::from_str(yaml).map_err(|e| TranslationError::ParseError(format!("无法解析YAML: {}", e)))?;
+        let exclude = build_glob_set(&options.exclude)?;
+        let context = self.new_context(&options.translate_options)?;
+        if !context.components.config.enabled {
+            return Ok(yaml.to_string());
+        }
+
+        let mut leaves = Vec::new();
+        collect_yaml_leaves(&mut value, String::new(), &exclude, &mut leaves);
+        self.translate_leaves(leaves, &context).await?;
+        serde_yaml::to_string(&value).map_err(|e| TranslationError::ParseError(format!("无法序列化YAML: {}", e)))
+    }
+
+    /// 打包翻译所有叶子值并写回，保留每个值首尾的空白
+    async fn translate_leaves(&self, leaves: Vec<&mut String>, context: &CallContext) -> Result<()> {
+        let mut targets = Vec::new();
+        let mut items = Vec::new();
+        let mut placeholders = Vec::new();
+        for leaf in leaves {
+            let mut leaf_placeholders = Placeholders::default();
+            if let Some(protected) = protect_icu(leaf.trim(), &mut leaf_placeholders) {
+                targets.push(leaf);
+                items.push(protected);
+                placeholders.push(leaf_placeholders);
+            }
+        }
+
+        let translations = self.translate_items(&items, true, context).await;
+        for ((leaf, leaf_placeholders), translated) in targets.into_iter().zip(placeholders).zip(translations) {
+            let translated = translated?;
+            context.record_protection(&leaf_placeholders);
+            let restored = leaf_placeholders.restore(translated.trim());
+            for (_, placeholder) in &restored.missing {
+                context.warn(format!("ICU占位符在译文中丢失，{:?} 未出现在译文中", placeholder));
+            }
+            let leading = &leaf[..leaf.len() - leaf.trim_start().len()];
+            let trailing = &leaf[leaf.trim_end().len()..];
+            *leaf = format!("{}{}{}", leading, restored.text, trailing);
+        }
+        Ok(())
+    }
+}
+
+/// 拼接键路径
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// 收集JSON中未被排除的字符串叶子值
+fn collect_json_leaves<'a>(value: &'a mut Value, path: String, exclude: &GlobSet, leaves: &mut Vec<&'a mut String>) {
+    match value {
+        Value::String(text) if !exclude.is_match(&path) => leaves.push(text),
+        Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                collect_json_leaves(item, join_path(&path, &index.to_string()), exclude, leaves);
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                collect_json_leaves(item, join_path(&path, key), exclude, leaves);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 收集YAML中未被排除的字符串叶子值，非标量的键不参与路径匹配
+#[cfg(feature = "yaml")]
+fn collect_yaml_leaves<'a>(
+    value: &'a mut serde_yaml::Value,
+    path: String,
+    exclude: &GlobSet,
+    leaves: &mut Vec<&'a mut String>,
+) {
+    use serde_yaml::Value;
+    match value {
+        Value::String(text) if !exclude.is_match(&path) => leaves.push(text),
+        Value::Sequence(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                collect_yaml_leaves(item, join_path(&path, &index.to_string()), exclude, leaves);
+            }
+        }
+        Value::Mapping(map) => {
+            for (key, item) in map.iter_mut() {
+                let key = match key {
+                    Value::String(key) => key.clone(),
+                    Value::Number(key) => key.to_string(),
+                    Value::Bool(key) => key.to_string(),
+                    _ => continue,
+                };
+                collect_yaml_leaves(item, join_path(&path, &key), exclude, leaves);
+            }
+        }
+        Value::Tagged(tagged) => collect_yaml_leaves(&mut tagged.value, path, exclude, leaves),
+        _ => {}
+    }
+}
+
+/// 以占位符保护花括号包围的ICU表达式（按嵌套层级匹配），占位符之外没有文字时返回 `None`
+fn protect_icu(text: &str, placeholders: &mut Placeholders) -> Option<String> {
+    let mut protected = String::with_capacity(text.len());
+    let mut has_text = false;
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let mut depth = 0;
+        let end = rest[start..].char_indices().find_map(|(offset, c)| {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            (depth == 0).then_some(start + offset + 1)
+        });
+        let Some(end) = end else { break };
+        has_text |= rest[..start].chars().any(char::is_alphanumeric);
+        protected.push_str(&rest[..start]);
+        protected.push_str(&placeholders.insert(ICU_PLACEHOLDER, rest[start..end].to_string()));
+        rest = &rest[end..];
+    }
+    has_text |= rest.chars().any(char::is_alphanumeric);
+    protected.push_str(rest);
+    has_text.then_some(protected)
+}
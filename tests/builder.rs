@@ -0,0 +1,124 @@
+mod common;
+
+use common::{MockServer, Reply};
+use markdown_translator::{CacheConfig, FnTranslator, QualityVerdict, TranslationError, TranslationService, TranslationServiceBuilder};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[tokio::test]
+async fn glossary_terms_are_restored_after_translation() {
+    let server = MockServer::echo();
+    let service = server
+        .builder()
+        .glossary(HashMap::from([("worker node".to_string(), "工作节点".to_string())]))
+        .build()
+        .unwrap();
+
+    let (translated, report) = service.translate_with_report("Add a worker node.").await.unwrap();
+    assert_eq!(translated, "Add a 工作节点.");
+    assert_eq!(report.glossary_hits, 1);
+    assert!(server.texts().iter().all(|text| !text.contains("worker node")));
+}
+
+#[tokio::test]
+async fn default_headers_and_user_agent_reach_the_backend() {
+    let server = MockServer::start(|request| {
+        Reply::data(format!("{}|{}", request.header("user-agent").unwrap(), request.header("x-team").unwrap()))
+    });
+    let service = server
+        .builder()
+        .user_agent("docs-pipeline/2.1")
+        .default_header("X-Team", "docs")
+        .build()
+        .unwrap();
+
+    assert_eq!(service.translate("Hello").await.unwrap(), "docs-pipeline/2.1|docs");
+}
+
+#[test]
+fn invalid_default_header_is_rejected_by_build() {
+    let result = TranslationServiceBuilder::new().default_header("X Team", "docs").build();
+    assert!(matches!(result, Err(TranslationError::ConfigError(_))));
+}
+
+#[tokio::test]
+async fn pre_process_runs_before_the_request_is_sent() {
+    let server = MockServer::echo();
+    let service = server
+        .builder()
+        .pre_process(|text| text.replace("db01.corp.internal", "[host]"))
+        .build()
+        .unwrap();
+
+    let translated = service.translate("Connect to db01.corp.internal first.").await.unwrap();
+    assert_eq!(translated, "Connect to [host] first.");
+    let requests = server.requests();
+    assert!(!requests.is_empty());
+    assert!(requests.iter().all(|request| !request.raw.contains("db01.corp.internal")));
+}
+
+#[tokio::test]
+async fn rejected_chunks_are_retranslated() {
+    let count = AtomicUsize::new(0);
+    let server = MockServer::start(move |request| {
+        if count.fetch_add(1, Ordering::SeqCst) == 0 {
+            Reply::data("\u{fffd}\u{fffd}\u{fffd}")
+        } else {
+            Reply::data(request.text().to_uppercase())
+        }
+    });
+    let service = server
+        .builder()
+        .quality_check(|_source, translated| {
+            if translated.contains('\u{fffd}') {
+                QualityVerdict::Reject("译文包含替换字符".to_string())
+            } else {
+                QualityVerdict::Accept
+            }
+        })
+        .build()
+        .unwrap();
+
+    let (translated, report) = service.translate_with_report("Restart the node.").await.unwrap();
+    assert_eq!(translated, "RESTART THE NODE.");
+    assert_eq!(report.retranslated_chunks.len(), 1);
+    assert!(report.retranslated_chunks[0].passed);
+    assert_eq!(server.request_count(), 2);
+}
+
+#[tokio::test]
+async fn custom_backend_is_retried_and_cached() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let recorded = calls.clone();
+    let backend = FnTranslator::new(move |text| {
+        let mut calls = recorded.lock().unwrap();
+        calls.push(text.to_string());
+        // 第二段第一次请求失败，由服务重试
+        if text.starts_with("Second") && calls.iter().filter(|call| call.starts_with("Second")).count() == 1 {
+            return Err(TranslationError::Custom("temporarily unavailable".to_string()));
+        }
+        Ok(text.to_uppercase())
+    });
+    let service = TranslationService::builder()
+        .enabled(true)
+        .max_rps(100.0)
+        .max_text_length(30)
+        .backend(backend)
+        .configure(|config| config.cache = Some(CacheConfig::default()))
+        .build()
+        .unwrap();
+
+    let text = "First paragraph here.\n\nSecond paragraph here.\n\n```sh\nmake test\n```";
+    let (translated, report) = service.translate_with_report(text).await.unwrap();
+    assert_eq!(translated, "FIRST PARAGRAPH HERE.\n\nSECOND PARAGRAPH HERE.\n\n```sh\nmake test\n```");
+    assert_eq!((report.chunks, report.retries_used), (3, 1));
+    assert_eq!(report.backend.as_deref(), Some("custom"));
+    // 代码块不交给后端，两段文字共请求三次（含一次重试）
+    assert_eq!(calls.lock().unwrap().len(), 3);
+
+    // 再次翻译全部命中缓存，不调用后端
+    let (_, report) = service.translate_with_report(text).await.unwrap();
+    assert_eq!(report.cache_hits, 2);
+    assert_eq!(calls.lock().unwrap().len(), 3);
+}
@@ -0,0 +1,331 @@
+mod common;
+
+use common::{MockServer, Reply};
+use futures::StreamExt;
+use markdown_translator::{
+    CacheBackend, CacheConfig, CacheKey, FailurePolicy, FnTranslator, TranslateOptions, TranslationConfig,
+    TranslationError, TranslationLibConfig, TranslationService, WarmOptions,
+};
+use serde_json::json;
+use std::collections::HashMap;
+
+#[test]
+fn cache_key_covers_settings_that_change_the_translation() {
+    let config = TranslationConfig::default();
+    let key = CacheKey::new(&config, "Hello").unwrap();
+    assert!(key.to_string().starts_with(&format!("v{}-", CacheKey::VERSION)));
+
+    let changes: Vec<fn(&mut TranslationConfig)> = vec![
+        |c| c.deeplx_api_url = "http://localhost:1189/translate".to_string(),
+        |c| c.deeplx_api_url = "http://localhost:1188/dptrans".to_string(),
+        |c| c.source_lang = "en".to_string(),
+        |c| c.target_lang = "ja".to_string(),
+        |c| c.glossary = HashMap::from([("node".to_string(), "节点".to_string())]),
+        |c| c.glossary_id = Some("gls-1".to_string()),
+    ];
+    for change in changes {
+        let mut changed = config.clone();
+        change(&mut changed);
+        assert_ne!(CacheKey::new(&changed, "Hello").unwrap(), key);
+    }
+    assert_ne!(CacheKey::new(&config, "Hello!").unwrap(), key);
+}
+
+#[test]
+fn cache_key_ignores_unrelated_settings() {
+    let config = TranslationConfig::default();
+    let key = CacheKey::new(&config, "Hello").unwrap();
+
+    // 速率限制、超时等无关配置不改变键，语言代码按规范形式比较
+    let mut unrelated = config.clone();
+    unrelated.max_requests_per_second = 50.0;
+    unrelated.burst_size = Some(16);
+    unrelated.request_timeout_secs = 5;
+    unrelated.target_lang = " ZH ".to_string();
+    assert_eq!(CacheKey::new(&unrelated, "Hello").unwrap(), key);
+}
+
+#[tokio::test]
+async fn cache_stats_track_entries_and_evictions() {
+    let server = MockServer::uppercase();
+    let service = server
+        .builder()
+        .configure(|config| config.cache = Some(CacheConfig { max_entries: Some(2), ..Default::default() }))
+        .build()
+        .unwrap();
+
+    for text in ["one", "two", "three"] {
+        service.translate(text).await.unwrap();
+    }
+
+    // 原文和译文各占 3、3、5 字节，"one" 被淘汰
+    let stats = service.cache_stats().unwrap();
+    assert_eq!((stats.entries, stats.bytes, stats.evictions, stats.expirations), (2, 16, 1, 0));
+    assert_eq!(service.compact_cache(), Some(stats));
+
+    let uncached = TranslationService::builder().build().unwrap();
+    assert!(uncached.cache_stats().is_none());
+}
+
+#[tokio::test]
+async fn warm_cache_translates_each_distinct_chunk_once() {
+    let server = MockServer::uppercase();
+    let service = server
+        .builder()
+        .configure(|config| config.cache = Some(CacheConfig::default()))
+        .build()
+        .unwrap();
+    let texts = ["Install the package.", "Run the tests.", "Install the package."];
+
+    // 预演：两段内容需要请求，第三段与第一段相同
+    let plan = service.warm_cache_with(&texts, &WarmOptions::new().dry_run(true)).await.unwrap();
+    assert_eq!((plan.translated, plan.cached, plan.duplicates), (2, 0, 1));
+    assert_eq!(server.request_count(), 0);
+
+    let report = service.warm_cache(&texts).await.unwrap();
+    assert_eq!((report.translated, report.cached, report.duplicates), (2, 0, 1));
+    assert_eq!(server.request_count(), 2);
+
+    // 预热后翻译这些文本不再请求API
+    let (translated, report) = service.translate_with_report("Run the tests.").await.unwrap();
+    assert_eq!(translated, "RUN THE TESTS.");
+    assert_eq!(report.cache_hits, 1);
+
+    let again = service.warm_cache(&texts).await.unwrap();
+    assert_eq!((again.translated, again.cached), (0, 2));
+    assert_eq!(server.request_count(), 2);
+}
+
+#[tokio::test]
+async fn warm_cache_requires_a_cache() {
+    let server = MockServer::uppercase();
+    let service = server.builder().build().unwrap();
+    assert!(service.warm_cache(&["Hello"]).await.is_err());
+    assert_eq!(server.request_count(), 0);
+}
+
+#[tokio::test]
+async fn cache_is_shared_by_clones_and_evicts_least_recently_used() {
+    let server = MockServer::uppercase();
+    let service = server
+        .builder()
+        .configure(|config| config.cache = Some(CacheConfig { max_entries: Some(1), ..Default::default() }))
+        .build()
+        .unwrap();
+
+    let (_, first) = service.translate_with_report("Hello").await.unwrap();
+    assert_eq!((first.cache_hits, first.metrics.requests), (0, 1));
+
+    // 服务的克隆共用同一份缓存
+    let (translated, second) = service.clone().translate_with_report("Hello").await.unwrap();
+    assert_eq!(translated, "HELLO");
+    assert_eq!((second.cache_hits, second.metrics.requests), (1, 0));
+
+    // 只保留一个条目，翻译其他文本后 "Hello" 被淘汰
+    service.translate("World").await.unwrap();
+    let (_, third) = service.translate_with_report("Hello").await.unwrap();
+    assert_eq!(third.cache_hits, 0);
+}
+
+#[tokio::test]
+async fn offline_mode_only_uses_cached_translations() {
+    let server = MockServer::uppercase();
+    let service = server
+        .builder()
+        .configure(|config| {
+            config.cache = Some(CacheConfig::default());
+            config.max_text_length = 30;
+        })
+        .build()
+        .unwrap();
+
+    // 在线时翻译第一段，写入缓存
+    service.translate("First paragraph here.").await.unwrap();
+
+    service
+        .update_config(|config| {
+            config.offline = true;
+            config.on_error = FailurePolicy::KeepOriginal;
+        })
+        .unwrap();
+    let requests = server.request_count();
+    let text = "First paragraph here.\n\nSecond paragraph here.";
+    let (translated, report) = service.translate_with_report(text).await.unwrap();
+    assert_eq!(translated, "FIRST PARAGRAPH HERE.\n\nSecond paragraph here.");
+    assert_eq!((report.cached_chunks, report.offline_misses), (vec![0], vec![1]));
+    assert_eq!(server.request_count(), requests);
+
+    // 按 on_error = "fail" 处理未命中
+    service.update_config(|config| config.on_error = FailurePolicy::Fail).unwrap();
+    let error = service.translate("Second paragraph here.").await.unwrap_err();
+    assert!(matches!(error.root_cause(), TranslationError::OfflineCacheMiss));
+}
+
+#[tokio::test]
+async fn offline_mode_reads_a_disk_cache_warmed_by_another_service() {
+    let server = MockServer::uppercase();
+    let dir = std::env::temp_dir().join("markdown-translator-test-offline-disk");
+    let _ = std::fs::remove_dir_all(&dir);
+    let build = |offline: bool| {
+        server
+            .builder()
+            .configure(|config: &mut TranslationConfig| {
+                config.cache = Some(CacheConfig {
+                    backend: CacheBackend::Disk,
+                    path: Some(dir.clone()),
+                    ..Default::default()
+                });
+                config.offline = offline;
+            })
+            .build()
+            .unwrap()
+    };
+
+    build(false).warm_cache(&["Install the package."]).await.unwrap();
+    let requests = server.request_count();
+
+    let offline = build(true);
+    let (translated, report) = offline.translate_with_report("Install the package.").await.unwrap();
+    assert_eq!(translated, "INSTALL THE PACKAGE.");
+    assert_eq!((report.cached_chunks, report.offline_misses), (vec![0], vec![]));
+    assert_eq!(server.request_count(), requests);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn disk_cache_survives_rebuilding_the_service() {
+    let server = MockServer::uppercase();
+    let dir = std::env::temp_dir().join("markdown-translator-test-disk-cache");
+    let _ = std::fs::remove_dir_all(&dir);
+    let build = || {
+        server
+            .builder()
+            .configure(|config| {
+                config.cache = Some(CacheConfig {
+                    backend: CacheBackend::Disk,
+                    path: Some(dir.clone()),
+                    ..Default::default()
+                })
+            })
+            .build()
+            .unwrap()
+    };
+
+    let service = build();
+    service.translate("Install the package.").await.unwrap();
+    service.translate("Run the tests.").await.unwrap();
+    assert_eq!(server.request_count(), 2);
+    drop(service);
+
+    // 新服务从目录恢复条目，不再请求API
+    let rebuilt = build();
+    assert_eq!(rebuilt.cache_stats().unwrap().entries, 2);
+    let (translated, report) = rebuilt.translate_with_report("Run the tests.").await.unwrap();
+    assert_eq!(translated, "RUN THE TESTS.");
+    assert_eq!(report.cache_hits, 1);
+    assert_eq!(server.request_count(), 2);
+    assert!(dir.join(format!("entries-v{}.jsonl", CacheKey::VERSION)).is_file());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn custom_backend_and_endpoint_do_not_share_entries() {
+    let server = MockServer::uppercase();
+    let dir = std::env::temp_dir().join("markdown-translator-test-cache-backend");
+    let _ = std::fs::remove_dir_all(&dir);
+    let cache = CacheConfig { backend: CacheBackend::Disk, path: Some(dir.clone()), ..Default::default() };
+
+    let endpoint = server.builder().configure(|config| config.cache = Some(cache.clone())).build().unwrap();
+    assert_eq!(endpoint.translate("Hello").await.unwrap(), "HELLO");
+    drop(endpoint);
+
+    // 地址相同的自定义后端不读取端点写入的条目，反之亦然
+    let custom = server
+        .builder()
+        .backend(FnTranslator::new(|text| Ok(format!("custom: {}", text))))
+        .configure(|config| config.cache = Some(cache.clone()))
+        .build()
+        .unwrap();
+    assert_eq!(custom.translate("Hello").await.unwrap(), "custom: Hello");
+    assert_eq!(custom.translate("Hello").await.unwrap(), "custom: Hello");
+    drop(custom);
+
+    let endpoint = server.builder().configure(|config| config.cache = Some(cache)).build().unwrap();
+    assert_eq!(endpoint.translate("Hello").await.unwrap(), "HELLO");
+    assert_eq!(server.request_count(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn request_options_are_part_of_the_cache_key() {
+    let server = MockServer::start(|request| {
+        let translated = if request.body["context"].is_string() { "Pod" } else { "豆荚" };
+        Reply::json(json!({ "code": 200, "data": translated, "alternatives": ["荚", "容器组", "集装箱"] }))
+    });
+    let service = server
+        .builder()
+        .configure(|config| config.cache = Some(CacheConfig::default()))
+        .build()
+        .unwrap();
+
+    // 带上下文的请求不复用不带上下文时缓存的译文
+    assert_eq!(service.translate("pod").await.unwrap(), "豆荚");
+    let options = TranslateOptions::new().context("Kubernetes documentation");
+    assert_eq!(service.translate_with("pod", &options).await.unwrap(), "Pod");
+    assert_eq!(service.translate_with("pod", &options).await.unwrap(), "Pod");
+    assert_eq!(server.request_count(), 2);
+
+    // 未请求备选译文时缓存的响应不含备选译文，请求3个时重新发送
+    let options = TranslateOptions::new().num_alternatives(3);
+    let segment = Box::pin(service.translate_stream_with("pod", &options)).next().await.unwrap().unwrap();
+    assert_eq!(segment.alternatives, ["荚", "容器组", "集装箱"]);
+    assert_eq!(server.request_count(), 3);
+}
+
+#[tokio::test]
+async fn fuzzy_matches_above_the_threshold_reuse_translations() {
+    let server = MockServer::uppercase();
+    let service = server
+        .builder()
+        .configure(|config| config.cache = Some(CacheConfig { fuzzy_threshold: Some(0.8), ..Default::default() }))
+        .build()
+        .unwrap();
+
+    service.translate("Returns the number of items.").await.unwrap();
+
+    // 相似度约为0.84，复用已有的译文，不发送请求
+    let (translated, report) = service.translate_with_report("Returns the number of elements.").await.unwrap();
+    assert_eq!(translated, "RETURNS THE NUMBER OF ITEMS.");
+    assert_eq!((report.cache_hits, report.fuzzy_hits, report.metrics.requests), (0, 1, 0));
+
+    // 低于阈值的文本照常翻译
+    let (translated, report) = service.translate_with_report("Returns the first item.").await.unwrap();
+    assert_eq!(translated, "RETURNS THE FIRST ITEM.");
+    assert_eq!((report.fuzzy_hits, report.metrics.requests), (0, 1));
+}
+
+#[test]
+fn cache_table_is_validated_and_checked_for_unknown_keys() {
+    // 磁盘缓存必须指定目录，数量和大小上限不能为0
+    let invalid = TranslationLibConfig::parse(
+        r#"
+        [translation.cache]
+        backend = "disk"
+        max_entries = 0
+        "#,
+        true,
+    )
+    .unwrap()
+    .translation;
+    let fields: Vec<String> = invalid.validate().unwrap_err().into_iter().map(|e| e.field).collect();
+    assert_eq!(fields, ["cache.path", "cache.max_entries"]);
+
+    // 严格模式同样检查缓存表中的未知项
+    let error = TranslationLibConfig::parse("[translation.cache]\nmax_entrys = 10\n", true).unwrap_err();
+    assert!(error.to_string().contains("translation.cache.max_entrys"));
+    assert!(error.to_string().contains("max_entries"));
+
+    // 没有 [translation.cache] 表时不使用缓存
+    let config = TranslationLibConfig::parse("[translation]\n", true).unwrap().translation;
+    assert!(config.cache.is_none());
+}
@@ -0,0 +1,188 @@
+mod common;
+
+use common::MockServer;
+use futures::future::{BoxFuture, FutureExt};
+use markdown_translator::{
+    ChunkStrategy, ChunkingConfig, LengthUnit, Result, SegmentKind, TranslationConfig, TranslationLibConfig,
+    TranslationService, Translator, UnclosedFence,
+};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// 每块最长12个单位、不发送请求的服务，用于观察 `estimate` 的分块结果
+fn estimating(chunking: ChunkingConfig) -> TranslationService {
+    TranslationService::new(TranslationConfig {
+        enabled: true,
+        max_text_length: 12,
+        chunking,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn chunking_table_round_trips_through_a_config_file() {
+    let path = std::env::temp_dir().join("markdown-translator-test-chunking.toml");
+    std::fs::write(
+        &path,
+        r#"
+[translation.chunking]
+length_unit = "chars"
+sentence_breaks = ["。", "！", "？", ";"]
+atomic_kinds = ["table", "list"]
+unclosed_fence = "text"
+strategy = "paragraph"
+overlap = 50
+min_chunk_length = 20
+"#,
+    )
+    .unwrap();
+
+    let config = TranslationLibConfig::from_file(&path).unwrap();
+    let chunking = &config.translation.chunking;
+    assert_eq!(chunking.length_unit, LengthUnit::Chars);
+    assert_eq!(chunking.strategy, ChunkStrategy::Paragraph);
+    assert_eq!((chunking.overlap, chunking.min_chunk_length), (50, 20));
+    assert_eq!(chunking.sentence_breaks, ['。', '！', '？', ';']);
+    assert_eq!(chunking.atomic_kinds, [SegmentKind::Table, SegmentKind::List]);
+    assert_eq!(chunking.unclosed_fence, UnclosedFence::Text);
+
+    // 保存后重新加载得到相同的分块配置
+    config.save_to_file(&path).unwrap();
+    let reloaded = TranslationLibConfig::from_file(&path).unwrap();
+    assert_eq!(reloaded.translation.chunking, config.translation.chunking);
+    std::fs::remove_file(&path).unwrap();
+
+    // 未配置时使用默认值
+    let default = TranslationLibConfig::parse("[translation]\n", true).unwrap();
+    assert_eq!(default.translation.chunking.length_unit, LengthUnit::Bytes);
+    assert!(default.translation.chunking.atomic_kinds.is_empty());
+}
+
+#[test]
+fn length_unit_chars_counts_cjk_characters_once() {
+    // 12个字符的中文句子占36个字节，按字符计算时不需要拆分
+    let text = "第一句话。第二句话完了。";
+    assert!(estimating(ChunkingConfig::default()).estimate(text).chunks > 1);
+    let chars = ChunkingConfig { length_unit: LengthUnit::Chars, ..Default::default() };
+    assert_eq!(estimating(chars).estimate(text).chunks, 1);
+}
+
+#[test]
+fn atomic_kinds_are_sent_whole() {
+    let table = "| a | b |\n|---|---|\n| 1 | 2 |\n| 3 | 4 |";
+    assert!(estimating(ChunkingConfig::default()).estimate(table).chunks > 1);
+    let atomic = ChunkingConfig { atomic_kinds: vec![SegmentKind::Table], ..Default::default() };
+    assert_eq!(estimating(atomic).estimate(table).chunks, 1);
+}
+
+#[test]
+fn unclosed_fence_is_code_until_the_end_by_default() {
+    let unclosed = "Introduction.\n\n```\nnot closed";
+    assert_eq!(estimating(ChunkingConfig::default()).estimate(unclosed).skipped_segments, 1);
+    let text_fence = ChunkingConfig { unclosed_fence: UnclosedFence::Text, ..Default::default() };
+    assert_eq!(estimating(text_fence).estimate(unclosed).skipped_segments, 0);
+}
+
+#[test]
+fn paragraph_strategy_splits_paragraphs_and_merges_short_ones() {
+    // paragraph策略下每个段落单独成块，短于 min_chunk_length 的标题与下一段合并
+    let paragraphs = "# A\n\nB.\n\nC.";
+    assert_eq!(estimating(ChunkingConfig::default()).estimate(paragraphs).chunks, 1);
+    let paragraph = ChunkingConfig { strategy: ChunkStrategy::Paragraph, ..Default::default() };
+    assert_eq!(estimating(paragraph.clone()).estimate(paragraphs).chunks, 3);
+    let merged = ChunkingConfig { min_chunk_length: 4, ..paragraph };
+    assert_eq!(estimating(merged).estimate(paragraphs).chunks, 2);
+}
+
+#[tokio::test]
+async fn code_comments_are_translated_for_listed_languages() {
+    let server = MockServer::uppercase();
+    let service = |languages: &[&str]| {
+        server
+            .builder()
+            .configure(|config| {
+                config.max_text_length = 40;
+                config.chunking = ChunkingConfig {
+                    code_comment_languages: languages.iter().map(|language| language.to_string()).collect(),
+                    ..Default::default()
+                };
+            })
+            .build()
+            .unwrap()
+    };
+    let text = "Add one to the value.\n\n```rust\n// add one\nlet y = x + 1; // \"//\" is fine\n```";
+
+    let translated = service(&["rs"]).translate(text).await.unwrap();
+    assert!(translated.starts_with("ADD ONE TO THE VALUE."));
+    assert!(translated.ends_with("```rust\n// ADD ONE\nlet y = x + 1; // \"//\" IS FINE\n```"));
+
+    // 默认不翻译代码注释
+    let translated = service(&[]).translate(text).await.unwrap();
+    assert!(translated.ends_with("```rust\n// add one\nlet y = x + 1; // \"//\" is fine\n```"));
+}
+
+#[tokio::test]
+async fn overlap_sends_the_previous_chunk_tail_as_context() {
+    let server = MockServer::uppercase();
+    let service = server
+        .builder()
+        .max_text_length(30)
+        .configure(|config| config.chunking = ChunkingConfig { overlap: 10, ..Default::default() })
+        .build()
+        .unwrap();
+
+    let translated = service.translate("First paragraph here.\n\nSecond paragraph here.").await.unwrap();
+    // 上下文不出现在译文中
+    assert_eq!(translated, "FIRST PARAGRAPH HERE.\n\nSECOND PARAGRAPH HERE.");
+
+    // 第一个块没有上下文，第二个块带上第一个块末尾的10个字节
+    let mut contexts: Vec<Option<String>> = server
+        .requests()
+        .iter()
+        .map(|request| request.body["context"].as_str().map(str::to_string))
+        .collect();
+    contexts.sort();
+    assert_eq!(contexts, [None, Some("raph here.".to_string())]);
+}
+
+/// 第一次请求（语言检测）时把 `max_text_length` 改大，其余请求原样返回
+struct ReconfiguringBackend {
+    service: Arc<OnceLock<TranslationService>>,
+    texts: Mutex<Vec<String>>,
+}
+
+impl Translator for ReconfiguringBackend {
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>> {
+        async move {
+            let mut texts = self.texts.lock().unwrap();
+            if texts.is_empty() {
+                let service = self.service.get().unwrap();
+                service.update_config(|config| config.max_text_length = 1000).unwrap();
+            }
+            texts.push(text.to_string());
+            Ok(text.to_string())
+        }
+        .boxed()
+    }
+}
+
+#[tokio::test]
+async fn chunking_uses_the_config_snapshot_of_the_call() {
+    let handle = Arc::new(OnceLock::new());
+    let backend = Arc::new(ReconfiguringBackend { service: handle.clone(), texts: Mutex::new(Vec::new()) });
+    let service = TranslationService::builder()
+        .enabled(true)
+        .max_rps(100.0)
+        .max_text_length(30)
+        .backend(backend.clone())
+        .configure(|config| config.skip_if_already_target = true)
+        .build()
+        .unwrap();
+    let _ = handle.set(service.clone());
+
+    // 分块在语言检测之后进行，此时配置已被修改，但本次调用仍按调用开始时的30字节分块
+    let document = "First paragraph here.\n\nSecond paragraph here.\n\nThird paragraph here.";
+    service.translate(document).await.unwrap();
+    let texts = backend.texts.lock().unwrap();
+    assert!(texts.iter().all(|text| !text.contains("\n\n")), "{texts:?}");
+    assert!(texts.iter().any(|text| text.starts_with("Third")));
+}
@@ -0,0 +1,208 @@
+//! 集成测试共用的模拟DeepLX后端
+//!
+//! 每个测试启动一个只监听本机端口的HTTP服务，按测试给出的处理函数应答并记录收到的请求。
+
+#![allow(dead_code)]
+
+use markdown_translator::TranslationServiceBuilder;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 模拟后端收到的一次请求
+#[derive(Debug, Clone)]
+pub struct Request {
+    /// 请求路径
+    pub path: String,
+    /// 请求头，名称为小写
+    pub headers: HashMap<String, String>,
+    /// 原始请求体
+    pub raw: String,
+    /// 解析后的JSON请求体，不是JSON时为 `Value::Null`
+    pub body: Value,
+}
+
+impl Request {
+    /// 请求体中的 `text` 字段
+    pub fn text(&self) -> &str {
+        self.body["text"].as_str().unwrap_or_default()
+    }
+
+    /// 按名称（不区分大小写）读取请求头
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_ascii_lowercase()).map(String::as_str)
+    }
+}
+
+/// 模拟后端的响应
+#[derive(Debug, Clone)]
+pub struct Reply {
+    status: u16,
+    content_type: String,
+    body: String,
+    headers: Vec<(String, String)>,
+    delay: Duration,
+}
+
+impl Reply {
+    /// 标准DeepLX格式的成功响应，`data` 为译文
+    pub fn data(text: impl Into<String>) -> Self {
+        Self::json(json!({ "code": 200, "data": text.into() }))
+    }
+
+    /// 状态码200的JSON响应
+    pub fn json(value: Value) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json".to_string(),
+            body: value.to_string(),
+            headers: Vec::new(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// 指定状态码的纯文本响应
+    pub fn status(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type: "text/plain".to_string(),
+            body: body.into(),
+            headers: Vec::new(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// 替换 `Content-Type`
+    pub fn content_type(mut self, content_type: &str) -> Self {
+        self.content_type = content_type.to_string();
+        self
+    }
+
+    /// 添加响应头
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// 延迟发送响应
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+type Handler = dyn Fn(&Request) -> Reply + Send + Sync;
+
+/// 模拟的DeepLX后端
+pub struct MockServer {
+    /// 翻译接口地址
+    pub url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    /// 启动模拟后端，每个请求在单独的线程中交给 `handler` 应答
+    pub fn start(handler: impl Fn(&Request) -> Reply + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/translate", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+        let recorded = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                let handler = handler.clone();
+                let recorded = recorded.clone();
+                std::thread::spawn(move || serve(stream, &*handler, &recorded));
+            }
+        });
+        Self { url, requests }
+    }
+
+    /// 原样返回请求文本的后端
+    pub fn echo() -> Self {
+        Self::start(|request| Reply::data(request.text()))
+    }
+
+    /// 返回大写请求文本的后端
+    pub fn uppercase() -> Self {
+        Self::start(|request| Reply::data(request.text().to_uppercase()))
+    }
+
+    /// 到目前为止收到的请求，按收到的顺序排列
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// 到目前为止收到的请求文本
+    pub fn texts(&self) -> Vec<String> {
+        self.requests().iter().map(|request| request.text().to_string()).collect()
+    }
+
+    /// 收到的请求数
+    pub fn request_count(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    /// 指向本后端、已启用且不限速的构建器
+    pub fn builder(&self) -> TranslationServiceBuilder {
+        TranslationServiceBuilder::new().enabled(true).api_url(self.url.clone()).max_rps(100.0)
+    }
+}
+
+/// 读取一个请求，应答后关闭连接
+fn serve(stream: TcpStream, handler: &Handler, recorded: &Mutex<Vec<Request>>) {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let path = request_line.split_whitespace().nth(1).unwrap_or_default().to_string();
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+    let length = headers.get("content-length").map_or(0, |value| value.parse().unwrap_or(0));
+    let mut body = vec![0; length];
+    if reader.read_exact(&mut body).is_err() {
+        return;
+    }
+    let raw = String::from_utf8_lossy(&body).into_owned();
+    let request = Request {
+        path,
+        headers,
+        body: serde_json::from_str(&raw).unwrap_or(Value::Null),
+        raw,
+    };
+    recorded.lock().unwrap().push(request.clone());
+
+    let reply = handler(&request);
+    std::thread::sleep(reply.delay);
+    let mut head = format!(
+        "HTTP/1.1 {} Mock\r\ncontent-type: {}\r\ncontent-length: {}\r\nconnection: close\r\n",
+        reply.status,
+        reply.content_type,
+        reply.body.len()
+    );
+    for (name, value) in &reply.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    let stream = reader.get_mut();
+    let _ = write!(stream, "{}\r\n{}", head, reply.body);
+    let _ = stream.flush();
+}
+
+/// 没有服务监听的本机地址，请求会被拒绝连接
+pub fn unreachable_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    format!("http://{}/translate", listener.local_addr().unwrap())
+}
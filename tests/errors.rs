@@ -0,0 +1,236 @@
+mod common;
+
+use common::{unreachable_url, MockServer, Reply};
+use markdown_translator::{RetryConfig, TranslationError, TranslationLibConfig, TranslationServiceBuilder};
+#[cfg(feature = "serde")]
+use markdown_translator::RateLimitHeaders;
+#[cfg(feature = "serde")]
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[tokio::test]
+async fn html_error_page_is_not_taken_as_a_translation() {
+    let server = MockServer::start(|_| {
+        Reply::status(200, "<html><body><h1>Sign in to continue</h1></body></html>").content_type("text/html")
+    });
+    let service = server
+        .builder()
+        .retry(RetryConfig { max_retries: 0, ..Default::default() })
+        .build()
+        .unwrap();
+
+    let error = service.translate("Hello").await.unwrap_err();
+    match error.root_cause() {
+        TranslationError::ResponseParseError { backend, content_type, body_excerpt, .. } => {
+            assert_eq!(backend, "DeepLX");
+            assert_eq!(content_type.as_deref(), Some("text/html"));
+            // 默认只记录长度和哈希值
+            assert!(!body_excerpt.contains("Sign in"));
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[tokio::test]
+async fn refused_connection_is_a_retryable_connection_failure() {
+    let service = TranslationServiceBuilder::new()
+        .enabled(true)
+        .api_url(unreachable_url())
+        .retry(RetryConfig { max_retries: 0, ..Default::default() })
+        .build()
+        .unwrap();
+
+    let error = service.translate("Hello").await.unwrap_err();
+    assert!(matches!(error.root_cause(), TranslationError::ConnectionFailed { .. }));
+    assert!(error.is_retryable());
+}
+
+#[tokio::test]
+async fn slow_reply_times_out_with_the_configured_limit() {
+    let server = MockServer::start(|request| Reply::data(request.text()).delay(Duration::from_secs(3)));
+    let service = server
+        .builder()
+        .retry(RetryConfig { max_retries: 0, ..Default::default() })
+        .configure(|config| config.request_timeout_secs = 1)
+        .build()
+        .unwrap();
+
+    let error = service.translate("Hello").await.unwrap_err();
+    let timeout = error.root_cause();
+    assert!(matches!(timeout, TranslationError::Timeout { elapsed } if *elapsed == Duration::from_secs(1)));
+    assert_eq!(timeout.to_string(), "Request timed out (request_timeout_secs = 1)");
+    assert!(error.is_retryable());
+}
+
+/// FNV-1a 64位哈希
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3))
+}
+
+#[tokio::test]
+async fn redacted_bodies_carry_a_stable_fingerprint() {
+    let body = "backend failed on: Unreleased roadmap";
+    let server = MockServer::start(move |_| Reply::status(500, body));
+    let service = server.builder().retry(RetryConfig { max_retries: 0, ..Default::default() }).build().unwrap();
+
+    // 哈希不依赖标准库的 DefaultHasher，不同构建记录的值可以比对
+    let error = service.translate("Unreleased roadmap").await.unwrap_err();
+    let fingerprint = format!("<{} 字节，哈希 {:016x}>", body.len(), fnv1a(body.as_bytes()));
+    assert!(error.to_string().contains(&fingerprint), "{}", error);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn every_variant_serializes_to_the_same_shape() {
+    let http = || reqwest::Client::new().get("not a url").build().unwrap_err();
+    let io = || std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+    let errors = [
+        (TranslationError::Http(http()), "http", "{}"),
+        (TranslationError::Custom("boom".into()), "other", "{}"),
+        (TranslationError::RateLimitError("slow down".into()), "rate_limited", "{}"),
+        (
+            TranslationError::ApiError {
+                status: 503,
+                retry_after: None,
+                rate_limit: RateLimitHeaders::default(),
+                body_excerpt: "down".into(),
+            },
+            "api",
+            r#"{"retry_after_secs":null,"rate_limit":{"limit":null,"remaining":null,"reset":null},"body_excerpt":"down"}"#,
+        ),
+        (TranslationError::AuthError { status: 401, message: "invalid key".into() }, "auth", "{}"),
+        (TranslationError::ParseError("bad line".into()), "parse", "{}"),
+        (
+            TranslationError::ResponseParseError {
+                backend: "DeepLX".into(),
+                expected: "JSON".into(),
+                content_type: Some("text/html".into()),
+                body_excerpt: "<5 字节>".into(),
+            },
+            "response_parse",
+            r#"{"backend":"DeepLX","expected":"JSON","content_type":"text/html","body_excerpt":"<5 字节>"}"#,
+        ),
+        (TranslationError::Json(serde_json::from_str::<u8>("x").unwrap_err()), "json", "{}"),
+        (TranslationError::Toml(toml::from_str::<toml::Table>("=").unwrap_err()), "toml", "{}"),
+        (TranslationError::DeadlineExceeded { completed: 1, total: 4 }, "deadline_exceeded", r#"{"completed":1,"total":4}"#),
+        (TranslationError::Timeout { elapsed: Duration::from_secs(30) }, "timeout", r#"{"elapsed_ms":30000}"#),
+        (
+            TranslationError::ConnectionFailed { message: "connection refused".into(), source: http() },
+            "connection_failed",
+            "{}",
+        ),
+        (TranslationError::Cancelled { completed: 2, total: 4 }, "cancelled", r#"{"completed":2,"total":4}"#),
+        (TranslationError::ShuttingDown, "shutting_down", "{}"),
+        (
+            TranslationError::ChunkTimeout { index: 2, elapsed: Duration::from_millis(1500) },
+            "chunk_timeout",
+            r#"{"index":2,"elapsed_ms":1500}"#,
+        ),
+        (
+            TranslationError::InvalidLanguage { given: "cn".into(), suggestions: vec!["zh".into()] },
+            "invalid_language",
+            r#"{"given":"cn","suggestions":["zh"]}"#,
+        ),
+        (TranslationError::ConfigError("bad".into()), "config", "{}"),
+        (TranslationError::Io { path: PathBuf::from("a.md"), source: io() }, "io", r#"{"path":"a.md"}"#),
+        (
+            TranslationError::FileTooLarge { path: PathBuf::from("a.md"), size: 10, limit: 5 },
+            "file_too_large",
+            r#"{"path":"a.md","size":10,"limit":5}"#,
+        ),
+        (TranslationError::OutputExists { path: PathBuf::from("a.zh.md") }, "output_exists", r#"{"path":"a.zh.md"}"#),
+        (TranslationError::InsideAsyncRuntime, "inside_async_runtime", "{}"),
+        (
+            TranslationError::QualityCheckFailed { index: 1, reason: "too short".into() },
+            "quality_check_failed",
+            r#"{"index":1,"reason":"too short"}"#,
+        ),
+        (
+            TranslationError::ChunkFailed {
+                index: 0,
+                byte_range: None,
+                preview: "<5 字节>".into(),
+                attempts: 1,
+                source: Box::new(TranslationError::ShuttingDown),
+            },
+            "chunk_failed",
+            r#"{"index":0,"byte_range":null,"attempts":1,"preview":"<5 字节>"}"#,
+        ),
+        (TranslationError::OfflineCacheMiss, "offline_cache_miss", "{}"),
+    ];
+
+    for (error, code, details) in errors {
+        let value = serde_json::to_value(&error).unwrap();
+        let keys: Vec<_> = value.as_object().unwrap().keys().cloned().collect();
+        assert_eq!(keys, ["version", "code", "message", "retryable", "status", "details", "source"]);
+        assert_eq!(value["code"], code);
+        assert_eq!(value["message"], error.to_string());
+        assert_eq!(value["details"].to_string(), details, "{}", code);
+        assert_eq!(value["source"].is_null(), code != "chunk_failed");
+    }
+}
+
+#[tokio::test]
+async fn keep_original_returns_the_document_when_the_endpoint_is_unreachable() {
+    let config = TranslationLibConfig::parse("[translation]\non_error = \"keep_original\"\n", true).unwrap();
+    let service = TranslationServiceBuilder::from_config(config.translation)
+        .enabled(true)
+        .api_url(unreachable_url())
+        .max_rps(100.0)
+        .retry(RetryConfig { max_retries: 1, initial_delay_ms: 1, max_delay_ms: 1, ..Default::default() })
+        .configure(|config| config.max_text_length = 20)
+        .build()
+        .unwrap();
+
+    let document = "First paragraph here.\n\nSecond paragraph here.\n\nThird paragraph here.";
+    let (translated, report) = service.translate_with_report(document).await.unwrap();
+    assert_eq!(translated, document);
+    assert_eq!(report.warnings.len(), 1);
+    assert!(report.warnings[0].contains("整篇文档保留原文"));
+}
+
+#[tokio::test]
+async fn rate_limit_response_is_parsed_and_body_truncated() {
+    let server = MockServer::start(|_| {
+        Reply::status(429, "quota exceeded for this key, please slow down")
+            .header("retry-after", "30")
+            .header("x-ratelimit-limit", "60")
+            .header("x-ratelimit-remaining", "0")
+    });
+    let service = server
+        .builder()
+        .retry(RetryConfig { max_retries: 0, ..Default::default() })
+        .configure(|config| {
+            config.log_content = true;
+            config.error_body_max_chars = 14;
+        })
+        .build()
+        .unwrap();
+
+    let error = service.translate("Hello").await.unwrap_err();
+    match error.root_cause() {
+        TranslationError::ApiError { status, retry_after, rate_limit, body_excerpt } => {
+            assert_eq!(*status, 429);
+            assert_eq!(*retry_after, Some(std::time::Duration::from_secs(30)));
+            assert_eq!(rate_limit.limit, Some(60));
+            assert_eq!(rate_limit.remaining, Some(0));
+            assert!(body_excerpt.starts_with("quota exceeded…"));
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+    assert!(error.is_rate_limited());
+}
+
+#[tokio::test]
+async fn error_messages_leave_out_content_unless_log_content_is_set() {
+    let server = MockServer::start(|request| Reply::status(500, format!("backend failed on: {}", request.text())));
+    let builder = || server.builder().retry(RetryConfig { max_retries: 0, ..Default::default() });
+
+    let error = builder().build().unwrap().translate("Unreleased roadmap").await.unwrap_err();
+    assert!(error.to_string().contains("500"));
+    assert!(!error.to_string().contains("roadmap"));
+
+    let debugging = builder().configure(|config| config.log_content = true).build().unwrap();
+    let error = debugging.translate("Unreleased roadmap").await.unwrap_err();
+    assert!(error.to_string().contains("Unreleased roadmap"));
+}
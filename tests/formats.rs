@@ -0,0 +1,259 @@
+mod common;
+
+use common::{MockServer, Reply};
+#[cfg(feature = "csv")]
+use markdown_translator::CsvOptions;
+use markdown_translator::{PoOptions, SubtitleFormat, ValueOptions};
+
+#[cfg(feature = "csv")]
+const CSV: &str = "id,title,price
+1,Blue shirt,19.99
+2,\"Red hat, wool\",12.50
+3,Broken row
+";
+
+const NOTEBOOK: &str = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": [
+    "# Intro\n",
+    "\n",
+    "Load the data."
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "metadata": {},
+   "outputs": [],
+   "source": [
+    "# load the data\n",
+    "df = load()"
+   ]
+  }
+ ],
+ "metadata": {},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"##;
+
+const PO: &str = r#"msgid ""
+msgstr ""
+"Language: de\n"
+"Plural-Forms: nplurals=2; plural=(n != 1);\n"
+
+#: src/main.c:10
+#, c-format
+msgid "Hello, %s!"
+msgstr ""
+
+#: src/main.c:12
+#, c-format
+msgid "%d file"
+msgid_plural "%d files"
+msgstr[0] ""
+msgstr[1] ""
+
+msgid "Quit"
+msgstr "Beenden"
+"#;
+
+const SRT: &str = "1
+00:00:01,000 --> 00:00:03,500
+<i>Where are you going?</i>
+
+2
+00:00:04,000 --> 00:00:06,000
+- To the station.
+- Wait for me!
+
+3
+00:00:07,000 --> 00:00:09,000
+I told you we should have left the house an hour ago.
+";
+
+const JSON: &str = r#"{
+  "meta": {
+    "title": "Settings",
+    "version": 3
+  },
+  "save": "Save",
+  "greeting": "Hello, {name}!",
+  "files": "{count, plural, one {# file} other {# files}}",
+  "sizes": [
+    12,
+    16
+  ]
+}
+"#;
+
+#[cfg(feature = "yaml")]
+const YAML: &str = "\
+menu:
+  id: main-menu
+  items:
+  - Open file
+  - Quit {app}
+enabled: true
+";
+
+#[cfg(feature = "csv")]
+#[tokio::test]
+async fn csv_translates_selected_columns_and_reports_broken_rows() {
+    let server = MockServer::uppercase();
+    let service = server.builder().build().unwrap();
+
+    let result = service.translate_csv(CSV, &CsvOptions::new().column("title")).await.unwrap();
+    assert_eq!(
+        result.content,
+        "id,title,price
+1,BLUE SHIRT,19.99
+2,\"RED HAT, WOOL\",12.50
+3,Broken row
+"
+    );
+    assert_eq!(result.cells_translated, 2);
+    assert_eq!(result.failed_rows.len(), 1);
+    assert_eq!(result.failed_rows[0].0, 4);
+
+    let result = service.translate_csv(CSV, &CsvOptions::new().column(1usize).append(true)).await.unwrap();
+    assert!(result.content.starts_with("id,title,price,title_zh\n1,Blue shirt,19.99,BLUE SHIRT\n"));
+}
+
+#[tokio::test]
+async fn html_keeps_markup_and_translates_text_and_attributes() {
+    // 返回大写的请求文本，占位符不受影响
+    let server = MockServer::start(|request| {
+        let words: Vec<String> = request
+            .text()
+            .split(' ')
+            .map(|word| if word.contains('{') { word.to_string() } else { word.to_uppercase() })
+            .collect();
+        Reply::data(words.join(" "))
+    });
+    let service = server.builder().build().unwrap();
+
+    let html = concat!(
+        "<h1 class=\"title\">Install</h1>\n",
+        "<p>Run <code>cargo build</code> &amp; wait.</p>\n",
+        "<img src=\"logo.png\" alt=\"Project logo\">\n",
+        "<pre>keep me</pre>",
+    );
+    let translated = service.translate_html(html).await.unwrap();
+    assert_eq!(
+        translated,
+        concat!(
+            "<h1 class=\"title\">INSTALL</h1>\n",
+            "<p>RUN <code>cargo build</code> &amp; WAIT.</p>\n",
+            "<img src=\"logo.png\" alt=\"PROJECT LOGO\">\n",
+            "<pre>keep me</pre>",
+        )
+    );
+}
+
+#[tokio::test]
+async fn notebook_translates_markdown_cells_only() {
+    let server = MockServer::uppercase();
+    let service = server.builder().build().unwrap();
+
+    let translated = service.translate_notebook(NOTEBOOK).await.unwrap();
+    assert_eq!(translated, NOTEBOOK.replace("# Intro", "# INTRO").replace("Load the data.", "LOAD THE DATA."));
+}
+
+#[tokio::test]
+async fn po_fills_empty_entries_and_marks_them_fuzzy() {
+    let server = MockServer::uppercase();
+    let service = server.builder().build().unwrap();
+
+    let translated = service.translate_po(PO, &PoOptions::new().mark_fuzzy(true)).await.unwrap();
+    assert_eq!(
+        translated,
+        PO.replace("#, c-format", "#, fuzzy, c-format")
+            .replace("msgstr \"\"\n\n#: src/main.c:12", "msgstr \"HELLO, %s!\"\n\n#: src/main.c:12")
+            .replace("msgstr[0] \"\"", "msgstr[0] \"%d FILE\"")
+            .replace("msgstr[1] \"\"", "msgstr[1] \"%d FILES\"")
+    );
+}
+
+#[tokio::test]
+async fn subtitles_keep_timing_and_rewrap_long_lines() {
+    let server = MockServer::uppercase();
+    let service = server.builder().build().unwrap();
+
+    let translated = service.translate_subtitles(SRT, SubtitleFormat::Srt).await.unwrap();
+    assert_eq!(
+        translated,
+        "1
+00:00:01,000 --> 00:00:03,500
+<i>WHERE ARE YOU GOING?</i>
+
+2
+00:00:04,000 --> 00:00:06,000
+- TO THE STATION.
+- WAIT FOR ME!
+
+3
+00:00:07,000 --> 00:00:09,000
+I TOLD YOU WE SHOULD HAVE
+LEFT THE HOUSE AN HOUR AGO.
+"
+    );
+}
+
+#[tokio::test]
+async fn json_values_skip_excluded_paths_and_icu_messages() {
+    let server = MockServer::uppercase();
+    let service = server.builder().build().unwrap();
+
+    let options = ValueOptions::new().exclude(["meta.*"]);
+    let translated = service.translate_json_values(JSON, &options).await.unwrap();
+    assert_eq!(translated, JSON.replace("\"Save\"", "\"SAVE\"").replace("Hello, {name}!", "HELLO, {name}!"));
+}
+
+#[cfg(feature = "yaml")]
+#[tokio::test]
+async fn yaml_values_skip_excluded_paths() {
+    let server = MockServer::uppercase();
+    let service = server.builder().build().unwrap();
+
+    let options = ValueOptions::new().exclude(["*.id"]);
+    let translated = service.translate_yaml_values(YAML, &options).await.unwrap();
+    assert_eq!(translated, YAML.replace("Open file", "OPEN FILE").replace("Quit {app}", "QUIT {app}"));
+}
+
+#[tokio::test]
+async fn incremental_reuses_unchanged_segments() {
+    let server = MockServer::uppercase();
+    let service = server.builder().build().unwrap();
+
+    let old_source = "# Guide\n\nFirst paragraph.\n\nSecond paragraph.\n";
+    let old_translation = "# 指南\n\n第一段。\n\n第二段。\n";
+    // 两个段落交换了位置，并插入了一个新段落
+    let new_source = "# Guide\n\nSecond paragraph.\n\nA new paragraph.\n\nFirst paragraph.\n";
+
+    let (translated, report) = service
+        .translate_incremental(new_source, old_source, old_translation)
+        .await
+        .unwrap();
+    assert_eq!(translated, "# 指南\n\n第二段。\n\nA NEW PARAGRAPH.\n\n第一段。\n");
+    assert_eq!(report.reused_segments, 3);
+    assert_eq!(server.texts(), ["A new paragraph."]);
+}
+
+#[tokio::test]
+async fn fan_out_sends_one_request_per_target() {
+    let server = MockServer::start(|request| {
+        Reply::data(format!("[{}] {}", request.body["target_lang"].as_str().unwrap(), request.text()))
+    });
+    let service = server.builder().build().unwrap();
+
+    let translations = service.translate_to_many("Hello", &["ja", "de", "fr"]).await.unwrap();
+    assert_eq!(translations.len(), 3);
+    assert_eq!(translations["ja"], "[ja] Hello");
+    assert_eq!(translations["de"], "[de] Hello");
+    assert_eq!(translations["fr"], "[fr] Hello");
+    assert_eq!(server.request_count(), 3);
+}
@@ -0,0 +1,148 @@
+use markdown_translator::{
+    retry_with_backoff, EndpointPool, RateLimitHeaders, RateLimiter, RetryConfig, TranslationConfig, TranslationError,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn parent_limiter_caps_all_children() {
+    // 两个端点各自每秒10个请求，共享每秒1个请求的全局上限
+    let global = RateLimiter::with_burst(1.0, 1);
+    let primary = RateLimiter::with_burst(10.0, 10).with_parent(global.clone());
+    let backup = RateLimiter::with_burst(10.0, 10).with_parent(global);
+
+    let start = Instant::now();
+    primary.acquire().await.unwrap();
+    backup.acquire().await.unwrap();
+    primary.acquire().await.unwrap();
+    // 端点限制器都有余量，全局上限使三个请求间隔1秒
+    assert_eq!(start.elapsed().as_secs(), 2);
+}
+
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn throttled_child_does_not_hold_global_budget() {
+    let global = RateLimiter::with_burst(1.0, 2);
+    let throttled = RateLimiter::with_burst(0.1, 1).with_parent(global.clone());
+    let other = RateLimiter::with_burst(10.0, 10).with_parent(global);
+
+    let start = Instant::now();
+    throttled.acquire().await.unwrap();
+    // 第二个请求要等本端点10秒后的令牌，等待期间不占用剩下的一个全局令牌
+    let waiting = tokio::spawn(async move {
+        throttled.acquire().await.unwrap();
+        start.elapsed()
+    });
+    tokio::task::yield_now().await;
+
+    other.acquire().await.unwrap();
+    assert_eq!(start.elapsed(), Duration::ZERO);
+    assert_eq!(waiting.await.unwrap().as_secs(), 10);
+}
+
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn global_cap_does_not_inherit_the_endpoint_burst() {
+    // 每个端点的突发容量为1，全局上限每秒10个请求，突发容量按全局速率计算
+    let config = TranslationConfig {
+        max_requests_per_second: 10.0,
+        burst_size: Some(1),
+        global_max_requests_per_second: Some(10.0),
+        fallback_api_urls: vec!["http://localhost:1189/translate".to_string()],
+        ..Default::default()
+    };
+    let pool = EndpointPool::from_config(&config);
+    let [primary, backup] = pool.endpoints() else { panic!("expected two endpoints") };
+
+    let start = Instant::now();
+    primary.rate_limiter.acquire().await.unwrap();
+    backup.rate_limiter.acquire().await.unwrap();
+    assert_eq!(start.elapsed(), Duration::ZERO);
+
+    // 单个端点仍受自身的突发容量限制
+    primary.rate_limiter.acquire().await.unwrap();
+    assert_eq!(start.elapsed(), Duration::from_millis(100));
+}
+
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn independent_limiters_do_not_block_each_other() {
+    // 没有上级限制器时，一个端点用尽许可不影响另一个端点
+    let slow = RateLimiter::with_burst(1.0, 1);
+    let fast = RateLimiter::with_burst(10.0, 10);
+    let start = Instant::now();
+    slow.acquire().await.unwrap();
+    for _ in 0..5 {
+        fast.acquire().await.unwrap();
+    }
+    assert_eq!(start.elapsed().as_secs(), 0);
+    slow.acquire().await.unwrap();
+    assert_eq!(start.elapsed().as_secs(), 1);
+}
+
+// 暂停的时钟下，每秒1个令牌、容量为1的限制器每发放一个许可时间恰好前进1秒，
+// 因此经过的整秒数就是第一个许可之后获取的许可数
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn retry_takes_one_permit_per_attempt() {
+    let limiter = RateLimiter::with_burst(1.0, 1);
+    let config = RetryConfig { max_retries: 2, initial_delay_ms: 1, max_delay_ms: 1, ..Default::default() };
+    let start = Instant::now();
+    let permits = || (start.elapsed().as_secs_f64() + 0.5) as u64 + 1;
+
+    // 首次成功：只获取一个许可
+    let result = retry_with_backoff(|| async { Ok(()) }, &config, &limiter).await;
+    assert!(result.is_ok());
+    assert_eq!(permits(), 1);
+
+    // 失败一次后成功：每次尝试各获取一个许可
+    let attempts = AtomicUsize::new(0);
+    let result = retry_with_backoff(
+        || async {
+            match attempts.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(TranslationError::Custom("connection reset".into())),
+                _ => Ok(()),
+            }
+        },
+        &config,
+        &limiter,
+    )
+    .await;
+    assert!(result.is_ok());
+    assert_eq!((attempts.load(Ordering::SeqCst), permits()), (2, 3));
+
+    // 可重试的错误：共尝试 max_retries + 1 次，最后一次失败后不再获取许可
+    let attempts = AtomicUsize::new(0);
+    let result: Result<(), _> = retry_with_backoff(
+        || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(TranslationError::Custom("connection reset".into()))
+        },
+        &config,
+        &limiter,
+    )
+    .await;
+    assert!(result.is_err());
+    assert_eq!((attempts.load(Ordering::SeqCst), permits()), (3, 6));
+
+    // 不可重试的错误：只尝试一次，只获取一个许可
+    let attempts = AtomicUsize::new(0);
+    let result: Result<(), _> = retry_with_backoff(
+        || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(TranslationError::ApiError {
+                status: 400,
+                retry_after: None,
+                rate_limit: RateLimitHeaders::default(),
+                body_excerpt: "bad request".into(),
+            })
+        },
+        &config,
+        &limiter,
+    )
+    .await;
+    assert!(result.is_err());
+    assert_eq!((attempts.load(Ordering::SeqCst), permits()), (1, 7));
+
+    // 下一个许可在第7秒后发放，说明之前恰好发放了7个
+    limiter.acquire().await.unwrap();
+    assert_eq!(start.elapsed().as_secs(), 7);
+    assert!(start.elapsed() < Duration::from_millis(7100));
+}
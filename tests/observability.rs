@@ -0,0 +1,121 @@
+mod common;
+
+use common::MockServer;
+use futures::future::{BoxFuture, FutureExt};
+use markdown_translator::{Result, RetryConfig, TranslateOptions, TranslationError, TranslationService, Translator};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// 每次请求耗时1秒；"Second" 块的第一次请求失败
+struct SlowFlakyBackend(AtomicBool);
+
+impl Translator for SlowFlakyBackend {
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>> {
+        async move {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            if text.starts_with("Second") && !self.0.swap(true, Ordering::SeqCst) {
+                return Err(TranslationError::Custom("temporarily unavailable".to_string()));
+            }
+            Ok(text.to_uppercase())
+        }
+        .boxed()
+    }
+}
+
+// 时钟暂停，等待时间是确定的
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn metrics_accumulate_waits_and_requests_across_calls() {
+    let service = TranslationService::builder()
+        .enabled(true)
+        .max_rps(1.0)
+        .burst_size(1)
+        .max_text_length(20)
+        .retry(RetryConfig { max_retries: 1, initial_delay_ms: 500, max_delay_ms: 500, ..Default::default() })
+        .backend(SlowFlakyBackend(AtomicBool::new(false)))
+        .build()
+        .unwrap();
+    assert_eq!(service.metrics().requests, 0);
+
+    // 第二块等待1秒才获得许可，失败后退避0.5秒再重试，重试时令牌已补充
+    let (_, report) = service.translate_with_report("First paragraph.\n\nSecond paragraph.").await.unwrap();
+    let metrics = service.metrics();
+    assert_eq!(metrics, report.metrics);
+    assert_eq!((metrics.requests, metrics.retries, metrics.rate_limited_responses), (3, 1, 0));
+    assert_eq!((metrics.limiter_wait_total, metrics.limiter_wait_max), (Duration::from_secs(1), Duration::from_secs(1)));
+    assert_eq!(metrics.backoff_wait_total, Duration::from_millis(500));
+    assert_eq!((metrics.http_time_total, metrics.http_time_max), (Duration::from_secs(3), Duration::from_secs(1)));
+
+    // 服务级指标跨调用累计，单次报告只包含本次调用
+    let (_, report) = service.translate_with_report("Third.").await.unwrap();
+    assert_eq!(report.metrics.requests, 1);
+    assert_eq!(service.metrics().requests, 4);
+}
+
+#[cfg(feature = "prometheus")]
+#[tokio::test]
+async fn prometheus_recorder_exports_requests_characters_and_durations() {
+    use markdown_translator::metrics::PrometheusRecorder;
+    use std::sync::Arc;
+
+    let server = MockServer::uppercase();
+    let recorder = Arc::new(PrometheusRecorder::new());
+    let service = server.builder().metrics_recorder(recorder.clone()).build().unwrap();
+
+    service.translate("Hello").await.unwrap();
+
+    let exposition = recorder.gather();
+    assert!(exposition.contains("# TYPE translation_requests_total counter"));
+    assert!(exposition.contains("status=\"ok\"} 1"));
+    assert!(exposition.contains("translation_characters_total{direction=\"sent\"} 5"));
+    assert!(exposition.contains("translation_chunk_duration_seconds_count{outcome=\"ok\"} 1"));
+}
+
+#[tokio::test]
+async fn debug_dump_writes_request_without_credentials_and_raw_response() {
+    let server = MockServer::uppercase();
+    let dir = std::env::temp_dir().join("markdown-translator-test-dump");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let service = server
+        .builder()
+        .api_key("secret-key")
+        .configure(|config| config.debug_dump_dir = Some(dir.display().to_string()))
+        .build()
+        .unwrap();
+
+    let options = TranslateOptions::new().document_id("doc");
+    service.translate_with("Hello", &options).await.unwrap();
+
+    let request = std::fs::read_to_string(dir.join("doc-0-1.request.json")).unwrap();
+    assert!(request.contains("\"text\": \"Hello\""));
+    assert!(!request.contains("secret-key"));
+    let response = std::fs::read_to_string(dir.join("doc-0-1.response.txt")).unwrap();
+    assert!(response.starts_with("HTTP 200 OK"));
+    assert!(response.ends_with(r#"{"code":200,"data":"HELLO"}"#));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn audit_log_records_each_request_without_content() {
+    let server = MockServer::uppercase();
+    let path = std::env::temp_dir().join("markdown-translator-test-audit.jsonl");
+    let _ = std::fs::remove_file(&path);
+
+    let service = server
+        .builder()
+        .configure(|config| config.audit_log_path = Some(path.display().to_string()))
+        .build()
+        .unwrap();
+
+    let options = TranslateOptions::new().document_id("doc");
+    service.translate_with("Hello", &options).await.unwrap();
+    service.shutdown(Duration::from_secs(5)).await;
+
+    let log = std::fs::read_to_string(&path).unwrap();
+    let record: serde_json::Value = serde_json::from_str(log.lines().next().unwrap()).unwrap();
+    assert_eq!(record["document_id"], "doc");
+    assert_eq!(record["status"], "ok");
+    assert!(record.get("source_text").is_none());
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,370 @@
+mod common;
+
+use common::{MockServer, Reply};
+use futures::future::{BoxFuture, FutureExt};
+use markdown_translator::{
+    BilingualOrder, CacheConfig, CacheMode, FailurePolicy, FnTranslator, OutputMode, Result, SegmentKind, TextFormat,
+    TranslateOptions, TranslationError, TranslationScope, TranslationService, Translator,
+};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// 以 "Slow" 开头的块需要60秒，其余立即返回大写的原文
+struct SlowBackend;
+
+impl Translator for SlowBackend {
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>> {
+        async move {
+            if text.starts_with("Slow") {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            }
+            Ok(text.to_uppercase())
+        }
+        .boxed()
+    }
+}
+
+fn slow_service() -> TranslationService {
+    TranslationService::builder()
+        .enabled(true)
+        .max_rps(100.0)
+        .max_text_length(25)
+        .backend(SlowBackend)
+        .build()
+        .unwrap()
+}
+
+const SLOW_DOCUMENT: &str = "Fast paragraph one.\n\nSlow paragraph two.\n\nFast paragraph three.";
+
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn overall_timeout_fails_or_keeps_completed_chunks() {
+    let service = slow_service();
+
+    // 第一块已完成；第三块虽然也已翻译，但排在未完成的第二块之后
+    let start = Instant::now();
+    let options = TranslateOptions::new().overall_timeout(Duration::from_secs(10));
+    let error = service.translate_with(SLOW_DOCUMENT, &options).await.unwrap_err();
+    assert!(matches!(error, TranslationError::DeadlineExceeded { completed: 1, total: 3 }));
+    assert_eq!(start.elapsed().as_secs(), 10);
+
+    let options = options.on_error(FailurePolicy::KeepOriginal);
+    let result = service.translate_detailed(SLOW_DOCUMENT, &options).await.unwrap();
+    assert_eq!(result.text, "FAST PARAGRAPH ONE.\n\nSlow paragraph two.\n\nFast paragraph three.");
+    assert_eq!(result.report.warnings.len(), 1);
+    assert_eq!(start.elapsed().as_secs(), 20);
+}
+
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn chunk_timeout_keeps_the_original_of_the_slow_chunk_only() {
+    let service = slow_service();
+
+    let start = Instant::now();
+    let options = TranslateOptions::new().chunk_timeout(Duration::from_secs(5));
+    let result = service.translate_detailed(SLOW_DOCUMENT, &options).await.unwrap();
+    assert_eq!(result.text, "FAST PARAGRAPH ONE.\n\nSlow paragraph two.\n\nFAST PARAGRAPH THREE.");
+    assert_eq!((result.report.chunks, result.report.failed_chunks), (3, 1));
+    assert_eq!(result.report.warnings.len(), 1);
+    assert_eq!(start.elapsed().as_secs(), 5);
+
+    // 策略为 Fail 时超时的块使整个调用失败
+    let options = options.on_chunk_timeout(FailurePolicy::Fail);
+    let error = service.translate_with(SLOW_DOCUMENT, &options).await.unwrap_err();
+    assert!(matches!(error.root_cause(), TranslationError::ChunkTimeout { index: 1, .. }));
+}
+
+#[tokio::test]
+async fn context_is_sent_with_each_request() {
+    // 收到上下文时选择领域译法
+    let server = MockServer::start(|request| match request.body["context"].as_str() {
+        Some(context) if context.contains("Kubernetes") => Reply::data("Pod"),
+        _ => Reply::data("豆荚"),
+    });
+    let service = server.builder().build().unwrap();
+
+    let options = TranslateOptions::new().context("Kubernetes documentation");
+    let result = service.translate_detailed("pod", &options).await.unwrap();
+    assert_eq!(result.text, "Pod");
+    assert!(result.report.context_sent);
+
+    let result = service.translate_detailed("pod", &TranslateOptions::new()).await.unwrap();
+    assert_eq!(result.text, "豆荚");
+    assert!(!result.report.context_sent);
+}
+
+#[tokio::test]
+async fn document_id_prefixes_the_request_id_header() {
+    let server = MockServer::uppercase();
+    let service = server.builder().build().unwrap();
+
+    let options = TranslateOptions::new().document_id("trace-4bf92f35");
+    let result = service.translate_detailed("Hello", &options).await.unwrap();
+    assert_eq!(result.report.document_id, "trace-4bf92f35");
+    assert_eq!(server.requests()[0].header("x-request-id"), Some("trace-4bf92f35-0"));
+}
+
+#[tokio::test]
+async fn translate_kinds_leaves_unlisted_blocks_unsent() {
+    let server = MockServer::uppercase();
+    let service = server.builder().build().unwrap();
+
+    let document = "# Setup\n\nInstall it.\n\n> Note: keep this quote.\n\n- first step\n- second step\n";
+    let options = TranslateOptions::new().translate_kinds([SegmentKind::Paragraph, SegmentKind::List]);
+    let translated = service.translate_with(document, &options).await.unwrap();
+    assert_eq!(translated, "# Setup\n\nINSTALL IT.\n\n> Note: keep this quote.\n\n- FIRST STEP\n- SECOND STEP\n");
+    assert!(server.texts().iter().all(|text| !text.contains("Setup") && !text.contains("Note")));
+}
+
+#[tokio::test]
+async fn keep_terms_match_literals_and_regexes() {
+    let server = MockServer::uppercase();
+    let service = server.builder().build().unwrap();
+
+    let options = TranslateOptions::new().keep_terms(["Kubernetes", "--dry-run", r"re:v\d+\.\d+"]);
+    let translated = service.translate_with("run kubectl --dry-run on Kubernetes v1.30", &options).await.unwrap();
+    assert_eq!(translated, "RUN KUBECTL --dry-run ON Kubernetes v1.30");
+
+    // 无效的正则条目是配置错误
+    let options = TranslateOptions::new().keep_terms(["re:("]);
+    let error = service.translate_with("Hello", &options).await.unwrap_err();
+    assert!(matches!(error, TranslationError::ConfigError(_)));
+}
+
+#[tokio::test]
+async fn checkpoint_resumes_chunks_without_requests() {
+    let server = MockServer::uppercase();
+    let service = server.builder().build().unwrap();
+    let checkpoint = std::env::temp_dir().join("markdown-translator-test-checkpoint.jsonl");
+    let _ = std::fs::remove_file(&checkpoint);
+    let options = TranslateOptions::new().checkpoint_path(&checkpoint);
+
+    let first = service.translate_detailed("Hello world", &options).await.unwrap();
+    assert_eq!(first.text, "HELLO WORLD");
+    assert_eq!(server.request_count(), 1);
+
+    // 再次翻译同一输入时从检查点恢复，不再请求API
+    let second = service.translate_detailed("Hello world", &options).await.unwrap();
+    assert_eq!(second.text, "HELLO WORLD");
+    assert_eq!(second.report.resumed_chunks, 1);
+    assert_eq!(server.request_count(), 1);
+    std::fs::remove_file(&checkpoint).unwrap();
+}
+
+#[tokio::test]
+async fn cache_mode_controls_reads_and_writes() {
+    let server = MockServer::uppercase();
+    let service = server
+        .builder()
+        .configure(|config| config.cache = Some(CacheConfig::default()))
+        .build()
+        .unwrap();
+    let translate = |text: &'static str, mode: CacheMode| {
+        let service = service.clone();
+        async move { service.translate_with(text, &TranslateOptions::new().cache_mode(mode)).await.unwrap() }
+    };
+
+    // Bypass：不读也不写缓存
+    translate("Hello", CacheMode::Bypass).await;
+    translate("Hello", CacheMode::Default).await;
+    assert_eq!(server.request_count(), 2);
+
+    // Default：读取上一次写入的条目
+    translate("Hello", CacheMode::Default).await;
+    assert_eq!(server.request_count(), 2);
+
+    // Refresh：重新请求并覆盖条目，之后的默认调用读取新条目
+    translate("Hello", CacheMode::Refresh).await;
+    translate("Hello", CacheMode::Default).await;
+    assert_eq!(server.request_count(), 3);
+
+    // Bypass 的结果不写入缓存
+    translate("World", CacheMode::Bypass).await;
+    translate("World", CacheMode::Default).await;
+    assert_eq!(server.request_count(), 5);
+}
+
+#[tokio::test]
+async fn bilingual_output_places_sources_next_to_translations() {
+    let service = TranslationService::builder()
+        .enabled(true)
+        .max_rps(100.0)
+        .max_text_length(20)
+        .backend(FnTranslator::new(|text| Ok(text.to_uppercase())))
+        .build()
+        .unwrap();
+    let document = "# Guide\n\nHello world.\n\n```sh\nls\n```";
+
+    // 每段译文后紧跟以引用块呈现的原文，原文中的标题渲染为加粗文本，代码块只输出一次
+    let options = TranslateOptions::new().output_mode(OutputMode::bilingual());
+    let reviewed = service.translate_with(document, &options).await.unwrap();
+    assert_eq!(reviewed, "# GUIDE\n\n> **Guide**\n\nHELLO WORLD.\n\n> Hello world.\n\n```sh\nls\n```");
+
+    // 原文在前，使用自定义模板
+    let options = TranslateOptions::new().output_mode(OutputMode::Bilingual {
+        order: BilingualOrder::SourceFirst,
+        source_format: "<!-- {source} -->".to_string(),
+    });
+    let reviewed = service.translate_with("Hello world.", &options).await.unwrap();
+    assert_eq!(reviewed, "<!-- Hello world. -->\n\nHELLO WORLD.");
+
+    // 默认只输出译文
+    assert_eq!(service.translate("Hello world.").await.unwrap(), "HELLO WORLD.");
+}
+
+#[tokio::test]
+async fn scope_sends_only_headings_or_only_body() {
+    let server = MockServer::uppercase();
+    let service = server.builder().build().unwrap();
+    let document = "# Install\n\nRun the installer.\n\nUsage\n-----\n\nCall it.\n";
+
+    let headings = TranslateOptions::new().scope(TranslationScope::HeadingsOnly);
+    assert_eq!(
+        service.translate_with(document, &headings).await.unwrap(),
+        "# INSTALL\n\nRun the installer.\n\nUSAGE\n-----\n\nCall it.\n"
+    );
+
+    let body = TranslateOptions::new().scope(TranslationScope::BodyOnly);
+    assert_eq!(
+        service.translate_with(document, &body).await.unwrap(),
+        "# Install\n\nRUN THE INSTALLER.\n\nUsage\n-----\n\nCALL IT.\n"
+    );
+}
+
+#[tokio::test]
+async fn plain_format_ignores_markdown_syntax() {
+    let server = MockServer::uppercase();
+    let service = server.builder().max_text_length(40).build().unwrap();
+    let transcript = "# make install\n> copying files... done.\n```\nInstalled 3 files. Restart the shell.\n";
+
+    let options = TranslateOptions::new().format(TextFormat::Plain);
+    assert_eq!(
+        service.translate_with(transcript, &options).await.unwrap(),
+        "# MAKE INSTALL\n> COPYING FILES... DONE.\n```\nINSTALLED 3 FILES. RESTART THE SHELL.\n"
+    );
+}
+
+#[tokio::test]
+async fn rst_format_keeps_markup_and_redraws_section_adornments() {
+    // 翻译两个标题，其余文本转为大写
+    let server = MockServer::start(|request| match request.text() {
+        "Installation" => Reply::data("安装"),
+        "Configuration" => Reply::data("配置"),
+        text => Reply::data(text.to_uppercase()),
+    });
+    let service = server.builder().build().unwrap();
+    let document = "\
+.. _install:
+
+============
+Installation
+============
+
+Install the package with :command:`pip` and check the |project| version::
+
+    $ pip install sphinx-demo
+
+.. code-block:: python
+
+   import demo
+
+.. note::
+   Use a ``virtualenv`` to keep dependencies isolated.
+
+Configuration
+-------------
+
+See :ref:`config-options` for details.
+";
+
+    let options = TranslateOptions::new().format(TextFormat::Rst);
+    assert_eq!(
+        service.translate_with(document, &options).await.unwrap(),
+        "\
+.. _install:
+
+====
+安装
+====
+
+INSTALL THE PACKAGE WITH :command:`pip` AND CHECK THE |project| VERSION::
+
+    $ pip install sphinx-demo
+
+.. code-block:: python
+
+   import demo
+
+.. note::
+   USE A ``virtualenv`` TO KEEP DEPENDENCIES ISOLATED.
+
+配置
+----
+
+SEE :ref:`config-options` FOR DETAILS.
+"
+    );
+}
+
+#[tokio::test]
+async fn asciidoc_format_keeps_listings_attributes_and_macros() {
+    let server = MockServer::uppercase();
+    let service = server.builder().build().unwrap();
+    let document = "\
+= Getting Started
+:toc:
+:source-highlighter: rouge
+
+Install the CLI first.
+See <<config,the configuration guide>> and {product-name}.
+
+[source,rust]
+----
+fn main() {}
+----
+
+image::architecture.png[Architecture overview]
+
+.Supported platforms
+* Linux and macOS
+* Windows via link:https://example.com/wsl[WSL docs]
+
+NOTE: Run `cargo build` before testing.
+
+[[config]]
+== Configuration
+
+// internal comment
+The settings live in `config.toml`.
+";
+
+    let options = TranslateOptions::new().format(TextFormat::Asciidoc);
+    assert_eq!(
+        service.translate_with(document, &options).await.unwrap(),
+        "\
+= GETTING STARTED
+:toc:
+:source-highlighter: rouge
+
+INSTALL THE CLI FIRST.
+SEE <<config,THE CONFIGURATION GUIDE>> AND {product-name}.
+
+[source,rust]
+----
+fn main() {}
+----
+
+image::architecture.png[Architecture overview]
+
+.SUPPORTED PLATFORMS
+* LINUX AND MACOS
+* WINDOWS VIA link:https://example.com/wsl[WSL DOCS]
+
+NOTE: RUN `cargo build` BEFORE TESTING.
+
+[[config]]
+== CONFIGURATION
+
+// internal comment
+THE SETTINGS LIVE IN `config.toml`.
+"
+    );
+}
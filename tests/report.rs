@@ -0,0 +1,148 @@
+mod common;
+
+use common::MockServer;
+use futures::future::{BoxFuture, FutureExt};
+use markdown_translator::{
+    CacheConfig, FailurePolicy, ProgressEvent, Result, TranslateOptions, TranslationError, TranslationService,
+    Translator,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[tokio::test]
+async fn progress_eta_respects_the_rate_limit() {
+    let server = MockServer::uppercase();
+    let service = server.builder().max_text_length(30).build().unwrap();
+
+    let updates = Arc::new(Mutex::new(Vec::new()));
+    let recorded = updates.clone();
+    let options = TranslateOptions::new().on_progress(move |progress| recorded.lock().unwrap().push(progress));
+    let document = "First paragraph here.\n\nSecond paragraph here.\n\nThird paragraph here.";
+    service.translate_with(document, &options).await.unwrap();
+
+    let updates = updates.lock().unwrap();
+    let planned = &updates[0];
+    assert_eq!(planned.event, ProgressEvent::PlanComplete);
+    assert_eq!(planned.total_chunks, 3);
+    // 3个请求、每秒100个请求，至少需要30毫秒
+    assert!(planned.eta.unwrap() >= Duration::from_millis(30));
+
+    let finished = updates.last().unwrap();
+    assert_eq!(finished.percent_complete, 100.0);
+    assert_eq!(finished.eta, Some(Duration::ZERO));
+}
+
+#[tokio::test]
+async fn protection_stats_count_code_blocks_and_kept_terms() {
+    let server = MockServer::uppercase();
+    let service = server
+        .builder()
+        .configure(|config| {
+            config.keep_terms = vec!["kubectl".to_string()];
+            config.max_text_length = 30;
+        })
+        .build()
+        .unwrap();
+
+    let document = "Run kubectl to list the pods.\n\n```\nkubectl get pods\n```\n";
+    let (translated, report) = service.translate_with_report(document).await.unwrap();
+    assert!(translated.starts_with("RUN kubectl TO LIST THE PODS."));
+    assert_eq!(report.protection.code_blocks, 1);
+    assert_eq!(report.protection.keep_terms, 1);
+    assert_eq!(report.protection.translated_chars, "Run  to list the pods.".len());
+}
+
+#[tokio::test]
+async fn repeated_paragraphs_are_translated_once() {
+    let server = MockServer::uppercase();
+    let service = server.builder().max_text_length(30).build().unwrap();
+
+    let changelog = "## 1.2.0\n\nNo breaking changes here.\n\n## 1.1.0\n\nNo breaking changes here.";
+    let (translated, report) = service.translate_with_report(changelog).await.unwrap();
+    assert_eq!(translated, "## 1.2.0\n\nNO BREAKING CHANGES HERE.\n\n## 1.1.0\n\nNO BREAKING CHANGES HERE.");
+    assert_eq!((report.unique_chunks, report.duplicate_chunks), (3, 1));
+    assert_eq!(report.metrics.requests, 3);
+    assert_eq!(server.request_count(), 3);
+}
+
+/// "Beta" 块耗时2秒，"Broken" 块耗时3秒后以不可重试的错误失败，其余耗时1秒
+struct Backend;
+
+impl Translator for Backend {
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>> {
+        async move {
+            let seconds = match text {
+                _ if text.starts_with("Beta") => 2,
+                _ if text.starts_with("Broken") => 3,
+                _ => 1,
+            };
+            tokio::time::sleep(Duration::from_secs(seconds)).await;
+            if text.starts_with("Broken") {
+                return Err(TranslationError::ParseError("unexpected reply".to_string()));
+            }
+            Ok(text.to_uppercase())
+        }
+        .boxed()
+    }
+}
+
+// 时钟暂停，耗时统计是确定的
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn report_covers_cache_failures_protection_and_timings() {
+    let service = TranslationService::builder()
+        .enabled(true)
+        .max_rps(100.0)
+        .max_text_length(15)
+        .backend(Backend)
+        .configure(|config| config.cache = Some(CacheConfig::default()))
+        .build()
+        .unwrap();
+    // 预先翻译第一段，写入缓存
+    service.translate("Alpha one.").await.unwrap();
+
+    let text = "Alpha one.\n\nBeta two.\n\nBroken three.\n\n```sh\nls\n```";
+    let options = TranslateOptions::new().on_error(FailurePolicy::KeepOriginal);
+    let result = service.translate_detailed(text, &options).await.unwrap();
+    assert_eq!(result.text, "ALPHA ONE.\n\nBETA TWO.\n\nBroken three.\n\n```sh\nls\n```");
+    let report = result.report;
+
+    // 分块：三个文本块和一个代码块，第一块命中缓存，另外两块各请求一次
+    assert_eq!(report.chunks, 4);
+    assert_eq!((report.cache_hits, report.fuzzy_hits), (1, 0));
+    assert_eq!(report.cached_chunks, [0]);
+    assert_eq!((report.unique_chunks, report.duplicate_chunks), (3, 0));
+    assert_eq!((report.metrics.requests, report.retries_used), (2, 0));
+    assert_eq!(report.backend.as_deref(), Some("custom"));
+
+    // 失败的块保留原文并记录一条警告
+    assert_eq!(report.failed_chunks, 1);
+    assert_eq!(report.warnings.len(), 1);
+
+    // 字符数：发送 "Beta two." 和 "Broken three."，收到 "BETA TWO."；缓存命中的块也计入已翻译字符
+    assert_eq!((report.metrics.characters_sent, report.metrics.characters_received), (22, 9));
+    assert_eq!(report.protection.translated_chars, 19);
+    assert_eq!((report.protection.code_blocks, report.protection.code_block_chars), (1, 12));
+
+    // 耗时：缓存命中的块为0，其余两块分别为2秒和3秒，两块并发，总耗时3秒
+    let timing = &report.timing_summary;
+    assert_eq!((timing.chunks, timing.attempts), (3, 2));
+    assert_eq!((timing.total.p50, timing.total.max), (Duration::from_secs(2), Duration::from_secs(3)));
+    assert_eq!(report.metrics.http_time_total, Duration::from_secs(5));
+    assert_eq!(report.elapsed, Duration::from_secs(3));
+}
+
+#[tokio::test]
+async fn detailed_timings_list_each_chunk() {
+    let server = MockServer::uppercase();
+    let service = server.builder().build().unwrap();
+
+    let result = service.translate_detailed("Hello, world.", &TranslateOptions::new()).await.unwrap();
+    assert!(result.report.chunk_timings.is_empty());
+
+    let options = TranslateOptions::new().detailed_timings(true);
+    let report = service.translate_detailed("Hello, world.", &options).await.unwrap().report;
+    assert_eq!((report.timing_summary.chunks, report.timing_summary.attempts), (1, 1));
+    assert!(report.timing_summary.http.max <= report.timing_summary.total.max);
+    assert_eq!(report.chunk_timings.len(), 1);
+    assert_eq!(report.chunk_timings[0].attempts.len(), 1);
+}
@@ -0,0 +1,290 @@
+mod common;
+
+use common::{MockServer, Reply};
+use futures::future::{BoxFuture, FutureExt};
+use futures::StreamExt;
+use markdown_translator::{
+    CacheConfig, CancellationToken, FailurePolicy, FnTranslator, Result, RetryConfig, TranslateOptions, TranslationConfig,
+    TranslationError, TranslationService, Translator,
+};
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// 排在前面的块延迟更久：第 N 段（共8段）延迟 (8 - N) 秒后返回大写的原文，并记录完成顺序
+struct Staggered {
+    completed: Mutex<Vec<String>>,
+}
+
+impl Translator for Staggered {
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>> {
+        async move {
+            let index: u64 = text.split_whitespace().nth(1).unwrap().parse().unwrap();
+            tokio::time::sleep(Duration::from_secs(8 - index)).await;
+            self.completed.lock().unwrap().push(text.to_string());
+            Ok(text.to_uppercase())
+        }
+        .boxed()
+    }
+}
+
+// 时钟暂停，完成顺序只取决于各块的延迟
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn concurrent_chunks_are_joined_in_document_order() {
+    let backend = Arc::new(Staggered { completed: Mutex::new(Vec::new()) });
+    let service = TranslationService::builder()
+        .enabled(true)
+        .max_rps(1000.0)
+        .max_text_length(25)
+        .backend(backend.clone())
+        .build()
+        .unwrap();
+
+    let paragraphs: Vec<String> = (0..8).map(|index| format!("Paragraph {} is here.", index)).collect();
+    let start = Instant::now();
+    let translated = service.translate(&paragraphs.join("\n\n")).await.unwrap();
+
+    // 后面的块先完成
+    let completed = backend.completed.lock().unwrap().clone();
+    assert_eq!(completed.len(), paragraphs.len());
+    assert_ne!(completed, paragraphs);
+    assert_eq!(completed[0], paragraphs[4]);
+    // 译文仍按原文顺序拼接
+    let expected: Vec<String> = paragraphs.iter().map(|paragraph| paragraph.to_uppercase()).collect();
+    assert_eq!(translated, expected.join("\n\n"));
+    // 并发执行：总耗时小于各块延迟之和
+    assert!(start.elapsed() < Duration::from_secs(36));
+}
+
+#[tokio::test]
+async fn retry_budget_is_shared_by_all_chunks() {
+    // 后端总是返回可重试的错误
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counted = calls.clone();
+    let backend = FnTranslator::new(move |_| {
+        counted.fetch_add(1, Ordering::SeqCst);
+        Err(TranslationError::Custom("temporarily unavailable".to_string()))
+    });
+    let service = TranslationService::builder()
+        .enabled(true)
+        .max_rps(100.0)
+        .max_text_length(20)
+        .retry(RetryConfig { max_retries: 3, initial_delay_ms: 1, max_delay_ms: 1, ..Default::default() })
+        .backend(backend)
+        .configure(|config| {
+            config.max_total_retries = Some(2);
+            config.on_error = FailurePolicy::KeepOriginal;
+        })
+        .build()
+        .unwrap();
+
+    let text = "First paragraph.\n\nSecond paragraph.\n\nThird paragraph.";
+    let (translated, report) = service.translate_with_report(text).await.unwrap();
+    // 三块都失败并保留原文；整篇文档共重试2次，而不是每块各3次
+    assert_eq!(translated, text);
+    assert_eq!((report.retries_used, report.retry_budget), (2, Some(2)));
+    assert_eq!(report.failed_chunks, 3);
+    assert_eq!(calls.load(Ordering::SeqCst), 3 + 2);
+    assert_eq!(serde_json::to_value(&report).unwrap()["retries_used"], 2);
+}
+
+/// 以 "Slow" 开头的块需要60秒，完成时做标记；其余立即返回大写的原文
+struct SlowBackend(Arc<AtomicBool>);
+
+impl Translator for SlowBackend {
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>> {
+        async move {
+            if text.starts_with("Slow") {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                self.0.store(true, Ordering::SeqCst);
+            }
+            Ok(text.to_uppercase())
+        }
+        .boxed()
+    }
+}
+
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn cancellation_aborts_in_flight_requests() {
+    let slow_finished = Arc::new(AtomicBool::new(false));
+    let service = TranslationService::builder()
+        .enabled(true)
+        .max_rps(100.0)
+        .max_text_length(25)
+        .backend(SlowBackend(slow_finished.clone()))
+        .build()
+        .unwrap();
+
+    // 5秒后在另一个任务中取消
+    let token = CancellationToken::new();
+    let canceller = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        canceller.cancel();
+    });
+
+    let start = Instant::now();
+    let options = TranslateOptions::new().cancellation_token(token);
+    let text = "Fast paragraph one.\n\nSlow paragraph two.\n\nFast paragraph three.";
+    let error = service.translate_with(text, &options).await.unwrap_err();
+    assert!(matches!(error, TranslationError::Cancelled { completed: 1, total: 3 }));
+    assert_eq!(start.elapsed().as_secs(), 5);
+
+    // 慢块的请求已被中止，时间推进后也不会完成
+    tokio::time::sleep(Duration::from_secs(120)).await;
+    assert!(!slow_finished.load(Ordering::SeqCst));
+}
+
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn shutdown_returns_after_aborted_calls_have_exited() {
+    let service = TranslationService::builder()
+        .enabled(true)
+        .max_rps(100.0)
+        .backend(SlowBackend(Arc::new(AtomicBool::new(false))))
+        .build()
+        .unwrap();
+
+    let call = tokio::spawn({
+        let service = service.clone();
+        async move { service.translate("Slow paragraph.").await }
+    });
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    // 宽限期内没有完成，调用被中止；返回时调用已经结束，不会与之后的收尾工作竞争
+    assert!(!service.shutdown(Duration::from_secs(5)).await);
+    assert!(call.is_finished());
+    let error = call.await.unwrap().unwrap_err();
+    assert!(matches!(error, TranslationError::Cancelled { .. }));
+}
+
+#[tokio::test]
+async fn translate_to_overrides_the_target_for_one_call() {
+    // 在译文前标出请求的目标语言
+    let server = MockServer::start(|request| {
+        Reply::data(format!("[{}] {}", request.body["target_lang"].as_str().unwrap(), request.text()))
+    });
+    let service = server.builder().target_lang("zh").build().unwrap();
+
+    for lang in ["ja", "ko", "de"] {
+        let translated = service.translate_to("Hello, world!", lang).await.unwrap();
+        assert_eq!(translated, format!("[{}] Hello, world!", lang));
+    }
+    // 服务配置的目标语言不受影响
+    assert_eq!(service.translate("Hello, world!").await.unwrap(), "[zh] Hello, world!");
+    assert_eq!(service.config().target_lang, "zh");
+}
+
+#[tokio::test]
+async fn translate_detailed_reports_language_backend_cache_and_warnings() {
+    // 拒绝以 "Broken" 开头的文本，其余返回大写的原文并报告源语言
+    let server = MockServer::start(|request| {
+        if request.text().starts_with("Broken") {
+            Reply::status(400, "")
+        } else {
+            Reply::json(json!({ "code": 200, "data": request.text().to_uppercase(), "source_lang": "EN" }))
+        }
+    });
+    let service = server
+        .builder()
+        .max_text_length(20)
+        .configure(|config| config.cache = Some(CacheConfig::default()))
+        .build()
+        .unwrap();
+    let options = TranslateOptions::new().on_error(FailurePolicy::KeepOriginal);
+
+    let result = service.translate_detailed("Hello there.\n\nBroken paragraph.", &options).await.unwrap();
+    assert_eq!(result.text, "HELLO THERE.\n\nBroken paragraph.");
+    assert_eq!(result.detected_source_lang.as_deref(), Some("en"));
+    assert_eq!(result.backend.as_deref(), Some(server.url.as_str()));
+    assert!(!result.from_cache);
+    // 保留原文的块产生一条警告，不打印到标准输出
+    assert_eq!(result.warnings.len(), 1);
+    assert_eq!(result.warnings, result.report.warnings);
+
+    // 再次翻译已缓存的段落，结果完全来自缓存
+    let result = service.translate_detailed("Hello there.", &options).await.unwrap();
+    assert_eq!(result.text, "HELLO THERE.");
+    assert!(result.from_cache);
+    assert!(result.warnings.is_empty());
+}
+
+#[tokio::test]
+async fn stream_applies_backpressure_and_keeps_order() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counted = calls.clone();
+    let backend = FnTranslator::new(move |text| {
+        counted.fetch_add(1, Ordering::SeqCst);
+        Ok(text.to_uppercase())
+    });
+    let service = TranslationService::builder()
+        .enabled(true)
+        .max_rps(1000.0)
+        .max_text_length(25)
+        .backend(backend)
+        .build()
+        .unwrap();
+
+    let text = (0..12).map(|index| format!("Paragraph {} is here.", index)).collect::<Vec<_>>().join("\n\n");
+    let mut segments = Box::pin(service.translate_stream(&text));
+    let first = segments.next().await.unwrap().unwrap();
+    assert_eq!((first.index, first.translated.as_str()), (0, "PARAGRAPH 0 IS HERE."));
+    // 调用方暂停读取时，最多只有并发上限（5）个块被请求
+    let requested = calls.load(Ordering::SeqCst);
+    assert!((1..=5).contains(&requested), "{} chunks requested", requested);
+
+    // 继续读取得到其余各段，顺序与原文一致
+    let mut translated = vec![first.translated];
+    while let Some(segment) = segments.next().await {
+        let segment = segment.unwrap();
+        assert_eq!(segment.index, translated.len());
+        translated.push(segment.translated);
+    }
+    assert_eq!(translated.join("\n\n"), text.to_uppercase());
+    assert_eq!(calls.load(Ordering::SeqCst), 12);
+}
+
+#[tokio::test]
+async fn stream_segments_carry_requested_alternatives() {
+    let server = MockServer::start(|_| {
+        Reply::json(json!({
+            "code": 200,
+            "data": "你好，世界！",
+            "alternatives": ["世界你好！", "哈喽，世界！", "您好，世界！"],
+        }))
+    });
+    let service = server.builder().build().unwrap();
+    let options = TranslateOptions::new().num_alternatives(2);
+
+    let mut segments = Box::pin(service.translate_stream_with("Hello, world!", &options));
+    let segment = segments.next().await.unwrap().unwrap();
+    assert_eq!(segment.translated, "你好，世界！");
+    assert_eq!(segment.alternatives, ["世界你好！", "哈喽，世界！"]);
+    assert_eq!(server.requests()[0].body["num_alternatives"], 2);
+}
+
+#[tokio::test]
+async fn caller_client_is_used_and_kept_across_config_updates() {
+    // 把请求的User-Agent作为译文返回
+    let server = MockServer::start(|request| Reply::data(request.header("user-agent").unwrap_or_default()));
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .user_agent("my-app/1.0")
+        .build()
+        .unwrap();
+    let config = TranslationConfig {
+        enabled: true,
+        deeplx_api_url: server.url.clone(),
+        max_requests_per_second: 100.0,
+        ..Default::default()
+    };
+    let service = TranslationService::with_client(config, client);
+
+    // 请求由调用方的客户端发出，带有其User-Agent
+    assert_eq!(service.translate("Hello").await.unwrap(), "my-app/1.0");
+
+    // 修改超时设置不会替换该客户端
+    service.update_config(|config| config.request_timeout_secs = 5).unwrap();
+    assert_eq!(service.translate("Hello again").await.unwrap(), "my-app/1.0");
+}